@@ -1,18 +1,149 @@
+pub mod world;
+
+use glam::{Mat4, Quat, Vec3};
+
+pub use world::World;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct EntityId(pub u64);
 
+/// Local transform, relative to the parent entity (or the world).
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Transform {
-    pub position: [f32; 3],
-    pub rotation: [f32; 4],
-    pub scale: [f32; 3],
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        position: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    pub fn from_position(position: Vec3) -> Self {
+        Self {
+            position,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_position_rotation(position: Vec3, rotation: Quat) -> Self {
+        Self {
+            position,
+            rotation,
+            scale: Vec3::ONE,
+        }
+    }
+
+    pub fn with_scale(mut self, scale: Vec3) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.position)
+    }
+
+    pub fn from_matrix(m: Mat4) -> Self {
+        let (scale, rotation, position) = m.to_scale_rotation_translation();
+        Self {
+            position,
+            rotation,
+            scale,
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Bit set of render layers. Things are drawn into a view when their
+/// layers intersect the view's mask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderLayers(pub u32);
+
+impl RenderLayers {
+    pub const NONE: Self = Self(0);
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// Ordinary world geometry.
+    pub const DEFAULT: Self = Self(1 << 0);
+    /// The local user's own head: hidden from their eyes, shown in
+    /// mirrors and spectator cameras.
+    pub const LOCAL_HEAD: Self = Self(1 << 1);
+    /// Developer visualisations.
+    pub const DEBUG: Self = Self(1 << 2);
+    /// Editor and screen-space helpers such as the compass.
+    pub const GIZMOS: Self = Self(1 << 3);
+    /// The local user's first-person-only geometry (their avatar with the
+    /// head cut away): shown in their eyes, never in mirrors.
+    pub const LOCAL_FIRST_PERSON: Self = Self(1 << 4);
+
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn with(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn without(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+}
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl std::ops::BitOr for RenderLayers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.with(rhs)
+    }
+}
+
+impl std::ops::BitAnd for RenderLayers {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
 }
 
 pub struct Renderable {
     pub mesh: u32,
     pub material: u32,
+    pub layers: RenderLayers,
+}
+
+impl Renderable {
+    pub fn new(mesh: u32, material: u32) -> Self {
+        Self {
+            mesh,
+            material,
+            layers: RenderLayers::DEFAULT,
+        }
+    }
+
+    pub fn with_layers(mut self, layers: RenderLayers) -> Self {
+        self.layers = layers;
+        self
+    }
 }
 
 pub struct Script {
     pub script_handle: u32,
 }
-
-// Core ECS and world logic will go here.
@@ -0,0 +1,347 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+use glam::Mat4;
+
+use crate::{EntityId, Transform};
+
+/* =========================================================
+   ENTITY
+   ========================================================= */
+
+pub struct Entity {
+    pub name: String,
+    pub transform: Transform,
+
+    parent: Option<EntityId>,
+    children: Vec<EntityId>,
+}
+
+/* =========================================================
+   WORLD
+   ========================================================= */
+
+/// Entities with a parent/child transform hierarchy plus loosely typed
+/// components (one value of each type per entity).
+#[derive(Default)]
+pub struct World {
+    entities: HashMap<EntityId, Entity>,
+    components: HashMap<TypeId, Box<dyn Storage>>,
+    next_id: u64,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawn(&mut self, name: &str, transform: Transform) -> EntityId {
+        self.next_id += 1;
+        let id = EntityId(self.next_id);
+
+        self.entities.insert(
+            id,
+            Entity {
+                name: name.to_string(),
+                transform,
+                parent: None,
+                children: Vec::new(),
+            },
+        );
+
+        id
+    }
+
+    /// Removes the entity and its whole subtree.
+    pub fn despawn(&mut self, id: EntityId) {
+        self.detach(id);
+
+        let mut stack = vec![id];
+        while let Some(e) = stack.pop() {
+            if let Some(entity) = self.entities.remove(&e) {
+                stack.extend(entity.children);
+            }
+            for storage in self.components.values_mut() {
+                storage.remove_entity(e);
+            }
+        }
+    }
+
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.entities.contains_key(&id)
+    }
+
+    pub fn entity(&self, id: EntityId) -> Option<&Entity> {
+        self.entities.get(&id)
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.entities.keys().copied()
+    }
+
+    pub fn find(&self, name: &str) -> Option<EntityId> {
+        self.entities
+            .iter()
+            .find(|(_, e)| e.name == name)
+            .map(|(id, _)| *id)
+    }
+
+    /* ================= TRANSFORMS ================= */
+
+    pub fn transform(&self, id: EntityId) -> Transform {
+        self.entities
+            .get(&id)
+            .map(|e| e.transform)
+            .unwrap_or_default()
+    }
+
+    pub fn set_transform(&mut self, id: EntityId, transform: Transform) {
+        if let Some(e) = self.entities.get_mut(&id) {
+            e.transform = transform;
+        }
+    }
+
+    /// Local-to-world matrix, walking up the parent chain.
+    pub fn world_matrix(&self, id: EntityId) -> Mat4 {
+        let mut m = Mat4::IDENTITY;
+        let mut current = Some(id);
+
+        while let Some(e) = current.and_then(|c| self.entities.get(&c)) {
+            m = e.transform.matrix() * m;
+            current = e.parent;
+        }
+
+        m
+    }
+
+    pub fn world_transform(&self, id: EntityId) -> Transform {
+        Transform::from_matrix(self.world_matrix(id))
+    }
+
+    /// Sets the local transform so the entity ends up at `world` in world space.
+    pub fn set_world_transform(&mut self, id: EntityId, world: Transform) {
+        let parent = self
+            .parent(id)
+            .map(|p| self.world_matrix(p))
+            .unwrap_or(Mat4::IDENTITY);
+
+        self.set_transform(id, Transform::from_matrix(parent.inverse() * world.matrix()));
+    }
+
+    /* ================= HIERARCHY ================= */
+
+    pub fn parent(&self, id: EntityId) -> Option<EntityId> {
+        self.entities.get(&id).and_then(|e| e.parent)
+    }
+
+    pub fn children(&self, id: EntityId) -> &[EntityId] {
+        self.entities
+            .get(&id)
+            .map(|e| e.children.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Reparents `child`. With `keep_world` the child stays where it is in
+    /// world space (its local transform is recomputed), otherwise its local
+    /// transform is kept and it moves with the new parent.
+    pub fn set_parent(&mut self, child: EntityId, parent: Option<EntityId>, keep_world: bool) {
+        if !self.entities.contains_key(&child) || parent == Some(child) {
+            return;
+        }
+        if let Some(p) = parent {
+            if !self.entities.contains_key(&p) || self.is_ancestor(child, p) {
+                return;
+            }
+        }
+
+        let world = self.world_matrix(child);
+
+        self.detach(child);
+
+        if let Some(p) = parent {
+            self.entities.get_mut(&p).unwrap().children.push(child);
+        }
+        self.entities.get_mut(&child).unwrap().parent = parent;
+
+        if keep_world {
+            let parent_world = parent
+                .map(|p| self.world_matrix(p))
+                .unwrap_or(Mat4::IDENTITY);
+            self.set_transform(child, Transform::from_matrix(parent_world.inverse() * world));
+        }
+    }
+
+    /// True if `ancestor` is `id` or one of its parents.
+    pub fn is_ancestor(&self, ancestor: EntityId, id: EntityId) -> bool {
+        let mut current = Some(id);
+        while let Some(c) = current {
+            if c == ancestor {
+                return true;
+            }
+            current = self.parent(c);
+        }
+        false
+    }
+
+    fn detach(&mut self, child: EntityId) {
+        if let Some(old) = self.parent(child) {
+            if let Some(p) = self.entities.get_mut(&old) {
+                p.children.retain(|c| *c != child);
+            }
+        }
+        if let Some(e) = self.entities.get_mut(&child) {
+            e.parent = None;
+        }
+    }
+
+    /* ================= COMPONENTS ================= */
+
+    pub fn insert<T: 'static>(&mut self, id: EntityId, component: T) {
+        self.storage_mut::<T>().insert(id, component);
+    }
+
+    pub fn remove<T: 'static>(&mut self, id: EntityId) -> Option<T> {
+        self.storage_mut::<T>().remove(&id)
+    }
+
+    pub fn get<T: 'static>(&self, id: EntityId) -> Option<&T> {
+        self.storage::<T>().and_then(|s| s.get(&id))
+    }
+
+    pub fn get_mut<T: 'static>(&mut self, id: EntityId) -> Option<&mut T> {
+        self.storage_mut::<T>().get_mut(&id)
+    }
+
+    pub fn has<T: 'static>(&self, id: EntityId) -> bool {
+        self.get::<T>(id).is_some()
+    }
+
+    /// Every entity with a `T`, in id order.
+    pub fn query<T: 'static>(&self) -> Vec<(EntityId, &T)> {
+        let mut out: Vec<_> = self
+            .storage::<T>()
+            .map(|s| s.iter().map(|(id, c)| (*id, c)).collect())
+            .unwrap_or_default();
+        out.sort_by_key(|(id, _)| *id);
+        out
+    }
+
+    fn storage<T: 'static>(&self) -> Option<&HashMap<EntityId, T>> {
+        self.components
+            .get(&TypeId::of::<T>())
+            .and_then(|s| s.as_any().downcast_ref())
+    }
+
+    fn storage_mut<T: 'static>(&mut self) -> &mut HashMap<EntityId, T> {
+        self.components
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(HashMap::<EntityId, T>::new()))
+            .as_any_mut()
+            .downcast_mut()
+            .unwrap()
+    }
+}
+
+/* =========================================================
+   STORAGE
+   ========================================================= */
+
+/// Type-erased `HashMap<EntityId, T>` so despawn can clear every component.
+trait Storage {
+    fn remove_entity(&mut self, id: EntityId);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: 'static> Storage for HashMap<EntityId, T> {
+    fn remove_entity(&mut self, id: EntityId) {
+        self.remove(&id);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Quat, Vec3};
+
+    use super::*;
+
+    fn at(world: &mut World, name: &str, position: Vec3) -> EntityId {
+        world.spawn(name, Transform::from_position(position))
+    }
+
+    #[test]
+    fn reparenting_keeps_the_world_pose_or_the_local_one() {
+        let mut world = World::new();
+        let hand = world.spawn(
+            "hand",
+            Transform::from_position_rotation(Vec3::new(1.0, 1.0, 0.0), Quat::from_rotation_y(1.0)),
+        );
+        let held = at(&mut world, "held", Vec3::new(1.0, 1.5, -0.5));
+        let before = world.world_transform(held);
+
+        world.set_parent(held, Some(hand), true);
+        assert_eq!(world.parent(held), Some(hand));
+        assert_eq!(world.children(hand), [held]);
+        assert!(world.world_transform(held).position.abs_diff_eq(before.position, 1e-5));
+        assert!(world.world_transform(held).rotation.abs_diff_eq(before.rotation, 1e-5));
+
+        // the child follows its parent
+        world.set_transform(hand, Transform::from_position(Vec3::new(1.0, 2.0, 0.0)));
+        let local = world.transform(held);
+        let expected = Vec3::new(1.0, 2.0, 0.0) + local.position;
+        assert!(world.world_transform(held).position.abs_diff_eq(expected, 1e-5));
+
+        // dropped without keep_world, the local pose becomes the world one
+        world.set_parent(held, None, false);
+        assert_eq!(world.parent(held), None);
+        assert!(world.children(hand).is_empty());
+        assert!(world.world_transform(held).position.abs_diff_eq(local.position, 1e-5));
+    }
+
+    #[test]
+    fn cycles_and_unknown_parents_are_refused() {
+        let mut world = World::new();
+        let a = at(&mut world, "a", Vec3::ZERO);
+        let b = at(&mut world, "b", Vec3::X);
+        world.set_parent(b, Some(a), true);
+
+        world.set_parent(a, Some(b), true);
+        world.set_parent(a, Some(a), true);
+        world.set_parent(b, Some(EntityId(99)), true);
+
+        assert_eq!(world.parent(a), None);
+        assert_eq!(world.parent(b), Some(a));
+        assert!(world.is_ancestor(a, b) && !world.is_ancestor(b, a));
+    }
+
+    #[test]
+    fn despawn_removes_the_subtree_and_its_components() {
+        let mut world = World::new();
+        let root = at(&mut world, "root", Vec3::ZERO);
+        let child = at(&mut world, "child", Vec3::X);
+        let grandchild = at(&mut world, "grandchild", Vec3::Y);
+        let sibling = at(&mut world, "sibling", Vec3::Z);
+        world.set_parent(root, Some(sibling), false);
+        world.set_parent(child, Some(root), false);
+        world.set_parent(grandchild, Some(child), false);
+        for id in [root, child, grandchild, sibling] {
+            world.insert(id, 1_u32);
+        }
+
+        world.despawn(root);
+
+        assert!(!world.contains(root) && !world.contains(child) && !world.contains(grandchild));
+        assert!(world.contains(sibling));
+        assert!(world.children(sibling).is_empty());
+        assert_eq!(world.query::<u32>(), [(sibling, &1)]);
+        assert_eq!(world.find("grandchild"), None);
+    }
+}
@@ -0,0 +1,208 @@
+use engine_core::{EntityId, World};
+
+use super::clip::{AnimationClip, Animations};
+use super::pose::Pose;
+use super::state_machine::{AnimationParams, AnimationState, StateMachine};
+
+/// A clip played on top of the state machine, e.g. a wave or breathing.
+/// It adds its difference from its own first frame, so it only moves
+/// what it animates.
+#[derive(Clone, Debug)]
+pub struct AdditiveLayer {
+    pub clip: usize,
+    pub weight: f32,
+    pub speed: f32,
+    pub looping: bool,
+    time: f32,
+}
+
+impl AdditiveLayer {
+    pub fn new(clip: usize, weight: f32) -> Self {
+        Self {
+            clip,
+            weight,
+            speed: 1.0,
+            looping: true,
+            time: 0.0,
+        }
+    }
+}
+
+/// A state being faded out.
+#[derive(Clone, Debug)]
+struct Fade {
+    state: usize,
+    time: f32,
+    elapsed: f32,
+    duration: f32,
+}
+
+/// Advances `time` by `dt` through a clip, wrapping or holding the end.
+fn advance(time: f32, dt: f32, clip: &AnimationClip, looping: bool) -> f32 {
+    let time = time + dt;
+
+    if clip.duration <= 0.0 {
+        0.0
+    } else if looping {
+        time.rem_euclid(clip.duration)
+    } else {
+        time.clamp(0.0, clip.duration)
+    }
+}
+
+/* =========================================================
+   ANIMATOR
+   ========================================================= */
+
+/// Component next to a model's `Animations`: plays its clips through a
+/// state machine, crossfading between states, with additive layers on
+/// top, and poses the animated entities (a part-built model's nodes or
+/// a skinned mesh's joints) every `animate`.
+#[derive(Clone, Debug)]
+pub struct Animator {
+    pub machine: StateMachine,
+    pub params: AnimationParams,
+    /// Whether `animate` feeds it the local character's params.
+    pub follow_character: bool,
+    pub layers: Vec<AdditiveLayer>,
+
+    state: usize,
+    time: f32,
+    fade: Option<Fade>,
+    previous: AnimationParams,
+    /// Every animated entity at rest; clips sample into a copy.
+    rest: Pose,
+}
+
+impl Animator {
+    pub fn new(machine: StateMachine, clips: &Animations, world: &World) -> Self {
+        let rest = Pose::capture(world, clips.clips.iter().flat_map(AnimationClip::targets));
+
+        Self {
+            state: machine.initial,
+            machine,
+            params: AnimationParams::default(),
+            follow_character: false,
+            layers: Vec::new(),
+            time: 0.0,
+            fade: None,
+            previous: AnimationParams::default(),
+            rest,
+        }
+    }
+
+    /// The locomotion state machine following the local character when
+    /// the clips have an idle, else the first clip on a loop.
+    pub fn for_clips(clips: &Animations, world: &World) -> Option<Self> {
+        match StateMachine::locomotion(clips) {
+            Some(machine) => Some(Self::new(machine, clips, world).following_character()),
+            None if !clips.clips.is_empty() => Some(Self::new(StateMachine::single(0), clips, world)),
+            None => None,
+        }
+    }
+
+    pub fn following_character(mut self) -> Self {
+        self.follow_character = true;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: AdditiveLayer) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    pub fn state(&self) -> &AnimationState {
+        &self.machine.states[self.state]
+    }
+
+    /// Switches to the named state (fading over `fade` seconds), whatever
+    /// the transitions say. Returns false if there is no such state.
+    pub fn play(&mut self, name: &str, fade: f32) -> bool {
+        match self.machine.find(name) {
+            Some(state) => {
+                self.enter(state, fade);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn enter(&mut self, state: usize, fade: f32) {
+        // a transition mid-fade drops the older state
+        self.fade = (fade > 0.0).then_some(Fade {
+            state: self.state,
+            time: self.time,
+            elapsed: 0.0,
+            duration: fade,
+        });
+        self.state = state;
+        self.time = 0.0;
+    }
+
+    /// Runs the state machine and clocks for `dt` and returns the pose.
+    pub fn update(&mut self, dt: f32, clips: &Animations) -> Pose {
+        if let Some(t) = self.machine.next(self.state, &self.params, &self.previous) {
+            let (to, fade) = (t.to, t.fade);
+            self.enter(to, fade);
+        }
+        self.previous = self.params;
+
+        let mut pose = self.rest.clone();
+        let Some(clip) = clips.clips.get(self.state().clip) else {
+            return pose;
+        };
+
+        let state = &self.machine.states[self.state];
+        self.time = advance(self.time, dt * state.rate(&self.params), clip, state.looping);
+        clip.sample(self.time, &mut pose);
+
+        if let Some(fade) = &mut self.fade {
+            let old = &self.machine.states[fade.state];
+            fade.elapsed += dt;
+
+            if let Some(old_clip) = clips.clips.get(old.clip).filter(|_| fade.elapsed < fade.duration) {
+                fade.time = advance(fade.time, dt * old.rate(&self.params), old_clip, old.looping);
+
+                let mut from = self.rest.clone();
+                old_clip.sample(fade.time, &mut from);
+                from.blend(&pose, fade.elapsed / fade.duration);
+                pose = from;
+            } else {
+                self.fade = None;
+            }
+        }
+
+        for layer in &mut self.layers {
+            let Some(clip) = clips.clips.get(layer.clip) else { continue };
+            layer.time = advance(layer.time, dt * layer.speed, clip, layer.looping);
+
+            let mut reference = self.rest.clone();
+            clip.sample(0.0, &mut reference);
+            let mut additive = self.rest.clone();
+            clip.sample(layer.time, &mut additive);
+
+            pose.add(&additive, &reference, layer.weight);
+        }
+
+        pose
+    }
+}
+
+/// Advances every `Animator` by `dt` and poses its entities. Animators
+/// following the character take `character` as their params first.
+pub fn animate(world: &mut World, dt: f32, character: &AnimationParams) {
+    let roots: Vec<EntityId> = world.query::<Animator>().into_iter().map(|(id, _)| id).collect();
+
+    for root in roots {
+        let Some(mut animator) = world.remove::<Animator>(root) else { continue };
+        if animator.follow_character {
+            animator.params = *character;
+        }
+
+        if let Some(clips) = world.get::<Animations>(root) {
+            let pose = animator.update(dt, clips);
+            pose.apply(world);
+        }
+        world.insert(root, animator);
+    }
+}
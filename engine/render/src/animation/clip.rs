@@ -0,0 +1,289 @@
+use std::ops::{Add, Mul};
+
+use engine_core::EntityId;
+use glam::{Quat, Vec3};
+
+use super::pose::Pose;
+
+/// How values between two keyframes are found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Hold the previous keyframe.
+    Step,
+    /// Lerp (slerp for rotations).
+    Linear,
+    /// Hermite spline; each keyframe stores an in-tangent, the value and
+    /// an out-tangent, in that order.
+    CubicSpline,
+}
+
+/// Keyframe values of one channel, one per time (three for cubic
+/// splines). Morph weights are flattened: one value per target per key.
+#[derive(Clone, Debug)]
+pub enum Keyframes {
+    Translation(Vec<Vec3>),
+    Rotation(Vec<Quat>),
+    Scale(Vec<Vec3>),
+    Weights(Vec<f32>),
+}
+
+/// Animates one property of one entity.
+#[derive(Clone, Debug)]
+pub struct Channel {
+    pub target: EntityId,
+    pub interpolation: Interpolation,
+    /// Seconds, increasing.
+    pub times: Vec<f32>,
+    pub keyframes: Keyframes,
+}
+
+impl Channel {
+    /// Keys either side of `time` and how far between them it is; the
+    /// first and last key hold before and after the clip.
+    fn segment(&self, time: f32) -> (usize, usize, f32) {
+        let last = self.times.len().saturating_sub(1);
+        let next = self.times.partition_point(|&t| t <= time);
+
+        if next == 0 {
+            (0, 0, 0.0)
+        } else if next > last {
+            (last, last, 0.0)
+        } else {
+            let (t0, t1) = (self.times[next - 1], self.times[next]);
+            (next - 1, next, (time - t0) / (t1 - t0).max(1e-6))
+        }
+    }
+
+    /// Value of a channel at `time`, reading its `n`th keyframe value
+    /// (three per key for cubic splines) through `value`.
+    fn sample<T>(&self, value: impl Fn(usize) -> Option<T>, time: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T>
+    where
+        T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+    {
+        let (a, b, t) = self.segment(time);
+
+        match self.interpolation {
+            Interpolation::Step => value(a),
+            Interpolation::Linear => Some(lerp(value(a)?, value(b)?, t)),
+            Interpolation::CubicSpline => {
+                let (v0, out0) = (value(a * 3 + 1)?, value(a * 3 + 2)?);
+                let (in1, v1) = (value(b * 3)?, value(b * 3 + 1)?);
+                if a == b {
+                    return Some(v0);
+                }
+
+                // tangents are per second; scale them to the segment
+                let dt = self.times[b] - self.times[a];
+                let (t2, t3) = (t * t, t * t * t);
+                Some(
+                    v0 * (2.0 * t3 - 3.0 * t2 + 1.0)
+                        + out0 * ((t3 - 2.0 * t2 + t) * dt)
+                        + v1 * (3.0 * t2 - 2.0 * t3)
+                        + in1 * ((t3 - t2) * dt),
+                )
+            }
+        }
+    }
+
+    /// Writes this channel's value at `time` into its target's entry of
+    /// `pose`; targets the pose doesn't hold are left alone.
+    pub fn sample_into(&self, time: f32, pose: &mut Pose) {
+        let target = &self.target;
+
+        match &self.keyframes {
+            Keyframes::Translation(v) => {
+                let value = self.sample(|i| v.get(i).copied(), time, Vec3::lerp);
+                if let (Some(t), Some(p)) = (pose.transforms.get_mut(target), value) {
+                    t.position = p;
+                }
+            }
+            Keyframes::Rotation(v) => {
+                let value = self.sample(|i| v.get(i).copied(), time, Quat::slerp);
+                if let (Some(t), Some(r)) = (pose.transforms.get_mut(target), value) {
+                    t.rotation = r.normalize();
+                }
+            }
+            Keyframes::Scale(v) => {
+                let value = self.sample(|i| v.get(i).copied(), time, Vec3::lerp);
+                if let (Some(t), Some(s)) = (pose.transforms.get_mut(target), value) {
+                    t.scale = s;
+                }
+            }
+            Keyframes::Weights(v) => {
+                if let Some(weights) = pose.weights.get_mut(target) {
+                    let targets = self.weight_targets(v);
+                    if weights.len() < targets {
+                        weights.resize(targets, 0.0);
+                    }
+                    self.sample_weights(v, time, weights);
+                }
+            }
+        }
+    }
+
+    /// Morph targets in a flattened weights channel.
+    fn weight_targets(&self, values: &[f32]) -> usize {
+        let per_key = match self.interpolation {
+            Interpolation::CubicSpline => 3,
+            _ => 1,
+        };
+        values.len() / (self.times.len() * per_key).max(1)
+    }
+
+    /// Samples each target's weight from the flattened keys into
+    /// `weights`; targets past its end are skipped.
+    fn sample_weights(&self, values: &[f32], time: f32, weights: &mut [f32]) {
+        let targets = self.weight_targets(values);
+
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+        for (i, weight) in weights.iter_mut().enumerate().take(targets) {
+            if let Some(w) = self.sample(|n| values.get(n * targets + i).copied(), time, lerp) {
+                *weight = w;
+            }
+        }
+    }
+}
+
+/// Keyframed animation of an entity hierarchy (an imported model's
+/// nodes), e.g. "idle" or "walk".
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    pub name: String,
+    pub channels: Vec<Channel>,
+    /// Time of the last keyframe of any channel.
+    pub duration: f32,
+}
+
+impl AnimationClip {
+    pub fn new(name: &str, channels: Vec<Channel>) -> Self {
+        let duration = channels
+            .iter()
+            .filter_map(|c| c.times.last().copied())
+            .fold(0.0, f32::max);
+
+        Self {
+            name: name.to_string(),
+            channels,
+            duration,
+        }
+    }
+
+    /// Entities any channel animates.
+    pub fn targets(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.channels.iter().map(|c| c.target)
+    }
+
+    /// Overwrites `pose` with the clip at `time` (seconds from its start);
+    /// whatever the clip doesn't animate keeps its value.
+    pub fn sample(&self, time: f32, pose: &mut Pose) {
+        for channel in &self.channels {
+            channel.sample_into(time, pose);
+        }
+    }
+}
+
+/// Component on a model's root entity: the clips that came with it.
+#[derive(Clone, Debug, Default)]
+pub struct Animations {
+    pub clips: Vec<AnimationClip>,
+}
+
+impl Animations {
+    pub fn find(&self, name: &str) -> Option<&AnimationClip> {
+        self.clips.iter().find(|c| c.name == name)
+    }
+
+    /// Index of the first clip whose name contains `name`, ignoring case
+    /// ("Walk", "mixamo.com|walking" and "walk_forward" all match "walk").
+    pub fn position_like(&self, name: &str) -> Option<usize> {
+        let name = name.to_lowercase();
+        self.clips.iter().position(|c| c.name.to_lowercase().contains(&name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine_core::Transform;
+
+    fn channel(interpolation: Interpolation, times: &[f32], keyframes: Keyframes) -> Channel {
+        Channel {
+            target: EntityId(1),
+            interpolation,
+            times: times.to_vec(),
+            keyframes,
+        }
+    }
+
+    fn pose() -> Pose {
+        let mut pose = Pose::default();
+        pose.transforms.insert(EntityId(1), Transform::default());
+        pose.weights.insert(EntityId(1), Vec::new());
+        pose
+    }
+
+    fn x_at(channel: &Channel, time: f32) -> f32 {
+        let mut pose = pose();
+        channel.sample_into(time, &mut pose);
+        pose.transforms[&EntityId(1)].position.x
+    }
+
+    #[test]
+    fn step_holds_the_previous_key() {
+        let keys = Keyframes::Translation(vec![Vec3::ZERO, Vec3::X * 10.0, Vec3::X * 20.0]);
+        let c = channel(Interpolation::Step, &[0.0, 1.0, 2.0], keys);
+
+        assert_eq!(x_at(&c, -1.0), 0.0);
+        assert_eq!(x_at(&c, 0.99), 0.0);
+        assert_eq!(x_at(&c, 1.0), 10.0);
+        assert_eq!(x_at(&c, 1.5), 10.0);
+        assert_eq!(x_at(&c, 5.0), 20.0);
+    }
+
+    #[test]
+    fn linear_lerps_and_slerps() {
+        let keys = Keyframes::Translation(vec![Vec3::ZERO, Vec3::X * 10.0, Vec3::X * 20.0]);
+        let c = channel(Interpolation::Linear, &[0.0, 1.0, 2.0], keys);
+        assert!((x_at(&c, 0.25) - 2.5).abs() < 1e-5);
+        assert!((x_at(&c, 1.5) - 15.0).abs() < 1e-5);
+        assert_eq!(x_at(&c, 3.0), 20.0);
+
+        let quarter = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+        let c = channel(Interpolation::Linear, &[0.0, 1.0], Keyframes::Rotation(vec![Quat::IDENTITY, quarter]));
+        let mut pose = pose();
+        c.sample_into(0.5, &mut pose);
+        let eighth = Quat::from_rotation_y(std::f32::consts::FRAC_PI_4);
+        assert!(pose.transforms[&EntityId(1)].rotation.abs_diff_eq(eighth, 1e-5));
+    }
+
+    #[test]
+    fn cubic_splines_follow_their_tangents() {
+        // in-tangent, value, out-tangent per key; flat tangents ease in and out
+        let flat = vec![Vec3::ZERO, Vec3::ZERO, Vec3::ZERO, Vec3::ZERO, Vec3::X * 2.0, Vec3::ZERO];
+        let c = channel(Interpolation::CubicSpline, &[0.0, 2.0], Keyframes::Translation(flat));
+        assert!((x_at(&c, 0.5) - 0.3125).abs() < 1e-5);
+        assert!((x_at(&c, 1.0) - 1.0).abs() < 1e-5);
+        assert_eq!(x_at(&c, 2.0), 2.0);
+        assert_eq!(x_at(&c, 9.0), 2.0);
+
+        // tangents of 1 m/s over a 2 m rise in 2 s: a straight line
+        let steady = vec![Vec3::ZERO, Vec3::ZERO, Vec3::X, Vec3::X, Vec3::X * 2.0, Vec3::ZERO];
+        let c = channel(Interpolation::CubicSpline, &[0.0, 2.0], Keyframes::Translation(steady));
+        assert!((x_at(&c, 0.5) - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn weights_are_sampled_per_target() {
+        // two targets crossing over
+        let c = channel(Interpolation::Linear, &[0.0, 1.0], Keyframes::Weights(vec![0.0, 1.0, 1.0, 0.0]));
+
+        let mut pose = pose();
+        c.sample_into(0.25, &mut pose);
+        assert_eq!(pose.weights[&EntityId(1)], [0.25, 0.75]);
+
+        // extra weights are left alone
+        let mut weights = [0.0, 0.0, 0.5];
+        c.sample_weights(&[0.0, 1.0, 1.0, 0.0], 1.0, &mut weights);
+        assert_eq!(weights, [1.0, 0.0, 0.5]);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod animator;
+pub mod clip;
+pub mod pose;
+pub mod state_machine;
+
+pub use animator::{animate, AdditiveLayer, Animator};
+pub use clip::{AnimationClip, Animations, Channel, Interpolation, Keyframes};
+pub use pose::Pose;
+pub use state_machine::{AnimationParams, AnimationState, Condition, StateMachine, Transition};
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use engine_core::{EntityId, Transform, World};
+use glam::{Quat, Vec3};
+
+use crate::renderer::mesh::morph::MorphWeights;
+
+/// Local transforms (and morph weights) of the animated entities of one
+/// hierarchy. Clips are sampled into a copy of the rest pose, so anything
+/// a clip leaves out stays at rest and poses of different clips always
+/// blend entry for entry.
+#[derive(Clone, Debug, Default)]
+pub struct Pose {
+    pub transforms: HashMap<EntityId, Transform>,
+    /// Of the entities with `MorphWeights`.
+    pub weights: HashMap<EntityId, Vec<f32>>,
+}
+
+impl Pose {
+    /// The current local transforms and morph weights of `entities`.
+    pub fn capture(world: &World, entities: impl IntoIterator<Item = EntityId>) -> Self {
+        let entities: Vec<EntityId> = entities.into_iter().filter(|&id| world.contains(id)).collect();
+
+        Self {
+            transforms: entities.iter().map(|&id| (id, world.transform(id))).collect(),
+            weights: entities
+                .iter()
+                .filter_map(|&id| world.get::<MorphWeights>(id).map(|w| (id, w.weights.clone())))
+                .collect(),
+        }
+    }
+
+    /// Moves this pose towards `other` by `weight` (0 keeps it, 1 takes
+    /// `other`).
+    pub fn blend(&mut self, other: &Pose, weight: f32) {
+        for (id, t) in &mut self.transforms {
+            if let Some(o) = other.transforms.get(id) {
+                t.position = t.position.lerp(o.position, weight);
+                t.rotation = t.rotation.slerp(o.rotation, weight);
+                t.scale = t.scale.lerp(o.scale, weight);
+            }
+        }
+
+        for (id, w) in &mut self.weights {
+            if let Some(o) = other.weights.get(id) {
+                for (w, o) in w.iter_mut().zip(o) {
+                    *w += (o - *w) * weight;
+                }
+            }
+        }
+    }
+
+    /// Layers the difference between `additive` and `reference` on top,
+    /// scaled by `weight`. Rotations are added in each entity's local frame.
+    pub fn add(&mut self, additive: &Pose, reference: &Pose, weight: f32) {
+        for (id, t) in &mut self.transforms {
+            let (Some(a), Some(r)) = (additive.transforms.get(id), reference.transforms.get(id)) else {
+                continue;
+            };
+
+            t.position += (a.position - r.position) * weight;
+            t.rotation = (t.rotation * Quat::IDENTITY.slerp(r.rotation.inverse() * a.rotation, weight)).normalize();
+            t.scale *= Vec3::ONE.lerp(a.scale / r.scale.max(Vec3::splat(1e-6)), weight);
+        }
+
+        for (id, w) in &mut self.weights {
+            let (Some(a), Some(r)) = (additive.weights.get(id), reference.weights.get(id)) else {
+                continue;
+            };
+
+            for (w, (a, r)) in w.iter_mut().zip(a.iter().zip(r)) {
+                *w += (a - r) * weight;
+            }
+        }
+    }
+
+    /// Sets the local transform and morph weights of every entity in the
+    /// pose.
+    pub fn apply(&self, world: &mut World) {
+        for (&id, &t) in &self.transforms {
+            if world.contains(id) {
+                world.set_transform(id, t);
+            }
+        }
+
+        for (&id, weights) in &self.weights {
+            if let Some(morph) = world.get_mut::<MorphWeights>(id) {
+                morph.weights.clone_from(weights);
+            }
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use super::clip::Animations;
+use crate::locomotion::CharacterController;
+
+/// What a character is doing, for the state machine to react to.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AnimationParams {
+    /// Horizontal speed, m/s.
+    pub speed: f32,
+    /// Upwards speed, m/s (negative when falling).
+    pub vertical_speed: f32,
+    pub grounded: bool,
+    /// Jumps since the character last stood on the ground.
+    pub jump_count: u8,
+}
+
+impl AnimationParams {
+    pub fn from_controller(controller: &CharacterController) -> Self {
+        Self {
+            speed: controller.horizontal_speed(),
+            vertical_speed: controller.velocity.y,
+            grounded: controller.grounded,
+            jump_count: controller.jump_count,
+        }
+    }
+}
+
+/// Part of a transition's test; all of them must hold.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Condition {
+    SpeedAbove(f32),
+    SpeedBelow(f32),
+    Grounded,
+    Airborne,
+    /// Moving down (vertical speed below zero).
+    Falling,
+    /// The jump count went up since the last update (double jumps too).
+    Jumped,
+}
+
+impl Condition {
+    pub fn holds(&self, params: &AnimationParams, previous: &AnimationParams) -> bool {
+        match *self {
+            Condition::SpeedAbove(s) => params.speed > s,
+            Condition::SpeedBelow(s) => params.speed <= s,
+            Condition::Grounded => params.grounded,
+            Condition::Airborne => !params.grounded,
+            Condition::Falling => params.vertical_speed < 0.0,
+            Condition::Jumped => params.jump_count > previous.jump_count,
+        }
+    }
+}
+
+/// One clip the machine can be in.
+#[derive(Clone, Debug)]
+pub struct AnimationState {
+    pub name: String,
+    /// Index into the model's `Animations`.
+    pub clip: usize,
+    pub speed: f32,
+    pub looping: bool,
+    /// Ground speed the clip was authored at; when set, playback scales
+    /// with the character's speed so feet don't slide.
+    pub ground_speed: Option<f32>,
+}
+
+impl AnimationState {
+    pub fn new(name: &str, clip: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            clip,
+            speed: 1.0,
+            looping: true,
+            ground_speed: None,
+        }
+    }
+
+    pub fn once(mut self) -> Self {
+        self.looping = false;
+        self
+    }
+
+    pub fn with_ground_speed(mut self, speed: f32) -> Self {
+        self.ground_speed = Some(speed);
+        self
+    }
+
+    /// Playback rate for `params`.
+    pub fn rate(&self, params: &AnimationParams) -> f32 {
+        match self.ground_speed {
+            Some(authored) => self.speed * params.speed / authored.max(0.01),
+            None => self.speed,
+        }
+    }
+}
+
+/// Moves to `to` when every condition holds, crossfading over `fade`
+/// seconds. `from: None` applies in any state but `to` itself, except
+/// that a `Jumped` transition restarts its state (a double jump).
+#[derive(Clone, Debug)]
+pub struct Transition {
+    pub from: Option<usize>,
+    pub to: usize,
+    pub conditions: Vec<Condition>,
+    pub fade: f32,
+}
+
+/* =========================================================
+   STATE MACHINE
+   ========================================================= */
+
+/// States and the transitions between them; the first transition that
+/// passes wins, so list the urgent ones (jumping) first.
+#[derive(Clone, Debug, Default)]
+pub struct StateMachine {
+    pub states: Vec<AnimationState>,
+    pub transitions: Vec<Transition>,
+    pub initial: usize,
+}
+
+impl StateMachine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Just `clip`, looping.
+    pub fn single(clip: usize) -> Self {
+        Self {
+            states: vec![AnimationState::new("default", clip)],
+            transitions: Vec::new(),
+            initial: 0,
+        }
+    }
+
+    pub fn add_state(&mut self, state: AnimationState) -> usize {
+        self.states.push(state);
+        self.states.len() - 1
+    }
+
+    pub fn add_transition(&mut self, from: Option<usize>, to: usize, conditions: &[Condition], fade: f32) {
+        self.transitions.push(Transition {
+            from,
+            to,
+            conditions: conditions.to_vec(),
+            fade,
+        });
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|s| s.name == name)
+    }
+
+    /// The transition to take from `current`, if any.
+    pub fn next(&self, current: usize, params: &AnimationParams, previous: &AnimationParams) -> Option<&Transition> {
+        self.transitions.iter().find(|t| {
+            (t.to != current || t.conditions.contains(&Condition::Jumped))
+                && t.from.is_none_or(|from| from == current)
+                && t.conditions.iter().all(|c| c.holds(params, previous))
+        })
+    }
+
+    /// Idle, walk, run, jump and fall, from clips named like that. Needs
+    /// an idle clip; a missing walk or run stands in for the other, and
+    /// likewise jump and fall.
+    pub fn locomotion(clips: &Animations) -> Option<Self> {
+        use Condition::*;
+
+        // authored pace of walk and run clips, and the speed between them
+        const WALK_SPEED: f32 = 1.4;
+        const RUN_SPEED: f32 = 3.5;
+        const RUNNING: f32 = 2.5;
+        const MOVING: f32 = 0.1;
+
+        let idle = clips.position_like("idle")?;
+        let walk = clips.position_like("walk").map(|c| (c, WALK_SPEED));
+        let run = clips.position_like("run").map(|c| (c, RUN_SPEED));
+        let (jump, fall) = (clips.position_like("jump"), clips.position_like("fall"));
+
+        let mut machine = Self::new();
+        machine.initial = machine.add_state(AnimationState::new("idle", idle));
+
+        let mut gait = |name: &str, clip: Option<(usize, f32)>| {
+            clip.map(|(c, speed)| machine.add_state(AnimationState::new(name, c).with_ground_speed(speed)))
+        };
+        let walk_state = gait("walk", walk.or(run));
+        let run_state = gait("run", run.or(walk));
+
+        let jump_state = jump.or(fall).map(|c| machine.add_state(AnimationState::new("jump", c).once()));
+        let fall_state = match (fall, jump) {
+            (Some(c), _) => Some(machine.add_state(AnimationState::new("fall", c))),
+            // hold the end of the jump
+            (None, Some(c)) => Some(machine.add_state(AnimationState::new("fall", c).once())),
+            (None, None) => None,
+        };
+
+        if let Some(jump) = jump_state {
+            machine.add_transition(None, jump, &[Jumped], 0.1);
+        }
+        if let Some(fall) = fall_state {
+            machine.add_transition(None, fall, &[Airborne, Falling], 0.3);
+        }
+        machine.add_transition(None, machine.initial, &[Grounded, SpeedBelow(MOVING)], 0.25);
+        if let Some(walk) = walk_state {
+            machine.add_transition(None, walk, &[Grounded, SpeedAbove(MOVING), SpeedBelow(RUNNING)], 0.25);
+        }
+        if let Some(run) = run_state {
+            machine.add_transition(None, run, &[Grounded, SpeedAbove(RUNNING)], 0.25);
+        }
+
+        Some(machine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::clip::AnimationClip;
+    use glam::Vec3;
+
+    fn clips(names: &[&str]) -> Animations {
+        Animations {
+            clips: names.iter().map(|n| AnimationClip::new(n, Vec::new())).collect(),
+        }
+    }
+
+    fn params(speed: f32, grounded: bool, vertical_speed: f32, jump_count: u8) -> AnimationParams {
+        AnimationParams {
+            speed,
+            vertical_speed,
+            grounded,
+            jump_count,
+        }
+    }
+
+    /// Name of the state `machine` moves to from `current`.
+    fn next<'a>(machine: &'a StateMachine, current: &str, now: AnimationParams, before: AnimationParams) -> Option<&'a str> {
+        let current = machine.find(current).unwrap();
+        machine.next(current, &now, &before).map(|t| machine.states[t.to].name.as_str())
+    }
+
+    #[test]
+    fn locomotion_needs_an_idle_clip() {
+        assert!(StateMachine::locomotion(&clips(&["walk", "run"])).is_none());
+        assert!(StateMachine::locomotion(&clips(&["Idle"])).is_some());
+    }
+
+    #[test]
+    fn ground_speed_picks_idle_walk_or_run() {
+        let m = StateMachine::locomotion(&clips(&["Idle", "mixamo.com|Walking", "run", "jump", "fall"])).unwrap();
+        let standing = params(0.0, true, 0.0, 0);
+
+        assert_eq!(next(&m, "idle", standing, standing), None);
+        assert_eq!(next(&m, "idle", params(1.5, true, 0.0, 0), standing), Some("walk"));
+        assert_eq!(next(&m, "walk", params(4.0, true, 0.0, 0), standing), Some("run"));
+        assert_eq!(next(&m, "run", params(1.5, true, 0.0, 0), standing), Some("walk"));
+        assert_eq!(next(&m, "run", standing, standing), Some("idle"));
+
+        assert_eq!(m.states[m.find("walk").unwrap()].clip, 1);
+        assert_eq!(m.states[m.find("run").unwrap()].rate(&params(7.0, true, 0.0, 0)), 2.0);
+    }
+
+    #[test]
+    fn half_pushed_sticks_walk() {
+        let m = StateMachine::locomotion(&clips(&["idle", "walk", "run"])).unwrap();
+        let standing = AnimationParams::default();
+
+        let mut controller = CharacterController::new();
+        controller.walk(0.1, Vec3::Z * 0.5);
+        assert_eq!(next(&m, "idle", AnimationParams::from_controller(&controller), standing), Some("walk"));
+
+        controller.walk(0.1, Vec3::Z);
+        assert_eq!(next(&m, "idle", AnimationParams::from_controller(&controller), standing), Some("run"));
+    }
+
+    #[test]
+    fn jumps_restart_the_jump_and_falls_follow() {
+        let m = StateMachine::locomotion(&clips(&["idle", "walk", "run", "jump", "fall"])).unwrap();
+
+        let running = params(4.0, true, 0.0, 0);
+        let jumped = params(4.0, false, 5.0, 1);
+        assert_eq!(next(&m, "run", jumped, running), Some("jump"));
+        assert_eq!(next(&m, "jump", jumped, jumped), None);
+
+        // the double jump plays the jump again
+        let again = params(4.0, false, 5.0, 2);
+        assert_eq!(next(&m, "jump", again, jumped), Some("jump"));
+
+        let falling = params(4.0, false, -1.0, 2);
+        assert_eq!(next(&m, "jump", falling, again), Some("fall"));
+        assert_eq!(next(&m, "fall", params(4.0, true, 0.0, 0), falling), Some("run"));
+    }
+
+    #[test]
+    fn missing_clips_stand_in_for_each_other() {
+        let m = StateMachine::locomotion(&clips(&["idle", "run", "fall"])).unwrap();
+        let walk = m.find("walk").unwrap();
+        let jump = m.find("jump").unwrap();
+
+        assert_eq!(m.states[walk].clip, 1);
+        assert_eq!(m.states[jump].clip, 2);
+        assert!(!m.states[jump].looping);
+        assert!(m.states[m.find("fall").unwrap()].looping);
+    }
+}
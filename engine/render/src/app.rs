@@ -1,14 +1,22 @@
-use std::collections::HashSet;
 use std::time::Instant;
 
+use engine_core::{RenderLayers, Transform};
 use glam::Vec3;
+
 use winit::{
     event::*,
     event_loop::{ControlFlow, EventLoop},
     window::{CursorGrabMode, WindowBuilder},
 };
 
+use crate::locomotion::{heading_of, LocomotionInput};
 use crate::renderer::Renderer;
+use crate::settings::UserSettings;
+use crate::ui::{PointerSource, UiPointer};
+use crate::xr::{DesktopPoseProvider, PoseProvider};
+
+#[cfg(any(windows, target_os = "linux"))]
+use crate::xr::openxr::{OpenXrBackend, XrOptions};
 
 pub fn run() {
     let event_loop = EventLoop::new();
@@ -17,113 +25,256 @@ pub fn run() {
         .build(&event_loop)
         .unwrap();
 
+    /* ================= XR (opt-in) ================= */
+
+    #[cfg(any(windows, target_os = "linux"))]
+    let (mut xr, mut renderer) = {
+        let args: Vec<String> = std::env::args().collect();
+        let options = XrOptions {
+            headless: args.iter().any(|a| a == "--xr-headless"),
+            ..Default::default()
+        };
+        let wanted = options.headless || args.iter().any(|a| a == "--xr");
+
+        let backend = wanted.then(|| OpenXrBackend::new(&options));
+        if let Some(Ok((xr, _))) = &backend {
+            for warning in &xr.warnings {
+                println!("OpenXR: {}", warning);
+            }
+        }
+
+        match backend {
+            Some(Ok((xr, Some(device)))) => (Some(xr), Renderer::with_device(&window, device)),
+            Some(Ok((xr, None))) => (Some(xr), pollster::block_on(Renderer::new(&window))),
+            Some(Err(e)) => {
+                println!("OpenXR unavailable ({}), falling back to desktop mode", e);
+                (None, pollster::block_on(Renderer::new(&window)))
+            }
+            None => (None, pollster::block_on(Renderer::new(&window))),
+        }
+    };
+
+    #[cfg(not(any(windows, target_os = "linux")))]
     let mut renderer = pollster::block_on(Renderer::new(&window));
 
-    let mut pressed = HashSet::new();
-    let mut last_frame = Instant::now();
+    /* ================= USER SETTINGS ================= */
 
-    let mut jump_requested = false;
+    let user = user_name();
+    let (settings, settings_unreadable) = match UserSettings::load_for(&user) {
+        Ok(settings) => (settings, false),
+        Err(e) => {
+            println!("Ignoring settings for '{}': {}", user, e);
+            (UserSettings::default(), true)
+        }
+    };
+    for warning in &settings.warnings {
+        println!("Settings for '{}': {}", user, warning);
+    }
+    renderer.locomotion.settings = settings.locomotion;
+    renderer.boundary = settings.boundary;
 
-    let mut mouse_dx = 0.0f32;
-    let mut mouse_dy = 0.0f32;
-    let mut scroll = 0.0f32;
-    let mut middle_mouse_held = false;
+    /* ================= MODELS ================= */
+
+    // next to the table, facing the spawn point
+    for path in model_paths() {
+        let placement = Transform::from_position(Vec3::new(1.5, 0.0, 1.0));
+        match renderer.load_model(&path, placement) {
+            Ok(model) => {
+                for warning in &model.warnings {
+                    println!("{}: {}", path, warning);
+                }
+            }
+            Err(e) => println!("Failed to load {}: {}", path, e),
+        }
+    }
+
+    if let Some(path) = avatar_path() {
+        if path.ends_with(".json") {
+            if let Err(e) = renderer.load_avatar_definition(&path) {
+                println!("Failed to load avatar {}: {}", path, e);
+            }
+        } else {
+            match renderer.load_avatar(&path) {
+                Ok(vrm) => {
+                    for warning in &vrm.model.warnings {
+                        println!("{}: {}", path, warning);
+                    }
+                }
+                Err(e) => println!("Failed to load avatar {}: {}", path, e),
+            }
+        }
+    }
+
+    let mut desktop = DesktopPoseProvider::new();
+    #[cfg(any(windows, target_os = "linux"))]
+    let mut jump_held = false;
+    let mut last_frame = Instant::now();
 
     event_loop.run(move |event, _, control_flow| {
         *control_flow = ControlFlow::Poll;
 
         match event {
-            Event::WindowEvent { event, .. } => match event {
-                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+            Event::WindowEvent { event, .. } => {
+                let was_held = desktop.middle_mouse_held();
+                desktop.handle_window_event(&event);
 
-                WindowEvent::Resized(size) => {
-                    renderer.resize(size.width, size.height);
+                if desktop.middle_mouse_held() != was_held {
+                    if desktop.middle_mouse_held() {
+                        let _ = window.set_cursor_grab(CursorGrabMode::Confined);
+                        window.set_cursor_visible(false);
+                    } else {
+                        let _ = window.set_cursor_grab(CursorGrabMode::None);
+                        window.set_cursor_visible(true);
+                    }
                 }
 
-                WindowEvent::KeyboardInput { input, .. } => {
-                    if let Some(key) = input.virtual_keycode {
-                        match input.state {
-                            ElementState::Pressed => {
-                                if key == VirtualKeyCode::Space {
-                                    jump_requested = true;
-                                }
-                                pressed.insert(key);
-                            }
-                            ElementState::Released => {
-                                pressed.remove(&key);
-                            }
+                match event {
+                    WindowEvent::CloseRequested => {
+                        #[cfg(any(windows, target_os = "linux"))]
+                        if let Some(xr) = &mut xr {
+                            // let the runtime stop the session, then exit
+                            xr.request_exit();
+                            return;
                         }
+
+                        *control_flow = ControlFlow::Exit;
                     }
-                }
 
-                WindowEvent::MouseInput { state, button, .. } => {
-                    if button == MouseButton::Middle {
-                        middle_mouse_held = state == ElementState::Pressed;
+                    WindowEvent::Resized(size) => {
+                        renderer.resize(size.width, size.height);
+                    }
 
-                        if middle_mouse_held {
-                            let _ = window.set_cursor_grab(CursorGrabMode::Confined);
-                            window.set_cursor_visible(false);
+                    // F3 shows/hides the debug layer in the desktop window
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::F3),
+                                ..
+                            },
+                        ..
+                    } => {
+                        let layers = renderer.desktop_layers;
+                        renderer.desktop_layers = if layers.contains(RenderLayers::DEBUG) {
+                            layers.without(RenderLayers::DEBUG)
                         } else {
-                            let _ = window.set_cursor_grab(CursorGrabMode::None);
-                            window.set_cursor_visible(true);
-                        }
+                            layers.with(RenderLayers::DEBUG)
+                        };
                     }
-                }
 
-                WindowEvent::MouseWheel { delta, .. } => {
-                    let amount = match delta {
-                        MouseScrollDelta::LineDelta(_, y) => y,
-                        MouseScrollDelta::PixelDelta(p) => p.y as f32 * 0.01,
-                    };
-                    scroll += amount;
+                    // F4 tints the world by shadow cascade
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::F4),
+                                ..
+                            },
+                        ..
+                    } => {
+                        renderer.shadows.debug_cascades = !renderer.shadows.debug_cascades;
+                    }
+
+                    _ => {}
                 }
+            }
 
-                _ => {}
-            },
+            Event::DeviceEvent { event, .. } => {
+                desktop.handle_device_event(&event);
+            }
 
-            Event::DeviceEvent { event, .. } => match event {
-                DeviceEvent::MouseMotion { delta } => {
-                    if middle_mouse_held {
-                        mouse_dx += delta.0 as f32;
-                        mouse_dy += delta.1 as f32;
+            Event::MainEventsCleared => {
+                #[cfg(any(windows, target_os = "linux"))]
+                if let Some(backend) = &mut xr {
+                    if let Err(e) = backend.poll_events() {
+                        println!("OpenXR error: {}", e);
+                        *control_flow = ControlFlow::Exit;
+                        return;
+                    }
+                    if backend.exit_requested() {
+                        *control_flow = ControlFlow::Exit;
+                        return;
                     }
                 }
-                _ => {}
-            },
 
-            Event::MainEventsCleared => {
                 let now = Instant::now();
                 let dt = (now - last_frame).as_secs_f32();
                 last_frame = now;
 
-                let mut input = Vec3::ZERO;
-
-                if pressed.contains(&VirtualKeyCode::W) {
-                    input.z -= 1.0;
-                }
-                if pressed.contains(&VirtualKeyCode::S) {
-                    input.z += 1.0;
-                }
-                if pressed.contains(&VirtualKeyCode::A) {
-                    input.x -= 1.0;
-                }
-                if pressed.contains(&VirtualKeyCode::D) {
-                    input.x += 1.0;
+                if let Some(reloaded) = renderer.reload_avatar_definition(dt) {
+                    let path = renderer.avatar_file.as_ref().unwrap().path.display();
+                    match reloaded {
+                        Ok(name) => println!("Reloaded avatar \"{}\" from {}", name, path),
+                        Err(e) => println!("Keeping the current avatar, {} is invalid: {}", path, e),
+                    }
                 }
 
+                let (mouse_dx, mouse_dy, scroll) = desktop.take_mouse();
+
+                // the wheel scrolls a panel under the cursor instead of zooming
+                let over_ui = renderer.ui.is_pointing(PointerSource::Mouse);
+
                 renderer.camera.handle_mouse(
                     mouse_dx,
                     mouse_dy,
-                    scroll,
-                    middle_mouse_held,
+                    if over_ui { 0.0 } else { scroll },
+                    desktop.middle_mouse_held(),
                 );
 
-                mouse_dx = 0.0;
-                mouse_dy = 0.0;
-                scroll = 0.0;
+                let poses = desktop.sample(dt);
+
+                #[cfg(any(windows, target_os = "linux"))]
+                if let Some(backend) = &mut xr {
+                    // waits on the runtime, which paces the loop to the display
+                    let frame = backend.begin_frame().and_then(|timing| {
+                        if timing.is_some() {
+                            let poses = backend.sample(dt);
+                            let to_world = renderer.tracking_to_world();
 
-                renderer.update(dt, input, jump_requested);
-                jump_requested = false;
+                            let heading = heading_of(
+                                glam::Quat::from_mat4(&to_world) * poses.head.orientation,
+                            );
+                            let jump = poses.right.primary && !jump_held;
+                            jump_held = poses.right.primary;
+
+                            let input = LocomotionInput::from_poses(&poses, heading, to_world, jump);
+                            renderer.update(dt, &input, &poses);
+                            backend.end_frame(&mut renderer)?;
+                        }
+                        Ok(timing)
+                    });
+
+                    match frame {
+                        Ok(Some(_)) => {
+                            window.request_redraw();
+                            return;
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            // the frame's update may already have run
+                            println!("OpenXR frame error: {}", e);
+                            window.request_redraw();
+                            return;
+                        }
+                    }
+                }
+
+                if let Some(cursor) = desktop.cursor().filter(|_| !desktop.middle_mouse_held()) {
+                    renderer.set_mouse_pointer(UiPointer {
+                        source: PointerSource::Mouse,
+                        ray: renderer.mouse_ray(cursor),
+                        click: desktop.left_mouse_held(),
+                        scroll: if over_ui { scroll } else { 0.0 },
+                    });
+                }
+
+                let input = LocomotionInput::from_poses(
+                    &poses,
+                    renderer.camera.yaw,
+                    renderer.tracking_to_world(),
+                    desktop.take_jump(),
+                );
+                renderer.update(dt, &input, &poses);
 
                 window.request_redraw();
             }
@@ -132,7 +283,71 @@ pub fn run() {
                 renderer.render();
             }
 
+            // every way out (window closed, session ended, XR error) ends here
+            Event::LoopDestroyed => {
+                // a file that failed to load is kept aside, not overwritten
+                if settings_unreadable {
+                    match UserSettings::back_up(&user) {
+                        Ok(backup) => println!("Kept the unreadable settings as {}", backup.display()),
+                        Err(e) => {
+                            println!("Not saving settings for '{}', backing up the old ones failed: {}", user, e);
+                            return;
+                        }
+                    }
+                }
+
+                let settings = UserSettings {
+                    locomotion: renderer.locomotion.settings,
+                    boundary: renderer.boundary.clone(),
+                    ..Default::default()
+                };
+                if let Err(e) = settings.save_for(&user) {
+                    println!("Failed to save settings for '{}': {}", user, e);
+                }
+            }
+
             _ => {}
         }
     });
 }
+
+/// `--user NAME`, or the OS account.
+fn user_name() -> String {
+    let args: Vec<String> = std::env::args().collect();
+
+    let name = args
+        .iter()
+        .position(|a| a == "--user")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    match name {
+        Some(name) if UserSettings::is_valid_user(&name) => name,
+        Some(name) => {
+            let user = UserSettings::default_user();
+            println!("Invalid user name '{}', using '{}'", name, user);
+            user
+        }
+        None => UserSettings::default_user(),
+    }
+}
+
+/// Every `--model PATH` (glTF or GLB).
+fn model_paths() -> Vec<String> {
+    let args: Vec<String> = std::env::args().collect();
+
+    args.windows(2)
+        .filter(|pair| pair[0] == "--model")
+        .map(|pair| pair[1].clone())
+        .collect()
+}
+
+/// `--avatar PATH` (a `.json` avatar definition, or VRM 0.x or 1.0).
+fn avatar_path() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+
+    args.iter()
+        .position(|a| a == "--avatar")
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
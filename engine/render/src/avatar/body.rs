@@ -0,0 +1,300 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use glam::{Mat3, Quat, Vec3};
+
+use super::definition::{AvatarDefinition, PartShape};
+use super::ik::{solve_chain, solve_two_bone, FootPlanter};
+use super::procedural::ProceduralWalk;
+use super::skeleton::{Joint, Proportions, SkeletonPose};
+use crate::animation::AnimationParams;
+use crate::locomotion::{heading_of, Ground};
+use crate::xr::TrackedPose;
+
+/// World-space head and hand targets for one frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BodyTargets {
+    pub head: TrackedPose,
+    pub left: TrackedPose,
+    pub right: TrackedPose,
+}
+
+/// One part of the posed body, ready to draw.
+#[derive(Clone, Copy, Debug)]
+pub struct BodyPart {
+    /// Joint the part hangs off (the far end, for limb bones).
+    pub joint: Joint,
+    pub shape: PartShape,
+    pub color: [f32; 3],
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+/// Rotation whose +Y is `up` and +Z is as close to `forward` as possible.
+pub(super) fn frame(up: Vec3, forward: Vec3) -> Quat {
+    let y = up.normalize_or_zero();
+    let mut z = forward - y * forward.dot(y);
+    if z.length_squared() < 1e-6 {
+        z = y.any_orthonormal_vector();
+    }
+    let z = z.normalize();
+    let x = y.cross(z);
+
+    Quat::from_mat3(&Mat3::from_cols(x, y, z))
+}
+
+/* =========================================================
+   BODY
+   ========================================================= */
+
+/// Three-point tracked humanoid: head and hands come from tracking, the
+/// spine, arms and legs are solved, and the feet step on their own. Its
+/// looks and size come from an `AvatarDefinition`.
+pub struct AvatarBody {
+    pub proportions: Proportions,
+    pub definition: AvatarDefinition,
+    pub pose: SkeletonPose,
+
+    /// How far the head may turn before the body follows.
+    pub max_head_twist: f32,
+    /// Poses the body when nothing tracks it (see `animate`).
+    pub walk: ProceduralWalk,
+
+    body_yaw: f32,
+    feet: [FootPlanter; 2],
+    /// Scale of `parts` about the feet, from the walk's squash.
+    squash: Vec3,
+}
+
+impl AvatarBody {
+    pub fn new(def: &AvatarDefinition) -> Self {
+        let proportions = Proportions::from_definition(def);
+        let pose = SkeletonPose::rest(&proportions, Vec3::ZERO);
+
+        let feet = [
+            FootPlanter::new(pose[Joint::LeftFoot]),
+            FootPlanter::new(pose[Joint::RightFoot]),
+        ];
+
+        Self {
+            proportions,
+            definition: def.clone(),
+            pose,
+            max_head_twist: 40f32.to_radians(),
+            walk: ProceduralWalk::new(),
+            body_yaw: 0.0,
+            feet,
+            squash: Vec3::ONE,
+        }
+    }
+
+    /// Swaps in a new definition (e.g. its file changed), resizing the
+    /// skeleton to it; the pose catches up on the next update.
+    pub fn set_definition(&mut self, def: AvatarDefinition) {
+        self.proportions = Proportions::from_definition(&def);
+        self.definition = def;
+    }
+
+    /// World position and orientation of the named socket this frame.
+    pub fn socket(&self, name: &str) -> Option<(Vec3, Quat)> {
+        let socket = self.definition.socket(name)?;
+        let rotation = self.pose.rotation(socket.joint);
+        let position = self.pose[socket.joint] + rotation * (socket.offset * self.definition.scale);
+
+        Some((position, rotation * socket.rotation))
+    }
+
+    pub fn body_yaw(&self) -> f32 {
+        self.body_yaw
+    }
+
+    pub fn facing(&self) -> Quat {
+        Quat::from_rotation_y(self.body_yaw)
+    }
+
+    /// Where the body wants its feet (on the ground under the hips).
+    fn foot_targets(&self, hips: Vec3, ground: &Ground) -> [Vec3; 2] {
+        let facing = self.facing();
+        let p = &self.proportions;
+
+        [1.0, -1.0].map(|s| {
+            let f = hips + facing * Vec3::new(s * p.hip_width * 0.5, 0.0, 0.0);
+            let y = ground.height_at(f.x, f.z).unwrap_or(0.0);
+            Vec3::new(f.x, y, f.z)
+        })
+    }
+
+    pub fn update(&mut self, dt: f32, targets: &BodyTargets, ground: &Ground) {
+        let p = self.proportions;
+        let head = targets.head;
+        self.squash = Vec3::ONE;
+
+        /* ================= BODY YAW ================= */
+
+        let wrap = |a: f32| (a + PI).rem_euclid(TAU) - PI;
+        let head_yaw = heading_of(head.orientation);
+
+        let twist = wrap(head_yaw - self.body_yaw);
+        if twist.abs() > self.max_head_twist {
+            self.body_yaw += twist - twist.signum() * self.max_head_twist;
+        }
+
+        // then slowly settle under the head
+        let twist = wrap(head_yaw - self.body_yaw);
+        self.body_yaw += twist * (dt * 0.75).min(1.0);
+
+        let facing = self.facing();
+        let forward = facing * Vec3::Z;
+
+        /* ================= SPINE ================= */
+
+        let neck = head.position + head.orientation * Vec3::new(0.0, -p.head_to_neck, 0.08);
+        let floor = ground.height_at(neck.x, neck.z).unwrap_or(0.0);
+
+        let standing = floor + p.leg() + p.ankle;
+        let hips_y = (neck.y - 0.95 * (p.spine + p.neck)).clamp(floor + 0.45 * p.leg(), standing);
+        let hips = Vec3::new(neck.x, hips_y, neck.z) - forward * 0.06;
+
+        // crouching shortens hips→neck, so the chain bends forward
+        let mut spine = [hips, hips + Vec3::Y * p.spine + forward * 0.05, neck];
+        solve_chain(&mut spine, &[p.spine, p.neck], neck, 8);
+        let chest = spine[1];
+
+        self.pose.set(Joint::Head, head.position);
+        self.pose.set(Joint::Neck, spine[2]);
+        self.pose.set(Joint::Chest, chest);
+        self.pose.set(Joint::Hips, hips);
+
+        let chest_rot = frame(spine[2] - chest, forward);
+        self.pose.set_rotation(Joint::Head, head.orientation);
+        self.pose.set_rotation(Joint::Neck, chest_rot);
+        self.pose.set_rotation(Joint::Chest, chest_rot);
+        self.pose.set_rotation(Joint::Hips, frame(chest - hips, forward));
+
+        /* ================= ARMS ================= */
+
+        for (s, hand, [shoulder_j, elbow_j, hand_j]) in [
+            (1.0, &targets.left, [Joint::LeftShoulder, Joint::LeftElbow, Joint::LeftHand]),
+            (-1.0, &targets.right, [Joint::RightShoulder, Joint::RightElbow, Joint::RightHand]),
+        ] {
+            let shoulder = chest + chest_rot * Vec3::new(s * p.shoulder_width * 0.5, 0.0, 0.0);
+
+            let target = if hand.valid {
+                hand.position
+            } else {
+                shoulder + chest_rot * Vec3::new(s * 0.05, -p.arm(), 0.0)
+            };
+
+            let pole = shoulder + chest_rot * Vec3::new(s * 0.3, -0.5, -0.3);
+            let (elbow, end) = solve_two_bone(shoulder, target, pole, p.upper_arm, p.forearm);
+
+            self.pose.set(shoulder_j, shoulder);
+            self.pose.set(elbow_j, elbow);
+            self.pose.set(hand_j, end);
+            self.pose.set_rotation(shoulder_j, frame(shoulder - elbow, forward));
+            self.pose.set_rotation(elbow_j, frame(elbow - end, forward));
+            // hands point down their controller's -Z; untracked ones hang
+            let hand_rot = if hand.valid {
+                hand.orientation
+            } else {
+                chest_rot * Quat::from_rotation_x(-FRAC_PI_2)
+            };
+            self.pose.set_rotation(hand_j, hand_rot);
+        }
+
+        /* ================= FEET ================= */
+
+        let desired = self.foot_targets(hips, ground);
+        let errors = [self.feet[0].error(desired[0]), self.feet[1].error(desired[1])];
+
+        if errors[0].max(errors[1]) > 1.5 {
+            // teleported
+            self.feet[0].snap(desired[0]);
+            self.feet[1].snap(desired[1]);
+        } else if !self.feet[0].stepping() && !self.feet[1].stepping() {
+            let i = if errors[0] >= errors[1] { 0 } else { 1 };
+            if errors[i] > FootPlanter::STEP_DISTANCE {
+                self.feet[i].begin_step(desired[i]);
+            }
+        }
+
+        /* ================= LEGS ================= */
+
+        for (i, (s, [hip_j, knee_j, foot_j])) in [
+            (1.0, [Joint::LeftHip, Joint::LeftKnee, Joint::LeftFoot]),
+            (-1.0, [Joint::RightHip, Joint::RightKnee, Joint::RightFoot]),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let hip = hips + facing * Vec3::new(s * p.hip_width * 0.5, 0.0, 0.0);
+            let foot = self.feet[i].update(dt) + Vec3::Y * p.ankle;
+
+            let pole = hip + forward * 0.5 - Vec3::Y * 0.2;
+            let (knee, end) = solve_two_bone(hip, foot, pole, p.thigh, p.shin);
+
+            self.pose.set(hip_j, hip);
+            self.pose.set(knee_j, knee);
+            self.pose.set(foot_j, end);
+            self.pose.set_rotation(hip_j, frame(hip - knee, forward));
+            self.pose.set_rotation(knee_j, frame(knee - end, forward));
+            self.pose.set_rotation(foot_j, facing);
+        }
+    }
+
+    /// Poses the body from its character's movement instead of tracking:
+    /// standing at `root`, turning to walk towards `yaw` and looking at
+    /// `look_at` when it can (see `ProceduralWalk`).
+    pub fn animate(&mut self, dt: f32, params: &AnimationParams, root: Vec3, yaw: f32, look_at: Option<Vec3>) {
+        let wrap = |a: f32| (a + PI).rem_euclid(TAU) - PI;
+        self.body_yaw += wrap(yaw - self.body_yaw) * (dt * 8.0).min(1.0);
+
+        self.pose = self
+            .walk
+            .update(dt, params, &self.proportions, root, self.body_yaw, look_at);
+        self.squash = self.walk.squash_scale();
+
+        // tracking picks up from where the feet are
+        for (planter, foot) in self.feet.iter_mut().zip([Joint::LeftFoot, Joint::RightFoot]) {
+            planter.snap(self.pose[foot] - Vec3::Y * self.proportions.ankle);
+        }
+    }
+
+    /// The definition's parts on the current pose.
+    pub fn parts(&self) -> Vec<BodyPart> {
+        let scale = self.definition.scale;
+
+        let feet = self.pose[Joint::LeftFoot].y.min(self.pose[Joint::RightFoot].y) - self.proportions.ankle;
+        let base = Vec3::new(self.pose[Joint::Hips].x, feet, self.pose[Joint::Hips].z);
+        let squash = |part: BodyPart| BodyPart {
+            position: base + (part.position - base) * self.squash,
+            scale: part.scale * self.squash,
+            ..part
+        };
+
+        let parts = self.definition.parts.iter().map(|def| {
+            let (position, rotation, size) = match def.from {
+                // stretched along the bone
+                Some(from) => {
+                    let (a, b) = (self.pose[from], self.pose[def.joint]);
+                    let dir = b - a;
+                    let rotation = Quat::from_rotation_arc(Vec3::Y, dir.normalize_or_zero());
+                    let size = Vec3::new(def.size.x * scale, def.size.y * dir.length(), def.size.z * scale);
+                    ((a + b) * 0.5, rotation, size)
+                }
+                None => (self.pose[def.joint], self.pose.rotation(def.joint), def.size * scale),
+            };
+
+            BodyPart {
+                joint: def.joint,
+                shape: def.shape,
+                color: def.color,
+                position: position + rotation * (def.offset * scale),
+                rotation,
+                scale: size,
+            }
+        });
+
+        parts.map(squash).collect()
+    }
+}
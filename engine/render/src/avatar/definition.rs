@@ -1,14 +1,417 @@
-use glam::Vec3;
+use std::fmt;
+use std::io;
 
-#[derive(Clone)]
+use glam::{EulerRot, Quat, Vec3};
+use serde_json::{Map, Value};
+
+use super::skeleton::Joint;
+use crate::renderer::mesh::MeshId;
+
+/*
+   What a part avatar looks like, as a JSON file:
+
+     {
+       "name": "default",
+       "height": 1.72,
+       "scale": 1.0,
+       "parts": [
+         { "name": "torso", "joint": "chest", "offset": [0, -0.2, 0],
+           "size": [0.38, 0.5, 0.22], "color": [0.8, 0.8, 0.8] },
+         { "name": "left_forearm", "from": "left_elbow", "joint": "left_hand",
+           "shape": "capsule", "size": [0.09, 1, 0.09] }
+       ],
+       "sockets": [
+         { "name": "hat", "joint": "head", "offset": [0, 0.14, 0] }
+       ]
+     }
+
+   `height` (metres) sizes the skeleton and `scale` scales everything,
+   skeleton, offsets and sizes alike. A part rides on `joint`, offset in
+   the joint's frame; with `from` it spans the bone from that joint
+   instead and `size.y` is a fraction of the bone's length. Shapes are
+   "box" (the default), "sphere" and "capsule", each filling `size`.
+   Sockets are named places on the body to attach things to, rotated by
+   `rotation` (degrees about X, Y, Z). Joints are named like "hips",
+   "chest", "left_elbow" and "right_foot" (see `Joint::name`).
+
+   Unknown keys are errors, so a typo does not silently fall back to a
+   default.
+*/
+#[derive(Clone, Debug, PartialEq)]
 pub struct AvatarDefinition {
+    pub name: String,
+    /// Standing height before `scale`, metres.
+    pub height: f32,
     pub scale: f32,
     pub parts: Vec<AvatarPartDef>,
+    pub sockets: Vec<AvatarSocket>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartShape {
+    Box,
+    Sphere,
+    Capsule,
+}
+
+impl PartShape {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "box" => Some(PartShape::Box),
+            "sphere" => Some(PartShape::Sphere),
+            "capsule" => Some(PartShape::Capsule),
+            _ => None,
+        }
+    }
+
+    /// The built-in library mesh drawing the shape.
+    pub fn mesh(self) -> MeshId {
+        match self {
+            PartShape::Box => MeshId::CUBE,
+            PartShape::Sphere => MeshId::SPHERE,
+            PartShape::Capsule => MeshId::CAPSULE,
+        }
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct AvatarPartDef {
-    pub name: &'static str,
-    pub local_offset: Vec3,
+    pub name: String,
+    pub joint: Joint,
+    /// Makes the part a limb spanning from this joint to `joint`.
+    pub from: Option<Joint>,
+    pub shape: PartShape,
+    pub offset: Vec3,
     pub size: Vec3,
+    pub color: [f32; 3],
+}
+
+/// A named attachment point riding on a joint.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AvatarSocket {
+    pub name: String,
+    pub joint: Joint,
+    pub offset: Vec3,
+    pub rotation: Quat,
+}
+
+/* =========================================================
+   ERRORS
+   ========================================================= */
+
+#[derive(Debug)]
+pub enum DefinitionError {
+    Io(io::Error),
+    /// Not JSON; the message has the line and column.
+    Json(serde_json::Error),
+    /// `field` (like `parts[2].joint`) breaks the format's rules.
+    Invalid { field: String, message: String },
+}
+
+impl DefinitionError {
+    fn invalid(field: &str, message: impl Into<String>) -> Self {
+        DefinitionError::Invalid {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for DefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DefinitionError::Io(e) => write!(f, "{}", e),
+            DefinitionError::Json(e) => write!(f, "json: {}", e),
+            DefinitionError::Invalid { field, message } => write!(f, "{}: {}", field, message),
+        }
+    }
+}
+
+impl std::error::Error for DefinitionError {}
+
+impl From<io::Error> for DefinitionError {
+    fn from(e: io::Error) -> Self {
+        DefinitionError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for DefinitionError {
+    fn from(e: serde_json::Error) -> Self {
+        DefinitionError::Json(e)
+    }
+}
+
+/* =========================================================
+   PARSING
+   ========================================================= */
+
+impl AvatarDefinition {
+    /// Parses and validates a definition file's text.
+    pub fn parse(text: &str) -> Result<Self, DefinitionError> {
+        let root: Value = serde_json::from_str(text)?;
+        let root = Object::new(&root, "definition", &["name", "height", "scale", "parts", "sockets"])?;
+
+        let height = root.number("height", Some(1.72))?;
+        let scale = root.number("scale", Some(1.0))?;
+        for (key, value) in [("height", height), ("scale", scale)] {
+            if value <= 0.0 {
+                return Err(DefinitionError::invalid(key, "must be above zero"));
+            }
+        }
+
+        let parts = root.array("parts")?;
+        if parts.is_empty() {
+            return Err(DefinitionError::invalid("parts", "needs at least one part"));
+        }
+        let parts = parts
+            .iter()
+            .enumerate()
+            .map(|(i, p)| part(p, &format!("parts[{}]", i)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let sockets = match root.get("sockets") {
+            Some(_) => root.array("sockets")?,
+            None => &[],
+        };
+        let sockets = sockets
+            .iter()
+            .enumerate()
+            .map(|(i, s)| socket(s, &format!("sockets[{}]", i)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        unique(parts.iter().map(|p| p.name.as_str()), "parts")?;
+        unique(sockets.iter().map(|s| s.name.as_str()), "sockets")?;
+
+        Ok(Self {
+            name: root.string("name", Some("avatar"))?,
+            height,
+            scale,
+            parts,
+            sockets,
+        })
+    }
+
+    pub fn part(&self, name: &str) -> Option<&AvatarPartDef> {
+        self.parts.iter().find(|p| p.name == name)
+    }
+
+    pub fn socket(&self, name: &str) -> Option<&AvatarSocket> {
+        self.sockets.iter().find(|s| s.name == name)
+    }
+}
+
+fn part(value: &Value, path: &str) -> Result<AvatarPartDef, DefinitionError> {
+    let p = Object::new(value, path, &["name", "joint", "from", "shape", "offset", "size", "color"])?;
+
+    let name = p.name()?;
+    let joint = p.joint("joint")?;
+    let from = match p.get("from") {
+        Some(_) => Some(p.joint("from")?),
+        None => None,
+    };
+    if from == Some(joint) {
+        return Err(p.error("from", "must differ from `joint`"));
+    }
+
+    let shape = p.string("shape", Some("box"))?;
+    let shape = PartShape::from_name(&shape)
+        .ok_or_else(|| p.error("shape", format!("unknown shape \"{}\" (expected box, sphere or capsule)", shape)))?;
+
+    let size = p.vec3("size", None)?;
+    if size.min_element() <= 0.0 {
+        return Err(p.error("size", "every component must be above zero"));
+    }
+
+    let color = p.vec3("color", Some(Vec3::splat(0.8)))?;
+    if color.min_element() < 0.0 {
+        return Err(p.error("color", "components cannot be negative"));
+    }
+
+    Ok(AvatarPartDef {
+        name,
+        joint,
+        from,
+        shape,
+        offset: p.vec3("offset", Some(Vec3::ZERO))?,
+        size,
+        color: color.to_array(),
+    })
+}
+
+fn socket(value: &Value, path: &str) -> Result<AvatarSocket, DefinitionError> {
+    let s = Object::new(value, path, &["name", "joint", "offset", "rotation"])?;
+    let [x, y, z] = s.vec3("rotation", Some(Vec3::ZERO))?.to_array().map(f32::to_radians);
+
+    Ok(AvatarSocket {
+        name: s.name()?,
+        joint: s.joint("joint")?,
+        offset: s.vec3("offset", Some(Vec3::ZERO))?,
+        rotation: Quat::from_euler(EulerRot::XYZ, x, y, z),
+    })
+}
+
+fn unique<'a>(names: impl Iterator<Item = &'a str>, list: &str) -> Result<(), DefinitionError> {
+    let mut seen = Vec::new();
+    for name in names {
+        if seen.contains(&name) {
+            return Err(DefinitionError::invalid(list, format!("\"{}\" is defined twice", name)));
+        }
+        seen.push(name);
+    }
+    Ok(())
+}
+
+/// A JSON object being read, with its path for error messages.
+struct Object<'a> {
+    map: &'a Map<String, Value>,
+    path: &'a str,
+}
+
+impl<'a> Object<'a> {
+    /// Fails on anything but an object with only `keys`.
+    fn new(value: &'a Value, path: &'a str, keys: &[&str]) -> Result<Self, DefinitionError> {
+        let map = value
+            .as_object()
+            .ok_or_else(|| DefinitionError::invalid(path, "expected an object"))?;
+
+        if let Some(key) = map.keys().find(|k| !keys.contains(&k.as_str())) {
+            let expected = keys.join(", ");
+            return Err(DefinitionError::invalid(path, format!("unknown key \"{}\" (expected {})", key, expected)));
+        }
+
+        Ok(Self { map, path })
+    }
+
+    fn get(&self, key: &str) -> Option<&'a Value> {
+        self.map.get(key)
+    }
+
+    fn error(&self, key: &str, message: impl Into<String>) -> DefinitionError {
+        let field = if self.path == "definition" {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.path, key)
+        };
+        DefinitionError::invalid(&field, message)
+    }
+
+    /// The value of `key`, or `default` when it is missing.
+    fn field<T>(
+        &self,
+        key: &str,
+        default: Option<T>,
+        expected: &str,
+        read: impl Fn(&'a Value) -> Option<T>,
+    ) -> Result<T, DefinitionError> {
+        match (self.get(key), default) {
+            (Some(value), _) => read(value).ok_or_else(|| self.error(key, format!("expected {}", expected))),
+            (None, Some(default)) => Ok(default),
+            (None, None) => Err(self.error(key, "missing")),
+        }
+    }
+
+    fn number(&self, key: &str, default: Option<f32>) -> Result<f32, DefinitionError> {
+        self.field(key, default, "a number", |v| v.as_f64().map(|n| n as f32))
+    }
+
+    fn string(&self, key: &str, default: Option<&str>) -> Result<String, DefinitionError> {
+        self.field(key, default.map(str::to_string), "a string", |v| v.as_str().map(str::to_string))
+    }
+
+    fn vec3(&self, key: &str, default: Option<Vec3>) -> Result<Vec3, DefinitionError> {
+        self.field(key, default, "[x, y, z]", |v| match v.as_array()?.as_slice() {
+            [x, y, z] => Some(Vec3::new(x.as_f64()? as f32, y.as_f64()? as f32, z.as_f64()? as f32)),
+            _ => None,
+        })
+    }
+
+    fn array(&self, key: &str) -> Result<&'a [Value], DefinitionError> {
+        self.field(key, None, "an array", |v| v.as_array().map(Vec::as_slice))
+    }
+
+    fn name(&self) -> Result<String, DefinitionError> {
+        let name = self.string("name", None)?;
+        if name.is_empty() {
+            return Err(self.error("name", "cannot be empty"));
+        }
+        Ok(name)
+    }
+
+    fn joint(&self, key: &str) -> Result<Joint, DefinitionError> {
+        let name = self.string(key, None)?;
+        Joint::from_name(&name).ok_or_else(|| {
+            let expected: Vec<&str> = Joint::ALL.iter().map(|j| j.name()).collect();
+            self.error(key, format!("unknown joint \"{}\" (expected one of {})", name, expected.join(", ")))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_of(text: &str) -> String {
+        AvatarDefinition::parse(text).expect_err("should not parse").to_string()
+    }
+
+    #[test]
+    fn defaults_fill_in_optional_fields() {
+        let def = AvatarDefinition::parse(
+            r#"{ "parts": [{ "name": "head", "joint": "head", "size": [0.2, 0.2, 0.2] }],
+                 "sockets": [{ "name": "hat", "joint": "head", "rotation": [0, 90, 0] }] }"#,
+        )
+        .unwrap();
+
+        assert_eq!((def.name.as_str(), def.height, def.scale), ("avatar", 1.72, 1.0));
+        let head = def.part("head").unwrap();
+        assert_eq!((head.shape, head.from, head.offset, head.color), (PartShape::Box, None, Vec3::ZERO, [0.8; 3]));
+
+        let hat = def.socket("hat").unwrap();
+        assert!(hat.rotation.abs_diff_eq(Quat::from_rotation_y(90f32.to_radians()), 1e-5));
+    }
+
+    #[test]
+    fn the_built_in_avatar_is_valid() {
+        let def = AvatarDefinition::parse(include_str!("default.avatar.json")).unwrap();
+        assert!(def.part("left_forearm").is_some_and(|p| p.from == Some(Joint::LeftElbow)));
+    }
+
+    #[test]
+    fn invalid_definitions_name_the_field() {
+        let part = |fields: &str| format!(r#"{{ "parts": [{{ "name": "a", "joint": "head", {} }}] }}"#, fields);
+
+        assert!(error_of("{").starts_with("json: "));
+        assert_eq!(error_of("[]"), "definition: expected an object");
+        assert_eq!(
+            error_of(r#"{ "partz": [] }"#),
+            "definition: unknown key \"partz\" (expected name, height, scale, parts, sockets)"
+        );
+        assert_eq!(error_of(r#"{ "parts": [] }"#), "parts: needs at least one part");
+        assert_eq!(error_of(r#"{ "height": 0, "parts": [] }"#), "height: must be above zero");
+        assert_eq!(error_of(r#"{ "scale": "big", "parts": [] }"#), "scale: expected a number");
+
+        assert_eq!(error_of(&part(r#""size": [1, 0, 1]"#)), "parts[0].size: every component must be above zero");
+        assert_eq!(error_of(&part(r#""size": [1, 1]"#)), "parts[0].size: expected [x, y, z]");
+        assert_eq!(error_of(&part(r#""from": "head", "size": [1, 1, 1]"#)), "parts[0].from: must differ from `joint`");
+        assert_eq!(
+            error_of(&part(r#""size": [1, 1, 1], "color": [1, -1, 1]"#)),
+            "parts[0].color: components cannot be negative"
+        );
+        assert!(error_of(&part(r#""size": [1, 1, 1], "shape": "cone""#))
+            .starts_with("parts[0].shape: unknown shape \"cone\""));
+        assert!(error_of(r#"{ "parts": [{ "name": "a", "joint": "left_elbo", "size": [1, 1, 1] }] }"#)
+            .starts_with("parts[0].joint: unknown joint \"left_elbo\" (expected one of hips, "));
+        assert_eq!(error_of(r#"{ "parts": [{ "joint": "head", "size": [1, 1, 1] }] }"#), "parts[0].name: missing");
+
+        assert_eq!(
+            error_of(r#"{ "parts": [{ "name": "a", "joint": "head", "size": [1, 1, 1] },
+                                    { "name": "a", "joint": "hips", "size": [1, 1, 1] }] }"#),
+            "parts: \"a\" is defined twice"
+        );
+        assert_eq!(
+            error_of(r#"{ "parts": [{ "name": "a", "joint": "head", "size": [1, 1, 1] }],
+                          "sockets": [{ "name": "hat", "joint": "head", "offset": [0, 0] }] }"#),
+            "sockets[0].offset: expected [x, y, z]"
+        );
+    }
 }
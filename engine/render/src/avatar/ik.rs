@@ -0,0 +1,251 @@
+use glam::Vec3;
+
+/* =========================================================
+   TWO-BONE
+   ========================================================= */
+
+/// Analytic two-bone IK (shoulder-elbow-hand, hip-knee-foot).
+///
+/// Returns the middle joint and the end effector; the end is pulled back
+/// onto the reachable sphere when `target` is too far. `pole` is a world
+/// position the middle joint bends towards. Collapsed bones (shorter than
+/// 0.1 mm, as a refit to a broken rig can produce) leave nothing to bend:
+/// the middle joint stays on the root and the end on the target.
+pub fn solve_two_bone(root: Vec3, target: Vec3, pole: Vec3, upper: f32, lower: f32) -> (Vec3, Vec3) {
+    const MIN_BONE: f32 = 1e-4;
+
+    let to_target = target - root;
+    let reach = upper + lower;
+
+    if !(upper > MIN_BONE && lower > MIN_BONE && reach.is_finite()) {
+        return (root, target);
+    }
+
+    let dist = to_target.length().clamp((upper - lower).abs() + 1e-4, reach - 1e-4);
+    let dir = to_target.normalize_or_zero();
+    if dir == Vec3::ZERO {
+        return (root + (pole - root).normalize_or_zero() * upper, root);
+    }
+
+    let end = root + dir * dist;
+
+    // law of cosines: angle at the root between the target and the upper bone
+    let cos_a = ((upper * upper + dist * dist - lower * lower) / (2.0 * upper * dist)).clamp(-1.0, 1.0);
+    let along = upper * cos_a;
+    let up = upper * (1.0 - cos_a * cos_a).sqrt();
+
+    // bend direction: the pole, flattened against the root→target axis
+    let to_pole = pole - root;
+    let mut bend = to_pole - dir * to_pole.dot(dir);
+    if bend.length_squared() < 1e-8 {
+        bend = dir.any_orthonormal_vector();
+    }
+    let bend = bend.normalize();
+
+    (root + dir * along + bend * up, end)
+}
+
+/* =========================================================
+   CHAIN (FABRIK)
+   ========================================================= */
+
+/// FABRIK for a chain with a fixed root (spine/neck). `points[0]` stays
+/// put, the last point reaches for `target`. `lengths[i]` is the distance
+/// between `points[i]` and `points[i + 1]`.
+pub fn solve_chain(points: &mut [Vec3], lengths: &[f32], target: Vec3, iterations: usize) {
+    let n = points.len();
+    if n < 2 || lengths.len() + 1 != n {
+        return;
+    }
+
+    let root = points[0];
+    let total: f32 = lengths.iter().sum();
+
+    if root.distance(target) >= total {
+        // out of reach: straighten towards the target
+        let dir = (target - root).normalize_or_zero();
+        for i in 1..n {
+            points[i] = points[i - 1] + dir * lengths[i - 1];
+        }
+        return;
+    }
+
+    for _ in 0..iterations {
+        // backward: end to target
+        points[n - 1] = target;
+        for i in (0..n - 1).rev() {
+            let dir = (points[i] - points[i + 1]).normalize_or_zero();
+            points[i] = points[i + 1] + dir * lengths[i];
+        }
+
+        // forward: root back in place
+        points[0] = root;
+        for i in 1..n {
+            let dir = (points[i] - points[i - 1]).normalize_or_zero();
+            points[i] = points[i - 1] + dir * lengths[i - 1];
+        }
+
+        if points[n - 1].distance(target) < 1e-3 {
+            break;
+        }
+    }
+}
+
+/* =========================================================
+   FOOT STEPPING
+   ========================================================= */
+
+#[derive(Clone, Copy, Debug)]
+struct Step {
+    from: Vec3,
+    to: Vec3,
+    t: f32,
+}
+
+/// Keeps a foot planted until the body drifts too far from it, then
+/// swings it to a new spot along a short arc.
+#[derive(Clone, Debug)]
+pub struct FootPlanter {
+    pub planted: Vec3,
+    step: Option<Step>,
+}
+
+impl FootPlanter {
+    pub const STEP_DISTANCE: f32 = 0.25;
+    pub const STEP_DURATION: f32 = 0.22;
+    pub const STEP_HEIGHT: f32 = 0.08;
+
+    pub fn new(at: Vec3) -> Self {
+        Self {
+            planted: at,
+            step: None,
+        }
+    }
+
+    pub fn stepping(&self) -> bool {
+        self.step.is_some()
+    }
+
+    /// Distance from where the body wants this foot.
+    pub fn error(&self, desired: Vec3) -> f32 {
+        let d = desired - self.planted;
+        Vec3::new(d.x, 0.0, d.z).length()
+    }
+
+    /// Starts a step towards `to`, overshooting a little along the motion
+    /// so a walking body doesn't trail behind its feet.
+    pub fn begin_step(&mut self, to: Vec3) {
+        let overshoot = (to - self.planted) * 0.2;
+        self.step = Some(Step {
+            from: self.planted,
+            to: to + Vec3::new(overshoot.x, 0.0, overshoot.z),
+            t: 0.0,
+        });
+    }
+
+    /// Advances a running step and returns the current foot position.
+    pub fn update(&mut self, dt: f32) -> Vec3 {
+        let Some(step) = &mut self.step else {
+            return self.planted;
+        };
+
+        step.t = (step.t + dt / Self::STEP_DURATION).min(1.0);
+
+        let s = step.t * step.t * (3.0 - 2.0 * step.t);
+        let lift = (step.t * std::f32::consts::PI).sin() * Self::STEP_HEIGHT;
+        let p = step.from.lerp(step.to, s) + Vec3::Y * lift;
+
+        if step.t >= 1.0 {
+            self.planted = step.to;
+            self.step = None;
+            return self.planted;
+        }
+
+        p
+    }
+
+    /// Plants immediately (teleports, big jumps).
+    pub fn snap(&mut self, to: Vec3) {
+        self.planted = to;
+        self.step = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-3;
+
+    #[test]
+    fn two_bone_keeps_bone_lengths_and_bends_towards_the_pole() {
+        let (root, pole) = (Vec3::ZERO, Vec3::new(0.0, 0.0, 1.0));
+        let target = Vec3::new(0.0, -0.5, 0.0);
+
+        let (middle, end) = solve_two_bone(root, target, pole, 0.3, 0.3);
+
+        assert!(end.distance(target) < EPSILON);
+        assert!((middle.distance(root) - 0.3).abs() < EPSILON);
+        assert!((middle.distance(end) - 0.3).abs() < EPSILON);
+        assert!(middle.z > 0.0);
+    }
+
+    #[test]
+    fn two_bone_clamps_unreachable_targets() {
+        let (root, pole) = (Vec3::ZERO, Vec3::Z);
+
+        // too far: stretched along the target direction, just short of it
+        let (middle, end) = solve_two_bone(root, Vec3::new(0.0, -2.0, 0.0), pole, 0.3, 0.3);
+        assert!((end.length() - 0.6).abs() < EPSILON);
+        assert!(end.normalize().abs_diff_eq(Vec3::NEG_Y, EPSILON));
+        assert!((middle.distance(end) - 0.3).abs() < EPSILON);
+
+        // too close for unequal bones: pushed out to the shortest reach
+        let (_, end) = solve_two_bone(root, Vec3::new(0.0, -0.01, 0.0), pole, 0.4, 0.2);
+        assert!((end.length() - 0.2).abs() < EPSILON);
+    }
+
+    #[test]
+    fn chain_reaches_targets_in_range_and_points_at_others() {
+        let lengths = [0.2, 0.2, 0.2];
+        let straight = || [Vec3::ZERO, Vec3::Y * 0.2, Vec3::Y * 0.4, Vec3::Y * 0.6];
+
+        let mut points = straight();
+        let target = Vec3::new(0.3, 0.4, 0.0);
+        solve_chain(&mut points, &lengths, target, 20);
+        assert_eq!(points[0], Vec3::ZERO);
+        assert!(points[3].distance(target) < EPSILON);
+        for (pair, length) in points.windows(2).zip(lengths) {
+            assert!((pair[0].distance(pair[1]) - length).abs() < EPSILON);
+        }
+
+        let mut points = straight();
+        solve_chain(&mut points, &lengths, Vec3::new(5.0, 0.0, 0.0), 20);
+        assert!(points[3].abs_diff_eq(Vec3::new(0.6, 0.0, 0.0), EPSILON));
+    }
+
+    #[test]
+    fn chain_survives_degenerate_input() {
+        // zero lengths collapse onto the root
+        let mut points = [Vec3::ZERO, Vec3::Y, Vec3::Y * 2.0];
+        solve_chain(&mut points, &[0.0, 0.0], Vec3::X, 10);
+        assert_eq!(points, [Vec3::ZERO; 3]);
+
+        // mismatched lengths and single points are left alone
+        let mut points = [Vec3::ZERO, Vec3::Y];
+        solve_chain(&mut points, &[1.0, 1.0], Vec3::X, 10);
+        assert_eq!(points, [Vec3::ZERO, Vec3::Y]);
+        let mut points = [Vec3::Y];
+        solve_chain(&mut points, &[], Vec3::X, 10);
+        assert_eq!(points, [Vec3::Y]);
+    }
+
+    #[test]
+    fn two_bone_leaves_collapsed_bones_alone() {
+        let (root, target, pole) = (Vec3::ZERO, Vec3::new(0.0, -0.5, 0.0), Vec3::Z);
+
+        for (upper, lower) in [(0.0, 0.0), (0.3, 0.0), (0.0, 0.3), (f32::NAN, 0.3), (f32::INFINITY, 0.3)] {
+            assert_eq!(solve_two_bone(root, target, pole, upper, lower), (root, target));
+        }
+    }
+}
@@ -1,25 +1,69 @@
-use glam::Vec3;
-use super::definition::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+use super::definition::{AvatarDefinition, DefinitionError};
+
+/// The avatar everyone gets without a definition of their own.
 pub fn load_default_avatar() -> AvatarDefinition {
-    AvatarDefinition {
-        scale: 1.0,
-        parts: vec![
-            AvatarPartDef {
-                name: "head",
-                local_offset: Vec3::new(0.0, 1.6, 0.0),
-                size: Vec3::splat(0.4),
-            },
-            AvatarPartDef {
-                name: "body",
-                local_offset: Vec3::new(0.0, 1.0, 0.0),
-                size: Vec3::new(0.5, 0.7, 0.3),
-            },
-            AvatarPartDef {
-                name: "legs",
-                local_offset: Vec3::new(0.0, 0.4, 0.0),
-                size: Vec3::new(0.4, 0.8, 0.3),
-            },
-        ],
+    AvatarDefinition::parse(include_str!("default.avatar.json")).expect("built-in avatar definition is invalid")
+}
+
+pub fn load_avatar_definition(path: impl AsRef<Path>) -> Result<AvatarDefinition, DefinitionError> {
+    AvatarDefinition::parse(&fs::read_to_string(path)?)
+}
+
+/*
+   An avatar definition file, reloaded when it changes on disk.
+
+   `poll` looks at the file's modification time every half second. A
+   change that fails to load is reported once and the caller keeps its
+   current definition, so the file can be fixed and saved again.
+*/
+pub struct AvatarFile {
+    pub path: PathBuf,
+    modified: Option<SystemTime>,
+    /// Seconds since the file was last looked at.
+    since_check: f32,
+}
+
+impl AvatarFile {
+    const CHECK_INTERVAL: f32 = 0.5;
+
+    /// Loads the file and starts watching it.
+    pub fn open(path: impl AsRef<Path>) -> Result<(Self, AvatarDefinition), DefinitionError> {
+        let path = path.as_ref().to_path_buf();
+        let modified = Self::modified(&path);
+        let definition = load_avatar_definition(&path)?;
+
+        let file = Self {
+            path,
+            modified,
+            since_check: 0.0,
+        };
+        Ok((file, definition))
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Advances the check timer by `dt`; returns the file's definition
+    /// if it changed since it was last read.
+    pub fn poll(&mut self, dt: f32) -> Option<Result<AvatarDefinition, DefinitionError>> {
+        self.since_check += dt;
+        if self.since_check < Self::CHECK_INTERVAL {
+            return None;
+        }
+        self.since_check = 0.0;
+
+        // a missing file (mid-save) is not a change
+        let modified = Self::modified(&self.path)?;
+        if Some(modified) == self.modified {
+            return None;
+        }
+
+        self.modified = Some(modified);
+        Some(load_avatar_definition(&self.path))
     }
 }
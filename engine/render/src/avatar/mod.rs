@@ -1,3 +1,13 @@
-pub mod capsule;
+pub mod body;
+pub mod definition;
+pub mod ik;
+pub mod loader;
+pub mod procedural;
+pub mod skeleton;
+pub mod vrm;
 
-pub use capsule::{CapsuleAvatar, CapsulePart};
+pub use body::{AvatarBody, BodyPart, BodyTargets};
+pub use definition::{AvatarDefinition, AvatarPartDef, AvatarSocket, DefinitionError, PartShape};
+pub use loader::{load_avatar_definition, load_default_avatar, AvatarFile};
+pub use procedural::ProceduralWalk;
+pub use skeleton::{Joint, Proportions, SkeletonPose};
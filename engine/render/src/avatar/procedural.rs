@@ -0,0 +1,232 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use glam::{Quat, Vec3};
+
+use super::body::frame;
+use super::ik::solve_two_bone;
+use super::skeleton::{Joint, Proportions, SkeletonPose};
+use crate::animation::AnimationParams;
+
+/*
+   Procedural walk for part avatars, for when nothing tracks the body.
+
+   The cycle's phase advances with the distance walked, so the feet keep
+   up with the ground at any speed: each foot swings forward lifted and
+   slides back planted, the hips ride over the straight stance leg (which
+   is what makes them bob) and the arms swing against the legs. The head
+   turns towards a point of interest while it is within reach, and a
+   spring squashes and stretches the body on takeoff and landing.
+*/
+#[derive(Clone, Debug)]
+pub struct ProceduralWalk {
+    /// Leg swing either side of straight down at full stride, radians.
+    pub leg_swing: f32,
+    /// Arm swing at full stride, radians.
+    pub arm_swing: f32,
+    /// Speed of a full stride; slower walks take shorter steps.
+    pub stride_speed: f32,
+    /// How high a swinging foot lifts, metres.
+    pub foot_lift: f32,
+    /// Extra hip drop at mid-stride, metres.
+    pub bob: f32,
+    /// Furthest the head turns from the body to look at something.
+    pub max_look: f32,
+    /// Peak stretch per m/s of takeoff speed, and squash per m/s of
+    /// landing speed.
+    pub squash: f32,
+
+    /// Walk cycle; each leg takes one step per turn.
+    phase: f32,
+    /// 0 standing … 1 at full stride, eased.
+    stride: f32,
+    /// Head yaw (from the body) and pitch, eased.
+    look: (f32, f32),
+    /// Squash spring: vertical scale - 1, and its rate.
+    spring: (f32, f32),
+    grounded: bool,
+    /// Fastest downward speed since leaving the ground.
+    fall_speed: f32,
+}
+
+impl ProceduralWalk {
+    pub fn new() -> Self {
+        Self {
+            leg_swing: 0.45,
+            arm_swing: 0.4,
+            stride_speed: 3.0,
+            foot_lift: 0.12,
+            bob: 0.02,
+            max_look: 70f32.to_radians(),
+            squash: 0.03,
+            phase: 0.0,
+            stride: 0.0,
+            look: (0.0, 0.0),
+            spring: (0.0, 0.0),
+            grounded: true,
+            fall_speed: 0.0,
+        }
+    }
+
+    /// Scale for the posed body about its feet: stretched up after a
+    /// takeoff, squashed after a landing, keeping its volume.
+    pub fn squash_scale(&self) -> Vec3 {
+        let y = 1.0 + self.spring.0;
+        let xz = 1.0 / y.sqrt();
+        Vec3::new(xz, y, xz)
+    }
+
+    fn update_squash(&mut self, dt: f32, params: &AnimationParams) {
+        const STIFFNESS: f32 = 150.0;
+        const DAMPING: f32 = 8.0;
+
+        // a kick of v peaks near v / sqrt(stiffness)
+        let kick = self.squash * STIFFNESS.sqrt();
+        if self.grounded && !params.grounded && params.vertical_speed > 0.0 {
+            self.spring.1 += kick * params.vertical_speed;
+        } else if !self.grounded && params.grounded {
+            self.spring.1 -= kick * self.fall_speed;
+        }
+
+        self.fall_speed = if params.grounded {
+            0.0
+        } else {
+            self.fall_speed.max(-params.vertical_speed)
+        };
+        self.grounded = params.grounded;
+
+        let (offset, rate) = &mut self.spring;
+        *rate += (-STIFFNESS * *offset - DAMPING * *rate) * dt;
+        *offset = (*offset + *rate * dt).clamp(-0.3, 0.3);
+    }
+
+    /// Advances the cycle by `dt` and poses a body with proportions `p`
+    /// standing at `root` (its feet), facing `yaw` and looking towards
+    /// `look_at`, if it can.
+    pub fn update(
+        &mut self,
+        dt: f32,
+        params: &AnimationParams,
+        p: &Proportions,
+        root: Vec3,
+        yaw: f32,
+        look_at: Option<Vec3>,
+    ) -> SkeletonPose {
+        self.update_squash(dt, params);
+
+        /* ================= CYCLE ================= */
+
+        let target = if params.grounded {
+            (params.speed / self.stride_speed).min(1.0)
+        } else {
+            0.0
+        };
+        self.stride += (target - self.stride) * (dt * 6.0).min(1.0);
+
+        // one turn covers a step of each leg
+        let swing = self.leg_swing * self.stride;
+        let step = (2.0 * p.leg() * swing.sin()).max(0.1);
+        if params.grounded {
+            self.phase = (self.phase + params.speed * dt / (2.0 * step) * TAU).rem_euclid(TAU);
+        }
+        let (sin, cos) = self.phase.sin_cos();
+
+        let facing = Quat::from_rotation_y(yaw);
+        let forward = facing * Vec3::Z;
+        let mut pose = SkeletonPose::rest(p, root);
+
+        /* ================= SPINE ================= */
+
+        // hips over the straight stance leg, a little lower mid-stride
+        let stance = p.leg() * 0.98 * (swing * sin).cos();
+        let hips = root + Vec3::Y * (p.ankle + stance - self.bob * self.stride * sin * sin);
+
+        let lean = Quat::from_axis_angle(facing * Vec3::X, 0.12 * self.stride);
+        let up = lean * Vec3::Y;
+        let chest = hips + up * p.spine;
+        let neck = chest + up * p.neck;
+        let head = neck + Vec3::Y * p.head_to_neck + forward * 0.08;
+
+        let chest_rot = frame(neck - chest, forward);
+        pose.set(Joint::Hips, hips);
+        pose.set(Joint::Chest, chest);
+        pose.set(Joint::Neck, neck);
+        pose.set(Joint::Head, head);
+        pose.set_rotation(Joint::Hips, frame(chest - hips, forward));
+        pose.set_rotation(Joint::Chest, chest_rot);
+        pose.set_rotation(Joint::Neck, chest_rot);
+
+        /* ================= HEAD ================= */
+
+        let wrap = |a: f32| (a + PI).rem_euclid(TAU) - PI;
+        let look = look_at
+            .map(|at| at - head)
+            .map(|d| (wrap(d.x.atan2(d.z) - yaw), d.y.atan2(Vec3::new(d.x, 0.0, d.z).length())))
+            .filter(|(turn, _)| turn.abs() <= self.max_look)
+            .map_or((0.0, 0.0), |(turn, pitch)| (turn, pitch.clamp(-0.6, 0.6)));
+
+        let ease = (dt * 6.0).min(1.0);
+        self.look.0 += (look.0 - self.look.0) * ease;
+        self.look.1 += (look.1 - self.look.1) * ease;
+
+        // heads look down their -Z
+        let head_rot = Quat::from_rotation_y(yaw + self.look.0 + PI) * Quat::from_rotation_x(self.look.1);
+        pose.set_rotation(Joint::Head, head_rot);
+
+        /* ================= LEGS ================= */
+
+        for (s, phase, [hip_j, knee_j, foot_j]) in [
+            (1.0, 1.0, [Joint::LeftHip, Joint::LeftKnee, Joint::LeftFoot]),
+            (-1.0, -1.0, [Joint::RightHip, Joint::RightKnee, Joint::RightFoot]),
+        ] {
+            let hip = hips + facing * Vec3::new(s * p.hip_width * 0.5, 0.0, 0.0);
+
+            // forward while the leg swings (lifted), back while it stands
+            let reach = p.leg() * (swing * sin * phase).sin();
+            let lift = self.foot_lift * self.stride * (cos * phase).max(0.0);
+            let foot = Vec3::new(hip.x, root.y + p.ankle + lift, hip.z) + forward * reach;
+
+            let pole = hip + forward * 0.5 - Vec3::Y * 0.2;
+            let (knee, end) = solve_two_bone(hip, foot, pole, p.thigh, p.shin);
+
+            pose.set(hip_j, hip);
+            pose.set(knee_j, knee);
+            pose.set(foot_j, end);
+            pose.set_rotation(hip_j, frame(hip - knee, forward));
+            pose.set_rotation(knee_j, frame(knee - end, forward));
+            pose.set_rotation(foot_j, facing);
+        }
+
+        /* ================= ARMS ================= */
+
+        for (s, phase, [shoulder_j, elbow_j, hand_j]) in [
+            (1.0, -1.0, [Joint::LeftShoulder, Joint::LeftElbow, Joint::LeftHand]),
+            (-1.0, 1.0, [Joint::RightShoulder, Joint::RightElbow, Joint::RightHand]),
+        ] {
+            let shoulder = chest + chest_rot * Vec3::new(s * p.shoulder_width * 0.5, 0.0, 0.0);
+
+            // against the leg on the same side, elbows bending with pace
+            let upper = self.arm_swing * self.stride * sin * phase;
+            let lower = upper + 0.1 + 0.4 * self.stride;
+            let down = |angle: f32| facing * Quat::from_rotation_x(-angle) * Vec3::NEG_Y;
+
+            let elbow = shoulder + down(upper) * p.upper_arm;
+            let hand = elbow + down(lower) * p.forearm;
+
+            pose.set(shoulder_j, shoulder);
+            pose.set(elbow_j, elbow);
+            pose.set(hand_j, hand);
+            pose.set_rotation(shoulder_j, frame(shoulder - elbow, forward));
+            pose.set_rotation(elbow_j, frame(elbow - hand, forward));
+            // fingers (-Z) along the forearm
+            pose.set_rotation(hand_j, facing * Quat::from_rotation_x(-lower - FRAC_PI_2));
+        }
+
+        pose
+    }
+}
+
+impl Default for ProceduralWalk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,218 @@
+use glam::{Quat, Vec3};
+
+use super::definition::AvatarDefinition;
+
+/* =========================================================
+   JOINTS
+   ========================================================= */
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Joint {
+    Hips,
+    Chest,
+    Neck,
+    Head,
+
+    LeftShoulder,
+    LeftElbow,
+    LeftHand,
+    RightShoulder,
+    RightElbow,
+    RightHand,
+
+    LeftHip,
+    LeftKnee,
+    LeftFoot,
+    RightHip,
+    RightKnee,
+    RightFoot,
+}
+
+impl Joint {
+    pub const COUNT: usize = 16;
+
+    pub const ALL: [Joint; Joint::COUNT] = [
+        Joint::Hips,
+        Joint::Chest,
+        Joint::Neck,
+        Joint::Head,
+        Joint::LeftShoulder,
+        Joint::LeftElbow,
+        Joint::LeftHand,
+        Joint::RightShoulder,
+        Joint::RightElbow,
+        Joint::RightHand,
+        Joint::LeftHip,
+        Joint::LeftKnee,
+        Joint::LeftFoot,
+        Joint::RightHip,
+        Joint::RightKnee,
+        Joint::RightFoot,
+    ];
+
+    /// Name in avatar definition files: "hips", "left_elbow", ...
+    pub fn name(self) -> &'static str {
+        match self {
+            Joint::Hips => "hips",
+            Joint::Chest => "chest",
+            Joint::Neck => "neck",
+            Joint::Head => "head",
+            Joint::LeftShoulder => "left_shoulder",
+            Joint::LeftElbow => "left_elbow",
+            Joint::LeftHand => "left_hand",
+            Joint::RightShoulder => "right_shoulder",
+            Joint::RightElbow => "right_elbow",
+            Joint::RightHand => "right_hand",
+            Joint::LeftHip => "left_hip",
+            Joint::LeftKnee => "left_knee",
+            Joint::LeftFoot => "left_foot",
+            Joint::RightHip => "right_hip",
+            Joint::RightKnee => "right_knee",
+            Joint::RightFoot => "right_foot",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Joint> {
+        Joint::ALL.into_iter().find(|j| j.name() == name)
+    }
+
+    /// Limb segments drawn between joints.
+    pub const BONES: [(Joint, Joint); 10] = [
+        (Joint::Hips, Joint::Chest),
+        (Joint::Chest, Joint::Neck),
+        (Joint::LeftShoulder, Joint::LeftElbow),
+        (Joint::LeftElbow, Joint::LeftHand),
+        (Joint::RightShoulder, Joint::RightElbow),
+        (Joint::RightElbow, Joint::RightHand),
+        (Joint::LeftHip, Joint::LeftKnee),
+        (Joint::LeftKnee, Joint::LeftFoot),
+        (Joint::RightHip, Joint::RightKnee),
+        (Joint::RightKnee, Joint::RightFoot),
+    ];
+}
+
+/* =========================================================
+   PROPORTIONS
+   ========================================================= */
+
+/// Segment lengths of the humanoid, in metres.
+#[derive(Clone, Copy, Debug)]
+pub struct Proportions {
+    pub height: f32,
+    pub eye_height: f32,
+
+    pub head_to_neck: f32,
+    pub neck: f32,
+    pub spine: f32,
+
+    pub shoulder_width: f32,
+    pub upper_arm: f32,
+    pub forearm: f32,
+
+    pub hip_width: f32,
+    pub thigh: f32,
+    pub shin: f32,
+    pub ankle: f32,
+}
+
+impl Proportions {
+    /// Average adult ratios scaled to `height`.
+    pub fn humanoid(height: f32) -> Self {
+        Self {
+            height,
+            eye_height: height * 0.93,
+            head_to_neck: height * 0.07,
+            neck: height * 0.06,
+            spine: height * 0.24,
+            shoulder_width: height * 0.22,
+            upper_arm: height * 0.17,
+            forearm: height * 0.16,
+            hip_width: height * 0.11,
+            thigh: height * 0.245,
+            shin: height * 0.245,
+            ankle: height * 0.04,
+        }
+    }
+
+    /// Sized for the definition's height and overall scale.
+    pub fn from_definition(def: &AvatarDefinition) -> Self {
+        Self::humanoid(def.height * def.scale)
+    }
+
+    pub fn arm(&self) -> f32 {
+        self.upper_arm + self.forearm
+    }
+
+    pub fn leg(&self) -> f32 {
+        self.thigh + self.shin
+    }
+}
+
+/* =========================================================
+   POSE
+   ========================================================= */
+
+/// World-space joint positions and orientations.
+#[derive(Clone, Debug)]
+pub struct SkeletonPose {
+    pub positions: [Vec3; Joint::COUNT],
+    pub rotations: [Quat; Joint::COUNT],
+}
+
+impl SkeletonPose {
+    /// Standing T-less rest pose facing +Z at `root`.
+    pub fn rest(p: &Proportions, root: Vec3) -> Self {
+        let mut pose = Self {
+            positions: [root; Joint::COUNT],
+            rotations: [Quat::IDENTITY; Joint::COUNT],
+        };
+
+        let hip_y = p.leg() + p.ankle;
+        let side = |s: f32, w: f32| Vec3::new(-s * w * 0.5, 0.0, 0.0);
+
+        pose.set(Joint::Hips, root + Vec3::Y * hip_y);
+        pose.set(Joint::Chest, pose[Joint::Hips] + Vec3::Y * p.spine);
+        pose.set(Joint::Neck, pose[Joint::Chest] + Vec3::Y * p.neck);
+        pose.set(Joint::Head, pose[Joint::Neck] + Vec3::Y * p.head_to_neck);
+
+        for (s, shoulder, elbow, hand) in [
+            (-1.0, Joint::LeftShoulder, Joint::LeftElbow, Joint::LeftHand),
+            (1.0, Joint::RightShoulder, Joint::RightElbow, Joint::RightHand),
+        ] {
+            pose.set(shoulder, pose[Joint::Chest] + side(s, p.shoulder_width));
+            pose.set(elbow, pose[shoulder] - Vec3::Y * p.upper_arm);
+            pose.set(hand, pose[elbow] - Vec3::Y * p.forearm);
+        }
+
+        for (s, hip, knee, foot) in [
+            (-1.0, Joint::LeftHip, Joint::LeftKnee, Joint::LeftFoot),
+            (1.0, Joint::RightHip, Joint::RightKnee, Joint::RightFoot),
+        ] {
+            pose.set(hip, pose[Joint::Hips] + side(s, p.hip_width));
+            pose.set(knee, pose[hip] - Vec3::Y * p.thigh);
+            pose.set(foot, pose[knee] - Vec3::Y * p.shin);
+        }
+
+        pose
+    }
+
+    pub fn set(&mut self, joint: Joint, position: Vec3) {
+        self.positions[joint as usize] = position;
+    }
+
+    pub fn rotation(&self, joint: Joint) -> Quat {
+        self.rotations[joint as usize]
+    }
+
+    pub fn set_rotation(&mut self, joint: Joint, rotation: Quat) {
+        self.rotations[joint as usize] = rotation;
+    }
+}
+
+impl std::ops::Index<Joint> for SkeletonPose {
+    type Output = Vec3;
+
+    fn index(&self, joint: Joint) -> &Vec3 {
+        &self.positions[joint as usize]
+    }
+}
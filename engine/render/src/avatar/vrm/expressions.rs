@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use engine_core::EntityId;
+
+/// One morph target of one drawn primitive, at `weight` when its
+/// expression is fully on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MorphBind {
+    pub entity: EntityId,
+    pub target: usize,
+    pub weight: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Expression {
+    /// VRM 1.0 name: "happy", "blink", "aa", ... or a custom one.
+    pub name: String,
+    /// Snaps to fully on or off.
+    pub binary: bool,
+    pub binds: Vec<MorphBind>,
+}
+
+/// Facial expression component on a VRM's root. Set weights by name;
+/// `morph_weights` blends them into per-primitive morph target weights.
+#[derive(Clone, Debug, Default)]
+pub struct Expressions {
+    pub expressions: Vec<Expression>,
+    weights: Vec<f32>,
+}
+
+impl Expressions {
+    pub fn new(expressions: Vec<Expression>) -> Self {
+        Self {
+            weights: vec![0.0; expressions.len()],
+            expressions,
+        }
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.expressions.iter().position(|e| e.name == name)
+    }
+
+    /// Returns false if the avatar has no such expression.
+    pub fn set(&mut self, name: &str, weight: f32) -> bool {
+        let Some(i) = self.find(name) else {
+            return false;
+        };
+        self.weights[i] = weight.clamp(0.0, 1.0);
+        true
+    }
+
+    pub fn get(&self, name: &str) -> f32 {
+        self.find(name).map_or(0.0, |i| self.weights[i])
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.expressions.iter().map(|e| e.name.as_str())
+    }
+
+    /// Summed weight of every bound morph target, clamped to 0..1, per
+    /// entity; entities with no binds are absent.
+    pub fn morph_weights(&self) -> HashMap<EntityId, Vec<f32>> {
+        let mut out: HashMap<EntityId, Vec<f32>> = HashMap::new();
+
+        for (expression, &weight) in self.expressions.iter().zip(&self.weights) {
+            let weight = if expression.binary {
+                if weight > 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            } else {
+                weight
+            };
+
+            for bind in &expression.binds {
+                let weights = out.entry(bind.entity).or_default();
+                if weights.len() <= bind.target {
+                    weights.resize(bind.target + 1, 0.0);
+                }
+                weights[bind.target] += bind.weight * weight;
+            }
+        }
+
+        for weights in out.values_mut() {
+            weights.iter_mut().for_each(|w| *w = w.clamp(0.0, 1.0));
+        }
+
+        out
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashSet;
+
+use engine_core::{EntityId, RenderLayers, Renderable, World};
+
+use super::parse::{FirstPersonFlag, VrmDocument};
+use crate::model::Model;
+use crate::renderer::mesh::morph::MorphWeights;
+use crate::renderer::mesh::skin::Skin;
+use crate::renderer::mesh::{MeshData, MeshId, MeshLibrary};
+
+/*
+   First-person visibility of a VRM's meshes.
+
+   The local user must not see the inside of their own head, but their
+   mirror image and other cameras should. Third-person-only meshes go on
+   `LOCAL_HEAD`, first-person-only ones on `LOCAL_FIRST_PERSON`. "Auto"
+   skinned meshes (usually the whole body in one) are split: the
+   original goes on `LOCAL_HEAD`, and a copy without the triangles
+   skinned to the head goes on `LOCAL_FIRST_PERSON`.
+*/
+
+/// Sets the layers of every primitive of the model; `head` is the head
+/// bone's entity. Meshes without an annotation are treated as "auto".
+pub fn apply_first_person(
+    doc: &VrmDocument,
+    model: &Model,
+    head: EntityId,
+    world: &mut World,
+    meshes: &mut MeshLibrary,
+) {
+    let mut flags: Vec<(EntityId, FirstPersonFlag)> = Vec::new();
+    let mut annotated = HashSet::new();
+
+    for &(target, flag) in &doc.first_person {
+        for node in doc.nodes(target) {
+            for &entity in model.primitives.get(node).into_iter().flatten() {
+                if annotated.insert(entity) {
+                    flags.push((entity, flag));
+                }
+            }
+        }
+    }
+
+    for &entity in model.primitives.iter().flatten() {
+        if annotated.insert(entity) {
+            flags.push((entity, FirstPersonFlag::Auto));
+        }
+    }
+
+    for (entity, flag) in flags {
+        let layers = match flag {
+            FirstPersonFlag::Both => RenderLayers::DEFAULT,
+            FirstPersonFlag::ThirdPersonOnly => RenderLayers::LOCAL_HEAD,
+            FirstPersonFlag::FirstPersonOnly => RenderLayers::LOCAL_FIRST_PERSON,
+            FirstPersonFlag::Auto => auto(entity, head, world, meshes),
+        };
+
+        if let Some(r) = world.get_mut::<Renderable>(entity) {
+            r.layers = layers;
+        }
+    }
+}
+
+/// Layers for an "auto" primitive, splitting off its headless copy.
+fn auto(entity: EntityId, head: EntityId, world: &mut World, meshes: &mut MeshLibrary) -> RenderLayers {
+    let Some(skin) = world.get::<Skin>(entity).cloned() else {
+        // rigid: hidden if it hangs off the head
+        return if world.is_ancestor(head, entity) {
+            RenderLayers::LOCAL_HEAD
+        } else {
+            RenderLayers::DEFAULT
+        };
+    };
+
+    let Some((mesh, material)) = world.get::<Renderable>(entity).map(|r| (r.mesh, r.material)) else {
+        return RenderLayers::DEFAULT;
+    };
+
+    let head_joints: Vec<bool> = skin.joints.iter().map(|&j| world.is_ancestor(head, j)).collect();
+    if !head_joints.contains(&true) {
+        return RenderLayers::DEFAULT;
+    }
+
+    let data = meshes.get(MeshId(mesh));
+    if !data.is_skinned() {
+        return RenderLayers::DEFAULT;
+    }
+
+    let on_head = |v: u32| {
+        let v = v as usize;
+        data.joints[v]
+            .iter()
+            .zip(data.weights[v])
+            .any(|(&j, w)| w > 0.0 && head_joints.get(j as usize).copied().unwrap_or(false))
+    };
+
+    let indices: Vec<u32> = data
+        .indices
+        .chunks_exact(3)
+        .filter(|tri| !tri.iter().any(|&v| on_head(v)))
+        .flatten()
+        .copied()
+        .collect();
+
+    let headless = MeshData {
+        name: format!("{}/first_person", data.name),
+        indices,
+        ..data.clone()
+    };
+    let headless = meshes.add(headless);
+
+    let name = world
+        .entity(entity)
+        .map(|e| format!("{}/first_person", e.name))
+        .unwrap_or_default();
+    let copy = world.spawn(&name, world.transform(entity));
+    world.set_parent(copy, world.parent(entity), false);
+    world.insert(
+        copy,
+        Renderable::new(headless.0, material).with_layers(RenderLayers::LOCAL_FIRST_PERSON),
+    );
+    world.insert(copy, skin);
+    // keeps the default shape; expressions only move the face it lacks
+    if let Some(weights) = world.get::<MorphWeights>(entity).cloned() {
+        world.insert(copy, weights);
+    }
+
+    RenderLayers::LOCAL_HEAD
+}
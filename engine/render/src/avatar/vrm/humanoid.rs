@@ -0,0 +1,362 @@
+use glam::{Mat3, Quat, Vec3};
+
+use engine_core::{EntityId, Transform, World};
+
+use super::parse::{HumanBone, VrmDocument};
+use crate::avatar::{Joint, Proportions, SkeletonPose};
+use crate::model::Model;
+
+/*
+   Drives a VRM's humanoid bones from the tracked skeleton pose.
+
+   The pose's rotations are deltas from its rest pose (identity, facing
+   +Z, limbs hanging along -Y, hands pointing along -Z like a
+   controller, head looking down -Z like the HMD). Each bone gets
+   `pose * align * rest`, where `rest` is its world rotation in the
+   model's rest pose and `align` turns the model's rest limb direction
+   (VRMs rest in a T-pose) onto the pose's.
+*/
+
+struct DrivenBone {
+    entity: EntityId,
+    joint: Joint,
+    align: Quat,
+    rest: Quat,
+}
+
+pub struct Humanoid {
+    /// Parents before children.
+    bones: Vec<DrivenBone>,
+    hips: EntityId,
+    /// Hips relative to the thighs' midpoint, in the rest pose.
+    hips_offset: Vec3,
+    proportions: Proportions,
+}
+
+/// Pose joint driving each bone.
+const DRIVERS: [(HumanBone, Joint); 20] = [
+    (HumanBone::Hips, Joint::Hips),
+    (HumanBone::Spine, Joint::Hips),
+    (HumanBone::Chest, Joint::Chest),
+    (HumanBone::UpperChest, Joint::Chest),
+    (HumanBone::Neck, Joint::Neck),
+    (HumanBone::Head, Joint::Head),
+    (HumanBone::LeftShoulder, Joint::Chest),
+    (HumanBone::LeftUpperArm, Joint::LeftShoulder),
+    (HumanBone::LeftLowerArm, Joint::LeftElbow),
+    (HumanBone::LeftHand, Joint::LeftHand),
+    (HumanBone::RightShoulder, Joint::Chest),
+    (HumanBone::RightUpperArm, Joint::RightShoulder),
+    (HumanBone::RightLowerArm, Joint::RightElbow),
+    (HumanBone::RightHand, Joint::RightHand),
+    (HumanBone::LeftUpperLeg, Joint::LeftHip),
+    (HumanBone::LeftLowerLeg, Joint::LeftKnee),
+    (HumanBone::LeftFoot, Joint::LeftFoot),
+    (HumanBone::RightUpperLeg, Joint::RightHip),
+    (HumanBone::RightLowerLeg, Joint::RightKnee),
+    (HumanBone::RightFoot, Joint::RightFoot),
+];
+
+/// Shortest rotation turning `from` onto `to`; identity if either is
+/// zero length (a bone sitting on its child).
+fn arc(from: Vec3, to: Vec3) -> Quat {
+    let (from, to) = (from.normalize_or_zero(), to.normalize_or_zero());
+    if from == Vec3::ZERO || to == Vec3::ZERO {
+        return Quat::IDENTITY;
+    }
+    Quat::from_rotation_arc(from, to)
+}
+
+/// Rotation taking the `(dir, side)` frame onto `(to_dir, to_side)`.
+fn align(dir: Vec3, side: Vec3, to_dir: Vec3, to_side: Vec3) -> Quat {
+    let basis = |d: Vec3, s: Vec3| {
+        let d = d.normalize_or_zero();
+        let s = (s - d * s.dot(d)).normalize_or_zero();
+        Mat3::from_cols(d, s, d.cross(s))
+    };
+
+    let from = basis(dir, side);
+    let to = basis(to_dir, to_side);
+    if from.determinant().abs() < 0.5 || to.determinant().abs() < 0.5 {
+        return arc(dir, to_dir);
+    }
+
+    Quat::from_mat3(&(to * from.transpose())).normalize()
+}
+
+impl Humanoid {
+    /// Reads the rest pose; `model.root` must be at its rest transform
+    /// (at the origin, facing +Z).
+    pub fn new(doc: &VrmDocument, model: &Model, world: &World) -> Option<Self> {
+        let entity = |bone: HumanBone| doc.bone(bone).and_then(|n| model.node(n));
+        let rest = |bone: HumanBone| entity(bone).map(|e| world.world_transform(e));
+        let position = |bone: HumanBone| rest(bone).map(|t| t.position);
+
+        let required = [
+            HumanBone::Hips,
+            HumanBone::Head,
+            HumanBone::LeftUpperArm,
+            HumanBone::LeftLowerArm,
+            HumanBone::LeftHand,
+            HumanBone::RightUpperArm,
+            HumanBone::RightLowerArm,
+            HumanBone::RightHand,
+            HumanBone::LeftUpperLeg,
+            HumanBone::LeftLowerLeg,
+            HumanBone::LeftFoot,
+            HumanBone::RightUpperLeg,
+            HumanBone::RightLowerLeg,
+            HumanBone::RightFoot,
+        ];
+        if required.iter().any(|b| entity(*b).is_none()) {
+            return None;
+        }
+
+        let p = |bone: HumanBone| position(bone).unwrap();
+        let thighs = (p(HumanBone::LeftUpperLeg) + p(HumanBone::RightUpperLeg)) * 0.5;
+
+        let mut bones = Vec::new();
+        for (bone, joint) in DRIVERS {
+            let (Some(e), Some(t)) = (entity(bone), rest(bone)) else {
+                continue;
+            };
+
+            // direction the pose expects the bone to point, and the
+            // model's actual one
+            let child = match bone {
+                HumanBone::LeftUpperArm => Some(HumanBone::LeftLowerArm),
+                HumanBone::LeftLowerArm => Some(HumanBone::LeftHand),
+                HumanBone::RightUpperArm => Some(HumanBone::RightLowerArm),
+                HumanBone::RightLowerArm => Some(HumanBone::RightHand),
+                HumanBone::LeftUpperLeg => Some(HumanBone::LeftLowerLeg),
+                HumanBone::LeftLowerLeg => Some(HumanBone::LeftFoot),
+                HumanBone::RightUpperLeg => Some(HumanBone::RightLowerLeg),
+                HumanBone::RightLowerLeg => Some(HumanBone::RightFoot),
+                _ => None,
+            };
+
+            let align = match (bone, child) {
+                (_, Some(child)) => arc(p(child) - t.position, Vec3::NEG_Y),
+                (HumanBone::LeftHand | HumanBone::RightHand, _) => {
+                    let (forearm, finger, inward) = if bone == HumanBone::LeftHand {
+                        (HumanBone::LeftLowerArm, HumanBone::LeftMiddleProximal, Vec3::NEG_X)
+                    } else {
+                        (HumanBone::RightLowerArm, HumanBone::RightMiddleProximal, Vec3::X)
+                    };
+                    let dir = position(finger).unwrap_or(t.position * 2.0 - p(forearm)) - t.position;
+
+                    // T-pose palms face down
+                    align(dir, Vec3::NEG_Y, Vec3::NEG_Z, inward)
+                }
+                (HumanBone::Head, _) => Quat::from_rotation_y(std::f32::consts::PI),
+                _ => Quat::IDENTITY,
+            };
+
+            bones.push(DrivenBone {
+                entity: e,
+                joint,
+                align,
+                rest: t.rotation,
+            });
+        }
+
+        /* ---------- proportions ---------- */
+
+        let head = rest(HumanBone::Head).unwrap();
+        let eye = head.position + head.rotation * doc.eye_offset;
+        let shoulders = (p(HumanBone::LeftUpperArm) + p(HumanBone::RightUpperArm)) * 0.5;
+        let neck = position(HumanBone::Neck).unwrap_or((shoulders + head.position) * 0.5);
+        let ankle = (p(HumanBone::LeftFoot).y + p(HumanBone::RightFoot).y) * 0.5;
+
+        let proportions = Proportions {
+            height: eye.y / 0.93,
+            eye_height: eye.y,
+            head_to_neck: (eye.y - neck.y).max(0.02),
+            neck: (neck.y - shoulders.y).max(0.02),
+            spine: (shoulders.y - thighs.y).max(0.1),
+            shoulder_width: p(HumanBone::LeftUpperArm).distance(p(HumanBone::RightUpperArm)),
+            upper_arm: p(HumanBone::LeftUpperArm).distance(p(HumanBone::LeftLowerArm)),
+            forearm: p(HumanBone::LeftLowerArm).distance(p(HumanBone::LeftHand)),
+            hip_width: p(HumanBone::LeftUpperLeg).distance(p(HumanBone::RightUpperLeg)),
+            thigh: p(HumanBone::LeftUpperLeg).distance(p(HumanBone::LeftLowerLeg)),
+            shin: p(HumanBone::LeftLowerLeg).distance(p(HumanBone::LeftFoot)),
+            ankle: ankle.max(0.0),
+        };
+
+        Some(Self {
+            bones,
+            hips: entity(HumanBone::Hips).unwrap(),
+            hips_offset: p(HumanBone::Hips) - thighs,
+            proportions,
+        })
+    }
+
+    /// Segment lengths measured from the model's rest pose.
+    pub fn proportions(&self) -> Proportions {
+        self.proportions
+    }
+
+    pub fn apply(&self, world: &mut World, pose: &SkeletonPose) {
+        for bone in &self.bones {
+            let rotation = (pose.rotation(bone.joint) * bone.align * bone.rest).normalize();
+
+            if bone.entity == self.hips {
+                let thighs = (pose[Joint::LeftHip] + pose[Joint::RightHip]) * 0.5;
+                let position = thighs + pose.rotation(Joint::Hips) * self.hips_offset;
+                let scale = world.world_transform(bone.entity).scale;
+                world.set_world_transform(
+                    bone.entity,
+                    Transform::from_position_rotation(position, rotation).with_scale(scale),
+                );
+                continue;
+            }
+
+            let parent = world
+                .parent(bone.entity)
+                .map(|p| world.world_transform(p).rotation)
+                .unwrap_or(Quat::IDENTITY);
+
+            let mut local = world.transform(bone.entity);
+            local.rotation = (parent.inverse() * rotation).normalize();
+            world.set_transform(bone.entity, local);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avatar::vrm::parse::VrmVersion;
+
+    /// A VRM 1.0 T-pose, every bone straight under the root, hips 1 m up.
+    fn t_pose(bones: &[(HumanBone, Vec3)]) -> (World, Model, VrmDocument) {
+        let mut world = World::new();
+        let root = world.spawn("root", Transform::default());
+        let nodes = bones
+            .iter()
+            .map(|(_, at)| {
+                let e = world.spawn("bone", Transform::from_position(*at));
+                world.set_parent(e, Some(root), false);
+                Some(e)
+            })
+            .collect();
+
+        let model = Model {
+            root,
+            nodes,
+            primitives: Vec::new(),
+            meshes: Vec::new(),
+            materials: Vec::new(),
+            warnings: Vec::new(),
+        };
+        let doc = VrmDocument {
+            version: VrmVersion::V1,
+            name: "test".into(),
+            humanoid: bones.iter().enumerate().map(|(n, (b, _))| (*b, n)).collect(),
+            springs: Vec::new(),
+            colliders: Vec::new(),
+            expressions: Vec::new(),
+            first_person: Vec::new(),
+            eye_offset: Vec3::new(0.0, 0.1, 0.1),
+            toon: Vec::new(),
+            node_meshes: Vec::new(),
+        };
+        (world, model, doc)
+    }
+
+    fn skeleton() -> Vec<(HumanBone, Vec3)> {
+        vec![
+            (HumanBone::Hips, Vec3::new(0.0, 1.0, 0.0)),
+            (HumanBone::Head, Vec3::new(0.0, 1.5, 0.0)),
+            (HumanBone::LeftUpperArm, Vec3::new(0.2, 1.4, 0.0)),
+            (HumanBone::LeftLowerArm, Vec3::new(0.45, 1.4, 0.0)),
+            (HumanBone::LeftHand, Vec3::new(0.7, 1.4, 0.0)),
+            (HumanBone::RightUpperArm, Vec3::new(-0.2, 1.4, 0.0)),
+            (HumanBone::RightLowerArm, Vec3::new(-0.45, 1.4, 0.0)),
+            (HumanBone::RightHand, Vec3::new(-0.7, 1.4, 0.0)),
+            (HumanBone::LeftUpperLeg, Vec3::new(0.1, 0.9, 0.0)),
+            (HumanBone::LeftLowerLeg, Vec3::new(0.1, 0.5, 0.0)),
+            (HumanBone::LeftFoot, Vec3::new(0.1, 0.1, 0.0)),
+            (HumanBone::RightUpperLeg, Vec3::new(-0.1, 0.9, 0.0)),
+            (HumanBone::RightLowerLeg, Vec3::new(-0.1, 0.5, 0.0)),
+            (HumanBone::RightFoot, Vec3::new(-0.1, 0.1, 0.0)),
+        ]
+    }
+
+    fn rotation(world: &World, model: &Model, doc: &VrmDocument, bone: HumanBone) -> Quat {
+        world.world_transform(model.node(doc.bone(bone).unwrap()).unwrap()).rotation
+    }
+
+    #[test]
+    fn align_maps_frames_and_survives_zero_vectors() {
+        let q = align(Vec3::X, Vec3::NEG_Y, Vec3::NEG_Z, Vec3::NEG_X);
+        assert!((q * Vec3::X).abs_diff_eq(Vec3::NEG_Z, 1e-5));
+        assert!((q * Vec3::NEG_Y).abs_diff_eq(Vec3::NEG_X, 1e-5));
+
+        assert_eq!(align(Vec3::ZERO, Vec3::NEG_Y, Vec3::NEG_Z, Vec3::X), Quat::IDENTITY);
+        assert_eq!(arc(Vec3::ZERO, Vec3::NEG_Y), Quat::IDENTITY);
+    }
+
+    #[test]
+    fn needs_the_limbs() {
+        let mut bones = skeleton();
+        bones.retain(|(b, _)| *b != HumanBone::LeftFoot);
+        let (world, model, doc) = t_pose(&bones);
+        assert!(Humanoid::new(&doc, &model, &world).is_none());
+    }
+
+    #[test]
+    fn measures_proportions_from_the_rest_pose() {
+        let (world, model, doc) = t_pose(&skeleton());
+        let p = Humanoid::new(&doc, &model, &world).unwrap().proportions();
+
+        assert!((p.eye_height - 1.6).abs() < 1e-5);
+        assert!((p.shoulder_width - 0.4).abs() < 1e-5);
+        assert!((p.upper_arm - 0.25).abs() < 1e-5);
+        assert!((p.forearm - 0.25).abs() < 1e-5);
+        assert!((p.hip_width - 0.2).abs() < 1e-5);
+        assert!((p.thigh - 0.4).abs() < 1e-5);
+        assert!((p.shin - 0.4).abs() < 1e-5);
+        assert!((p.ankle - 0.1).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rest_pose_lowers_the_t_pose_arms() {
+        let (mut world, model, doc) = t_pose(&skeleton());
+        let humanoid = Humanoid::new(&doc, &model, &world).unwrap();
+        let pose = SkeletonPose::rest(&humanoid.proportions(), Vec3::ZERO);
+        humanoid.apply(&mut world, &pose);
+
+        let left = rotation(&world, &model, &doc, HumanBone::LeftUpperArm);
+        let right = rotation(&world, &model, &doc, HumanBone::RightLowerArm);
+        let shin = rotation(&world, &model, &doc, HumanBone::LeftLowerLeg);
+        assert!((left * Vec3::X).abs_diff_eq(Vec3::NEG_Y, 1e-4));
+        assert!((right * Vec3::NEG_X).abs_diff_eq(Vec3::NEG_Y, 1e-4));
+        assert!(shin.abs_diff_eq(Quat::IDENTITY, 1e-4));
+
+        // hips over the pose's thighs, kept 10 cm above them
+        let hips = world.world_transform(model.node(0).unwrap()).position;
+        let thighs = (pose[Joint::LeftHip] + pose[Joint::RightHip]) * 0.5;
+        assert!(hips.abs_diff_eq(thighs + Vec3::Y * 0.1, 1e-4));
+    }
+
+    #[test]
+    fn zero_length_bones_stay_at_rest() {
+        // forearm and hand on the same spot, and no finger to aim the hand
+        let mut bones = skeleton();
+        for (bone, at) in &mut bones {
+            if *bone == HumanBone::LeftLowerArm {
+                *at = Vec3::new(0.7, 1.4, 0.0);
+            }
+        }
+        let (mut world, model, doc) = t_pose(&bones);
+        let humanoid = Humanoid::new(&doc, &model, &world).unwrap();
+        humanoid.apply(&mut world, &SkeletonPose::rest(&humanoid.proportions(), Vec3::ZERO));
+
+        for (bone, _) in &bones {
+            assert!(rotation(&world, &model, &doc, *bone).is_finite(), "{:?}", bone);
+        }
+        let forearm = rotation(&world, &model, &doc, HumanBone::LeftLowerArm);
+        assert!(forearm.abs_diff_eq(Quat::IDENTITY, 1e-5));
+    }
+}
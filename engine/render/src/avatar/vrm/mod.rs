@@ -0,0 +1,161 @@
+pub mod expressions;
+pub mod first_person;
+pub mod humanoid;
+pub mod parse;
+pub mod spring;
+
+use std::fs;
+use std::path::Path;
+
+use glam::{Quat, Vec3};
+
+use engine_core::{Transform, World};
+
+use crate::avatar::{AvatarBody, Joint, Proportions};
+use crate::model::{gltf_import, Model, ModelError};
+use crate::renderer::material::MaterialLibrary;
+use crate::renderer::mesh::morph::MorphWeights;
+use crate::renderer::mesh::MeshLibrary;
+
+use expressions::{Expression, Expressions, MorphBind};
+use first_person::apply_first_person;
+use humanoid::Humanoid;
+use parse::{document_json, parse_vrm, HumanBone, VrmVersion};
+use spring::SpringBones;
+
+/*
+   A VRM (0.x or 1.0) avatar standing in for the capsule body.
+
+   The model is imported like any glTF, then its humanoid bones follow
+   the tracked `AvatarBody` pose, its spring bones swing, its meshes are
+   split for first-person viewing and its MToon materials shade as toon.
+   Facial expressions live in an `Expressions` component on the root
+   and drive the `MorphWeights` of the meshes they bind every update.
+*/
+pub struct VrmAvatar {
+    pub model: Model,
+    pub name: String,
+    pub version: VrmVersion,
+
+    humanoid: Humanoid,
+    springs: SpringBones,
+    /// Turns the model to face +Z (VRM 0.x faces -Z).
+    facing_fix: Quat,
+    last_root: Option<Vec3>,
+}
+
+impl VrmAvatar {
+    pub fn load(
+        path: impl AsRef<Path>,
+        world: &mut World,
+        meshes: &mut MeshLibrary,
+        materials: &mut MaterialLibrary,
+    ) -> Result<Self, ModelError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+        let base = path.parent().unwrap_or(Path::new("."));
+
+        // the VRM extensions are read before importing, so a plain glTF
+        // fails early
+        let doc = parse_vrm(&document_json(&bytes)?)?;
+        let model = gltf_import::import_gltf(&bytes, base, &doc.name, world, meshes, materials)?;
+
+        let facing_fix = match doc.version {
+            VrmVersion::V0 => Quat::from_rotation_y(std::f32::consts::PI),
+            VrmVersion::V1 => Quat::IDENTITY,
+        };
+        world.set_transform(model.root, Transform::from_position_rotation(Vec3::ZERO, facing_fix));
+
+        let humanoid = Humanoid::new(&doc, &model, world)
+            .ok_or_else(|| ModelError::Invalid("VRM humanoid is missing required bones".into()))?;
+        let springs = SpringBones::new(&doc, &model, world);
+
+        for &(index, toon) in &doc.toon {
+            if let Some(material) = model.materials.get(index).and_then(|&id| materials.get_mut(id)) {
+                material.toon = Some(toon);
+            }
+        }
+
+        let expressions = doc
+            .expressions
+            .iter()
+            .map(|e| Expression {
+                name: e.name.clone(),
+                binary: e.binary,
+                binds: e
+                    .binds
+                    .iter()
+                    .flat_map(|b| {
+                        doc.nodes(b.target)
+                            .into_iter()
+                            .flat_map(|n| model.primitives.get(n).cloned().unwrap_or_default())
+                            .map(move |entity| MorphBind {
+                                entity,
+                                target: b.index,
+                                weight: b.weight,
+                            })
+                    })
+                    .collect(),
+            })
+            .collect();
+        world.insert(model.root, Expressions::new(expressions));
+
+        if let Some(head) = doc.bone(HumanBone::Head).and_then(|n| model.node(n)) {
+            apply_first_person(&doc, &model, head, world, meshes);
+        }
+
+        Ok(Self {
+            model,
+            name: doc.name,
+            version: doc.version,
+            humanoid,
+            springs,
+            facing_fix,
+            last_root: None,
+        })
+    }
+
+    /// The model's segment lengths; give them to `AvatarBody` so the
+    /// solved pose fits the model.
+    pub fn proportions(&self) -> Proportions {
+        self.humanoid.proportions()
+    }
+
+    /// Poses the model from the body's solved skeleton.
+    pub fn update(&mut self, world: &mut World, body: &AvatarBody, dt: f32) {
+        let pose = &body.pose;
+
+        // the root stands on the floor under the hips, for whatever is
+        // not bound to a bone
+        let hips = pose[Joint::Hips];
+        let floor = pose[Joint::LeftFoot].y.min(pose[Joint::RightFoot].y) - body.proportions.ankle;
+        let root = Vec3::new(hips.x, floor, hips.z);
+        world.set_transform(
+            self.model.root,
+            Transform::from_position_rotation(root, body.facing() * self.facing_fix),
+        );
+
+        self.humanoid.apply(world, pose);
+
+        let teleported = self.last_root.is_none_or(|last| last.distance(root) > 1.0);
+        if teleported {
+            self.springs.reset(world);
+        } else {
+            self.springs.update(world, dt);
+        }
+        self.last_root = Some(root);
+
+        let expressions = world.get::<Expressions>(self.model.root).map(Expressions::morph_weights);
+        for (entity, weights) in expressions.unwrap_or_default() {
+            if let Some(morph) = world.get_mut::<MorphWeights>(entity) {
+                for (target, weight) in weights.into_iter().enumerate() {
+                    morph.set(target, weight);
+                }
+            }
+        }
+    }
+
+    pub fn expressions<'a>(&self, world: &'a mut World) -> Option<&'a mut Expressions> {
+        world.get_mut::<Expressions>(self.model.root)
+    }
+}
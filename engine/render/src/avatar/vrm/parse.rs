@@ -0,0 +1,599 @@
+use glam::Vec3;
+use serde_json::Value;
+
+use crate::model::ModelError;
+use crate::renderer::material::ToonShading;
+
+/*
+   The VRM extensions of a glTF document, read from its JSON.
+
+   VRM 0.x keeps everything under `extensions.VRM`, VRM 1.0 splits it
+   into `VRMC_vrm`, `VRMC_springBone` and a `VRMC_materials_mtoon` per
+   material. Both are normalised here into one description: VRM 1.0
+   names, linear colours, and VRM 0.x's flipped Z axis undone.
+*/
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VrmVersion {
+    /// Faces -Z.
+    V0,
+    /// Faces +Z.
+    V1,
+}
+
+/// Humanoid bones we retarget onto; VRM defines more (fingers, eyes,
+/// toes, jaw), which are left at rest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HumanBone {
+    Hips,
+    Spine,
+    Chest,
+    UpperChest,
+    Neck,
+    Head,
+    LeftShoulder,
+    LeftUpperArm,
+    LeftLowerArm,
+    LeftHand,
+    RightShoulder,
+    RightUpperArm,
+    RightLowerArm,
+    RightHand,
+    LeftUpperLeg,
+    LeftLowerLeg,
+    LeftFoot,
+    RightUpperLeg,
+    RightLowerLeg,
+    RightFoot,
+    /// Any finger's first bone, for the hand's direction.
+    LeftMiddleProximal,
+    RightMiddleProximal,
+}
+
+impl HumanBone {
+    /// VRM 1.0 name; VRM 0.x uses the same ones.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let bone = match name {
+            "hips" => HumanBone::Hips,
+            "spine" => HumanBone::Spine,
+            "chest" => HumanBone::Chest,
+            "upperChest" => HumanBone::UpperChest,
+            "neck" => HumanBone::Neck,
+            "head" => HumanBone::Head,
+            "leftShoulder" => HumanBone::LeftShoulder,
+            "leftUpperArm" => HumanBone::LeftUpperArm,
+            "leftLowerArm" => HumanBone::LeftLowerArm,
+            "leftHand" => HumanBone::LeftHand,
+            "rightShoulder" => HumanBone::RightShoulder,
+            "rightUpperArm" => HumanBone::RightUpperArm,
+            "rightLowerArm" => HumanBone::RightLowerArm,
+            "rightHand" => HumanBone::RightHand,
+            "leftUpperLeg" => HumanBone::LeftUpperLeg,
+            "leftLowerLeg" => HumanBone::LeftLowerLeg,
+            "leftFoot" => HumanBone::LeftFoot,
+            "rightUpperLeg" => HumanBone::RightUpperLeg,
+            "rightLowerLeg" => HumanBone::RightLowerLeg,
+            "rightFoot" => HumanBone::RightFoot,
+            "leftMiddleProximal" => HumanBone::LeftMiddleProximal,
+            "rightMiddleProximal" => HumanBone::RightMiddleProximal,
+            _ => return None,
+        };
+        Some(bone)
+    }
+}
+
+/// Where a spring joint's tail is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpringTail {
+    /// At this node.
+    Node(usize),
+    /// Past the end of a chain: 7 cm further along the parent bone.
+    Extend,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpringJointDef {
+    pub node: usize,
+    pub tail: SpringTail,
+    pub stiffness: f32,
+    pub gravity_power: f32,
+    pub gravity_dir: Vec3,
+    pub drag: f32,
+    pub hit_radius: f32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SpringChainDef {
+    /// Parents before children.
+    pub joints: Vec<SpringJointDef>,
+    /// Indices into `VrmDocument::colliders`.
+    pub colliders: Vec<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColliderShape {
+    Sphere { offset: Vec3, radius: f32 },
+    /// From `offset` to `tail`, both in the node's space.
+    Capsule { offset: Vec3, radius: f32, tail: Vec3 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColliderDef {
+    pub node: usize,
+    pub shape: ColliderShape,
+}
+
+/// What a morph target bind refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindTarget {
+    /// VRM 1.0: a node drawing a mesh.
+    Node(usize),
+    /// VRM 0.x: every node drawing this mesh.
+    Mesh(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MorphBindDef {
+    pub target: BindTarget,
+    pub index: usize,
+    /// 0..1
+    pub weight: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct ExpressionDef {
+    /// VRM 1.0 preset name ("happy", "aa", "blink", ...) or the custom
+    /// expression's name.
+    pub name: String,
+    pub binary: bool,
+    pub binds: Vec<MorphBindDef>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FirstPersonFlag {
+    /// Hide whatever is skinned to the head in first person.
+    Auto,
+    Both,
+    ThirdPersonOnly,
+    FirstPersonOnly,
+}
+
+#[derive(Clone, Debug)]
+pub struct VrmDocument {
+    pub version: VrmVersion,
+    pub name: String,
+    pub humanoid: Vec<(HumanBone, usize)>,
+    pub springs: Vec<SpringChainDef>,
+    pub colliders: Vec<ColliderDef>,
+    pub expressions: Vec<ExpressionDef>,
+    pub first_person: Vec<(BindTarget, FirstPersonFlag)>,
+    /// Eyes relative to the head bone, in the head's space.
+    pub eye_offset: Vec3,
+    /// MToon parameters by material index.
+    pub toon: Vec<(usize, ToonShading)>,
+    /// Mesh drawn by each node, by node index.
+    pub node_meshes: Vec<Option<usize>>,
+}
+
+impl VrmDocument {
+    pub fn bone(&self, bone: HumanBone) -> Option<usize> {
+        self.humanoid.iter().find(|(b, _)| *b == bone).map(|(_, n)| *n)
+    }
+
+    /// Nodes a bind or annotation refers to.
+    pub fn nodes(&self, target: BindTarget) -> Vec<usize> {
+        match target {
+            BindTarget::Node(n) => vec![n],
+            BindTarget::Mesh(m) => (0..self.node_meshes.len())
+                .filter(|&n| self.node_meshes[n] == Some(m))
+                .collect(),
+        }
+    }
+}
+
+/// The glTF JSON of a `.vrm`/`.glb` (or `.gltf`) file.
+pub fn document_json(bytes: &[u8]) -> Result<Value, ModelError> {
+    let json = if bytes.starts_with(b"glTF") {
+        gltf::Glb::from_slice(bytes)?.json.into_owned()
+    } else {
+        bytes.to_vec()
+    };
+
+    serde_json::from_slice(&json).map_err(|e| ModelError::Invalid(format!("json: {}", e)))
+}
+
+pub fn parse_vrm(root: &Value) -> Result<VrmDocument, ModelError> {
+    let extensions = &root["extensions"];
+
+    let mut doc = if extensions["VRMC_vrm"].is_object() {
+        parse_v1(root)
+    } else if extensions["VRM"].is_object() {
+        parse_v0(root)
+    } else {
+        return Err(ModelError::Invalid("no VRM extension".into()));
+    };
+
+    doc.node_meshes = array(&root["nodes"]).map(|n| index(&n["mesh"])).collect();
+    Ok(doc)
+}
+
+/* =========================================================
+   VRM 1.0
+   ========================================================= */
+
+fn parse_v1(root: &Value) -> VrmDocument {
+    let vrm = &root["extensions"]["VRMC_vrm"];
+    let spring = &root["extensions"]["VRMC_springBone"];
+
+    let humanoid = object(&vrm["humanoid"]["humanBones"])
+        .filter_map(|(name, bone)| Some((HumanBone::from_name(name)?, index(&bone["node"])?)))
+        .collect();
+
+    /* ---------- spring bones ---------- */
+
+    let colliders = array(&spring["colliders"])
+        .filter_map(|c| {
+            let shape = &c["shape"];
+            let shape = if shape["sphere"].is_object() {
+                let s = &shape["sphere"];
+                ColliderShape::Sphere {
+                    offset: vec3(&s["offset"]).unwrap_or(Vec3::ZERO),
+                    radius: number(&s["radius"]).unwrap_or(0.0),
+                }
+            } else {
+                let s = &shape["capsule"];
+                ColliderShape::Capsule {
+                    offset: vec3(&s["offset"]).unwrap_or(Vec3::ZERO),
+                    radius: number(&s["radius"]).unwrap_or(0.0),
+                    tail: vec3(&s["tail"]).unwrap_or(Vec3::ZERO),
+                }
+            };
+            Some(ColliderDef { node: index(&c["node"])?, shape })
+        })
+        .collect();
+
+    let groups: Vec<Vec<usize>> = array(&spring["colliderGroups"])
+        .map(|g| array(&g["colliders"]).filter_map(index).collect())
+        .collect();
+
+    let springs = array(&spring["springs"])
+        .map(|s| {
+            let nodes: Vec<(usize, &Value)> = array(&s["joints"])
+                .filter_map(|j| Some((index(&j["node"])?, j)))
+                .collect();
+
+            // each joint's tail is the next one; the last is only a tail
+            let joints = nodes
+                .windows(2)
+                .map(|pair| {
+                    let (node, j) = pair[0];
+                    SpringJointDef {
+                        node,
+                        tail: SpringTail::Node(pair[1].0),
+                        stiffness: number(&j["stiffness"]).unwrap_or(1.0),
+                        gravity_power: number(&j["gravityPower"]).unwrap_or(0.0),
+                        gravity_dir: vec3(&j["gravityDir"]).unwrap_or(Vec3::NEG_Y),
+                        drag: number(&j["dragForce"]).unwrap_or(0.5),
+                        hit_radius: number(&j["hitRadius"]).unwrap_or(0.0),
+                    }
+                })
+                .collect();
+
+            SpringChainDef {
+                joints,
+                colliders: collider_groups(&s["colliderGroups"], &groups),
+            }
+        })
+        .collect();
+
+    /* ---------- expressions ---------- */
+
+    let expressions = ["preset", "custom"]
+        .into_iter()
+        .flat_map(|kind| object(&vrm["expressions"][kind]))
+        .map(|(name, e)| ExpressionDef {
+            name: name.to_string(),
+            binary: e["isBinary"].as_bool().unwrap_or(false),
+            binds: array(&e["morphTargetBinds"])
+                .filter_map(|b| {
+                    Some(MorphBindDef {
+                        target: BindTarget::Node(index(&b["node"])?),
+                        index: index(&b["index"])?,
+                        weight: number(&b["weight"]).unwrap_or(1.0),
+                    })
+                })
+                .collect(),
+        })
+        .collect();
+
+    /* ---------- first person ---------- */
+
+    let first_person = array(&vrm["firstPerson"]["meshAnnotations"])
+        .filter_map(|a| {
+            let flag = match a["type"].as_str()? {
+                "auto" => FirstPersonFlag::Auto,
+                "both" => FirstPersonFlag::Both,
+                "thirdPersonOnly" => FirstPersonFlag::ThirdPersonOnly,
+                "firstPersonOnly" => FirstPersonFlag::FirstPersonOnly,
+                _ => return None,
+            };
+            Some((BindTarget::Node(index(&a["node"])?), flag))
+        })
+        .collect();
+
+    /* ---------- MToon ---------- */
+
+    let toon = array(&root["materials"])
+        .enumerate()
+        .filter_map(|(i, m)| {
+            let mtoon = &m["extensions"]["VRMC_materials_mtoon"];
+            mtoon.is_object().then(|| {
+                let defaults = ToonShading::default();
+                (
+                    i,
+                    ToonShading {
+                        shade_color: color3(&mtoon["shadeColorFactor"]).unwrap_or(defaults.shade_color),
+                        shading_shift: number(&mtoon["shadingShiftFactor"]).unwrap_or(0.0),
+                        shading_toony: number(&mtoon["shadingToonyFactor"]).unwrap_or(defaults.shading_toony),
+                        rim_color: color3(&mtoon["parametricRimColorFactor"]).unwrap_or(defaults.rim_color),
+                        rim_fresnel_power: number(&mtoon["parametricRimFresnelPowerFactor"])
+                            .unwrap_or(defaults.rim_fresnel_power),
+                    },
+                )
+            })
+        })
+        .collect();
+
+    VrmDocument {
+        version: VrmVersion::V1,
+        name: vrm["meta"]["name"].as_str().unwrap_or("vrm").to_string(),
+        humanoid,
+        springs,
+        colliders,
+        expressions,
+        first_person,
+        eye_offset: vec3(&vrm["lookAt"]["offsetFromHeadBone"]).unwrap_or(Vec3::new(0.0, 0.06, 0.0)),
+        toon,
+        node_meshes: Vec::new(),
+    }
+}
+
+/* =========================================================
+   VRM 0.x
+   ========================================================= */
+
+fn parse_v0(root: &Value) -> VrmDocument {
+    let vrm = &root["extensions"]["VRM"];
+
+    let humanoid = array(&vrm["humanoid"]["humanBones"])
+        .filter_map(|b| Some((HumanBone::from_name(b["bone"].as_str()?)?, index(&b["node"])?)))
+        .collect();
+
+    /* ---------- spring bones ---------- */
+
+    // collider groups are a node with spheres; flatten them
+    let mut colliders = Vec::new();
+    let groups: Vec<Vec<usize>> = array(&vrm["secondaryAnimation"]["colliderGroups"])
+        .map(|g| {
+            let node = index(&g["node"]);
+            array(&g["colliders"])
+                .filter_map(|c| {
+                    colliders.push(ColliderDef {
+                        node: node?,
+                        shape: ColliderShape::Sphere {
+                            offset: vec3_v0(&c["offset"]).unwrap_or(Vec3::ZERO),
+                            radius: number(&c["radius"]).unwrap_or(0.0),
+                        },
+                    });
+                    Some(colliders.len() - 1)
+                })
+                .collect()
+        })
+        .collect();
+
+    let springs = array(&vrm["secondaryAnimation"]["boneGroups"])
+        .map(|g| {
+            let gravity_dir = vec3_v0(&g["gravityDir"]).unwrap_or(Vec3::NEG_Y);
+
+            let joint = |node: usize, tail: SpringTail| SpringJointDef {
+                node,
+                tail,
+                // sic
+                stiffness: number(&g["stiffiness"]).unwrap_or(1.0),
+                gravity_power: number(&g["gravityPower"]).unwrap_or(0.0),
+                gravity_dir,
+                drag: number(&g["dragForce"]).unwrap_or(0.4),
+                hit_radius: number(&g["hitRadius"]).unwrap_or(0.02),
+            };
+
+            // every node under each listed bone swings, towards its
+            // first child (or past its end)
+            let mut joints = Vec::new();
+            let mut stack: Vec<usize> = array(&g["bones"]).filter_map(index).rev().collect();
+            while let Some(node) = stack.pop() {
+                let children: Vec<usize> = array(&root["nodes"][node]["children"]).filter_map(index).collect();
+                let tail = children.first().map_or(SpringTail::Extend, |&c| SpringTail::Node(c));
+                joints.push(joint(node, tail));
+                stack.extend(children.into_iter().rev());
+            }
+
+            SpringChainDef {
+                joints,
+                colliders: collider_groups(&g["colliderGroups"], &groups),
+            }
+        })
+        .collect();
+
+    /* ---------- expressions ---------- */
+
+    let expressions = array(&vrm["blendShapeMaster"]["blendShapeGroups"])
+        .map(|g| {
+            let preset = g["presetName"].as_str().unwrap_or("unknown");
+            let name = preset_v0(preset)
+                .map(str::to_string)
+                .unwrap_or_else(|| g["name"].as_str().unwrap_or(preset).to_string());
+
+            ExpressionDef {
+                name,
+                binary: g["isBinary"].as_bool().unwrap_or(false),
+                binds: array(&g["binds"])
+                    .filter_map(|b| {
+                        Some(MorphBindDef {
+                            target: BindTarget::Mesh(index(&b["mesh"])?),
+                            index: index(&b["index"])?,
+                            // 0..100 in 0.x
+                            weight: number(&b["weight"]).unwrap_or(100.0) / 100.0,
+                        })
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    /* ---------- first person ---------- */
+
+    let first_person = array(&vrm["firstPerson"]["meshAnnotations"])
+        .filter_map(|a| {
+            let flag = match a["firstPersonFlag"].as_str()? {
+                "Auto" => FirstPersonFlag::Auto,
+                "Both" => FirstPersonFlag::Both,
+                "ThirdPersonOnly" => FirstPersonFlag::ThirdPersonOnly,
+                "FirstPersonOnly" => FirstPersonFlag::FirstPersonOnly,
+                _ => return None,
+            };
+            Some((BindTarget::Mesh(index(&a["mesh"])?), flag))
+        })
+        .collect();
+
+    let eye_offset = vec3_v0(&vrm["firstPerson"]["firstPersonBoneOffset"]).unwrap_or(Vec3::new(0.0, 0.06, 0.0));
+
+    /* ---------- MToon ---------- */
+
+    // matched to glTF materials by name
+    let names: Vec<&str> = array(&root["materials"])
+        .map(|m| m["name"].as_str().unwrap_or(""))
+        .collect();
+
+    let toon = array(&vrm["materialProperties"])
+        .filter(|p| p["shader"].as_str() == Some("VRM/MToon"))
+        .filter_map(|p| {
+            let material = names.iter().position(|n| Some(*n) == p["name"].as_str())?;
+            Some((material, toon_v0(p)))
+        })
+        .collect();
+
+    VrmDocument {
+        version: VrmVersion::V0,
+        name: vrm["meta"]["title"].as_str().unwrap_or("vrm").to_string(),
+        humanoid,
+        springs,
+        colliders,
+        expressions,
+        first_person,
+        eye_offset,
+        toon,
+        node_meshes: Vec::new(),
+    }
+}
+
+/// 0.x presets under their 1.0 names.
+fn preset_v0(preset: &str) -> Option<&'static str> {
+    let name = match preset {
+        "neutral" => "neutral",
+        "a" => "aa",
+        "i" => "ih",
+        "u" => "ou",
+        "e" => "ee",
+        "o" => "oh",
+        "blink" => "blink",
+        "blink_l" => "blinkLeft",
+        "blink_r" => "blinkRight",
+        "joy" => "happy",
+        "angry" => "angry",
+        "sorrow" => "sad",
+        "fun" => "relaxed",
+        "lookup" => "lookUp",
+        "lookdown" => "lookDown",
+        "lookleft" => "lookLeft",
+        "lookright" => "lookRight",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// 0.x MToon's shade shift/toony mapped onto 1.0's (as three-vrm does);
+/// its colours are sRGB.
+fn toon_v0(properties: &Value) -> ToonShading {
+    let floats = &properties["floatProperties"];
+    let vectors = &properties["vectorProperties"];
+    let defaults = ToonShading::default();
+
+    let shade_shift = number(&floats["_ShadeShift"]).unwrap_or(0.0);
+    let shade_toony = number(&floats["_ShadeToony"]).unwrap_or(defaults.shading_toony);
+    let shading_toony = shade_toony + (1.0 - shade_toony) * (0.5 + 0.5 * shade_shift);
+
+    let srgb = |v: &Value| color3(v).map(|c| c.map(srgb_to_linear));
+
+    ToonShading {
+        shade_color: srgb(&vectors["_ShadeColor"]).unwrap_or(defaults.shade_color),
+        shading_shift: -shade_shift - (1.0 - shading_toony),
+        shading_toony,
+        rim_color: srgb(&vectors["_RimColor"]).unwrap_or(defaults.rim_color),
+        rim_fresnel_power: number(&floats["_RimFresnelPower"]).unwrap_or(defaults.rim_fresnel_power),
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/* =========================================================
+   JSON HELPERS
+   ========================================================= */
+
+fn array(value: &Value) -> impl DoubleEndedIterator<Item = &Value> {
+    value.as_array().into_iter().flatten()
+}
+
+fn object(value: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    value.as_object().into_iter().flatten()
+}
+
+/// Negative indices mean "none" in VRM 0.x.
+fn index(value: &Value) -> Option<usize> {
+    value.as_u64().map(|i| i as usize)
+}
+
+fn number(value: &Value) -> Option<f32> {
+    value.as_f64().map(|n| n as f32)
+}
+
+fn vec3(value: &Value) -> Option<Vec3> {
+    let a = value.as_array()?;
+    Some(Vec3::new(number(a.first()?)?, number(a.get(1)?)?, number(a.get(2)?)?))
+}
+
+fn color3(value: &Value) -> Option<[f32; 3]> {
+    let a = value.as_array()?;
+    Some([number(a.first()?)?, number(a.get(1)?)?, number(a.get(2)?)?])
+}
+
+/// VRM 0.x `{x, y, z}` with Z flipped into glTF space.
+fn vec3_v0(value: &Value) -> Option<Vec3> {
+    value.as_object()?;
+    let n = |k: &str| number(&value[k]).unwrap_or(0.0);
+    Some(Vec3::new(n("x"), n("y"), -n("z")))
+}
+
+fn collider_groups(value: &Value, groups: &[Vec<usize>]) -> Vec<usize> {
+    array(value)
+        .filter_map(index)
+        .filter_map(|g| groups.get(g))
+        .flatten()
+        .copied()
+        .collect()
+}
@@ -0,0 +1,316 @@
+use glam::{Quat, Vec3};
+
+use engine_core::{EntityId, World};
+
+use super::parse::{ColliderShape, SpringChainDef, SpringTail, VrmDocument};
+use crate::model::Model;
+
+/*
+   VRM spring bones: hair, skirts and ears swinging behind the body.
+
+   Each joint's tail is a Verlet particle pulled by inertia, stiffness
+   (back towards the rest direction) and gravity, pushed out of the
+   chain's colliders and kept at the bone's length; the joint then turns
+   to point at it. Same integration as UniVRM, so tuning carries over.
+*/
+
+struct SpringJoint {
+    entity: EntityId,
+    /// Rest local rotation.
+    rest: Quat,
+    /// Rest direction to the tail, in the joint's local space.
+    axis: Vec3,
+    length: f32,
+    stiffness: f32,
+    gravity: Vec3,
+    drag: f32,
+    hit_radius: f32,
+    tail: Vec3,
+    prev_tail: Vec3,
+}
+
+struct Collider {
+    entity: EntityId,
+    shape: ColliderShape,
+}
+
+struct SpringChain {
+    joints: Vec<SpringJoint>,
+    colliders: Vec<usize>,
+}
+
+pub struct SpringBones {
+    chains: Vec<SpringChain>,
+    colliders: Vec<Collider>,
+}
+
+/// Past the end of a chain, the tail sits this far along the last bone.
+const EXTEND: f32 = 0.07;
+
+impl SpringBones {
+    pub fn new(doc: &VrmDocument, model: &Model, world: &World) -> Self {
+        let colliders = doc
+            .colliders
+            .iter()
+            .map(|c| Collider {
+                entity: model.node(c.node).unwrap_or(model.root),
+                shape: c.shape,
+            })
+            .collect();
+
+        let chains = doc
+            .springs
+            .iter()
+            .map(|chain| SpringChain {
+                joints: Self::joints(chain, model, world),
+                colliders: chain.colliders.clone(),
+            })
+            .collect();
+
+        Self { chains, colliders }
+    }
+
+    fn joints(chain: &SpringChainDef, model: &Model, world: &World) -> Vec<SpringJoint> {
+        chain
+            .joints
+            .iter()
+            .filter_map(|def| {
+                let entity = model.node(def.node)?;
+                let head = world.world_transform(entity);
+
+                let tail = match def.tail {
+                    SpringTail::Node(n) => world.world_transform(model.node(n)?).position,
+                    SpringTail::Extend => {
+                        let parent = world.parent(entity).map(|p| world.world_transform(p).position);
+                        let dir = parent.map_or(Vec3::ZERO, |p| head.position - p).normalize_or_zero();
+                        let dir = if dir == Vec3::ZERO { head.rotation * Vec3::Y } else { dir };
+                        head.position + dir * EXTEND
+                    }
+                };
+
+                let length = head.position.distance(tail);
+                if length < 1e-5 {
+                    return None;
+                }
+
+                Some(SpringJoint {
+                    entity,
+                    rest: world.transform(entity).rotation,
+                    axis: (head.rotation.inverse() * (tail - head.position)).normalize(),
+                    length,
+                    stiffness: def.stiffness,
+                    gravity: def.gravity_dir.normalize_or_zero() * def.gravity_power,
+                    drag: def.drag.clamp(0.0, 1.0),
+                    hit_radius: def.hit_radius,
+                    tail,
+                    prev_tail: tail,
+                })
+            })
+            .collect()
+    }
+
+    /// Puts every bone back at rest with its tail at rest, e.g. after a
+    /// teleport so hair does not stream across the map.
+    pub fn reset(&mut self, world: &mut World) {
+        for chain in &mut self.chains {
+            for joint in &mut chain.joints {
+                let mut local = world.transform(joint.entity);
+                local.rotation = joint.rest;
+                world.set_transform(joint.entity, local);
+
+                let head = world.world_transform(joint.entity);
+                joint.tail = head.position + head.rotation * joint.axis * joint.length;
+                joint.prev_tail = joint.tail;
+            }
+        }
+    }
+
+    pub fn update(&mut self, world: &mut World, dt: f32) {
+        let dt = dt.min(1.0 / 30.0);
+
+        // colliders in world space: centre, segment end, radius
+        let colliders: Vec<(Vec3, Vec3, f32)> = self
+            .colliders
+            .iter()
+            .map(|c| {
+                let m = world.world_matrix(c.entity);
+                let scale = m.transform_vector3(Vec3::X).length();
+                match c.shape {
+                    ColliderShape::Sphere { offset, radius } => {
+                        let p = m.transform_point3(offset);
+                        (p, p, radius * scale)
+                    }
+                    ColliderShape::Capsule { offset, radius, tail } => {
+                        (m.transform_point3(offset), m.transform_point3(tail), radius * scale)
+                    }
+                }
+            })
+            .collect();
+
+        for chain in &mut self.chains {
+            for joint in &mut chain.joints {
+                let parent = world
+                    .parent(joint.entity)
+                    .map(|p| world.world_transform(p).rotation)
+                    .unwrap_or(Quat::IDENTITY);
+                let head = world.world_transform(joint.entity).position;
+                let rest_world = parent * joint.rest;
+
+                let inertia = (joint.tail - joint.prev_tail) * (1.0 - joint.drag);
+                let stiffness = rest_world * joint.axis * (joint.stiffness * dt);
+                let gravity = joint.gravity * dt;
+
+                let mut next = joint.tail + inertia + stiffness + gravity;
+                next = head + (next - head).normalize_or_zero() * joint.length;
+
+                for &i in &chain.colliders {
+                    let Some(&(a, b, radius)) = colliders.get(i) else {
+                        continue;
+                    };
+
+                    let ab = b - a;
+                    let t = if ab.length_squared() > 1e-8 {
+                        ((next - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0)
+                    } else {
+                        0.0
+                    };
+                    let closest = a + ab * t;
+
+                    let reach = radius + joint.hit_radius;
+                    let offset = next - closest;
+                    if offset.length_squared() < reach * reach {
+                        next = closest + offset.normalize_or_zero() * reach;
+                        next = head + (next - head).normalize_or_zero() * joint.length;
+                    }
+                }
+
+                joint.prev_tail = joint.tail;
+                joint.tail = next;
+
+                // turn from the rest direction towards the tail
+                let to = (rest_world.inverse() * (next - head)).normalize_or_zero();
+                let mut local = world.transform(joint.entity);
+                local.rotation = if to == Vec3::ZERO {
+                    joint.rest
+                } else {
+                    (joint.rest * Quat::from_rotation_arc(joint.axis, to)).normalize()
+                };
+                world.set_transform(joint.entity, local);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avatar::vrm::parse::{ColliderDef, SpringJointDef, VrmVersion};
+    use engine_core::Transform;
+
+    /// A 10 cm bone sticking out along +X at 1 m, its tail a child node,
+    /// falling under gravity with no stiffness.
+    fn strand(colliders: Vec<ColliderDef>) -> (World, Model, VrmDocument) {
+        let mut world = World::new();
+        let root = world.spawn("root", Transform::default());
+        let joint = world.spawn("joint", Transform::from_position(Vec3::Y));
+        let tail = world.spawn("tail", Transform::from_position(Vec3::X * 0.1));
+        let anchor = world.spawn("anchor", Transform::default());
+        world.set_parent(joint, Some(root), false);
+        world.set_parent(tail, Some(joint), false);
+        world.set_parent(anchor, Some(root), false);
+
+        let model = Model {
+            root,
+            nodes: vec![Some(joint), Some(tail), Some(anchor)],
+            primitives: Vec::new(),
+            meshes: Vec::new(),
+            materials: Vec::new(),
+            warnings: Vec::new(),
+        };
+        let doc = VrmDocument {
+            version: VrmVersion::V1,
+            name: "test".into(),
+            humanoid: Vec::new(),
+            springs: vec![SpringChainDef {
+                joints: vec![SpringJointDef {
+                    node: 0,
+                    tail: SpringTail::Node(1),
+                    stiffness: 0.0,
+                    gravity_power: 1.0,
+                    gravity_dir: Vec3::NEG_Y,
+                    drag: 0.4,
+                    hit_radius: 0.0,
+                }],
+                colliders: (0..colliders.len()).collect(),
+            }],
+            colliders,
+            expressions: Vec::new(),
+            first_person: Vec::new(),
+            eye_offset: Vec3::ZERO,
+            toon: Vec::new(),
+            node_meshes: Vec::new(),
+        };
+        (world, model, doc)
+    }
+
+    fn tail(world: &World, model: &Model) -> Vec3 {
+        world.world_transform(model.node(1).unwrap()).position
+    }
+
+    fn settle(springs: &mut SpringBones, world: &mut World) {
+        for _ in 0..600 {
+            springs.update(world, 1.0 / 60.0);
+        }
+    }
+
+    #[test]
+    fn gravity_swings_the_bone_down_at_its_length() {
+        let (mut world, model, doc) = strand(Vec::new());
+        let mut springs = SpringBones::new(&doc, &model, &world);
+        settle(&mut springs, &mut world);
+
+        let tail = tail(&world, &model);
+        assert!(tail.abs_diff_eq(Vec3::new(0.0, 0.9, 0.0), 1e-2), "{}", tail);
+        assert!((tail.distance(Vec3::Y) - 0.1).abs() < 1e-4);
+    }
+
+    #[test]
+    fn colliders_push_the_tail_out() {
+        let ball = ColliderDef {
+            node: 2,
+            shape: ColliderShape::Sphere {
+                offset: Vec3::new(0.0, 0.9, 0.0),
+                radius: 0.05,
+            },
+        };
+        let (mut world, model, doc) = strand(vec![ball]);
+        let mut springs = SpringBones::new(&doc, &model, &world);
+        settle(&mut springs, &mut world);
+
+        let tail = tail(&world, &model);
+        assert!(tail.distance(Vec3::new(0.0, 0.9, 0.0)) > 0.05 - 1e-3, "{}", tail);
+        assert!(tail.y < 1.0 && tail.x > 0.0, "{}", tail);
+    }
+
+    #[test]
+    fn reset_returns_to_rest() {
+        let (mut world, model, doc) = strand(Vec::new());
+        let mut springs = SpringBones::new(&doc, &model, &world);
+        settle(&mut springs, &mut world);
+        springs.reset(&mut world);
+
+        assert!(tail(&world, &model).abs_diff_eq(Vec3::new(0.1, 1.0, 0.0), 1e-5));
+
+        // nothing left over to swing with
+        springs.update(&mut world, 0.0);
+        assert!(tail(&world, &model).abs_diff_eq(Vec3::new(0.1, 1.0, 0.0), 1e-5));
+    }
+
+    #[test]
+    fn zero_length_bones_are_skipped() {
+        let (mut world, model, doc) = strand(Vec::new());
+        world.set_transform(model.node(1).unwrap(), Transform::default());
+        let springs = SpringBones::new(&doc, &model, &world);
+        assert!(springs.chains[0].joints.is_empty());
+    }
+}
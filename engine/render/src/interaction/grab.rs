@@ -0,0 +1,150 @@
+use engine_core::Transform;
+use glam::{Mat4, Vec3};
+
+/// Shape (in the entity's local space) a hand can grab anywhere inside.
+#[derive(Clone, Copy, Debug)]
+pub enum GrabVolume {
+    Sphere { center: Vec3, radius: f32 },
+    Box { center: Vec3, half_extents: Vec3 },
+}
+
+impl GrabVolume {
+    /// Unit cube, matching `cube_mesh`.
+    pub const UNIT_CUBE: Self = GrabVolume::Box {
+        center: Vec3::ZERO,
+        half_extents: Vec3::splat(0.5),
+    };
+
+    pub fn closest_point(&self, local: Vec3) -> Vec3 {
+        match *self {
+            GrabVolume::Sphere { center, radius } => {
+                let d = local - center;
+                if d.length() <= radius {
+                    local
+                } else {
+                    center + d.normalize() * radius
+                }
+            }
+            GrabVolume::Box { center, half_extents } => {
+                center + (local - center).clamp(-half_extents, half_extents)
+            }
+        }
+    }
+
+    /// World height of the volume's lowest point.
+    pub fn bottom(&self, object_world: Mat4) -> f32 {
+        match *self {
+            GrabVolume::Sphere { center, radius } => {
+                let scale = object_world.to_scale_rotation_translation().0.max_element();
+                object_world.transform_point3(center).y - radius * scale
+            }
+            GrabVolume::Box { center, half_extents } => {
+                object_world.transform_point3(center).y - world_extents(object_world, half_extents).y
+            }
+        }
+    }
+
+    /// World-space distance from `point` to the volume (0 inside).
+    pub fn distance(&self, object_world: Mat4, point: Vec3) -> f32 {
+        let local = object_world.inverse().transform_point3(point);
+        let closest = object_world.transform_point3(self.closest_point(local));
+        closest.distance(point)
+    }
+}
+
+/// A specific way to hold an object (handle, grip of a tool): when the hand
+/// is within `radius` the object snaps so this local pose sits in the palm.
+#[derive(Clone, Debug)]
+pub struct GrabPoint {
+    pub name: String,
+    pub local: Transform,
+    pub radius: f32,
+}
+
+/// Marks an entity as something hands can pick up.
+#[derive(Clone, Debug)]
+pub struct Grabbable {
+    pub volume: GrabVolume,
+    pub points: Vec<GrabPoint>,
+    /// A second hand can join in and steer the object.
+    pub two_handed: bool,
+    /// Keeps the release velocity (otherwise the object just stays put).
+    pub throwable: bool,
+}
+
+impl Grabbable {
+    pub fn new(volume: GrabVolume) -> Self {
+        Self {
+            volume,
+            points: Vec::new(),
+            two_handed: false,
+            throwable: true,
+        }
+    }
+
+    pub fn with_point(mut self, name: &str, local: Transform, radius: f32) -> Self {
+        self.points.push(GrabPoint {
+            name: name.to_string(),
+            local,
+            radius,
+        });
+        self
+    }
+
+    pub fn two_handed(mut self) -> Self {
+        self.two_handed = true;
+        self
+    }
+
+    /// Closest grab point whose snap radius contains `hand`.
+    pub fn snap_point(&self, object_world: Mat4, hand: Vec3) -> Option<&GrabPoint> {
+        self.points
+            .iter()
+            .map(|p| {
+                let world = object_world.transform_point3(p.local.position);
+                (p, world.distance(hand))
+            })
+            .filter(|(p, d)| *d <= p.radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(p, _)| p)
+    }
+}
+
+/// Free-flight state of a released object until it comes to rest.
+#[derive(Clone, Copy, Debug)]
+pub struct Motion {
+    pub linear: Vec3,
+    pub angular: Vec3,
+}
+
+/// A static box (in the entity's local space) whose top released objects
+/// land on, like a table. The ground always is one.
+#[derive(Clone, Copy, Debug)]
+pub struct RestSurface {
+    pub center: Vec3,
+    pub half_extents: Vec3,
+}
+
+impl RestSurface {
+    /// Unit cube, matching `cube_mesh`.
+    pub const UNIT_CUBE: Self = Self {
+        center: Vec3::ZERO,
+        half_extents: Vec3::splat(0.5),
+    };
+
+    /// Height of the top of the box's world bounds, if they cover (x, z).
+    pub fn top_at(&self, surface_world: Mat4, x: f32, z: f32) -> Option<f32> {
+        let center = surface_world.transform_point3(self.center);
+        let extents = world_extents(surface_world, self.half_extents);
+
+        let inside = (x - center.x).abs() <= extents.x && (z - center.z).abs() <= extents.z;
+        inside.then_some(center.y + extents.y)
+    }
+}
+
+/// Half extents of the world bounds of a box with local `half_extents`.
+fn world_extents(world: Mat4, half_extents: Vec3) -> Vec3 {
+    world.x_axis.truncate().abs() * half_extents.x
+        + world.y_axis.truncate().abs() * half_extents.y
+        + world.z_axis.truncate().abs() * half_extents.z
+}
@@ -0,0 +1,472 @@
+pub mod grab;
+pub mod velocity;
+
+use engine_core::{EntityId, Transform, World};
+use glam::{Mat4, Quat, Vec3};
+
+use crate::locomotion::Ground;
+use crate::xr::{ControllerState, Hand, HandSkeleton, PoseFrame, TrackedPose};
+
+pub use grab::{GrabPoint, GrabVolume, Grabbable, Motion, RestSurface};
+pub use velocity::VelocityEstimator;
+
+/// What a hand offers the interaction system: where it is (tracking space)
+/// and how hard it is squeezing. Built from a controller or from a tracked
+/// hand's grab/pinch gesture.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GrabInput {
+    pub pose: TrackedPose,
+    pub grip: f32,
+}
+
+impl From<&ControllerState> for GrabInput {
+    /// Only the grip grabs; the trigger is left to the UI laser.
+    fn from(c: &ControllerState) -> Self {
+        Self {
+            pose: c.pose,
+            grip: c.grip,
+        }
+    }
+}
+
+impl From<&HandSkeleton> for GrabInput {
+    fn from(h: &HandSkeleton) -> Self {
+        let g = h.gestures();
+
+        // a pinch holds at the fingertips, a fist in the palm
+        let mut pose = h.palm();
+        if g.pinch > g.grab {
+            pose.position = g.pinch_point;
+        }
+
+        Self {
+            pose,
+            grip: g.grab.max(g.pinch),
+        }
+    }
+}
+
+impl GrabInput {
+    /// The tracked hand when there is one, otherwise the controller.
+    pub fn from_frame(poses: &PoseFrame, hand: Hand) -> Self {
+        let tracked = poses.hand(hand);
+        if tracked.valid {
+            Self::from(tracked)
+        } else {
+            Self::from(poses.controller(hand))
+        }
+    }
+}
+
+/* =========================================================
+   HANDS
+   ========================================================= */
+
+pub struct HandInteractor {
+    pub hand: Hand,
+    /// Child of the rig; held objects become children of this.
+    pub entity: EntityId,
+    pub hovered: Option<EntityId>,
+    pub held: Option<EntityId>,
+
+    gripping: bool,
+    velocity: VelocityEstimator,
+}
+
+impl HandInteractor {
+    const GRIP_ON: f32 = 0.6;
+    const GRIP_OFF: f32 = 0.4;
+
+    fn new(hand: Hand, entity: EntityId) -> Self {
+        Self {
+            hand,
+            entity,
+            hovered: None,
+            held: None,
+            gripping: false,
+            velocity: VelocityEstimator::default(),
+        }
+    }
+}
+
+/// Second hand steering an object held by `primary`.
+struct TwoHanded {
+    object: EntityId,
+    primary: Hand,
+    start_dir: Vec3,
+    start_primary: Vec3,
+    start_object: Mat4,
+}
+
+/* =========================================================
+   INTERACTION
+   ========================================================= */
+
+/// Hover, grab, hold and throw for both hands.
+///
+/// The rig entity carries tracking space into the world; the hands are its
+/// children and a held object is parented to the hand holding it, so the
+/// hierarchy moves it with the hand (and with locomotion).
+pub struct Interaction {
+    pub rig: EntityId,
+    pub hands: [HandInteractor; 2],
+    pub hover_distance: f32,
+
+    two_handed: Option<TwoHanded>,
+    time: f32,
+}
+
+impl Interaction {
+    pub fn new(world: &mut World) -> Self {
+        let rig = world.spawn("rig", Transform::IDENTITY);
+
+        let left = world.spawn("left_hand", Transform::IDENTITY);
+        let right = world.spawn("right_hand", Transform::IDENTITY);
+        world.set_parent(left, Some(rig), false);
+        world.set_parent(right, Some(rig), false);
+
+        Self {
+            rig,
+            hands: [
+                HandInteractor::new(Hand::Left, left),
+                HandInteractor::new(Hand::Right, right),
+            ],
+            hover_distance: 0.06,
+            two_handed: None,
+            time: 0.0,
+        }
+    }
+
+    pub fn hand(&self, hand: Hand) -> &HandInteractor {
+        &self.hands[hand as usize]
+    }
+
+    pub fn is_held(&self, id: EntityId) -> bool {
+        self.hands.iter().any(|h| h.held == Some(id))
+    }
+
+    pub fn is_hovered(&self, id: EntityId) -> bool {
+        self.hands.iter().any(|h| h.hovered == Some(id))
+    }
+
+    pub fn update(
+        &mut self,
+        world: &mut World,
+        tracking_to_world: Mat4,
+        inputs: [GrabInput; 2],
+        dt: f32,
+    ) {
+        self.time += dt;
+
+        world.set_transform(self.rig, Transform::from_matrix(tracking_to_world));
+
+        for (hand, input) in self.hands.iter_mut().zip(&inputs) {
+            if input.pose.valid {
+                world.set_transform(
+                    hand.entity,
+                    Transform::from_position_rotation(input.pose.position, input.pose.orientation),
+                );
+            }
+
+            let w = world.world_transform(hand.entity);
+            hand.velocity.push(self.time, w.position, w.rotation);
+        }
+
+        for (i, input) in inputs.iter().enumerate() {
+            self.update_hover(world, i);
+
+            let grip = input.grip;
+
+            if !self.hands[i].gripping && grip > HandInteractor::GRIP_ON {
+                self.hands[i].gripping = true;
+                if let Some(target) = self.hands[i].hovered {
+                    self.grab(world, i, target);
+                }
+            } else if self.hands[i].gripping && grip < HandInteractor::GRIP_OFF {
+                self.hands[i].gripping = false;
+                self.release(world, i);
+            }
+        }
+
+        self.update_two_handed(world);
+    }
+
+    /* ================= HOVER ================= */
+
+    fn update_hover(&mut self, world: &World, i: usize) {
+        let hand = &self.hands[i];
+        if hand.held.is_some() {
+            self.hands[i].hovered = None;
+            return;
+        }
+
+        let point = world.world_transform(hand.entity).position;
+
+        // includes what the other hand holds: grabbing it passes it over
+        // (or steers it with both hands)
+        let hovered = world
+            .query::<Grabbable>()
+            .into_iter()
+            .map(|(id, g)| (id, g.volume.distance(world.world_matrix(id), point)))
+            .filter(|(_, d)| *d <= self.hover_distance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id);
+
+        self.hands[i].hovered = hovered;
+    }
+
+    /* ================= GRAB ================= */
+
+    fn grab(&mut self, world: &mut World, i: usize, target: EntityId) {
+        let other = 1 - i;
+
+        if self.hands[other].held == Some(target) {
+            let two_handed = world.get::<Grabbable>(target).is_some_and(|g| g.two_handed);
+
+            if !two_handed {
+                // hand-to-hand pass
+                self.hands[other].held = None;
+                self.attach(world, i, target);
+                return;
+            }
+
+            let primary = world.world_transform(self.hands[other].entity).position;
+            let secondary = world.world_transform(self.hands[i].entity).position;
+
+            self.two_handed = Some(TwoHanded {
+                object: target,
+                primary: self.hands[other].hand,
+                start_dir: secondary - primary,
+                start_primary: primary,
+                start_object: world.world_matrix(target),
+            });
+            self.hands[i].held = Some(target);
+            return;
+        }
+
+        world.remove::<Motion>(target);
+        self.attach(world, i, target);
+    }
+
+    fn attach(&mut self, world: &mut World, i: usize, target: EntityId) {
+        let hand_entity = self.hands[i].entity;
+        let hand = world.world_transform(hand_entity);
+        let object = world.world_matrix(target);
+
+        let snap = world
+            .get::<Grabbable>(target)
+            .and_then(|g| g.snap_point(object, hand.position))
+            .map(|p| p.local);
+
+        if let Some(point) = snap {
+            // put the grab point in the palm, keeping the object's scale
+            let scale = world.world_transform(target).scale;
+            let rotation = hand.rotation * point.rotation.inverse();
+            let position = hand.position - rotation * (scale * point.position);
+
+            world.set_world_transform(
+                target,
+                Transform::from_position_rotation(position, rotation).with_scale(scale),
+            );
+        }
+
+        // otherwise keep the offset it was grabbed at
+        world.set_parent(target, Some(hand_entity), true);
+        self.hands[i].held = Some(target);
+    }
+
+    /* ================= RELEASE ================= */
+
+    fn release(&mut self, world: &mut World, i: usize) {
+        let Some(object) = self.hands[i].held.take() else {
+            return;
+        };
+
+        if let Some(two) = self.two_handed.take() {
+            if two.object == object {
+                if two.primary == self.hands[i].hand {
+                    // the remaining hand takes over
+                    let other = 1 - i;
+                    world.set_parent(object, Some(self.hands[other].entity), true);
+                }
+                return;
+            }
+            self.two_handed = Some(two);
+        }
+
+        world.set_parent(object, None, true);
+
+        if !world.get::<Grabbable>(object).is_some_and(|g| g.throwable) {
+            return;
+        }
+
+        // rigid-body velocity of the object point swung by the hand
+        let hand = &self.hands[i];
+        let hand_pos = world.world_transform(hand.entity).position;
+        let lever = world.world_transform(object).position - hand_pos;
+
+        let angular = hand.velocity.angular();
+        let linear = hand.velocity.linear() + angular.cross(lever);
+
+        world.insert(object, Motion { linear, angular });
+    }
+
+    /* ================= TWO HANDS ================= */
+
+    fn update_two_handed(&mut self, world: &mut World) {
+        let Some(two) = &self.two_handed else {
+            return;
+        };
+
+        let primary = &self.hands[two.primary as usize];
+        let secondary = &self.hands[1 - two.primary as usize];
+
+        let p = world.world_transform(primary.entity).position;
+        let s = world.world_transform(secondary.entity).position;
+
+        let dir = s - p;
+        if dir.length_squared() < 1e-6 || two.start_dir.length_squared() < 1e-6 {
+            return;
+        }
+
+        let rotation = Quat::from_rotation_arc(two.start_dir.normalize(), dir.normalize());
+
+        let object = Mat4::from_translation(p)
+            * Mat4::from_quat(rotation)
+            * Mat4::from_translation(-two.start_primary)
+            * two.start_object;
+
+        world.set_world_transform(two.object, Transform::from_matrix(object));
+    }
+}
+
+/* =========================================================
+   MOTION
+   ========================================================= */
+
+/// Ballistic flight for thrown objects; they stop when the bottom of
+/// their grab volume reaches the ground or a `RestSurface` they were
+/// above.
+pub fn integrate_motion(world: &mut World, ground: &Ground, dt: f32) {
+    const GRAVITY: f32 = 9.8;
+
+    let surfaces: Vec<(Mat4, RestSurface)> = world
+        .query::<RestSurface>()
+        .into_iter()
+        .map(|(id, s)| (world.world_matrix(id), *s))
+        .collect();
+
+    let moving: Vec<EntityId> = world.query::<Motion>().into_iter().map(|(id, _)| id).collect();
+
+    for id in moving {
+        let volume = world.get::<Grabbable>(id).map_or(GrabVolume::UNIT_CUBE, |g| g.volume);
+        let mut motion = *world.get::<Motion>(id).unwrap();
+        let mut t = world.transform(id);
+        let was_above = volume.bottom(t.matrix());
+
+        motion.linear.y -= GRAVITY * dt;
+        t.position += motion.linear * dt;
+        t.rotation = (Quat::from_scaled_axis(motion.angular * dt) * t.rotation).normalize();
+
+        // passing beside a surface does not land on it
+        let (x, z) = (t.position.x, t.position.z);
+        let floor = surfaces
+            .iter()
+            .filter_map(|(m, s)| s.top_at(*m, x, z))
+            .filter(|top| was_above >= *top - 1e-3)
+            .fold(ground.height_at(x, z).unwrap_or(0.0), f32::max);
+
+        let bottom = volume.bottom(t.matrix());
+        if bottom <= floor {
+            t.position.y += floor - bottom;
+            world.remove::<Motion>(id);
+        } else {
+            world.insert(id, motion);
+        }
+
+        world.set_transform(id, t);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(position: Vec3, grip: f32) -> GrabInput {
+        GrabInput {
+            pose: TrackedPose::new(position, Quat::IDENTITY),
+            grip,
+        }
+    }
+
+    #[test]
+    fn controllers_grab_with_the_grip_not_the_ui_trigger() {
+        let mut controller = ControllerState {
+            trigger: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(GrabInput::from(&controller).grip, 0.0);
+
+        controller.grip = 0.8;
+        assert_eq!(GrabInput::from(&controller).grip, 0.8);
+    }
+
+    #[test]
+    fn grabbing_what_the_other_hand_holds_passes_it_over() {
+        let mut world = World::new();
+        let mut interaction = Interaction::new(&mut world);
+        let cube = world.spawn("cube", Transform::from_position(Vec3::ZERO).with_scale(Vec3::splat(0.1)));
+        world.insert(cube, Grabbable::new(GrabVolume::UNIT_CUBE));
+
+        let (left, right) = (Vec3::new(-0.02, 0.0, 0.0), Vec3::new(0.02, 0.0, 0.0));
+        let dt = 1.0 / 90.0;
+
+        interaction.update(&mut world, Mat4::IDENTITY, [input(left, 1.0), input(right, 0.0)], dt);
+        assert_eq!(interaction.hand(Hand::Left).held, Some(cube));
+
+        interaction.update(&mut world, Mat4::IDENTITY, [input(left, 1.0), input(right, 1.0)], dt);
+        assert_eq!(interaction.hand(Hand::Left).held, None);
+        assert_eq!(interaction.hand(Hand::Right).held, Some(cube));
+        assert_eq!(world.parent(cube), Some(interaction.hand(Hand::Right).entity));
+
+        // letting go with the first hand no longer drops it
+        interaction.update(&mut world, Mat4::IDENTITY, [input(left, 0.0), input(right, 1.0)], dt);
+        assert_eq!(interaction.hand(Hand::Right).held, Some(cube));
+    }
+
+    #[test]
+    fn released_objects_land_on_the_table() {
+        let mut world = World::new();
+        let table = world.spawn("table", Transform::from_position(Vec3::new(0.0, 0.5, 0.0)));
+        world.insert(table, RestSurface::UNIT_CUBE);
+
+        // tipped onto an edge, so it rests on its rotated bounds
+        let tipped = Quat::from_rotation_z(std::f32::consts::FRAC_PI_4);
+        let cube = world.spawn(
+            "cube",
+            Transform::from_position_rotation(Vec3::new(0.0, 1.5, 0.0), tipped).with_scale(Vec3::splat(0.1)),
+        );
+        world.insert(cube, Grabbable::new(GrabVolume::UNIT_CUBE));
+        world.insert(cube, Motion { linear: Vec3::ZERO, angular: Vec3::ZERO });
+
+        // dropped under the table's top, so it falls to the floor
+        let under = world.spawn(
+            "under",
+            Transform::from_position(Vec3::new(0.3, 0.9, 0.0)).with_scale(Vec3::splat(0.1)),
+        );
+        world.insert(under, Grabbable::new(GrabVolume::UNIT_CUBE));
+        world.insert(under, Motion { linear: Vec3::ZERO, angular: Vec3::ZERO });
+
+        let ground = Ground::floor_grid();
+        for _ in 0..200 {
+            integrate_motion(&mut world, &ground, 1.0 / 90.0);
+        }
+
+        let corner = 0.05 * std::f32::consts::SQRT_2;
+        assert!(world.get::<Motion>(cube).is_none());
+        assert!((world.transform(cube).position.y - (1.0 + corner)).abs() < 1e-4);
+
+        assert!(world.get::<Motion>(under).is_none());
+        assert!((world.transform(under).position.y - 0.05).abs() < 1e-4);
+    }
+}
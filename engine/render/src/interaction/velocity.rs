@@ -0,0 +1,62 @@
+use std::collections::VecDeque;
+
+use glam::{Quat, Vec3};
+
+/// Smooths hand motion over a short window so a throw uses the swing,
+/// not the last (often decelerating) frame.
+pub struct VelocityEstimator {
+    samples: VecDeque<(f32, Vec3, Quat)>,
+    pub window: f32,
+}
+
+impl VelocityEstimator {
+    pub fn new(window: f32) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            window,
+        }
+    }
+
+    pub fn push(&mut self, time: f32, position: Vec3, rotation: Quat) {
+        self.samples.push_back((time, position, rotation));
+
+        while let Some(&(t, ..)) = self.samples.front() {
+            if time - t > self.window && self.samples.len() > 2 {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn linear(&self) -> Vec3 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(a), Some(b)) if b.0 > a.0 => (b.1 - a.1) / (b.0 - a.0),
+            _ => Vec3::ZERO,
+        }
+    }
+
+    pub fn angular(&self) -> Vec3 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(a), Some(b)) if b.0 > a.0 => {
+                let mut delta = b.2 * a.2.inverse();
+                // shortest way round
+                if delta.w < 0.0 {
+                    delta = -delta;
+                }
+                delta.to_scaled_axis() / (b.0 - a.0)
+            }
+            _ => Vec3::ZERO,
+        }
+    }
+}
+
+impl Default for VelocityEstimator {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
@@ -1,3 +1,10 @@
+pub mod animation;
 pub mod app;
 pub mod renderer;
-pub mod avatar;
\ No newline at end of file
+pub mod settings;
+pub mod avatar;
+pub mod interaction;
+pub mod locomotion;
+pub mod model;
+pub mod ui;
+pub mod xr;
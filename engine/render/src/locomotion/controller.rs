@@ -0,0 +1,106 @@
+use glam::{Mat4, Vec3};
+
+use super::ground::Ground;
+
+/// The single character body every locomotion mode moves.
+/// Used to live inline in `Renderer::update`.
+pub struct CharacterController {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub velocity: Vec3,
+    pub grounded: bool,
+
+    pub jump_count: u8,
+    pub max_jumps: u8,
+
+    pub speed: f32,
+    pub jump_speed: f32,
+    pub gravity: f32,
+
+    /// Extra rotation of the play area from snap/smooth turning.
+    pub turn_yaw: f32,
+}
+
+impl CharacterController {
+    pub fn new() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            yaw: 0.0,
+            velocity: Vec3::ZERO,
+            grounded: true,
+            jump_count: 0,
+            max_jumps: 2,
+            speed: 4.0,
+            jump_speed: 5.0,
+            gravity: 9.8,
+            turn_yaw: 0.0,
+        }
+    }
+
+    /// Moves along `dir` (world space, y ignored) at `speed` times its
+    /// length, capped at 1, so a half-pushed stick walks.
+    pub fn walk(&mut self, dt: f32, dir: Vec3) {
+        let dir = Vec3::new(dir.x, 0.0, dir.z);
+
+        if dir.length_squared() > 0.0001 {
+            let velocity = dir.clamp_length_max(1.0) * self.speed;
+            self.yaw = dir.x.atan2(dir.z);
+            self.position += velocity * dt;
+            self.velocity.x = velocity.x;
+            self.velocity.z = velocity.z;
+        } else {
+            self.velocity.x = 0.0;
+            self.velocity.z = 0.0;
+        }
+    }
+
+    pub fn jump(&mut self) {
+        if self.jump_count < self.max_jumps {
+            self.velocity.y = self.jump_speed;
+            self.grounded = false;
+            self.jump_count += 1;
+        }
+    }
+
+    pub fn teleport(&mut self, to: Vec3) {
+        self.position = to;
+        self.velocity = Vec3::ZERO;
+        self.grounded = true;
+        self.jump_count = 0;
+    }
+
+    pub fn turn(&mut self, angle: f32) {
+        self.turn_yaw += angle;
+    }
+
+    /// Gravity and landing on the ground height below the character
+    /// (height 0 outside every walkable area).
+    pub fn step(&mut self, dt: f32, ground: &Ground) {
+        self.velocity.y -= self.gravity * dt;
+        self.position.y += self.velocity.y * dt;
+
+        let floor = ground.height_at(self.position.x, self.position.z).unwrap_or(0.0);
+
+        if self.position.y <= floor {
+            self.position.y = floor;
+            self.velocity.y = 0.0;
+            self.grounded = true;
+            self.jump_count = 0;
+        }
+    }
+
+    pub fn horizontal_speed(&self) -> f32 {
+        Vec3::new(self.velocity.x, 0.0, self.velocity.z).length()
+    }
+
+    /// Tracking space → world: the play area rides on the character.
+    pub fn tracking_to_world(&self) -> Mat4 {
+        Mat4::from_translation(self.position) * Mat4::from_rotation_y(self.turn_yaw)
+    }
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
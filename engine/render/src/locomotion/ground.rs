@@ -0,0 +1,47 @@
+use glam::{Vec2, Vec3};
+
+/// Axis-aligned walkable rectangle at a fixed height.
+#[derive(Clone, Copy, Debug)]
+pub struct WalkableArea {
+    pub min: Vec2,
+    pub max: Vec2,
+    pub height: f32,
+}
+
+impl WalkableArea {
+    pub fn contains(&self, x: f32, z: f32) -> bool {
+        x >= self.min.x && x <= self.max.x && z >= self.min.y && z <= self.max.y
+    }
+}
+
+/// What the character can stand on and teleport to.
+#[derive(Clone, Debug)]
+pub struct Ground {
+    pub areas: Vec<WalkableArea>,
+}
+
+impl Ground {
+    /// Matches the 40x40 grid drawn by `floor_mesh`.
+    pub fn floor_grid() -> Self {
+        Self {
+            areas: vec![WalkableArea {
+                min: Vec2::splat(-20.0),
+                max: Vec2::splat(20.0),
+                height: 0.0,
+            }],
+        }
+    }
+
+    /// Highest walkable height under (x, z), if any.
+    pub fn height_at(&self, x: f32, z: f32) -> Option<f32> {
+        self.areas
+            .iter()
+            .filter(|a| a.contains(x, z))
+            .map(|a| a.height)
+            .reduce(f32::max)
+    }
+
+    pub fn is_walkable(&self, p: Vec3) -> bool {
+        self.height_at(p.x, p.z).is_some()
+    }
+}
@@ -0,0 +1,214 @@
+pub mod controller;
+pub mod ground;
+pub mod teleport;
+pub mod turning;
+
+use glam::{Mat4, Quat, Vec2, Vec3};
+
+use crate::xr::PoseFrame;
+
+pub use controller::CharacterController;
+pub use ground::{Ground, WalkableArea};
+pub use teleport::TeleportArc;
+pub use turning::{TurnMode, Turning};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LocomotionMode {
+    /// Continuous stick/WASD walking.
+    Smooth,
+    /// Arc-targeted teleport; the move stick is ignored.
+    Teleport,
+}
+
+/// Per-user comfort options.
+#[derive(Clone, Copy, Debug)]
+pub struct LocomotionSettings {
+    pub mode: LocomotionMode,
+    pub turn: TurnMode,
+    pub teleport_range: f32,
+
+    pub vignette: bool,
+    /// 0..1, how far the vignette closes in at full speed.
+    pub vignette_strength: f32,
+}
+
+impl Default for LocomotionSettings {
+    fn default() -> Self {
+        Self {
+            mode: LocomotionMode::Smooth,
+            turn: TurnMode::default(),
+            teleport_range: 10.0,
+            vignette: false,
+            vignette_strength: 0.7,
+        }
+    }
+}
+
+/// One frame of locomotion input, already in world space.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LocomotionInput {
+    /// Yaw the move stick is relative to (camera or head).
+    pub heading: f32,
+    /// x = right, y = forward, -1..1.
+    pub move_axis: Vec2,
+    /// Turn stick, positive = right.
+    pub turn_axis: f32,
+    /// Teleport pointer (origin, direction) while aiming.
+    pub aim: Option<(Vec3, Vec3)>,
+    pub jump: bool,
+    /// Head position in tracking space, so teleports and turns keep the
+    /// head (not the play-area centre) where the user expects it.
+    pub head: Vec3,
+}
+
+impl LocomotionInput {
+    const AIM_THRESHOLD: f32 = 0.7;
+
+    /// Standard VR mapping: left stick moves, right stick x turns and
+    /// right stick forward aims a teleport from the right hand.
+    pub fn from_poses(poses: &PoseFrame, heading: f32, tracking_to_world: Mat4, jump: bool) -> Self {
+        let right = &poses.right;
+
+        let aim = (right.pose.valid && right.thumbstick.y > Self::AIM_THRESHOLD).then(|| {
+            (
+                tracking_to_world.transform_point3(right.pose.position),
+                tracking_to_world.transform_vector3(right.pose.forward()),
+            )
+        });
+
+        Self {
+            heading,
+            move_axis: poses.left.thumbstick,
+            turn_axis: right.thumbstick.x,
+            aim,
+            jump,
+            head: if poses.head.valid { poses.head.position } else { Vec3::ZERO },
+        }
+    }
+}
+
+/// Yaw (in the controller's forward convention) of a world-space rotation.
+pub fn heading_of(rotation: Quat) -> f32 {
+    let f = rotation * Vec3::NEG_Z;
+    f.x.atan2(f.z)
+}
+
+/* =========================================================
+   LOCOMOTION
+   ========================================================= */
+
+/// Applies the active mode to the character controller and tracks the
+/// teleport arc and comfort vignette for rendering.
+pub struct Locomotion {
+    pub settings: LocomotionSettings,
+    turning: Turning,
+
+    pub arc: Option<TeleportArc>,
+    vignette: f32,
+}
+
+impl Locomotion {
+    pub fn new(settings: LocomotionSettings) -> Self {
+        Self {
+            settings,
+            turning: Turning::default(),
+            arc: None,
+            vignette: 0.0,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        dt: f32,
+        input: &LocomotionInput,
+        controller: &mut CharacterController,
+        ground: &Ground,
+    ) {
+        let mut turn_rate = 0.0;
+
+        /* ================= TURN ================= */
+
+        let turn = self.turning.update(self.settings.turn, dt, input.turn_axis);
+        if turn != 0.0 {
+            // rotate the play area about the head so the view doesn't swing
+            let head = Vec3::new(input.head.x, 0.0, input.head.z);
+            let before = Quat::from_rotation_y(controller.turn_yaw) * head;
+            controller.turn(turn);
+            let after = Quat::from_rotation_y(controller.turn_yaw) * head;
+            controller.position += before - after;
+
+            if dt > 0.0 {
+                turn_rate = turn.abs() / dt;
+            }
+        }
+
+        /* ================= MOVE ================= */
+
+        match self.settings.mode {
+            LocomotionMode::Smooth => {
+                let forward = Vec3::new(input.heading.sin(), 0.0, input.heading.cos());
+                let right = Vec3::new(forward.z, 0.0, -forward.x);
+
+                let dir = forward * input.move_axis.y + right * -input.move_axis.x;
+                controller.walk(dt, dir);
+                self.arc = None;
+            }
+            LocomotionMode::Teleport => {
+                controller.walk(dt, Vec3::ZERO);
+
+                match input.aim {
+                    Some((origin, dir)) => {
+                        self.arc = Some(TeleportArc::cast(
+                            origin,
+                            dir,
+                            ground,
+                            self.settings.teleport_range,
+                        ));
+                    }
+                    None => {
+                        // releasing the aim commits a valid target
+                        if let Some(target) = self.arc.take().and_then(|a| a.target) {
+                            let head = Quat::from_rotation_y(controller.turn_yaw)
+                                * Vec3::new(input.head.x, 0.0, input.head.z);
+                            controller.teleport(target - head);
+                        }
+                    }
+                }
+            }
+        }
+
+        if input.jump {
+            controller.jump();
+        }
+
+        controller.step(dt, ground);
+
+        /* ================= VIGNETTE ================= */
+
+        let moving = controller.horizontal_speed() / controller.speed.max(0.001);
+        let turning = match self.settings.turn {
+            // snap turns are instant and don't need a vignette
+            TurnMode::Smooth { .. } => (turn_rate / 2.0).min(1.0),
+            _ => 0.0,
+        };
+        let target = moving.max(turning).clamp(0.0, 1.0);
+
+        let rate = if target > self.vignette { 8.0 } else { 3.0 };
+        self.vignette += (target - self.vignette) * (rate * dt).min(1.0);
+    }
+
+    /// Current vignette amount for the post-effect, 0 when disabled.
+    pub fn vignette(&self) -> f32 {
+        if self.settings.vignette {
+            self.vignette * self.settings.vignette_strength
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Default for Locomotion {
+    fn default() -> Self {
+        Self::new(LocomotionSettings::default())
+    }
+}
@@ -0,0 +1,120 @@
+use glam::Vec3;
+
+use super::ground::Ground;
+
+/// Parabolic pointer from a hand, landing on walkable ground.
+#[derive(Clone, Debug, Default)]
+pub struct TeleportArc {
+    pub points: Vec<Vec3>,
+    pub target: Option<Vec3>,
+}
+
+impl TeleportArc {
+    pub const LAUNCH_SPEED: f32 = 8.0;
+    pub const GRAVITY: f32 = 9.8;
+    pub const STEP: f32 = 0.03;
+    pub const MAX_STEPS: usize = 120;
+
+    /// Traces the arc until it hits the ground plane under it. The target
+    /// is only set when the landing point is walkable and within `max_distance`
+    /// (horizontally) of `origin`.
+    pub fn cast(origin: Vec3, direction: Vec3, ground: &Ground, max_distance: f32) -> Self {
+        let mut velocity = direction.normalize_or_zero() * Self::LAUNCH_SPEED;
+        let mut p = origin;
+        let mut points = vec![p];
+
+        for _ in 0..Self::MAX_STEPS {
+            let next = p + velocity * Self::STEP;
+            velocity.y -= Self::GRAVITY * Self::STEP;
+
+            let floor = ground.height_at(next.x, next.z).unwrap_or(0.0);
+            if next.y <= floor && velocity.y < 0.0 {
+                // interpolate to the crossing
+                let t = ((p.y - floor) / (p.y - next.y)).clamp(0.0, 1.0);
+                let hit = p.lerp(next, t);
+                points.push(hit);
+
+                let horizontal = Vec3::new(hit.x - origin.x, 0.0, hit.z - origin.z).length();
+                let valid = ground.is_walkable(hit) && horizontal <= max_distance;
+
+                return Self {
+                    points,
+                    target: valid.then_some(hit),
+                };
+            }
+
+            points.push(next);
+            p = next;
+        }
+
+        Self {
+            points,
+            target: None,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.target.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::*;
+    use crate::locomotion::ground::WalkableArea;
+
+    #[test]
+    fn arcs_land_on_walkable_ground_in_range() {
+        let origin = Vec3::new(0.0, 1.5, 0.0);
+        let arc = TeleportArc::cast(origin, Vec3::new(0.0, 0.5, -1.0), &Ground::floor_grid(), 20.0);
+
+        let target = arc.target.expect("should land on the floor");
+        assert!(target.y.abs() < 1e-4);
+        assert!(target.z < -1.0 && target.x.abs() < 1e-4);
+        assert_eq!(*arc.points.last().unwrap(), target);
+        assert!(arc.points.windows(2).all(|p| p[1].z < p[0].z));
+    }
+
+    #[test]
+    fn arcs_out_of_range_or_off_the_ground_have_no_target() {
+        let origin = Vec3::new(0.0, 1.5, 0.0);
+        let forward = Vec3::new(0.0, 0.5, -1.0);
+        let landing = TeleportArc::cast(origin, forward, &Ground::floor_grid(), 20.0).target.unwrap();
+
+        let short = TeleportArc::cast(origin, forward, &Ground::floor_grid(), landing.z.abs() - 0.1);
+        assert!(!short.is_valid());
+        assert!(short.points.last().unwrap().abs_diff_eq(landing, 1e-5));
+
+        let pad = Ground {
+            areas: vec![WalkableArea {
+                min: Vec2::splat(-0.5),
+                max: Vec2::splat(0.5),
+                height: 0.0,
+            }],
+        };
+        assert!(!TeleportArc::cast(origin, forward, &pad, 20.0).is_valid());
+    }
+
+    #[test]
+    fn arcs_land_on_raised_areas() {
+        let step = Ground {
+            areas: vec![
+                WalkableArea {
+                    min: Vec2::splat(-20.0),
+                    max: Vec2::splat(20.0),
+                    height: 0.0,
+                },
+                WalkableArea {
+                    min: Vec2::new(-1.0, -20.0),
+                    max: Vec2::new(1.0, 0.0),
+                    height: 0.5,
+                },
+            ],
+        };
+
+        let arc = TeleportArc::cast(Vec3::new(0.0, 1.5, 0.0), Vec3::new(0.0, 0.5, -1.0), &step, 20.0);
+        assert!((arc.target.unwrap().y - 0.5).abs() < 1e-4);
+    }
+}
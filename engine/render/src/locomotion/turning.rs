@@ -0,0 +1,89 @@
+/// How the right stick turns the player.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TurnMode {
+    /// Instant rotation by a fixed angle per stick flick.
+    Snap { degrees: f32 },
+    /// Continuous rotation while the stick is held.
+    Smooth { degrees_per_second: f32 },
+    Disabled,
+}
+
+impl Default for TurnMode {
+    fn default() -> Self {
+        TurnMode::Snap { degrees: 30.0 }
+    }
+}
+
+/// Turns stick deflection into yaw changes, re-arming snap turns only
+/// after the stick returns to centre.
+#[derive(Default)]
+pub struct Turning {
+    snap_armed: bool,
+}
+
+impl Turning {
+    const SNAP_THRESHOLD: f32 = 0.7;
+    const RELEASE_THRESHOLD: f32 = 0.3;
+    const DEADZONE: f32 = 0.15;
+
+    /// Returns the yaw change in radians. Positive stick is right, which
+    /// is a negative (clockwise from above) yaw.
+    pub fn update(&mut self, mode: TurnMode, dt: f32, stick_x: f32) -> f32 {
+        if stick_x.abs() < Self::RELEASE_THRESHOLD {
+            self.snap_armed = true;
+        }
+
+        match mode {
+            TurnMode::Snap { degrees } => {
+                if self.snap_armed && stick_x.abs() > Self::SNAP_THRESHOLD {
+                    self.snap_armed = false;
+                    -stick_x.signum() * degrees.to_radians()
+                } else {
+                    0.0
+                }
+            }
+            TurnMode::Smooth { degrees_per_second } => {
+                if stick_x.abs() > Self::DEADZONE {
+                    -stick_x * degrees_per_second.to_radians() * dt
+                } else {
+                    0.0
+                }
+            }
+            TurnMode::Disabled => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNAP: TurnMode = TurnMode::Snap { degrees: 30.0 };
+
+    #[test]
+    fn snap_turns_once_per_flick() {
+        let mut turning = Turning::default();
+
+        assert_eq!(turning.update(SNAP, 0.1, 0.5), 0.0);
+        assert_eq!(turning.update(SNAP, 0.1, 0.0), 0.0);
+        assert!((turning.update(SNAP, 0.1, 1.0) + 30f32.to_radians()).abs() < 1e-6);
+        // held, or only half released: no repeat
+        assert_eq!(turning.update(SNAP, 0.1, 1.0), 0.0);
+        assert_eq!(turning.update(SNAP, 0.1, 0.5), 0.0);
+        assert_eq!(turning.update(SNAP, 0.1, 0.9), 0.0);
+
+        assert_eq!(turning.update(SNAP, 0.1, 0.1), 0.0);
+        assert!((turning.update(SNAP, 0.1, -0.9) - 30f32.to_radians()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn smooth_turns_scale_with_stick_and_time() {
+        let mut turning = Turning::default();
+        let smooth = TurnMode::Smooth { degrees_per_second: 90.0 };
+
+        assert_eq!(turning.update(smooth, 0.5, 0.1), 0.0);
+        assert!((turning.update(smooth, 0.5, 1.0) + 45f32.to_radians()).abs() < 1e-6);
+        assert!((turning.update(smooth, 0.5, -0.5) - 22.5f32.to_radians()).abs() < 1e-6);
+        assert_eq!(turning.update(TurnMode::Disabled, 0.5, 1.0), 0.0);
+    }
+}
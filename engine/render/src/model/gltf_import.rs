@@ -0,0 +1,640 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use engine_core::{EntityId, Renderable, Transform, World};
+use glam::{Mat4, Quat, Vec3};
+use gltf::animation::util::ReadOutputs;
+use gltf::khr_lights_punctual::Kind;
+use gltf::mesh::Mode;
+use gltf::texture::{MagFilter, MinFilter, WrappingMode};
+use wgpu::{AddressMode, FilterMode};
+
+use crate::animation::{AnimationClip, Animations, Channel, Interpolation, Keyframes};
+use crate::model::sources::{image_bytes, load_buffers};
+use crate::model::{Model, ModelError};
+use crate::renderer::lighting::lights::{DirectionalLight, PointLight, SpotLight};
+use crate::renderer::material::{BlendMode, Material, MaterialId, MaterialLibrary, TextureId};
+use crate::renderer::mesh::morph::MorphWeights;
+use crate::renderer::mesh::skin::Skin;
+use crate::renderer::mesh::{MeshData, MeshId, MeshLibrary, MorphTarget};
+use crate::renderer::resources::mesh::Vertex;
+use crate::renderer::texture::{decode_texture, SamplerSettings};
+use crate::renderer::uniforms::camera::SceneCamera;
+
+/*
+   glTF 2.0 import.
+
+   Every primitive becomes a library mesh. A node whose mesh has one
+   primitive draws it itself; otherwise each primitive gets a child
+   entity, since a `Renderable` holds one mesh and one material. Only
+   the base color texture is used (first UV set); the other texture
+   slots are ignored.
+
+   Morph targets keep their position and normal deltas (tangents are
+   dropped) and take their names from the mesh's `extras.targetNames`.
+   Drawing entities of morphed meshes get `MorphWeights` with the node's
+   (or mesh's) default weights, and weight channels animate those.
+
+   Light intensities (candela for point and spot lights, lux for
+   directional ones) are taken as our brightness units unchanged, as
+   most realtime viewers do.
+*/
+
+/// Imports the default scene (or the first one) of a `.gltf`/`.glb`
+/// file already read into `bytes`. Relative URIs resolve against `base`.
+pub fn import_gltf(
+    bytes: &[u8],
+    base: &Path,
+    name: &str,
+    world: &mut World,
+    meshes: &mut MeshLibrary,
+    materials: &mut MaterialLibrary,
+) -> Result<Model, ModelError> {
+    let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(bytes)?;
+    let buffers = load_buffers(&document, blob, base)?;
+
+    let mut importer = Importer {
+        buffers: &buffers,
+        base,
+        name,
+        meshes,
+        materials,
+        textures: HashMap::new(),
+        default_material: None,
+        warnings: Vec::new(),
+    };
+
+    let model_materials: Vec<MaterialId> = document
+        .materials()
+        .map(|m| importer.material(&m))
+        .collect();
+
+    let model_meshes: Vec<Vec<(MeshId, MaterialId)>> = document
+        .meshes()
+        .map(|mesh| {
+            mesh.primitives()
+                .filter_map(|p| {
+                    let material = match p.material().index() {
+                        Some(i) => model_materials[i],
+                        None => importer.default_material(),
+                    };
+                    importer.primitive(&mesh, &p).map(|m| (m, material))
+                })
+                .collect()
+        })
+        .collect();
+
+    /* ---------- nodes ---------- */
+
+    let root = world.spawn(name, Transform::IDENTITY);
+    let mut nodes = vec![None; document.nodes().len()];
+    let mut primitives: Vec<Vec<EntityId>> = vec![Vec::new(); document.nodes().len()];
+
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or_else(|| ModelError::Invalid("no scene".into()))?;
+
+    let mut stack: Vec<(gltf::Node, EntityId)> = scene.nodes().map(|n| (n, root)).collect();
+    while let Some((node, parent)) = stack.pop() {
+        let entity = spawn_node(world, &node, parent);
+        nodes[node.index()] = Some(entity);
+
+        if let Some(mesh) = node.mesh() {
+            primitives[node.index()] = spawn_primitives(world, entity, &model_meshes[mesh.index()]);
+
+            let defaults = node.weights().or(mesh.weights()).unwrap_or_default();
+            for (&entity, &(id, _)) in primitives[node.index()].iter().zip(&model_meshes[mesh.index()]) {
+                let data = importer.meshes.get(id);
+                if data.is_morphed() {
+                    let mut weights = MorphWeights::new(defaults.to_vec());
+                    weights.weights.resize(data.morph_targets.len(), 0.0);
+                    world.insert(entity, weights);
+                }
+            }
+        }
+
+        stack.extend(node.children().map(|c| (c, entity)));
+    }
+
+    /* ---------- skins ---------- */
+
+    for node in document.nodes() {
+        let Some(skin) = node.skin() else { continue };
+
+        let joints = skin
+            .joints()
+            .map(|j| nodes[j.index()].unwrap_or(root))
+            .collect();
+        let inverse_bind = skin
+            .reader(|b| Some(&buffers[b.index()][..]))
+            .read_inverse_bind_matrices()
+            .map(|m| m.map(|m| Mat4::from_cols_array_2d(&m)).collect())
+            .unwrap_or_default();
+
+        let skin = Skin::new(joints, inverse_bind);
+        for &entity in &primitives[node.index()] {
+            world.insert(entity, skin.clone());
+        }
+    }
+
+    /* ---------- animations ---------- */
+
+    let clips: Vec<AnimationClip> = document
+        .animations()
+        .enumerate()
+        .map(|(i, animation)| {
+            let channels = animation
+                .channels()
+                .flat_map(|c| import_channel(&c, &buffers, &nodes, &primitives))
+                .collect();
+            let name = animation.name().map(str::to_string).unwrap_or_else(|| format!("animation_{}", i));
+            AnimationClip::new(&name, channels)
+        })
+        .collect();
+
+    if !clips.is_empty() {
+        world.insert(root, Animations { clips });
+    }
+
+    Ok(Model {
+        root,
+        nodes,
+        primitives,
+        meshes: model_meshes,
+        materials: model_materials,
+        warnings: importer.warnings,
+    })
+}
+
+/// Spawns one node under `parent` with its camera and light.
+fn spawn_node(world: &mut World, node: &gltf::Node, parent: EntityId) -> EntityId {
+    let (position, rotation, scale) = node.transform().decomposed();
+    let transform = Transform {
+        position: Vec3::from(position),
+        rotation: Quat::from_array(rotation),
+        scale: Vec3::from(scale),
+    };
+
+    let name = node.name().map(str::to_string).unwrap_or_else(|| format!("node_{}", node.index()));
+    let entity = world.spawn(&name, transform);
+    world.set_parent(entity, Some(parent), false);
+
+    if let Some(camera) = node.camera() {
+        world.insert(entity, scene_camera(&camera));
+    }
+
+    if let Some(light) = node.light() {
+        let color = light.color();
+        let intensity = light.intensity();
+        let range = light.range().unwrap_or_else(|| default_range(intensity));
+
+        match light.kind() {
+            Kind::Directional => world.insert(entity, DirectionalLight::new(color, intensity)),
+            Kind::Point => world.insert(entity, PointLight::new(color, intensity, range)),
+            Kind::Spot { inner_cone_angle, outer_cone_angle } => world.insert(
+                entity,
+                SpotLight {
+                    color,
+                    intensity,
+                    range,
+                    inner_angle: inner_cone_angle,
+                    outer_angle: outer_cone_angle,
+                },
+            ),
+        }
+    }
+
+    entity
+}
+
+/// Makes `entity` draw its mesh's primitives, returning the entities
+/// that do the drawing.
+fn spawn_primitives(world: &mut World, entity: EntityId, primitives: &[(MeshId, MaterialId)]) -> Vec<EntityId> {
+    if let [(mesh, material)] = primitives {
+        world.insert(entity, Renderable::new(mesh.0, material.0));
+        return vec![entity];
+    }
+
+    let name = world.entity(entity).map(|e| e.name.clone()).unwrap_or_default();
+    primitives
+        .iter()
+        .enumerate()
+        .map(|(i, (mesh, material))| {
+            let child = world.spawn(&format!("{}/primitive_{}", name, i), Transform::IDENTITY);
+            world.set_parent(child, Some(entity), false);
+            world.insert(child, Renderable::new(mesh.0, material.0));
+            child
+        })
+        .collect()
+}
+
+/// Names of a mesh's morph targets from its `extras.targetNames`, which
+/// most exporters write; empty if there are none.
+fn target_names(mesh: &gltf::Mesh) -> Vec<String> {
+    let Some(extras) = mesh.extras() else {
+        return Vec::new();
+    };
+    let Ok(extras) = serde_json::from_str::<serde_json::Value>(extras.get()) else {
+        return Vec::new();
+    };
+
+    extras["targetNames"]
+        .as_array()
+        .map(|names| names.iter().map(|n| n.as_str().unwrap_or_default().to_string()).collect())
+        .unwrap_or_default()
+}
+
+fn scene_camera(camera: &gltf::Camera) -> SceneCamera {
+    match camera.projection() {
+        gltf::camera::Projection::Perspective(p) => SceneCamera::Perspective {
+            yfov: p.yfov(),
+            aspect: p.aspect_ratio(),
+            znear: p.znear(),
+            zfar: p.zfar(),
+        },
+        gltf::camera::Projection::Orthographic(o) => SceneCamera::Orthographic {
+            xmag: o.xmag(),
+            ymag: o.ymag(),
+            znear: o.znear(),
+            zfar: o.zfar(),
+        },
+    }
+}
+
+/// glTF lights without a range reach forever, ours need one for the
+/// light clusters: stop where they fade to 1% of a unit of brightness.
+fn default_range(intensity: f32) -> f32 {
+    (intensity.max(0.0) * 100.0).sqrt().max(0.1)
+}
+
+/// Weight channels become one channel per entity drawing the node's mesh.
+fn import_channel(
+    channel: &gltf::animation::Channel,
+    buffers: &[Vec<u8>],
+    nodes: &[Option<EntityId>],
+    primitives: &[Vec<EntityId>],
+) -> Vec<Channel> {
+    let node = channel.target().node().index();
+    let Some(target) = nodes[node] else {
+        return Vec::new();
+    };
+    let reader = channel.reader(|b| Some(&buffers[b.index()][..]));
+
+    let (Some(times), Some(outputs)) = (reader.read_inputs(), reader.read_outputs()) else {
+        return Vec::new();
+    };
+    let times: Vec<f32> = times.collect();
+    let keyframes = match outputs {
+        ReadOutputs::Translations(t) => Keyframes::Translation(t.map(Vec3::from).collect()),
+        ReadOutputs::Rotations(r) => Keyframes::Rotation(r.into_f32().map(Quat::from_array).collect()),
+        ReadOutputs::Scales(s) => Keyframes::Scale(s.map(Vec3::from).collect()),
+        ReadOutputs::MorphTargetWeights(w) => Keyframes::Weights(w.into_f32().collect()),
+    };
+
+    let interpolation = match channel.sampler().interpolation() {
+        gltf::animation::Interpolation::Step => Interpolation::Step,
+        gltf::animation::Interpolation::Linear => Interpolation::Linear,
+        gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+    };
+
+    let channel = Channel {
+        target,
+        interpolation,
+        times,
+        keyframes,
+    };
+
+    match channel.keyframes {
+        Keyframes::Weights(_) => primitives[node]
+            .iter()
+            .map(|&target| Channel { target, ..channel.clone() })
+            .collect(),
+        _ => vec![channel],
+    }
+}
+
+/* =========================================================
+   MESHES, MATERIALS AND TEXTURES
+   ========================================================= */
+
+struct Importer<'a> {
+    buffers: &'a [Vec<u8>],
+    base: &'a Path,
+    name: &'a str,
+    meshes: &'a mut MeshLibrary,
+    materials: &'a mut MaterialLibrary,
+    /// By texture index; `None` when the image failed to load.
+    textures: HashMap<usize, Option<TextureId>>,
+    /// glTF's default material, for primitives without one.
+    default_material: Option<MaterialId>,
+    warnings: Vec<String>,
+}
+
+impl Importer<'_> {
+    fn primitive(&mut self, mesh: &gltf::Mesh, primitive: &gltf::Primitive) -> Option<MeshId> {
+        let name = format!(
+            "{}/{}/{}",
+            self.name,
+            mesh.name().map(str::to_string).unwrap_or_else(|| format!("mesh_{}", mesh.index())),
+            primitive.index()
+        );
+        let buffers = self.buffers;
+        let reader = primitive.reader(|b| Some(&buffers[b.index()][..]));
+
+        let Some(positions) = reader.read_positions() else {
+            self.warnings.push(format!("{}: no positions", name));
+            return None;
+        };
+        let positions: Vec<[f32; 3]> = positions.collect();
+        let count = positions.len();
+
+        let indices: Vec<u32> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect(),
+            None => (0..count as u32).collect(),
+        };
+        let Some(mut indices) = triangle_list(primitive.mode(), indices) else {
+            self.warnings.push(format!("{}: {:?} primitives are not supported", name, primitive.mode()));
+            return None;
+        };
+        if indices.iter().any(|&i| i as usize >= count) {
+            self.warnings.push(format!("{}: index out of range", name));
+            return None;
+        }
+
+        let normals: Option<Vec<[f32; 3]>> = reader.read_normals().map(|n| n.collect());
+        let uvs: Vec<[f32; 2]> = match reader.read_tex_coords(0) {
+            Some(uvs) => uvs.into_f32().collect(),
+            None => vec![[0.0; 2]; count],
+        };
+        let colors: Vec<[f32; 3]> = match reader.read_colors(0) {
+            Some(colors) => colors.into_rgb_f32().collect(),
+            None => vec![[1.0; 3]; count],
+        };
+
+        let mut vertices: Vec<Vertex> = (0..count)
+            .map(|i| {
+                let normal = normals.as_ref().map_or([0.0; 3], |n| n[i]);
+                Vertex::lit(positions[i], normal, colors[i]).with_uv(uvs[i])
+            })
+            .collect();
+
+        let mut joints: Vec<[u16; 4]> = reader
+            .read_joints(0)
+            .map(|j| j.into_u16().collect())
+            .unwrap_or_default();
+        let mut weights: Vec<[f32; 4]> = reader
+            .read_weights(0)
+            .map(|w| w.into_f32().collect())
+            .unwrap_or_default();
+        if joints.len() != count || weights.len() != count {
+            joints.clear();
+            weights.clear();
+        }
+
+        let target_names = target_names(mesh);
+        let mut targets: Vec<MorphTarget> = reader
+            .read_morph_targets()
+            .enumerate()
+            .map(|(i, (positions, normals, _))| MorphTarget {
+                name: target_names.get(i).cloned().unwrap_or_else(|| format!("target_{}", i)),
+                positions: positions.map_or_else(|| vec![[0.0; 3]; count], |p| p.collect()),
+                normals: normals.map(|n| n.collect()).unwrap_or_default(),
+            })
+            .collect();
+        if targets
+            .iter()
+            .any(|t| t.positions.len() != count || !(t.normals.is_empty() || t.normals.len() == count))
+        {
+            self.warnings.push(format!("{}: morph targets do not match the vertices", name));
+            targets.clear();
+        }
+
+        if normals.is_none() {
+            flat_normals(&mut vertices, &mut indices, &mut joints, &mut weights, &mut targets);
+        }
+
+        let data = MeshData::new(&name, vertices, indices)
+            .with_skin(joints, weights)
+            .with_morph_targets(targets);
+        Some(self.meshes.add(data))
+    }
+
+    fn material(&mut self, material: &gltf::Material) -> MaterialId {
+        let index = material.index().unwrap_or(0);
+        let name = format!(
+            "{}/{}",
+            self.name,
+            material.name().map(str::to_string).unwrap_or_else(|| format!("material_{}", index))
+        );
+
+        let pbr = material.pbr_metallic_roughness();
+        let [r, g, b, a] = pbr.base_color_factor();
+        let strength = material.emissive_strength().unwrap_or(1.0);
+
+        let mut result = Material::new(&name, [r, g, b])
+            .with_alpha(a)
+            .with_roughness_metallic(pbr.roughness_factor(), pbr.metallic_factor())
+            .with_emissive(material.emissive_factor().map(|c| c * strength));
+
+        match material.alpha_mode() {
+            gltf::material::AlphaMode::Opaque => {}
+            gltf::material::AlphaMode::Blend => result = result.with_blend(BlendMode::Blend),
+            gltf::material::AlphaMode::Mask => {
+                self.warnings.push(format!("{}: alpha mask drawn opaque", name));
+            }
+        }
+
+        if let Some(info) = pbr.base_color_texture() {
+            if info.tex_coord() != 0 {
+                self.warnings.push(format!("{}: base color uses UV set {}, reading set 0", name, info.tex_coord()));
+            }
+            if let Some(texture) = self.texture(&info.texture()) {
+                result = result.with_base_color_texture(texture);
+            }
+        }
+
+        self.materials.add(result)
+    }
+
+    /// White, fully metallic and rough, per the glTF spec.
+    fn default_material(&mut self) -> MaterialId {
+        if let Some(id) = self.default_material {
+            return id;
+        }
+
+        let name = format!("{}/default", self.name);
+        let id = self
+            .materials
+            .add(Material::new(&name, [1.0, 1.0, 1.0]).with_roughness_metallic(1.0, 1.0));
+        self.default_material = Some(id);
+        id
+    }
+
+    /// Base color textures are sRGB; each is decoded once however many
+    /// materials use it.
+    fn texture(&mut self, texture: &gltf::Texture) -> Option<TextureId> {
+        if let Some(&id) = self.textures.get(&texture.index()) {
+            return id;
+        }
+
+        let loaded = image_bytes(&texture.source(), self.buffers, self.base)
+            .and_then(|bytes| Ok(decode_texture(&bytes, true)?));
+
+        let id = match loaded {
+            Ok(data) => Some(self.materials.add_texture(data.with_sampler(sampler_settings(&texture.sampler())))),
+            Err(e) => {
+                self.warnings.push(format!("{}: texture {}: {}", self.name, texture.index(), e));
+                None
+            }
+        };
+
+        self.textures.insert(texture.index(), id);
+        id
+    }
+}
+
+fn sampler_settings(sampler: &gltf::texture::Sampler) -> SamplerSettings {
+    let address = |mode: WrappingMode| match mode {
+        WrappingMode::ClampToEdge => AddressMode::ClampToEdge,
+        WrappingMode::MirroredRepeat => AddressMode::MirrorRepeat,
+        WrappingMode::Repeat => AddressMode::Repeat,
+    };
+
+    let mag_filter = match sampler.mag_filter() {
+        Some(MagFilter::Nearest) => FilterMode::Nearest,
+        Some(MagFilter::Linear) | None => FilterMode::Linear,
+    };
+
+    let (min_filter, mipmap_filter) = match sampler.min_filter() {
+        Some(MinFilter::Nearest) | Some(MinFilter::NearestMipmapNearest) => (FilterMode::Nearest, FilterMode::Nearest),
+        Some(MinFilter::Linear) | Some(MinFilter::LinearMipmapNearest) => (FilterMode::Linear, FilterMode::Nearest),
+        Some(MinFilter::NearestMipmapLinear) => (FilterMode::Nearest, FilterMode::Linear),
+        Some(MinFilter::LinearMipmapLinear) | None => (FilterMode::Linear, FilterMode::Linear),
+    };
+
+    SamplerSettings {
+        address_u: address(sampler.wrap_s()),
+        address_v: address(sampler.wrap_t()),
+        mag_filter,
+        min_filter,
+        mipmap_filter,
+        ..SamplerSettings::default()
+    }
+}
+
+/// Strips and fans unrolled into a triangle list, keeping the winding;
+/// `None` for points and lines.
+fn triangle_list(mode: Mode, indices: Vec<u32>) -> Option<Vec<u32>> {
+    match mode {
+        Mode::Triangles => Some(indices),
+        Mode::TriangleStrip => Some(
+            (0..indices.len().saturating_sub(2))
+                .flat_map(|i| {
+                    let odd = i % 2;
+                    [indices[i], indices[i + 1 + odd], indices[i + 2 - odd]]
+                })
+                .collect(),
+        ),
+        Mode::TriangleFan => Some(
+            (1..indices.len().saturating_sub(1))
+                .flat_map(|i| [indices[i], indices[i + 1], indices[0]])
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// glTF asks for flat shading when normals are missing: every triangle
+/// gets its own three vertices with the face normal.
+fn flat_normals(
+    vertices: &mut Vec<Vertex>,
+    indices: &mut Vec<u32>,
+    joints: &mut Vec<[u16; 4]>,
+    weights: &mut Vec<[f32; 4]>,
+    targets: &mut [MorphTarget],
+) {
+    let mut flat: Vec<Vertex> = indices.iter().map(|&i| vertices[i as usize]).collect();
+
+    for triangle in flat.chunks_mut(3) {
+        if let [a, b, c] = triangle {
+            let (pa, pb, pc) = (Vec3::from(a.position), Vec3::from(b.position), Vec3::from(c.position));
+            let normal = (pb - pa).cross(pc - pa).normalize_or_zero().to_array();
+            a.normal = normal;
+            b.normal = normal;
+            c.normal = normal;
+        }
+    }
+
+    if !joints.is_empty() {
+        *joints = indices.iter().map(|&i| joints[i as usize]).collect();
+        *weights = indices.iter().map(|&i| weights[i as usize]).collect();
+    }
+
+    // the faces are recomputed, not morphed
+    for target in targets {
+        target.positions = indices.iter().map(|&i| target.positions[i as usize]).collect();
+        target.normals.clear();
+    }
+
+    *vertices = flat;
+    *indices = (0..vertices.len() as u32).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(x: f32, y: f32) -> Vertex {
+        Vertex::new([x, y, 0.0], [1.0; 3])
+    }
+
+    #[test]
+    fn strips_and_fans_unroll_to_counter_clockwise_lists() {
+        let strip = triangle_list(Mode::TriangleStrip, vec![0, 1, 2, 3, 4]).unwrap();
+        assert_eq!(strip, [0, 1, 2, 1, 3, 2, 2, 3, 4]);
+
+        let fan = triangle_list(Mode::TriangleFan, vec![0, 1, 2, 3]).unwrap();
+        assert_eq!(fan, [1, 2, 0, 2, 3, 0]);
+
+        assert_eq!(triangle_list(Mode::Triangles, vec![2, 1, 0]).unwrap(), [2, 1, 0]);
+        assert!(triangle_list(Mode::TriangleStrip, vec![0, 1]).unwrap().is_empty());
+        assert!(triangle_list(Mode::TriangleFan, vec![]).unwrap().is_empty());
+        assert_eq!(triangle_list(Mode::Lines, vec![0, 1]), None);
+        assert_eq!(triangle_list(Mode::Points, vec![0]), None);
+    }
+
+    #[test]
+    fn flat_normals_split_vertices_per_face() {
+        // a quad folded along its diagonal: one face in z = 0, one in x = 1
+        let mut vertices = vec![vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(1.0, 1.0)];
+        vertices.push(Vertex::new([1.0, 0.0, -1.0], [1.0; 3]));
+        let mut indices = vec![0, 1, 2, 1, 3, 2];
+        let mut joints = vec![[0, 0, 0, 0], [1, 0, 0, 0], [2, 0, 0, 0], [3, 0, 0, 0]];
+        let mut weights = vec![[1.0, 0.0, 0.0, 0.0]; 4];
+        let mut targets = [MorphTarget {
+            name: "bend".to_string(),
+            positions: vec![[0.0; 3], [0.1, 0.0, 0.0], [0.2, 0.0, 0.0], [0.3, 0.0, 0.0]],
+            normals: vec![[0.0; 3]; 4],
+        }];
+
+        flat_normals(&mut vertices, &mut indices, &mut joints, &mut weights, &mut targets);
+
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(indices, [0, 1, 2, 3, 4, 5]);
+        for v in &vertices[..3] {
+            assert_eq!(v.normal, [0.0, 0.0, 1.0]);
+        }
+        for v in &vertices[3..] {
+            assert_eq!(v.normal, [1.0, 0.0, 0.0]);
+        }
+        assert_eq!(vertices[3].position, [1.0, 0.0, 0.0]);
+
+        // per-vertex data follows its vertex
+        let bones: Vec<u16> = joints.iter().map(|j| j[0]).collect();
+        assert_eq!(bones, [0, 1, 2, 1, 3, 2]);
+        assert_eq!(weights.len(), 6);
+        assert_eq!(targets[0].positions[4], [0.3, 0.0, 0.0]);
+        assert!(targets[0].normals.is_empty());
+    }
+}
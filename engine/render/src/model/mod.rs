@@ -0,0 +1,106 @@
+pub mod gltf_import;
+pub mod sources;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use engine_core::{EntityId, World};
+
+use crate::renderer::material::{MaterialId, MaterialLibrary};
+use crate::renderer::mesh::{MeshId, MeshLibrary};
+use crate::renderer::texture::TextureError;
+
+#[derive(Debug)]
+pub enum ModelError {
+    Io(io::Error),
+    Gltf(gltf::Error),
+    Texture(TextureError),
+    /// The file parsed but breaks the format's rules.
+    Invalid(String),
+    Unsupported(String),
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModelError::Io(e) => write!(f, "{}", e),
+            ModelError::Gltf(e) => write!(f, "gltf: {}", e),
+            ModelError::Texture(e) => write!(f, "texture: {}", e),
+            ModelError::Invalid(msg) => write!(f, "invalid model: {}", msg),
+            ModelError::Unsupported(what) => write!(f, "unsupported: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+impl From<io::Error> for ModelError {
+    fn from(e: io::Error) -> Self {
+        ModelError::Io(e)
+    }
+}
+
+impl From<gltf::Error> for ModelError {
+    fn from(e: gltf::Error) -> Self {
+        ModelError::Gltf(e)
+    }
+}
+
+impl From<TextureError> for ModelError {
+    fn from(e: TextureError) -> Self {
+        ModelError::Texture(e)
+    }
+}
+
+/*
+   What an import added to the world and the libraries.
+
+   The model's nodes hang under `root`, which sits at the origin; move
+   the model by moving the root. Indices follow the source file, so code
+   reading extensions (VRM, ...) can find the entity of node N.
+*/
+pub struct Model {
+    pub root: EntityId,
+    /// Entity of each node, by node index; `None` for nodes outside the
+    /// imported scene.
+    pub nodes: Vec<Option<EntityId>>,
+    /// Entities drawing each node's mesh, by node index: the node itself,
+    /// or one child per primitive.
+    pub primitives: Vec<Vec<EntityId>>,
+    /// Library mesh and material of each primitive, by mesh index.
+    /// Primitives that could not be imported are left out.
+    pub meshes: Vec<Vec<(MeshId, MaterialId)>>,
+    /// By material index.
+    pub materials: Vec<MaterialId>,
+    /// What was skipped or approximated (unsupported primitive modes,
+    /// undecodable images, alpha masks drawn opaque, ...).
+    pub warnings: Vec<String>,
+}
+
+impl Model {
+    pub fn node(&self, index: usize) -> Option<EntityId> {
+        self.nodes.get(index).copied().flatten()
+    }
+}
+
+/// Loads a `.gltf` (with its buffers and images next to it) or `.glb`
+/// file into `world`, adding its meshes, materials and textures to the
+/// libraries.
+pub fn load_model(
+    path: impl AsRef<Path>,
+    world: &mut World,
+    meshes: &mut MeshLibrary,
+    materials: &mut MaterialLibrary,
+) -> Result<Model, ModelError> {
+    let path = path.as_ref();
+    let bytes = fs::read(path)?;
+    let base = path.parent().unwrap_or(Path::new("."));
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "model".to_string());
+
+    gltf_import::import_gltf(&bytes, base, &name, world, meshes, materials)
+}
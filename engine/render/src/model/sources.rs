@@ -0,0 +1,100 @@
+use std::fs;
+use std::path::Path;
+
+use base64::Engine;
+
+use crate::model::ModelError;
+
+/// Reads every buffer of the document: the GLB binary chunk, base64
+/// `data:` URIs and files next to the model.
+pub fn load_buffers(
+    document: &gltf::Document,
+    mut blob: Option<Vec<u8>>,
+    base: &Path,
+) -> Result<Vec<Vec<u8>>, ModelError> {
+    let mut buffers = Vec::new();
+
+    for buffer in document.buffers() {
+        let mut data = match buffer.source() {
+            gltf::buffer::Source::Bin => blob
+                .take()
+                .ok_or_else(|| ModelError::Invalid("buffer refers to a missing GLB chunk".into()))?,
+            gltf::buffer::Source::Uri(uri) => read_uri(uri, base)?,
+        };
+
+        if data.len() < buffer.length() {
+            return Err(ModelError::Invalid(format!(
+                "buffer {} holds {} bytes, expected {}",
+                buffer.index(),
+                data.len(),
+                buffer.length()
+            )));
+        }
+
+        // GLB chunks are padded to four bytes
+        data.truncate(buffer.length());
+        buffers.push(data);
+    }
+
+    Ok(buffers)
+}
+
+/// Encoded bytes of an image, wherever the document keeps them.
+pub fn image_bytes<'a>(
+    image: &gltf::Image,
+    buffers: &'a [Vec<u8>],
+    base: &Path,
+) -> Result<std::borrow::Cow<'a, [u8]>, ModelError> {
+    match image.source() {
+        gltf::image::Source::View { view, .. } => {
+            let buffer = &buffers[view.buffer().index()];
+            Ok(buffer[view.offset()..view.offset() + view.length()].into())
+        }
+        gltf::image::Source::Uri { uri, .. } => Ok(read_uri(uri, base)?.into()),
+    }
+}
+
+fn read_uri(uri: &str, base: &Path) -> Result<Vec<u8>, ModelError> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, encoded) = data
+            .split_once(";base64,")
+            .ok_or_else(|| ModelError::Invalid("only base64 data URIs are supported".into()))?;
+
+        return base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| ModelError::Invalid(format!("data URI: {}", e)));
+    }
+
+    if uri.contains("://") {
+        return Err(ModelError::Unsupported(format!("remote URI {}", uri)));
+    }
+
+    Ok(fs::read(base.join(percent_decode(uri)))?)
+}
+
+/// URIs are percent-encoded ("my%20texture.png").
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
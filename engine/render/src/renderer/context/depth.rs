@@ -1,3 +1,5 @@
+use crate::renderer::resources::counters::create_texture;
+
 pub struct DepthTexture {
     pub view: wgpu::TextureView,
     pub format: wgpu::TextureFormat,
@@ -8,13 +10,17 @@ impl DepthTexture {
         device: &wgpu::Device,
         config: &wgpu::SurfaceConfiguration,
     ) -> Self {
+        Self::with_size(device, config.width, config.height)
+    }
+
+    pub fn with_size(device: &wgpu::Device, width: u32, height: u32) -> Self {
         let format = wgpu::TextureFormat::Depth24Plus;
 
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
+        let texture = create_texture(device, &wgpu::TextureDescriptor {
             label: Some("depth_texture"),
             size: wgpu::Extent3d {
-                width: config.width,
-                height: config.height,
+                width,
+                height,
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
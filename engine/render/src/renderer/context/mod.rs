@@ -47,7 +47,23 @@ impl RenderContext {
             queue,
         };
 
+        Self::with_device(window, render_device)
+    }
+
+    /// Builds the context around a device created elsewhere, e.g. one
+    /// the OpenXR runtime asked for.
+    pub fn with_device(window: &Window, render_device: RenderDevice) -> Self {
         let surface = RenderSurface::new(window, &render_device);
+        Self::with_surface(render_device, surface)
+    }
+
+    /// A context without a window, for drawing into offscreen targets
+    /// only (`FrameRenderer::render_stereo`).
+    pub fn headless(render_device: RenderDevice, width: u32, height: u32, format: wgpu::TextureFormat) -> Self {
+        Self::with_surface(render_device, RenderSurface::headless(width, height, format))
+    }
+
+    fn with_surface(render_device: RenderDevice, surface: RenderSurface) -> Self {
         let depth = DepthTexture::new(&render_device.device, &surface.config);
 
         let camera_layout =
@@ -58,10 +74,11 @@ impl RenderContext {
                     entries: &[
                         wgpu::BindGroupLayoutEntry {
                             binding: 0,
-                            visibility: wgpu::ShaderStages::VERTEX,
+                            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                             ty: wgpu::BindingType::Buffer {
                                 ty: wgpu::BufferBindingType::Uniform,
-                                has_dynamic_offset: false,
+                                // one ring buffer holds every view's camera
+                                has_dynamic_offset: true,
                                 min_binding_size: None,
                             },
                             count: None,
@@ -2,7 +2,9 @@ use winit::window::Window;
 use crate::renderer::context::device::RenderDevice;
 
 pub struct RenderSurface {
-    pub surface: wgpu::Surface,
+    /// None without a window; the context then only draws into targets
+    /// it is handed.
+    pub surface: Option<wgpu::Surface>,
     pub config: wgpu::SurfaceConfiguration,
 }
 
@@ -24,7 +26,25 @@ impl RenderSurface {
 
         surface.configure(&device.device, &config);
 
-        Self { surface, config }
+        Self {
+            surface: Some(surface),
+            config,
+        }
+    }
+
+    /// A surface-less stand-in of `width` x `height` and `format`.
+    pub fn headless(width: u32, height: u32, format: wgpu::TextureFormat) -> Self {
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+        };
+
+        Self { surface: None, config }
     }
 
     pub fn resize(&mut self, width: u32, height: u32, device: &RenderDevice) {
@@ -33,6 +53,8 @@ impl RenderSurface {
         }
         self.config.width = width;
         self.config.height = height;
-        self.surface.configure(&device.device, &self.config);
+        if let Some(surface) = &self.surface {
+            surface.configure(&device.device, &self.config);
+        }
     }
 }
@@ -0,0 +1,168 @@
+struct Sky {
+    sun_direction : vec3<f32>,
+    sun_sharpness : f32,
+    horizon_color : vec3<f32>,
+    sun_glow : f32,
+    zenith_color : vec3<f32>,
+    ground_color : vec3<f32>,
+};
+
+struct Bake {
+    face : u32,
+    roughness : f32,
+    size : f32,
+    source_size : f32,
+};
+
+@group(0) @binding(0)
+var<uniform> sky : Sky;
+
+@group(0) @binding(1)
+var<uniform> bake : Bake;
+
+// the sky cubemap, for the filter passes
+@group(1) @binding(0)
+var source : texture_cube<f32>;
+
+@group(1) @binding(1)
+var source_sampler : sampler;
+
+const PI : f32 = 3.14159265;
+
+@vertex
+fn vs_main(@builtin(vertex_index) i : u32) -> @builtin(position) vec4<f32> {
+    var positions = array<vec2<f32>, 3>(
+        vec2<f32>(-1.0, -3.0),
+        vec2<f32>( 3.0,  1.0),
+        vec2<f32>(-1.0,  1.0),
+    );
+
+    return vec4<f32>(positions[i], 0.0, 1.0);
+}
+
+/// World direction through a pixel of the face being drawn.
+fn face_direction(pixel : vec2<f32>) -> vec3<f32> {
+    let uv = pixel / bake.size * 2.0 - 1.0;
+    let u = uv.x;
+    let v = uv.y;
+
+    switch (bake.face) {
+        case 0u: { return normalize(vec3<f32>(1.0, -v, -u)); }
+        case 1u: { return normalize(vec3<f32>(-1.0, -v, u)); }
+        case 2u: { return normalize(vec3<f32>(u, 1.0, v)); }
+        case 3u: { return normalize(vec3<f32>(u, -1.0, -v)); }
+        case 4u: { return normalize(vec3<f32>(u, -v, 1.0)); }
+        default: { return normalize(vec3<f32>(-u, -v, -1.0)); }
+    }
+}
+
+/// Orthonormal basis around `n`.
+fn tangent_frame(n : vec3<f32>) -> mat3x3<f32> {
+    var up = vec3<f32>(0.0, 0.0, 1.0);
+    if (abs(n.z) > 0.999) {
+        up = vec3<f32>(1.0, 0.0, 0.0);
+    }
+    let tangent = normalize(cross(up, n));
+    let bitangent = cross(n, tangent);
+    return mat3x3<f32>(tangent, bitangent, n);
+}
+
+/* ================= SKY ================= */
+
+fn sky_radiance(dir : vec3<f32>) -> vec3<f32> {
+    let t = clamp(dir.y * 0.5 + 0.5, 0.0, 1.0);
+    var color = mix(sky.horizon_color, sky.zenith_color, t);
+
+    // fade to the ground just below the horizon
+    if (dir.y < 0.0) {
+        color = mix(color, sky.ground_color, smoothstep(0.0, 0.2, -dir.y));
+    }
+
+    let sun = pow(max(dot(dir, sky.sun_direction), 0.0), sky.sun_sharpness);
+    return color + sun * sky.sun_glow;
+}
+
+@fragment
+fn fs_sky(@builtin(position) pixel : vec4<f32>) -> @location(0) vec4<f32> {
+    return vec4<f32>(sky_radiance(face_direction(pixel.xy)), 1.0);
+}
+
+/* ================= SPECULAR ================= */
+
+const SPECULAR_SAMPLES : u32 = 128u;
+
+fn hammersley(i : u32, count : u32) -> vec2<f32> {
+    return vec2<f32>(f32(i) / f32(count), f32(reverseBits(i)) * 2.3283064365386963e-10);
+}
+
+fn importance_sample_ggx(xi : vec2<f32>, a : f32) -> vec3<f32> {
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    let sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+    return vec3<f32>(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta);
+}
+
+/// GGX-filtered sky around `n`, assuming view = normal. Samples a mip
+/// matched to each sample's footprint to avoid fireflies.
+@fragment
+fn fs_prefilter(@builtin(position) pixel : vec4<f32>) -> @location(0) vec4<f32> {
+    let n = face_direction(pixel.xy);
+    if (bake.roughness <= 0.0) {
+        return vec4<f32>(textureSampleLevel(source, source_sampler, n, 0.0).rgb, 1.0);
+    }
+
+    let frame = tangent_frame(n);
+    let a = bake.roughness * bake.roughness;
+    let texel_solid_angle = 4.0 * PI / (6.0 * bake.source_size * bake.source_size);
+
+    var color = vec3<f32>(0.0);
+    var weight = 0.0;
+    for (var i = 0u; i < SPECULAR_SAMPLES; i = i + 1u) {
+        let h = frame * importance_sample_ggx(hammersley(i, SPECULAR_SAMPLES), a);
+        let l = normalize(2.0 * dot(n, h) * h - n);
+        let n_dot_l = dot(n, l);
+        if (n_dot_l > 0.0) {
+            let n_dot_h = max(dot(n, h), 0.0);
+            let d = n_dot_h * n_dot_h * (a * a - 1.0) + 1.0;
+            let ggx = a * a / (PI * d * d);
+
+            // with v = n, pdf(l) = D / 4
+            let pdf = ggx * 0.25 + 0.0001;
+            let sample_solid_angle = 1.0 / (f32(SPECULAR_SAMPLES) * pdf);
+            let mip = max(0.5 * log2(sample_solid_angle / texel_solid_angle) + 1.0, 0.0);
+
+            color = color + textureSampleLevel(source, source_sampler, l, mip).rgb * n_dot_l;
+            weight = weight + n_dot_l;
+        }
+    }
+
+    return vec4<f32>(color / max(weight, 0.0001), 1.0);
+}
+
+/* ================= IRRADIANCE ================= */
+
+const IRRADIANCE_PHI_STEPS : u32 = 64u;
+const IRRADIANCE_THETA_STEPS : u32 = 16u;
+
+/// Cosine-weighted hemisphere integral of the sky around `n`, from a
+/// small mip where one texel already averages many.
+@fragment
+fn fs_irradiance(@builtin(position) pixel : vec4<f32>) -> @location(0) vec4<f32> {
+    let n = face_direction(pixel.xy);
+    let frame = tangent_frame(n);
+    let mip = max(log2(bake.source_size) - 5.0, 0.0);
+
+    var irradiance = vec3<f32>(0.0);
+    for (var p = 0u; p < IRRADIANCE_PHI_STEPS; p = p + 1u) {
+        let phi = (f32(p) + 0.5) / f32(IRRADIANCE_PHI_STEPS) * 2.0 * PI;
+        for (var t = 0u; t < IRRADIANCE_THETA_STEPS; t = t + 1u) {
+            let theta = (f32(t) + 0.5) / f32(IRRADIANCE_THETA_STEPS) * 0.5 * PI;
+            let local = vec3<f32>(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            let radiance = textureSampleLevel(source, source_sampler, frame * local, mip).rgb;
+            irradiance = irradiance + radiance * cos(theta) * sin(theta);
+        }
+    }
+
+    let samples = f32(IRRADIANCE_PHI_STEPS * IRRADIANCE_THETA_STEPS);
+    return vec4<f32>(PI * irradiance / samples, 1.0);
+}
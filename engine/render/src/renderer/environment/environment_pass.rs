@@ -0,0 +1,98 @@
+use wgpu::*;
+
+use crate::renderer::environment::environment_pipeline::SkyEnvironment;
+use crate::renderer::environment::{
+    BakeUniform, IRRADIANCE_SIZE, SKY_SIZE, SPECULAR_MIPS, SPECULAR_SIZE,
+};
+
+/// Bakes the sky cubemap (every mip), then filters it into the specular
+/// and irradiance cubemaps. Submits its own encoder.
+pub fn bake_environment(device: &Device, queue: &Queue, environment: &SkyEnvironment) {
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("environment_encoder"),
+    });
+    let mut slot = 0;
+
+    /* ================= SKY ================= */
+
+    for (mip, faces) in environment.sky_faces.iter().enumerate() {
+        for (face, target) in faces.iter().enumerate() {
+            let bake = BakeUniform {
+                face: face as u32,
+                roughness: 0.0,
+                size: (SKY_SIZE >> mip).max(1) as f32,
+                source_size: 0.0,
+            };
+            let mut pass = begin_bake(queue, &mut encoder, environment, target, &bake, slot);
+            pass.set_pipeline(&environment.sky_pipeline);
+            pass.draw(0..3, 0..1);
+            slot += 1;
+        }
+    }
+
+    /* ================= SPECULAR ================= */
+
+    for (mip, faces) in environment.specular_faces.iter().enumerate() {
+        for (face, target) in faces.iter().enumerate() {
+            let bake = BakeUniform {
+                face: face as u32,
+                roughness: mip as f32 / (SPECULAR_MIPS - 1) as f32,
+                size: (SPECULAR_SIZE >> mip).max(1) as f32,
+                source_size: SKY_SIZE as f32,
+            };
+            let mut pass = begin_bake(queue, &mut encoder, environment, target, &bake, slot);
+            pass.set_pipeline(&environment.prefilter_pipeline);
+            pass.set_bind_group(1, &environment.bind_group, &[]);
+            pass.draw(0..3, 0..1);
+            slot += 1;
+        }
+    }
+
+    /* ================= IRRADIANCE ================= */
+
+    for (face, target) in environment.irradiance_faces.iter().enumerate() {
+        let bake = BakeUniform {
+            face: face as u32,
+            roughness: 1.0,
+            size: IRRADIANCE_SIZE as f32,
+            source_size: SKY_SIZE as f32,
+        };
+        let mut pass = begin_bake(queue, &mut encoder, environment, target, &bake, slot);
+        pass.set_pipeline(&environment.irradiance_pipeline);
+        pass.set_bind_group(1, &environment.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+        slot += 1;
+    }
+
+    queue.submit(Some(encoder.finish()));
+}
+
+/// Writes `bake` into its own slot of the pass buffer and starts a pass
+/// drawing into `target` with it bound.
+fn begin_bake<'a>(
+    queue: &Queue,
+    encoder: &'a mut CommandEncoder,
+    environment: &'a SkyEnvironment,
+    target: &'a TextureView,
+    bake: &BakeUniform,
+    slot: u64,
+) -> RenderPass<'a> {
+    let offset = slot * environment.stride;
+    queue.write_buffer(&environment.pass_buffer, offset, bytemuck::bytes_of(bake));
+
+    let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some("environment_bake_pass"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(Color::BLACK),
+                store: true,
+            },
+        })],
+        depth_stencil_attachment: None,
+    });
+
+    pass.set_bind_group(0, &environment.bake_bind_group, &[offset as u32]);
+    pass
+}
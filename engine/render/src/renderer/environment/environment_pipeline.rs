@@ -0,0 +1,311 @@
+use glam::Vec3;
+use wgpu::*;
+
+use crate::renderer::environment::environment_pass::bake_environment;
+use crate::renderer::environment::{
+    mip_count, BakeUniform, ENVIRONMENT_FORMAT, IRRADIANCE_SIZE, SKY_SIZE, SPECULAR_MIPS,
+    SPECULAR_SIZE,
+};
+use crate::renderer::resources::counters::{create_bind_group, create_buffer, create_texture};
+use crate::renderer::skybox::{Sky, SkyUniform};
+
+/*
+   Image-based lighting from the procedural sky: the sky baked into a
+   cubemap, that cubemap prefiltered into specular mip levels, and its
+   cosine-weighted convolution as an irradiance cubemap.
+
+   Everything is allocated once; the bake reruns whenever the sky or
+   the sun direction changes.
+*/
+pub struct SkyEnvironment {
+    /// A cubemap and its sampler: the sky for the skybox pass, and the
+    /// bake's source.
+    pub layout: BindGroupLayout,
+    pub bind_group: BindGroup,
+    pub sampler: Sampler,
+
+    pub sky: TextureView,
+    pub specular: TextureView,
+    pub irradiance: TextureView,
+
+    /// Render targets, `[mip][face]`.
+    pub sky_faces: Vec<Vec<TextureView>>,
+    pub specular_faces: Vec<Vec<TextureView>>,
+    pub irradiance_faces: Vec<TextureView>,
+
+    pub sky_pipeline: RenderPipeline,
+    pub prefilter_pipeline: RenderPipeline,
+    pub irradiance_pipeline: RenderPipeline,
+    pub bake_bind_group: BindGroup,
+    pub sky_buffer: Buffer,
+    /// One `BakeUniform` per pass, `stride` apart.
+    pub pass_buffer: Buffer,
+    pub stride: u64,
+
+    current: Option<(Sky, Vec3)>,
+}
+
+impl SkyEnvironment {
+    pub fn new(device: &Device) -> Self {
+        let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("environment_layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::Cube,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let bake_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("environment_bake_layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: BufferSize::new(std::mem::size_of::<BakeUniform>() as u64),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("environment_sampler"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let (sky, sky_faces) = create_cubemap(device, "environment_sky", SKY_SIZE, mip_count(SKY_SIZE));
+        let (specular, specular_faces) =
+            create_cubemap(device, "environment_specular", SPECULAR_SIZE, SPECULAR_MIPS);
+        let (irradiance, mut irradiance_faces) =
+            create_cubemap(device, "environment_irradiance", IRRADIANCE_SIZE, 1);
+
+        let bind_group = create_bind_group(
+            device,
+            &BindGroupDescriptor {
+                label: Some("environment_bind_group"),
+                layout: &layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(&sky),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Sampler(&sampler),
+                    },
+                ],
+            },
+        );
+
+        let stride = {
+            let align = device.limits().min_uniform_buffer_offset_alignment as u64;
+            let size = std::mem::size_of::<BakeUniform>() as u64;
+            size.div_ceil(align) * align
+        };
+        let passes = 6 * (sky_faces.len() + specular_faces.len() + 1) as u64;
+
+        let sky_buffer = create_buffer(
+            device,
+            &BufferDescriptor {
+                label: Some("environment_sky_buffer"),
+                size: std::mem::size_of::<SkyUniform>() as BufferAddress,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        );
+        let pass_buffer = create_buffer(
+            device,
+            &BufferDescriptor {
+                label: Some("environment_pass_buffer"),
+                size: stride * passes,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        );
+
+        let bake_bind_group = create_bind_group(
+            device,
+            &BindGroupDescriptor {
+                label: Some("environment_bake_bind_group"),
+                layout: &bake_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: sky_buffer.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Buffer(BufferBinding {
+                            buffer: &pass_buffer,
+                            offset: 0,
+                            size: BufferSize::new(std::mem::size_of::<BakeUniform>() as u64),
+                        }),
+                    },
+                ],
+            },
+        );
+
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("environment_shader"),
+            source: ShaderSource::Wgsl(include_str!("environment.wgsl").into()),
+        });
+
+        let sky_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("environment_sky_pipeline_layout"),
+            bind_group_layouts: &[&bake_layout],
+            push_constant_ranges: &[],
+        });
+        let filter_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("environment_filter_pipeline_layout"),
+            bind_group_layouts: &[&bake_layout, &layout],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            sky_pipeline: create_bake_pipeline(device, &shader, &sky_layout, "fs_sky"),
+            prefilter_pipeline: create_bake_pipeline(device, &shader, &filter_layout, "fs_prefilter"),
+            irradiance_pipeline: create_bake_pipeline(device, &shader, &filter_layout, "fs_irradiance"),
+            layout,
+            bind_group,
+            sampler,
+            sky,
+            specular,
+            irradiance,
+            sky_faces,
+            specular_faces,
+            irradiance_faces: irradiance_faces.remove(0),
+            bake_bind_group,
+            sky_buffer,
+            pass_buffer,
+            stride,
+            current: None,
+        }
+    }
+
+    /// Re-bakes the cubemaps if the sky or the sun moved since the last
+    /// bake.
+    pub fn prepare(&mut self, device: &Device, queue: &Queue, sky: &Sky, sun_direction: Vec3) {
+        if self.current == Some((*sky, sun_direction)) {
+            return;
+        }
+
+        queue.write_buffer(&self.sky_buffer, 0, bytemuck::bytes_of(&SkyUniform::new(sky, sun_direction)));
+        bake_environment(device, queue, self);
+        self.current = Some((*sky, sun_direction));
+    }
+}
+
+/// A cube texture of `mips` levels: a cube view of all of them for
+/// sampling, and per mip one 2D view per face for rendering.
+fn create_cubemap(device: &Device, label: &str, size: u32, mips: u32) -> (TextureView, Vec<Vec<TextureView>>) {
+    let texture = create_texture(
+        device,
+        &TextureDescriptor {
+            label: Some(label),
+            size: Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: mips,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: ENVIRONMENT_FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        },
+    );
+
+    let view = texture.create_view(&TextureViewDescriptor {
+        label: Some(label),
+        dimension: Some(TextureViewDimension::Cube),
+        ..Default::default()
+    });
+
+    let faces = (0..mips)
+        .map(|mip| {
+            (0..6)
+                .map(|face| {
+                    texture.create_view(&TextureViewDescriptor {
+                        label: Some(label),
+                        dimension: Some(TextureViewDimension::D2),
+                        base_mip_level: mip,
+                        mip_level_count: Some(1),
+                        base_array_layer: face,
+                        array_layer_count: Some(1),
+                        ..Default::default()
+                    })
+                })
+                .collect()
+        })
+        .collect();
+
+    (view, faces)
+}
+
+/// Fullscreen triangle into one cube face.
+fn create_bake_pipeline(
+    device: &Device,
+    shader: &ShaderModule,
+    layout: &PipelineLayout,
+    entry_point: &str,
+) -> RenderPipeline {
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some(entry_point),
+        layout: Some(layout),
+
+        vertex: VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+
+        fragment: Some(FragmentState {
+            module: shader,
+            entry_point,
+            targets: &[Some(ColorTargetState {
+                format: ENVIRONMENT_FORMAT,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            })],
+        }),
+
+        primitive: PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: MultisampleState::default(),
+        multiview: None,
+    })
+}
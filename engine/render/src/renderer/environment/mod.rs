@@ -0,0 +1,35 @@
+pub mod environment_pipeline;
+pub mod environment_pass;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::TextureFormat;
+
+pub const ENVIRONMENT_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// Face size of the sky cubemap, drawn by the skybox and filtered into
+/// the maps below. It has a full mip chain.
+pub const SKY_SIZE: u32 = 512;
+
+/// Face size of the prefiltered specular cubemap. Mip `m` holds the sky
+/// as reflected by roughness `m / (SPECULAR_MIPS - 1)`.
+pub const SPECULAR_SIZE: u32 = 128;
+pub const SPECULAR_MIPS: u32 = 6;
+
+/// Face size of the diffuse irradiance cubemap.
+pub const IRRADIANCE_SIZE: u32 = 32;
+
+pub fn mip_count(size: u32) -> u32 {
+    32 - size.leading_zeros()
+}
+
+/// Per-pass parameters of the bake: which face is drawn, at what size
+/// and for what roughness.
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct BakeUniform {
+    pub face: u32,
+    pub roughness: f32,
+    pub size: f32,
+    /// Face size of the source mip 0, for filtered importance sampling.
+    pub source_size: f32,
+}
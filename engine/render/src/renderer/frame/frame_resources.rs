@@ -0,0 +1,537 @@
+use std::ops::Range;
+
+use engine_core::{EntityId, RenderLayers};
+use glam::{Mat4, Vec3};
+
+use crate::renderer::context::RenderContext;
+use crate::renderer::frame::overlay_pass::compass_mesh;
+use crate::renderer::frame::FrameScene;
+use crate::renderer::lighting::lighting_bindings::LightingBindings;
+use crate::renderer::material::material_bindings::MaterialBindings;
+use crate::renderer::material::{BlendMode, MaterialId};
+use crate::renderer::mesh::deform_bindings::DeformBindings;
+use crate::renderer::mesh::mesh_bindings::MeshBindings;
+use crate::renderer::mesh::MeshId;
+use crate::renderer::mirror::mirror_pass::mirror_quads;
+use crate::renderer::mirror::mirror_pipeline::MirrorTargets;
+use crate::renderer::panel::panel_pass::panel_quads;
+use crate::renderer::panel::panel_pipeline::{PanelTextures, PanelVertex};
+use crate::renderer::pipeline::RenderPipelineBundle;
+use crate::renderer::resources::dynamic::{BatchRange, DynamicBuffer, GeometryBatch};
+use crate::renderer::resources::instance::Instance;
+use crate::renderer::resources::mesh::{floor_mesh, Mesh, Vertex};
+use crate::renderer::resources::uniform_ring::UniformRing;
+use crate::renderer::environment::environment_pipeline::SkyEnvironment;
+use crate::renderer::lighting::clusters::LightClusters;
+use crate::renderer::shadow::shadow_pipeline::ShadowMaps;
+use crate::renderer::shadow::{ShadowSettings, MAX_CASCADES};
+use crate::renderer::uniforms::camera::CameraUniform;
+use crate::renderer::Prop;
+use crate::ui::PanelId;
+
+/*
+   GPU resources that live as long as the renderer.
+
+   Static meshes are built once. Everything that changes per frame is
+   written with `queue.write_buffer` into buffers that only grow, once
+   per frame in `prepare`, so every view (eyes, mirror reflections)
+   draws ranges of the same upload. Cameras go in a ring with one slot
+   per view, bound with a dynamic offset.
+
+   Props are instances of library meshes. Opaque model matrices are
+   sorted by skinning, material, mesh and render layers, so each
+   distinct combination is one draw and pipelines and materials are
+   switched as rarely as possible. Transparent ones follow, back to
+   front from the eye the frame is prepared for, so they blend over
+   each other in order; neighbours that share a draw still batch.
+   Skinned instances carry the offset of their joints in the frame's
+   palette, so copies of one skinned mesh still share a draw; likewise
+   morphed instances with their deltas and weights. Instance 0 is the
+   identity, used by the world-space batches, which draw with the
+   default material.
+*/
+/// One instanced draw of a library mesh.
+struct PropDraw {
+    blend: BlendMode,
+    /// Drawn with the skinned pipelines.
+    skinned: bool,
+    material: MaterialId,
+    mesh: MeshId,
+    layers: RenderLayers,
+    instances: Range<u32>,
+}
+
+/// Blend mode, skinning, material and mesh of `FrameScene::props[i]`,
+/// and `i`.
+type PropKey = (BlendMode, bool, MaterialId, MeshId, usize);
+
+/// Opaque props by draw state, then transparent ones back to front from
+/// `eye`.
+fn sort_props(order: &mut [PropKey], props: &[Prop], eye: Vec3) {
+    order.sort_unstable_by(|a, b| {
+        let transparent = a.0.is_transparent();
+        transparent.cmp(&b.0.is_transparent()).then_with(|| {
+            if transparent {
+                let depth = |i: usize| props[i].position.distance_squared(eye);
+                depth(b.4).total_cmp(&depth(a.4))
+            } else {
+                let state = |k: &PropKey| (k.1, k.2, k.3, props[k.4].layers.0);
+                state(a).cmp(&state(b))
+            }
+        })
+    });
+}
+
+pub struct FrameResources {
+    pub panels: PanelTextures,
+    pub mirrors: MirrorTargets,
+    pub cameras: UniformRing<CameraUniform>,
+    pub materials: MaterialBindings,
+    pub meshes: MeshBindings,
+    pub deform: DeformBindings,
+    pub lighting: LightingBindings,
+    pub shadows: ShadowMaps,
+    pub environment: SkyEnvironment,
+    pub clusters: LightClusters,
+
+    floor: Mesh,
+    instances: Vec<Instance>,
+    instance_buffer: DynamicBuffer,
+    prop_order: Vec<PropKey>,
+    /// Instances of library meshes, in draw order.
+    prop_draws: Vec<PropDraw>,
+
+    /// Hand meshes, lines and mirror quads, all world space.
+    world: GeometryBatch<Vertex>,
+    lines: Vec<BatchRange>,
+    cpu_meshes: Vec<BatchRange>,
+    mirror_quads: Vec<(EntityId, BatchRange)>,
+
+    panel_quads: GeometryBatch<PanelVertex>,
+    panel_draws: Vec<(PanelId, BatchRange)>,
+
+    /// Screen-space gizmos for the desktop view.
+    overlay: GeometryBatch<Vertex>,
+    compass: Option<BatchRange>,
+}
+
+impl FrameResources {
+    pub fn new(ctx: &RenderContext) -> Self {
+        let device = &ctx.device.device;
+        let deform = DeformBindings::new(device);
+        let shadows = ShadowMaps::new(device, &ctx.camera_layout, &deform.layout, &ShadowSettings::default());
+        let environment = SkyEnvironment::new(device);
+        let clusters = LightClusters::new(device);
+        let (fv, fi) = floor_mesh();
+
+        Self {
+            panels: PanelTextures::new(device),
+            mirrors: MirrorTargets::new(device),
+            cameras: UniformRing::new(device, &ctx.camera_layout, "camera_ring", 8),
+            materials: MaterialBindings::new(device, &ctx.device.queue),
+            meshes: MeshBindings::new(),
+            deform,
+            lighting: LightingBindings::new(device, &shadows, &environment, &clusters),
+            shadows,
+            environment,
+            clusters,
+            floor: Mesh::new(device, &fv, &fi),
+            instances: Vec::new(),
+            instance_buffer: DynamicBuffer::new(
+                device,
+                "instance_buffer",
+                wgpu::BufferUsages::VERTEX,
+                std::mem::size_of::<Instance>() as u64 * 256,
+            ),
+            prop_order: Vec::new(),
+            prop_draws: Vec::new(),
+            world: GeometryBatch::new(device, "world_batch"),
+            lines: Vec::new(),
+            cpu_meshes: Vec::new(),
+            mirror_quads: Vec::new(),
+            panel_quads: GeometryBatch::new(device, "panel_quad_batch"),
+            panel_draws: Vec::new(),
+            overlay: GeometryBatch::new(device, "overlay_batch"),
+            compass: None,
+        }
+    }
+
+    /// Uploads this frame's geometry, redraws stale panels, sizes the
+    /// mirror targets for views of `size` and `format`, and makes room
+    /// for the cameras (and light clusters) of `views` views, their
+    /// reflections and the shadow cascades. Transparent props are
+    /// ordered for views from `eye`.
+    pub fn prepare(
+        &mut self,
+        ctx: &RenderContext,
+        scene: &FrameScene,
+        size: (u32, u32),
+        format: wgpu::TextureFormat,
+        views: usize,
+        eye: Vec3,
+    ) {
+        let device = &ctx.device.device;
+        let queue = &ctx.device.queue;
+
+        self.panels.prepare(device, queue, scene.panels);
+        self.materials.prepare(device, queue, scene.materials);
+        self.meshes.prepare(device, scene.mesh_library);
+        self.deform.prepare(device, queue, scene.mesh_library, scene.joints, scene.morph_weights);
+        self.shadows.prepare(device, &ctx.camera_layout, &self.deform.layout, scene.shadows);
+        self.environment.prepare(device, queue, scene.sky, scene.lighting.sun_direction);
+
+        let cluster_views = views * (1 + scene.mirrors.len());
+        self.clusters.prepare(device, queue, scene.lights, cluster_views);
+
+        self.lighting.prepare(
+            device,
+            queue,
+            scene.lighting,
+            &self.shadows,
+            &self.environment,
+            &self.clusters,
+        );
+
+        let ids: Vec<_> = scene.mirrors.iter().map(|m| m.id).collect();
+        let scaled = (
+            (size.0 as f32 * scene.mirror_scale) as u32,
+            (size.1 as f32 * scene.mirror_scale) as u32,
+        );
+        self.mirrors.prepare(device, &ids, scaled, format);
+
+        self.cameras.reset();
+        let slots = cluster_views + MAX_CASCADES;
+        self.cameras.reserve(device, &ctx.camera_layout, slots as u32);
+
+        /* ---------- world geometry ---------- */
+
+        self.world.clear();
+        self.lines.clear();
+        self.cpu_meshes.clear();
+
+        for line in scene.lines.iter().filter(|l| !l.indices.is_empty()) {
+            let range = self.world.push(line.layers, &line.vertices, &line.indices);
+            self.lines.push(range);
+        }
+
+        for mesh in scene.meshes.iter().filter(|m| !m.indices.is_empty()) {
+            let range = self.world.push(mesh.layers, &mesh.vertices, &mesh.indices);
+            self.cpu_meshes.push(range);
+        }
+
+        mirror_quads(&mut self.world, scene.mirrors, &mut self.mirror_quads);
+        self.world.upload(device, queue);
+
+        /* ---------- prop instances ---------- */
+
+        self.instances.clear();
+        self.instances.push(Instance::IDENTITY);
+        self.prop_draws.clear();
+
+        self.prop_order.clear();
+        self.prop_order.extend(scene.props.iter().enumerate().map(|(i, prop)| {
+            let material = scene.materials.resolve(prop.material);
+            let mesh = scene.mesh_library.resolve(prop.mesh);
+            // skinned meshes without a palette draw in their bind pose
+            let skinned = prop.joints.is_some() && scene.mesh_library.get(mesh).is_skinned();
+            (scene.materials.get(material).blend, skinned, material, mesh, i)
+        }));
+        sort_props(&mut self.prop_order, scene.props, eye);
+
+        for &(blend, skinned, material, mesh, i) in &self.prop_order {
+            let prop = &scene.props[i];
+            let index = self.instances.len() as u32;
+            let model = Mat4::from_scale_rotation_translation(prop.scale, prop.rotation, prop.position);
+            let mut instance = Instance::new(model, prop.color).with_joint_offset(prop.joints.unwrap_or(0));
+            if let (Some(weights), Some(deltas)) = (prop.morph_weights, self.deform.morph_offset(mesh)) {
+                let targets = scene.mesh_library.get(mesh).morph_targets.len();
+                instance = instance.with_morph(deltas, weights, targets as u32);
+            }
+            self.instances.push(instance);
+
+            let batch = (skinned, material, mesh, prop.layers);
+            match self.prop_draws.last_mut() {
+                Some(draw) if (draw.skinned, draw.material, draw.mesh, draw.layers) == batch => {
+                    draw.instances.end = index + 1
+                }
+                _ => self.prop_draws.push(PropDraw {
+                    blend,
+                    skinned,
+                    material,
+                    mesh,
+                    layers: prop.layers,
+                    instances: index..index + 1,
+                }),
+            }
+        }
+
+        self.instance_buffer.write(device, queue, bytemuck::cast_slice(&self.instances));
+
+        panel_quads(&mut self.panel_quads, scene.panels, &mut self.panel_draws);
+        self.panel_quads.upload(device, queue);
+    }
+
+    /// Uploads the desktop compass for a camera facing `yaw`.
+    pub fn prepare_compass(&mut self, ctx: &RenderContext, yaw: f32) {
+        let (vertices, indices) = compass_mesh(yaw);
+
+        self.overlay.clear();
+        self.compass = Some(self.overlay.push(RenderLayers::GIZMOS, &vertices, &indices));
+        self.overlay.upload(&ctx.device.device, &ctx.device.queue);
+    }
+
+    /* ================= DRAWING ================= */
+
+    /// Floor grid (on the default layer) and line lists on `layers`.
+    pub fn draw_lines<'a>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        pipelines: &'a RenderPipelineBundle,
+        layers: RenderLayers,
+    ) {
+        pass.set_pipeline(&pipelines.lines);
+        pass.set_bind_group(1, self.materials.bind_group(MaterialId::DEFAULT), &[]);
+        pass.set_vertex_buffer(1, self.instance_buffer.buffer.slice(..));
+
+        if layers.intersects(RenderLayers::DEFAULT) {
+            self.floor.draw(pass);
+        }
+
+        self.draw_ranges(pass, &self.lines, layers);
+    }
+
+    /// Opaque props and the CPU meshes on `layers`.
+    pub fn draw_opaque<'a>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        pipelines: &'a RenderPipelineBundle,
+        layers: RenderLayers,
+    ) {
+        self.draw_props(pass, pipelines, layers, false);
+
+        pass.set_pipeline(&pipelines.main);
+        pass.set_bind_group(1, self.materials.bind_group(MaterialId::DEFAULT), &[]);
+        self.draw_ranges(pass, &self.cpu_meshes, layers);
+    }
+
+    /// Blended and additive props on `layers`; draw after everything
+    /// opaque.
+    pub fn draw_transparent<'a>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        pipelines: &'a RenderPipelineBundle,
+        layers: RenderLayers,
+    ) {
+        self.draw_props(pass, pipelines, layers, true);
+    }
+
+    fn draw_props<'a>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        pipelines: &'a RenderPipelineBundle,
+        layers: RenderLayers,
+        transparent: bool,
+    ) {
+        pass.set_vertex_buffer(1, self.instance_buffer.buffer.slice(..));
+
+        let mut pipeline = None;
+        let mut material = None;
+
+        let draws = self
+            .prop_draws
+            .iter()
+            .filter(|d| d.blend.is_transparent() == transparent && d.layers.intersects(layers));
+
+        for draw in draws {
+            if pipeline != Some((draw.blend, draw.skinned)) {
+                pass.set_pipeline(pipelines.for_blend(draw.blend, draw.skinned));
+                pipeline = Some((draw.blend, draw.skinned));
+            }
+            if material != Some(draw.material) {
+                pass.set_bind_group(1, self.materials.bind_group(draw.material), &[]);
+                material = Some(draw.material);
+            }
+
+            self.meshes.get(draw.mesh).draw_instanced(pass, draw.instances.clone());
+        }
+    }
+
+    /// Opaque props and CPU meshes on `layers`, with the shadow
+    /// pipelines; expects the cascade's camera at group 0.
+    pub fn draw_shadow_casters<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, layers: RenderLayers) {
+        pass.set_pipeline(&self.shadows.pipeline);
+        pass.set_bind_group(1, &self.deform.bind_group, &[]);
+        pass.set_vertex_buffer(1, self.instance_buffer.buffer.slice(..));
+
+        let draws = || {
+            self.prop_draws
+                .iter()
+                .filter(|d| !d.blend.is_transparent() && d.layers.intersects(layers))
+        };
+
+        for draw in draws().filter(|d| !d.skinned) {
+            self.meshes.get(draw.mesh).draw_instanced(pass, draw.instances.clone());
+        }
+
+        self.draw_ranges(pass, &self.cpu_meshes, layers);
+
+        if !draws().any(|d| d.skinned) {
+            return;
+        }
+
+        pass.set_pipeline(&self.shadows.skinned_pipeline);
+        pass.set_vertex_buffer(1, self.instance_buffer.buffer.slice(..));
+
+        for draw in draws().filter(|d| d.skinned) {
+            self.meshes.get(draw.mesh).draw_instanced(pass, draw.instances.clone());
+        }
+    }
+
+    fn draw_ranges<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, ranges: &[BatchRange], layers: RenderLayers) {
+        self.world.bind(pass);
+
+        for range in ranges.iter().filter(|r| r.layers.intersects(layers)) {
+            self.world.draw(pass, range);
+        }
+    }
+
+    pub fn draw_mirror_quad<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, id: EntityId) {
+        if let Some((_, range)) = self.mirror_quads.iter().find(|(m, _)| *m == id) {
+            self.world.bind(pass);
+            self.world.draw(pass, range);
+        }
+    }
+
+    /// Panel quads on `layers` with the texture bound per panel; expects
+    /// the panel pipeline.
+    pub fn draw_panel_quads<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, layers: RenderLayers) {
+        self.panel_quads.bind(pass);
+
+        for (id, range) in self.panel_draws.iter().filter(|(_, r)| r.layers.intersects(layers)) {
+            let Some(target) = self.panels.target(*id) else {
+                continue;
+            };
+
+            pass.set_bind_group(1, &target.bind_group, &[]);
+            self.panel_quads.draw(pass, range);
+        }
+    }
+
+    pub fn draw_compass<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        if let Some(range) = &self.compass {
+            self.overlay.bind(pass);
+            self.overlay.draw(pass, range);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::*;
+    use crate::renderer::context::device::RenderDevice;
+    use crate::renderer::lighting::Lighting;
+    use crate::renderer::material::MaterialLibrary;
+    use crate::renderer::mesh::MeshLibrary;
+    use crate::renderer::mirror::{Mirror, MirrorDraw};
+    use crate::renderer::resources::counters::GpuCounters;
+    use crate::renderer::shadow::ShadowSettings;
+    use crate::renderer::skybox::Sky;
+
+    const WINDOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+    const EYE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    /// On wgpu's software adapter where there is one, so the test runs
+    /// without a GPU; fails rather than skipping when there is none.
+    fn headless() -> RenderContext {
+        let instance = wgpu::Instance::default();
+        let fallback = wgpu::RequestAdapterOptions {
+            force_fallback_adapter: true,
+            ..Default::default()
+        };
+        let adapter = pollster::block_on(instance.request_adapter(&fallback))
+            .or_else(|| pollster::block_on(instance.request_adapter(&Default::default())))
+            .expect("no GPU or software adapter to render with");
+        let (device, queue) = pollster::block_on(adapter.request_device(&Default::default(), None))
+            .expect("no device on the adapter");
+
+        let render_device = RenderDevice {
+            instance,
+            adapter,
+            device,
+            queue,
+        };
+        RenderContext::headless(render_device, 800, 600, WINDOW_FORMAT)
+    }
+
+    #[test]
+    fn transparent_props_sort_back_to_front_after_opaque_ones() {
+        let at = |z: f32| Prop::new(Vec3::new(0.0, 0.0, z), Vec3::ONE);
+        let props = [at(-1.0), at(-5.0), at(-3.0), at(-2.0)];
+        let (near, far) = (MaterialId(1), MaterialId(2));
+        let mut order = vec![
+            (BlendMode::Blend, false, near, MeshId::CUBE, 0),
+            (BlendMode::Blend, false, far, MeshId::CUBE, 1),
+            (BlendMode::Opaque, false, far, MeshId::CUBE, 2),
+            (BlendMode::Opaque, false, near, MeshId::CUBE, 3),
+        ];
+
+        sort_props(&mut order, &props, Vec3::ZERO);
+
+        let sorted: Vec<usize> = order.iter().map(|k| k.4).collect();
+        assert_eq!(sorted, [3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn steady_stereo_and_desktop_frames_create_nothing() {
+        let ctx = headless();
+        let mut resources = FrameResources::new(&ctx);
+
+        let materials = MaterialLibrary::new();
+        let mesh_library = MeshLibrary::new();
+        let props = [
+            Prop::new(Vec3::new(0.0, 0.5, -1.0), Vec3::ONE),
+            Prop::new(Vec3::new(1.0, 0.5, -1.0), Vec3::splat(0.5)),
+        ];
+        let mirror = Mirror::new(Vec2::new(2.0, 2.0));
+        let mirrors = [MirrorDraw {
+            id: EntityId(1),
+            transform: Mat4::from_translation(Vec3::new(0.0, 1.5, -3.0)),
+            size: mirror.size,
+            tint: mirror.tint,
+            layers: mirror.layers,
+            reflects: mirror.reflects,
+        }];
+        let scene = FrameScene {
+            props: &props,
+            materials: &materials,
+            mesh_library: &mesh_library,
+            lighting: &Lighting::default(),
+            lights: &[],
+            sky: &Sky::default(),
+            shadows: &ShadowSettings::default(),
+            lines: &[],
+            joints: &[],
+            morph_weights: &[],
+            meshes: &[],
+            panels: &[],
+            mirrors: &mirrors,
+            mirror_scale: 0.5,
+            vignette: 0.0,
+        };
+
+        // what `render_stereo` (two eyes) and then `render` upload
+        let frame = |resources: &mut FrameResources| {
+            resources.prepare(&ctx, &scene, (1440, 1584), EYE_FORMAT, 2, Vec3::Y);
+            let window = (ctx.surface.config.width, ctx.surface.config.height);
+            resources.prepare(&ctx, &scene, window, WINDOW_FORMAT, 1, Vec3::Y);
+        };
+
+        frame(&mut resources);
+        let before = GpuCounters::now();
+        frame(&mut resources);
+
+        assert_eq!(GpuCounters::now().since(&before), GpuCounters::default());
+    }
+}
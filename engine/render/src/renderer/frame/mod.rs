@@ -1,49 +1,205 @@
+pub mod frame_resources;
 pub mod render_pass;
 pub mod overlay_pass;
+pub mod view;
+
+use engine_core::RenderLayers;
+use glam::{Mat4, Vec3};
 
 use crate::renderer::context::RenderContext;
+use crate::renderer::lighting::Lighting;
+use crate::renderer::material::MaterialLibrary;
+use crate::renderer::mesh::MeshLibrary;
+use crate::renderer::mirror::mirror_pipeline::MirrorPipeline;
+use crate::renderer::mirror::MirrorDraw;
+use crate::renderer::panel::panel_pipeline::PanelPipeline;
 use crate::renderer::pipeline::RenderPipelineBundle;
+use crate::renderer::lighting::lights::LightDraw;
+use crate::renderer::shadow::ShadowSettings;
+use crate::renderer::skybox::Sky;
+use crate::renderer::resources::mesh::Vertex;
 use crate::renderer::skybox::skybox_pipeline::SkyboxPipeline;
 use crate::renderer::uniforms::camera::OrbitCamera;
+use crate::renderer::vignette::vignette_pipeline::VignettePipeline;
 use crate::renderer::Prop;
+use crate::ui::Panel;
+
+use frame_resources::FrameResources;
+use view::RenderView;
+
+/// Everything the frame draws that changes per frame.
+#[derive(Clone, Copy)]
+pub struct FrameScene<'a> {
+    pub props: &'a [Prop],
+    pub materials: &'a MaterialLibrary,
+    pub mesh_library: &'a MeshLibrary,
+    pub lighting: &'a Lighting,
+    /// Point and spot lights.
+    pub lights: &'a [LightDraw],
+    pub sky: &'a Sky,
+    pub shadows: &'a ShadowSettings,
+    /// World-space line lists (teleport arc etc.), drawn depth-tested.
+    pub lines: &'a [SceneMesh],
+    /// Joint matrices of every skinned prop, indexed from `Prop::joints`.
+    pub joints: &'a [Mat4],
+    /// Target weights of every morphed prop, indexed from
+    /// `Prop::morph_weights`.
+    pub morph_weights: &'a [f32],
+    /// Extra world-space triangle meshes built on the CPU (tracked hands).
+    pub meshes: &'a [SceneMesh],
+    /// World-space UI panels.
+    pub panels: &'a [Panel],
+    /// Mirrors picked for this frame (already capped), nearest first.
+    pub mirrors: &'a [MirrorDraw],
+    /// Reflection resolution relative to the view.
+    pub mirror_scale: f32,
+    /// Comfort vignette strength, 0 = off.
+    pub vignette: f32,
+}
+
+/// World-space geometry built on the CPU this frame, on one set of
+/// render layers.
+pub struct SceneMesh {
+    pub layers: RenderLayers,
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u16>,
+}
+
+impl SceneMesh {
+    pub fn new(layers: RenderLayers, vertices: Vec<Vertex>, indices: Vec<u16>) -> Self {
+        Self { layers, vertices, indices }
+    }
+
+    /// Line-list vertices, drawn in order.
+    pub fn lines(layers: RenderLayers, vertices: Vec<Vertex>) -> Self {
+        let indices = (0..vertices.len() as u16).collect();
+        Self { layers, vertices, indices }
+    }
+}
+
+/// The pipelines that depend on the color format of the target.
+pub struct ViewPipelines {
+    pub format: wgpu::TextureFormat,
+    pub main: RenderPipelineBundle,
+    pub skybox: SkyboxPipeline,
+    pub vignette: VignettePipeline,
+    pub panels: PanelPipeline,
+    pub mirrors: MirrorPipeline,
+}
+
+impl ViewPipelines {
+    pub fn new(ctx: &RenderContext, format: wgpu::TextureFormat, resources: &FrameResources) -> Self {
+        Self {
+            format,
+            main: RenderPipelineBundle::with_format(
+                &ctx.device,
+                format,
+                &ctx.camera_layout,
+                &resources.materials.layout,
+                &resources.lighting.layout,
+                &resources.deform.layout,
+            ),
+            skybox: SkyboxPipeline::new(
+                &ctx.device.device,
+                format,
+                &ctx.camera_layout,
+                &resources.environment.layout,
+            ),
+            vignette: VignettePipeline::new(&ctx.device.device, format),
+            panels: PanelPipeline::new(
+                &ctx.device.device,
+                format,
+                &ctx.camera_layout,
+                &resources.panels.layout,
+            ),
+            mirrors: MirrorPipeline::new(
+                &ctx.device.device,
+                format,
+                &ctx.camera_layout,
+                &resources.mirrors.layout,
+            ),
+        }
+    }
+}
 
 pub struct FrameRenderer {
-    pipelines: RenderPipelineBundle,
-    skybox: SkyboxPipeline,
+    pipelines: ViewPipelines,
+    resources: FrameResources,
+
+    // headset swapchains may not share the window's color format
+    stereo: Option<ViewPipelines>,
 }
 
 impl FrameRenderer {
     pub fn new(ctx: &RenderContext) -> Self {
-        let pipelines = RenderPipelineBundle::new(
-            &ctx.device,              // ✅ FIX: RenderDevice
-            &ctx.surface.config,
-            &ctx.camera_layout,
-        );
-
-        let skybox = SkyboxPipeline::new(
-            &ctx.device.device,
-            ctx.surface.config.format,
-        );
+        let resources = FrameResources::new(ctx);
+        let pipelines = ViewPipelines::new(ctx, ctx.surface.config.format, &resources);
 
-        Self { pipelines, skybox }
+        Self {
+            pipelines,
+            resources,
+            stereo: None,
+        }
     }
 
     pub fn render(
         &mut self,
         ctx: &mut RenderContext,
         camera: &OrbitCamera,
-        avatar_pos: glam::Vec3,
-        avatar_yaw: f32,
-        props: &[Prop],
+        layers: RenderLayers,
+        scene: &FrameScene,
     ) {
-        render_pass::render_frame(
-            ctx,
-            camera,
-            avatar_pos,
-            avatar_yaw,
-            props,
-            &self.pipelines,
-            &self.skybox,
+        let size = (ctx.surface.config.width, ctx.surface.config.height);
+        let eye = camera.view_matrix().inverse().w_axis.truncate();
+        self.resources.prepare(ctx, scene, size, self.pipelines.format, 1, eye);
+        self.resources.shadows.update(
+            &ctx.device.queue,
+            scene.shadows,
+            scene.lighting.sun_direction,
+            camera.view_matrix(),
+            camera.proj_matrix(size.0 as f32 / size.1 as f32),
         );
+        if layers.intersects(RenderLayers::GIZMOS) {
+            self.resources.prepare_compass(ctx, camera.yaw);
+        }
+
+        render_pass::render_frame(ctx, camera, layers, scene, &self.pipelines, &self.resources);
+    }
+
+    pub fn render_stereo(
+        &mut self,
+        ctx: &RenderContext,
+        format: wgpu::TextureFormat,
+        views: &[RenderView],
+        scene: &FrameScene,
+    ) {
+        if self.stereo.as_ref().map(|p| p.format) != Some(format) {
+            self.stereo = Some(ViewPipelines::new(ctx, format, &self.resources));
+        }
+
+        if let Some(view) = views.first() {
+            // transparent props are ordered once for both eyes
+            let eye = views.iter().map(|v| v.view.inverse().w_axis.truncate()).sum::<Vec3>() / views.len() as f32;
+            self.resources.prepare(ctx, scene, view.size, format, views.len(), eye);
+            self.resources.shadows.update(
+                &ctx.device.queue,
+                scene.shadows,
+                scene.lighting.sun_direction,
+                view.view,
+                view.proj,
+            );
+        }
+
+        let pipelines = self.stereo.as_ref().unwrap();
+
+        let mut encoder = ctx.device.device.create_command_encoder(
+            &wgpu::CommandEncoderDescriptor {
+                label: Some("stereo_encoder"),
+            },
+        );
+
+        render_pass::render_stereo(ctx, &mut encoder, views, scene, pipelines, &self.resources);
+
+        ctx.device.queue.submit(Some(encoder.finish()));
     }
 }
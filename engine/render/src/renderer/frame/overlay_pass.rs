@@ -1,14 +1,10 @@
 use glam::{Mat3, Vec3};
 
-use crate::renderer::resources::mesh::{Mesh, Vertex};
+use crate::renderer::frame::frame_resources::FrameResources;
+use crate::renderer::resources::mesh::Vertex;
 
-pub fn draw_compass_overlay(
-    encoder: &mut wgpu::CommandEncoder,
-    view: &wgpu::TextureView,
-    device: &wgpu::Device,
-    pipeline: &wgpu::RenderPipeline,
-    camera_yaw: f32,
-) {
+/// Screen-space axis gizmo for a camera facing `camera_yaw`.
+pub fn compass_mesh(camera_yaw: f32) -> (Vec<Vertex>, Vec<u16>) {
     // Length of axis
     let length = 0.18;
 
@@ -44,10 +40,10 @@ pub fn draw_compass_overlay(
         let p2 = origin + dir - perp;
         let p3 = origin + dir + perp;
 
-        vertices.push(Vertex { position: p0.into(), color });
-        vertices.push(Vertex { position: p1.into(), color });
-        vertices.push(Vertex { position: p2.into(), color });
-        vertices.push(Vertex { position: p3.into(), color });
+        vertices.push(Vertex::new(p0.into(), color));
+        vertices.push(Vertex::new(p1.into(), color));
+        vertices.push(Vertex::new(p2.into(), color));
+        vertices.push(Vertex::new(p3.into(), color));
 
         // Two triangles
         indices.extend_from_slice(&[
@@ -58,8 +54,16 @@ pub fn draw_compass_overlay(
         i += 4;
     }
 
-    let mesh = Mesh::new(device, &vertices, &indices);
+    (vertices, indices)
+}
 
+/// Draws the compass uploaded by `FrameResources::prepare_compass`.
+pub fn draw_compass_overlay(
+    encoder: &mut wgpu::CommandEncoder,
+    view: &wgpu::TextureView,
+    pipeline: &wgpu::RenderPipeline,
+    resources: &FrameResources,
+) {
     let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
         label: Some("compass_overlay_pass"),
         color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -74,5 +78,5 @@ pub fn draw_compass_overlay(
     });
 
     pass.set_pipeline(pipeline);
-    mesh.draw(&mut pass);
+    resources.draw_compass(&mut pass);
 }
@@ -1,25 +1,34 @@
-use glam::{Mat3, Mat4, Vec3};
-use wgpu::util::DeviceExt;
+use engine_core::RenderLayers;
+use glam::Vec3;
 
 use crate::renderer::context::RenderContext;
-use crate::renderer::pipeline::RenderPipelineBundle;
-use crate::renderer::resources::mesh::{Mesh, floor_mesh, cube_mesh};
+use crate::renderer::resources::mesh::Vertex;
 use crate::renderer::uniforms::camera::{CameraUniform, OrbitCamera};
+use crate::renderer::frame::frame_resources::FrameResources;
 use crate::renderer::frame::overlay_pass::draw_compass_overlay;
+use crate::renderer::frame::view::RenderView;
+use crate::renderer::mirror::mirror_pass::{draw_mirrors, render_reflections};
+use crate::renderer::panel::panel_pass::draw_panels;
+use crate::renderer::shadow::shadow_pass::render_shadows;
 use crate::renderer::skybox::skybox_pass::draw_skybox;
-use crate::renderer::skybox::skybox_pipeline::SkyboxPipeline;
-use crate::renderer::Prop;
+use crate::renderer::vignette::vignette_pass::draw_vignette;
+use crate::renderer::frame::{FrameScene, ViewPipelines};
 
 pub fn render_frame(
     ctx: &mut RenderContext,
     camera: &OrbitCamera,
-    avatar_pos: Vec3,
-    avatar_yaw: f32,
-    props: &[Prop],                 // ← USED AGAIN
-    pipelines: &RenderPipelineBundle,
-    skybox: &SkyboxPipeline,
+    layers: RenderLayers,
+    scene: &FrameScene,
+    pipelines: &ViewPipelines,
+    resources: &FrameResources,
 ) {
-    let frame = ctx.surface.surface.get_current_texture().unwrap();
+    let frame = ctx
+        .surface
+        .surface
+        .as_ref()
+        .expect("desktop frame rendered without a window")
+        .get_current_texture()
+        .unwrap();
     let view_tex = frame
         .texture
         .create_view(&wgpu::TextureViewDescriptor::default());
@@ -30,76 +39,103 @@ pub fn render_frame(
         },
     );
 
-    /* ================= SKYBOX PASS ================= */
+    /* ================= SHADOWS ================= */
 
-    {
-        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("skybox_pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view_tex,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                    store: true,
-                },
-            })],
-            depth_stencil_attachment: None,
-        });
-
-        draw_skybox(&mut pass, skybox);
-    }
+    render_shadows(ctx, &mut encoder, resources, scene.shadows.casters);
 
     /* ================= CAMERA ================= */
 
     let view_m = camera.view_matrix();
-    let proj_m = Mat4::perspective_rh(
-        45.0_f32.to_radians(),
+    let proj_m = camera.proj_matrix(
         ctx.surface.config.width as f32 / ctx.surface.config.height as f32,
-        0.1,
-        100.0,
     );
 
-    let cam = CameraUniform {
-        view_proj: (proj_m * view_m).to_cols_array_2d(),
+    let view = RenderView {
+        color: &view_tex,
+        depth: &ctx.depth.view,
+        size: (ctx.surface.config.width, ctx.surface.config.height),
+        layers,
+        view: view_m,
+        proj: proj_m,
     };
 
-    let camera_buffer = ctx.device.device.create_buffer_init(
-        &wgpu::util::BufferInitDescriptor {
-            label: Some("camera_buffer"),
-            contents: bytemuck::bytes_of(&cam),
-            usage: wgpu::BufferUsages::UNIFORM,
-        },
-    );
+    render_view(ctx, &mut encoder, &view, scene, pipelines, resources);
 
-    let camera_bind_group = ctx.device.device.create_bind_group(
-        &wgpu::BindGroupDescriptor {
-            layout: &ctx.camera_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
-            label: None,
-        },
-    );
+    /* ================= COMPASS ================= */
 
-    /* ================= FLOOR ================= */
+    if layers.intersects(RenderLayers::GIZMOS) {
+        draw_compass_overlay(&mut encoder, &view_tex, &pipelines.main.overlay, resources);
+    }
 
-    let (fv, fi) = floor_mesh();
-    let floor = Mesh::new(&ctx.device.device, &fv, &fi);
+    ctx.device.queue.submit(Some(encoder.finish()));
+    frame.present();
+}
 
-    /* ================= AVATAR PARTS ================= */
+/// Renders the same world once per view (one per eye) into caller-owned
+/// targets. The caller submits nothing; all passes go into `encoder`.
+pub fn render_stereo(
+    ctx: &RenderContext,
+    encoder: &mut wgpu::CommandEncoder,
+    views: &[RenderView],
+    scene: &FrameScene,
+    pipelines: &ViewPipelines,
+    resources: &FrameResources,
+) {
+    render_shadows(ctx, encoder, resources, scene.shadows.casters);
 
-    let mut avatar_meshes = Vec::new();
+    for view in views {
+        render_view(ctx, encoder, view, scene, pipelines, resources);
+    }
+}
 
-    for prop in props {
-        let (mut verts, inds) = cube_mesh([0.8, 0.8, 0.8]);
+/// Skybox, world (with UI panels and mirrors) and vignette passes for a
+/// single view, drawing only what is on the view's layers. Mirrors render
+/// their reflections first, through this same function with a reflected
+/// camera. Nothing is allocated here; see `FrameResources::prepare`.
+pub fn render_view(
+    ctx: &RenderContext,
+    encoder: &mut wgpu::CommandEncoder,
+    view: &RenderView,
+    scene: &FrameScene,
+    pipelines: &ViewPipelines,
+    resources: &FrameResources,
+) {
+    /* ================= MIRROR REFLECTIONS ================= */
+
+    let reflections = render_reflections(ctx, encoder, view, scene, pipelines, resources);
+
+    /* ================= CAMERA ================= */
 
-        for v in &mut verts {
-            let p = Vec3::from(v.position) * prop.scale + prop.position;
-            v.position = p.into();
-        }
+    let to_world = view.view.inverse();
+    let (eye, forward) = (to_world.w_axis.truncate(), -to_world.z_axis.truncate().normalize());
+    let clusters = resources.clusters.push(&ctx.device.queue, view.view_proj(), eye, forward);
+
+    let cam = CameraUniform::new(view.view_proj(), eye).with_clusters(forward, clusters);
+    let camera_offset = resources.cameras.push(&ctx.device.queue, &cam);
+
+    /* ================= SKYBOX PASS ================= */
+
+    {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("skybox_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: view.color,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
 
-        avatar_meshes.push(Mesh::new(&ctx.device.device, &verts, &inds));
+        draw_skybox(
+            &mut pass,
+            &pipelines.skybox,
+            &resources.cameras.bind_group,
+            camera_offset,
+            &resources.environment,
+        );
     }
 
     /* ================= WORLD PASS ================= */
@@ -108,7 +144,7 @@ pub fn render_frame(
         let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("world_pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view_tex,
+                view: view.color,
                 resolve_target: None,
                 ops: wgpu::Operations {
                     load: wgpu::LoadOp::Load,
@@ -116,7 +152,7 @@ pub fn render_frame(
                 },
             })],
             depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                view: &ctx.depth.view,
+                view: view.depth,
                 depth_ops: Some(wgpu::Operations {
                     load: wgpu::LoadOp::Clear(1.0),
                     store: true,
@@ -125,26 +161,33 @@ pub fn render_frame(
             }),
         });
 
-        pass.set_pipeline(&pipelines.main);
-        pass.set_bind_group(0, &camera_bind_group, &[]);
+        pass.set_bind_group(0, &resources.cameras.bind_group, &[camera_offset]);
+        pass.set_bind_group(2, &resources.lighting.bind_group, &[]);
+        pass.set_bind_group(3, &resources.deform.bind_group, &[]);
 
-        floor.draw(&mut pass);
+        resources.draw_lines(&mut pass, &pipelines.main, view.layers);
+        resources.draw_opaque(&mut pass, &pipelines.main, view.layers);
 
-        for mesh in &avatar_meshes {
-            mesh.draw(&mut pass);
-        }
+        draw_mirrors(&mut pass, &pipelines.mirrors, resources, &reflections);
+        draw_panels(&mut pass, &pipelines.panels, resources, view.layers);
+
+        resources.draw_transparent(&mut pass, &pipelines.main, view.layers);
     }
 
-    /* ================= COMPASS ================= */
+    /* ================= VIGNETTE ================= */
 
-    draw_compass_overlay(
-        &mut encoder,
-        &view_tex,
-        &ctx.device.device,
-        &pipelines.overlay,
-        camera.yaw,
-    );
+    draw_vignette(encoder, &ctx.device.queue, view.color, &pipelines.vignette, scene.vignette);
+}
 
-    ctx.device.queue.submit(Some(encoder.finish()));
-    frame.present();
+/// Line-list vertices for a polyline (each segment as its own pair).
+pub fn polyline(points: &[Vec3], color: [f32; 3]) -> Vec<Vertex> {
+    points
+        .windows(2)
+        .flat_map(|w| {
+            [
+                Vertex::new(w[0].into(), color),
+                Vertex::new(w[1].into(), color),
+            ]
+        })
+        .collect()
 }
@@ -0,0 +1,38 @@
+use engine_core::RenderLayers;
+use glam::Mat4;
+
+/// One camera rendering into one color/depth target pair.
+/// The desktop window is a single view, a headset is one per eye.
+pub struct RenderView<'a> {
+    pub color: &'a wgpu::TextureView,
+    pub depth: &'a wgpu::TextureView,
+    /// Target size in pixels.
+    pub size: (u32, u32),
+    /// Only things on these layers are drawn.
+    pub layers: RenderLayers,
+    pub view: Mat4,
+    pub proj: Mat4,
+}
+
+impl RenderView<'_> {
+    pub fn view_proj(&self) -> Mat4 {
+        self.proj * self.view
+    }
+}
+
+/// Asymmetric perspective from per-eye field-of-view angles (radians,
+/// left/down negative), right-handed with 0..1 depth like `perspective_rh`.
+pub fn fov_projection(left: f32, right: f32, up: f32, down: f32, near: f32, far: f32) -> Mat4 {
+    let (tan_l, tan_r) = (left.tan(), right.tan());
+    let (tan_u, tan_d) = (up.tan(), down.tan());
+
+    let w = tan_r - tan_l;
+    let h = tan_u - tan_d;
+
+    Mat4::from_cols_array(&[
+        2.0 / w, 0.0, 0.0, 0.0,
+        0.0, 2.0 / h, 0.0, 0.0,
+        (tan_r + tan_l) / w, (tan_u + tan_d) / h, far / (near - far), -1.0,
+        0.0, 0.0, near * far / (near - far), 0.0,
+    ])
+}
@@ -0,0 +1,306 @@
+use std::cell::Cell;
+
+use glam::{Mat4, Vec2, Vec3, Vec4Swizzles};
+use wgpu::*;
+
+use crate::renderer::lighting::lights::{LightDraw, LightUniform};
+use crate::renderer::resources::counters::create_buffer;
+
+/* The froxel grid. The world shader has the same constants. */
+
+pub const CLUSTERS_X: usize = 16;
+pub const CLUSTERS_Y: usize = 9;
+pub const CLUSTERS_Z: usize = 24;
+pub const CLUSTER_COUNT: usize = CLUSTERS_X * CLUSTERS_Y * CLUSTERS_Z;
+
+/// Depth slices are spaced logarithmically between these distances;
+/// anything further shares the last slice.
+pub const CLUSTER_NEAR: f32 = 0.1;
+pub const CLUSTER_FAR: f32 = 60.0;
+
+/// Lights uploaded per frame; the rest are dropped.
+pub const MAX_LIGHTS: usize = 1024;
+
+/// Light indices per view, shared by all its clusters. Clusters past
+/// the end lose their lights.
+pub const MAX_CLUSTER_INDICES: usize = 32 * 1024;
+
+/// `u32`s per view: an (offset, count) pair per cluster, then the
+/// light indices they point into.
+const VIEW_WORDS: usize = CLUSTER_COUNT * 2 + MAX_CLUSTER_INDICES;
+
+/*
+   Clustered forward lighting: the view frustum is cut into froxels and
+   each gets the list of point/spot lights whose range reaches it, so a
+   fragment only loops over the lights near it.
+
+   Assignment runs on the CPU, once per view (eyes and mirror
+   reflections each have their own frustum), into its own region of
+   `clusters`; the camera uniform tells the shader which region.
+*/
+pub struct LightClusters {
+    pub lights: Buffer,
+    pub clusters: Buffer,
+    /// Bumped when `clusters` is reallocated.
+    pub generation: u64,
+    frame_lights: Vec<LightDraw>,
+    capacity: usize,
+    cursor: Cell<usize>,
+}
+
+impl LightClusters {
+    pub fn new(device: &Device) -> Self {
+        let lights = create_buffer(
+            device,
+            &BufferDescriptor {
+                label: Some("light_buffer"),
+                size: (std::mem::size_of::<LightUniform>() * MAX_LIGHTS) as BufferAddress,
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        );
+
+        Self {
+            lights,
+            clusters: Self::create_clusters(device, 1),
+            generation: 0,
+            frame_lights: Vec::new(),
+            capacity: 1,
+            cursor: Cell::new(0),
+        }
+    }
+
+    fn create_clusters(device: &Device, views: usize) -> Buffer {
+        create_buffer(
+            device,
+            &BufferDescriptor {
+                label: Some("light_cluster_buffer"),
+                size: (VIEW_WORDS * 4 * views) as BufferAddress,
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        )
+    }
+
+    /// Uploads this frame's lights and makes room for `views` cluster
+    /// grids.
+    pub fn prepare(&mut self, device: &Device, queue: &Queue, lights: &[LightDraw], views: usize) {
+        self.frame_lights.clear();
+        self.frame_lights.extend(lights.iter().take(MAX_LIGHTS).copied());
+
+        let uniforms: Vec<LightUniform> = self.frame_lights.iter().map(LightUniform::new).collect();
+        if !uniforms.is_empty() {
+            queue.write_buffer(&self.lights, 0, bytemuck::cast_slice(&uniforms));
+        }
+
+        self.cursor.set(0);
+        if views > self.capacity {
+            self.capacity = views.next_power_of_two();
+            self.clusters = Self::create_clusters(device, self.capacity);
+            self.generation += 1;
+        }
+    }
+
+    /// Assigns the frame's lights to the froxels of a view and uploads
+    /// them. Returns where in `clusters` the view's grid starts, in
+    /// `u32`s.
+    pub fn push(&self, queue: &Queue, view_proj: Mat4, eye: Vec3, forward: Vec3) -> u32 {
+        let slot = self.cursor.get();
+        assert!(slot < self.capacity, "light cluster views not reserved");
+        self.cursor.set(slot + 1);
+
+        let words = assign_lights(&self.frame_lights, view_proj, eye, forward);
+        let offset = slot * VIEW_WORDS;
+        queue.write_buffer(&self.clusters, (offset * 4) as BufferAddress, bytemuck::cast_slice(&words));
+
+        offset as u32
+    }
+}
+
+/// Depth slice of a point `depth` in front of the eye.
+pub fn depth_slice(depth: f32) -> usize {
+    if depth <= CLUSTER_NEAR {
+        return 0;
+    }
+
+    let t = (depth / CLUSTER_NEAR).ln() / (CLUSTER_FAR / CLUSTER_NEAR).ln();
+    ((t * CLUSTERS_Z as f32) as usize).min(CLUSTERS_Z - 1)
+}
+
+/// The cluster table of one view: (offset, count) per cluster, relative
+/// to the start of the view's region, followed by the light indices.
+/// Each light goes into every cluster its bounding sphere may touch.
+fn assign_lights(lights: &[LightDraw], view_proj: Mat4, eye: Vec3, forward: Vec3) -> Vec<u32> {
+    // per light: (x, y, z) cluster ranges
+    let boxes: Vec<Option<[(usize, usize); 3]>> = lights
+        .iter()
+        .map(|light| cluster_box(light, view_proj, eye, forward))
+        .collect();
+
+    let index = |x: usize, y: usize, z: usize| (z * CLUSTERS_Y + y) * CLUSTERS_X + x;
+
+    let mut counts = vec![0u32; CLUSTER_COUNT];
+    for [xs, ys, zs] in boxes.iter().flatten() {
+        for z in zs.0..=zs.1 {
+            for y in ys.0..=ys.1 {
+                for x in xs.0..=xs.1 {
+                    counts[index(x, y, z)] += 1;
+                }
+            }
+        }
+    }
+
+    let mut words = vec![0u32; CLUSTER_COUNT * 2];
+    let mut next = CLUSTER_COUNT * 2;
+    for (cluster, &count) in counts.iter().enumerate() {
+        let fits = (count as usize).min(VIEW_WORDS - next);
+        words[cluster * 2] = next as u32;
+        words[cluster * 2 + 1] = fits as u32;
+        next += fits;
+    }
+    words.resize(next, 0);
+
+    let mut filled = vec![0u32; CLUSTER_COUNT];
+    for (light, bounds) in boxes.iter().enumerate() {
+        let Some([xs, ys, zs]) = bounds else { continue };
+        for z in zs.0..=zs.1 {
+            for y in ys.0..=ys.1 {
+                for x in xs.0..=xs.1 {
+                    let cluster = index(x, y, z);
+                    if filled[cluster] < words[cluster * 2 + 1] {
+                        let at = words[cluster * 2] + filled[cluster];
+                        words[at as usize] = light as u32;
+                        filled[cluster] += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    words
+}
+
+/// Cluster ranges covered by a light's range sphere, or `None` when it
+/// is entirely out of view.
+fn cluster_box(light: &LightDraw, view_proj: Mat4, eye: Vec3, forward: Vec3) -> Option<[(usize, usize); 3]> {
+    let depth = (light.position - eye).dot(forward);
+    if depth + light.range < 0.0 {
+        return None;
+    }
+    let zs = (depth_slice(depth - light.range), depth_slice(depth + light.range));
+
+    // screen bounds of the sphere's box; the whole screen when the box
+    // reaches behind the eye
+    let mut min = Vec2::splat(-1.0);
+    let mut max = Vec2::splat(1.0);
+
+    let clip: Vec<_> = (0..8)
+        .map(|i| {
+            let sign = Vec3::new(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            view_proj * (light.position + sign * light.range).extend(1.0)
+        })
+        .collect();
+
+    if clip.iter().all(|c| c.w > 1e-4) {
+        let ndc: Vec<_> = clip.iter().map(|c| c.xy() / c.w).collect();
+        min = ndc.iter().fold(Vec2::splat(f32::MAX), |a, p| a.min(*p));
+        max = ndc.iter().fold(Vec2::splat(f32::MIN), |a, p| a.max(*p));
+
+        if max.x < -1.0 || max.y < -1.0 || min.x > 1.0 || min.y > 1.0 {
+            return None;
+        }
+    }
+
+    let cell = |v: f32, n: usize| (((v * 0.5 + 0.5) * n as f32).max(0.0) as usize).min(n - 1);
+    let xs = (cell(min.x, CLUSTERS_X), cell(max.x, CLUSTERS_X));
+    let ys = (cell(min.y, CLUSTERS_Y), cell(max.y, CLUSTERS_Y));
+
+    Some([xs, ys, zs])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(position: Vec3, range: f32) -> LightDraw {
+        LightDraw {
+            position,
+            range,
+            color: [1.0; 3],
+            intensity: 1.0,
+            spot: None,
+        }
+    }
+
+    /// Looking down -Z from the origin, 90° wide.
+    fn view() -> (Mat4, Vec3, Vec3) {
+        let view = Mat4::look_to_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y);
+        let proj = Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 16.0 / 9.0, 0.05, 100.0);
+        (proj * view, Vec3::ZERO, Vec3::NEG_Z)
+    }
+
+    fn lights_of(words: &[u32], cluster: usize) -> &[u32] {
+        let (offset, count) = (words[cluster * 2] as usize, words[cluster * 2 + 1] as usize);
+        &words[offset..offset + count]
+    }
+
+    #[test]
+    fn depth_slices_are_logarithmic_and_clamped() {
+        assert_eq!(depth_slice(-1.0), 0);
+        assert_eq!(depth_slice(CLUSTER_NEAR), 0);
+        assert_eq!(depth_slice(CLUSTER_FAR * 0.999), CLUSTERS_Z - 1);
+        assert_eq!(depth_slice(CLUSTER_FAR * 10.0), CLUSTERS_Z - 1);
+
+        // the geometric middle of near and far starts the middle slice
+        let middle = (CLUSTER_NEAR * CLUSTER_FAR).sqrt();
+        assert_eq!(depth_slice(middle * 1.001), CLUSTERS_Z / 2);
+        assert_eq!(depth_slice(middle * 0.999), CLUSTERS_Z / 2 - 1);
+
+        let slices: Vec<usize> = (1..200).map(|i| depth_slice(i as f32 * 0.3)).collect();
+        assert!(slices.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn lights_land_in_the_clusters_their_range_covers() {
+        let (view_proj, eye, forward) = view();
+        let lights = [
+            point(Vec3::new(0.0, 0.0, -5.0), 0.5),
+            point(Vec3::new(0.0, 0.0, 5.0), 1.0),
+            point(Vec3::new(0.0, 0.0, -5.2), 0.5),
+        ];
+
+        let words = assign_lights(&lights, view_proj, eye, forward);
+
+        // the table of (offset, count) pairs, then the indices it points into
+        let total: u32 = (0..CLUSTER_COUNT).map(|c| words[c * 2 + 1]).sum();
+        assert_eq!(words.len(), CLUSTER_COUNT * 2 + total as usize);
+
+        let centre = |depth: f32| (depth_slice(depth) * CLUSTERS_Y + CLUSTERS_Y / 2) * CLUSTERS_X + CLUSTERS_X / 2;
+        assert_eq!(lights_of(&words, centre(5.0)), [0, 2]);
+        assert_eq!(lights_of(&words, centre(1.0)), [] as [u32; 0]);
+
+        // the light behind the eye is nowhere
+        assert!((0..CLUSTER_COUNT).all(|c| !lights_of(&words, c).contains(&1)));
+
+        // a corner cluster far from both
+        assert!(lights_of(&words, 0).is_empty());
+    }
+
+    #[test]
+    fn clusters_past_the_index_budget_lose_their_lights() {
+        let (view_proj, eye, forward) = view();
+        // lights covering the whole frustum overflow the shared index list
+        let lights: Vec<LightDraw> = (0..10).map(|_| point(Vec3::ZERO, 100.0)).collect();
+        assert!(lights.len() * CLUSTER_COUNT > MAX_CLUSTER_INDICES);
+
+        let words = assign_lights(&lights, view_proj, eye, forward);
+
+        assert_eq!(words.len(), VIEW_WORDS);
+        assert_eq!(lights_of(&words, 0), (0..10).collect::<Vec<u32>>());
+        assert!(lights_of(&words, CLUSTER_COUNT - 1).is_empty());
+    }
+}
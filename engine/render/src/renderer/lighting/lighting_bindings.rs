@@ -0,0 +1,209 @@
+use wgpu::*;
+
+use crate::renderer::environment::environment_pipeline::SkyEnvironment;
+use crate::renderer::lighting::clusters::LightClusters;
+use crate::renderer::lighting::{Lighting, LightingUniform};
+use crate::renderer::resources::counters::{create_bind_group, create_buffer};
+use crate::renderer::shadow::shadow_pipeline::ShadowMaps;
+
+/// Group 2 of the world pipeline: the scene lighting, the sun's shadow
+/// map, the sky's environment maps and the clustered point/spot lights,
+/// shared by every view. The lighting uniform is rewritten only when the
+/// lighting changes, the bind group only when the shadow map or the
+/// cluster buffer is recreated.
+pub struct LightingBindings {
+    pub layout: BindGroupLayout,
+    pub bind_group: BindGroup,
+    buffer: Buffer,
+    current: Option<Lighting>,
+    /// Of the shadow map and the cluster buffer.
+    generations: (u64, u64),
+}
+
+impl LightingBindings {
+    pub fn new(
+        device: &Device,
+        shadows: &ShadowMaps,
+        environment: &SkyEnvironment,
+        clusters: &LightClusters,
+    ) -> Self {
+        let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("lighting_layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Depth,
+                        view_dimension: TextureViewDimension::D2Array,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Comparison),
+                    count: None,
+                },
+                cube_entry(4),
+                cube_entry(5),
+                BindGroupLayoutEntry {
+                    binding: 6,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+                storage_entry(7),
+                storage_entry(8),
+            ],
+        });
+
+        let buffer = create_buffer(
+            device,
+            &BufferDescriptor {
+                label: Some("lighting_buffer"),
+                size: std::mem::size_of::<LightingUniform>() as BufferAddress,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        );
+
+        let bind_group = Self::create_bind_group(device, &layout, &buffer, shadows, environment, clusters);
+
+        Self {
+            layout,
+            bind_group,
+            buffer,
+            current: None,
+            generations: (shadows.generation, clusters.generation),
+        }
+    }
+
+    fn create_bind_group(
+        device: &Device,
+        layout: &BindGroupLayout,
+        buffer: &Buffer,
+        shadows: &ShadowMaps,
+        environment: &SkyEnvironment,
+        clusters: &LightClusters,
+    ) -> BindGroup {
+        create_bind_group(
+            device,
+            &BindGroupDescriptor {
+                label: Some("lighting_bind_group"),
+                layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: shadows.buffer.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: BindingResource::TextureView(&shadows.view),
+                    },
+                    BindGroupEntry {
+                        binding: 3,
+                        resource: BindingResource::Sampler(&shadows.sampler),
+                    },
+                    BindGroupEntry {
+                        binding: 4,
+                        resource: BindingResource::TextureView(&environment.specular),
+                    },
+                    BindGroupEntry {
+                        binding: 5,
+                        resource: BindingResource::TextureView(&environment.irradiance),
+                    },
+                    BindGroupEntry {
+                        binding: 6,
+                        resource: BindingResource::Sampler(&environment.sampler),
+                    },
+                    BindGroupEntry {
+                        binding: 7,
+                        resource: clusters.lights.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 8,
+                        resource: clusters.clusters.as_entire_binding(),
+                    },
+                ],
+            },
+        )
+    }
+
+    pub fn prepare(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        lighting: &Lighting,
+        shadows: &ShadowMaps,
+        environment: &SkyEnvironment,
+        clusters: &LightClusters,
+    ) {
+        let generations = (shadows.generation, clusters.generation);
+        if self.generations != generations {
+            self.bind_group =
+                Self::create_bind_group(device, &self.layout, &self.buffer, shadows, environment, clusters);
+            self.generations = generations;
+        }
+
+        if self.current.as_ref() == Some(lighting) {
+            return;
+        }
+
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&LightingUniform::new(lighting)));
+        self.current = Some(*lighting);
+    }
+}
+
+/// A filterable cubemap seen by the fragment stage.
+fn cube_entry(binding: u32) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility: ShaderStages::FRAGMENT,
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: true },
+            view_dimension: TextureViewDimension::Cube,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+/// A read-only storage buffer seen by the fragment stage.
+fn storage_entry(binding: u32) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility: ShaderStages::FRAGMENT,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
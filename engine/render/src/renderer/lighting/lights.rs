@@ -0,0 +1,123 @@
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec3};
+
+/// Omnidirectional light component at the entity's position.
+#[derive(Clone, Copy, Debug)]
+pub struct PointLight {
+    /// Linear RGB.
+    pub color: [f32; 3],
+    /// Brightness of a white surface facing the light from one metre.
+    pub intensity: f32,
+    /// The light fades to nothing at this distance.
+    pub range: f32,
+}
+
+impl PointLight {
+    pub fn new(color: [f32; 3], intensity: f32, range: f32) -> Self {
+        Self {
+            color,
+            intensity,
+            range,
+        }
+    }
+}
+
+/// Cone light component shining down the entity's local -Z.
+#[derive(Clone, Copy, Debug)]
+pub struct SpotLight {
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub range: f32,
+    /// Half-angles in radians: full brightness inside `inner_angle`,
+    /// fading to dark at `outer_angle`.
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+}
+
+impl SpotLight {
+    pub fn new(color: [f32; 3], intensity: f32, range: f32, outer_angle: f32) -> Self {
+        Self {
+            color,
+            intensity,
+            range,
+            inner_angle: outer_angle * 0.8,
+            outer_angle,
+        }
+    }
+}
+
+/// Sun-like light component shining down the entity's local -Z. The
+/// renderer has one sun: the first of these in the world replaces the
+/// direction, colour and intensity of `Renderer::lighting`.
+#[derive(Clone, Copy, Debug)]
+pub struct DirectionalLight {
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+impl DirectionalLight {
+    pub fn new(color: [f32; 3], intensity: f32) -> Self {
+        Self { color, intensity }
+    }
+}
+
+/// A point or spot light in world space, for this frame.
+#[derive(Clone, Copy, Debug)]
+pub struct LightDraw {
+    pub position: Vec3,
+    pub range: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    /// `None` for point lights: direction, cos(inner), cos(outer).
+    pub spot: Option<(Vec3, f32, f32)>,
+}
+
+impl LightDraw {
+    pub fn point(to_world: Mat4, light: &PointLight) -> Self {
+        Self {
+            position: to_world.transform_point3(Vec3::ZERO),
+            range: light.range,
+            color: light.color,
+            intensity: light.intensity,
+            spot: None,
+        }
+    }
+
+    pub fn spot(to_world: Mat4, light: &SpotLight) -> Self {
+        let direction = to_world.transform_vector3(Vec3::NEG_Z).normalize_or_zero();
+        Self {
+            position: to_world.transform_point3(Vec3::ZERO),
+            range: light.range,
+            color: light.color,
+            intensity: light.intensity,
+            spot: Some((direction, light.inner_angle.cos(), light.outer_angle.cos())),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct LightUniform {
+    pub position: [f32; 3],
+    pub range: f32,
+    /// Color times intensity.
+    pub radiance: [f32; 3],
+    /// Below -1 for point lights.
+    pub cos_outer: f32,
+    pub direction: [f32; 3],
+    pub cos_inner: f32,
+}
+
+impl LightUniform {
+    pub fn new(light: &LightDraw) -> Self {
+        let (direction, cos_inner, cos_outer) = light.spot.unwrap_or((Vec3::NEG_Z, -2.0, -2.0));
+        Self {
+            position: light.position.to_array(),
+            range: light.range.max(0.01),
+            radiance: (Vec3::from(light.color) * light.intensity).to_array(),
+            cos_outer,
+            direction: direction.to_array(),
+            cos_inner: cos_inner.max(cos_outer + 1e-4),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+pub mod clusters;
+pub mod lighting_bindings;
+pub mod lights;
+
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
+
+/// Scene-wide light: one directional sun plus image-based light from the
+/// sky (see `Sky`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lighting {
+    /// Direction towards the sun (normalized on upload). The skybox draws
+    /// its sun here.
+    pub sun_direction: Vec3,
+    /// Linear RGB.
+    pub sun_color: [f32; 3],
+    /// Brightness of a white surface facing the sun.
+    pub sun_intensity: f32,
+    /// Scales the light reflected from the sky.
+    pub ambient_intensity: f32,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self {
+            sun_direction: Vec3::new(0.3, 0.7, 0.6),
+            sun_color: [1.0, 0.96, 0.88],
+            sun_intensity: 1.0,
+            ambient_intensity: 0.6,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct LightingUniform {
+    pub sun_direction: [f32; 3],
+    pub sun_intensity: f32,
+    pub sun_color: [f32; 3],
+    pub ambient_intensity: f32,
+}
+
+impl LightingUniform {
+    pub fn new(lighting: &Lighting) -> Self {
+        Self {
+            sun_direction: lighting.sun_direction.normalize_or_zero().to_array(),
+            sun_intensity: lighting.sun_intensity,
+            sun_color: lighting.sun_color,
+            ambient_intensity: lighting.ambient_intensity,
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::*;
+
+use crate::renderer::material::{Material, MaterialId, MaterialLibrary, TextureId};
+use crate::renderer::resources::counters::{create_bind_group, create_buffer, create_texture};
+use crate::renderer::texture::mips::generate_mips;
+use crate::renderer::texture::{SamplerSettings, TextureData};
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct MaterialUniform {
+    pub base_color: [f32; 4],
+    pub emissive: [f32; 3],
+    pub roughness: f32,
+    pub metallic: f32,
+    pub has_base_color_texture: u32,
+    /// 1 for `ToonShading`; the fields below are then used.
+    pub toon: u32,
+    pub shading_shift: f32,
+    pub shade_color: [f32; 3],
+    pub shading_toony: f32,
+    pub rim_color: [f32; 3],
+    pub rim_fresnel_power: f32,
+}
+
+impl MaterialUniform {
+    pub fn new(material: &Material) -> Self {
+        let toon = material.toon.unwrap_or_default();
+
+        Self {
+            base_color: material.base_color,
+            emissive: material.emissive,
+            roughness: material.roughness,
+            metallic: material.metallic,
+            has_base_color_texture: material.base_color_texture.is_some() as u32,
+            toon: material.toon.is_some() as u32,
+            shading_shift: toon.shading_shift,
+            shade_color: toon.shade_color,
+            shading_toony: toon.shading_toony,
+            rim_color: toon.rim_color,
+            rim_fresnel_power: toon.rim_fresnel_power,
+        }
+    }
+}
+
+struct MaterialEntry {
+    buffer: Buffer,
+    bind_group: BindGroup,
+    version: u64,
+    texture: Option<TextureId>,
+}
+
+/// GPU copy of a `MaterialLibrary`: one uniform buffer and bind group
+/// (group 1 of the world pipeline) per material. Buffers are rewritten
+/// when a material's version changes; bind groups are only rebuilt when
+/// its texture does. Textures are uploaded once, with mips, and share
+/// one sampler per `SamplerSettings`.
+pub struct MaterialBindings {
+    pub layout: BindGroupLayout,
+    samplers: HashMap<SamplerSettings, Sampler>,
+    white: TextureView,
+    textures: Vec<(TextureView, SamplerSettings)>,
+    entries: Vec<MaterialEntry>,
+}
+
+impl MaterialBindings {
+    pub fn new(device: &Device, queue: &Queue) -> Self {
+        let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("material_layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX_FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let defaults = SamplerSettings::default();
+        let samplers = HashMap::from([(defaults, device.create_sampler(&defaults.descriptor()))]);
+
+        let white = upload_texture(device, queue, &TextureData::new(1, 1, vec![255; 4], true));
+
+        Self {
+            layout,
+            samplers,
+            white,
+            textures: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Uploads new textures and new or changed materials.
+    pub fn prepare(&mut self, device: &Device, queue: &Queue, library: &MaterialLibrary) {
+        for texture in &library.textures()[self.textures.len()..] {
+            self.samplers
+                .entry(texture.sampler)
+                .or_insert_with(|| device.create_sampler(&texture.sampler.descriptor()));
+            self.textures.push((upload_texture(device, queue, texture), texture.sampler));
+        }
+
+        for i in 0..library.len() {
+            let id = MaterialId(i as u32);
+            let material = library.get(id);
+            let version = library.version(id);
+
+            if i == self.entries.len() {
+                let entry = self.create_entry(device, material, version);
+                self.entries.push(entry);
+                continue;
+            }
+
+            if self.entries[i].version == version {
+                continue;
+            }
+
+            if self.entries[i].texture != material.base_color_texture {
+                self.entries[i] = self.create_entry(device, material, version);
+            } else {
+                let entry = &mut self.entries[i];
+                queue.write_buffer(&entry.buffer, 0, bytemuck::bytes_of(&MaterialUniform::new(material)));
+                entry.version = version;
+            }
+        }
+    }
+
+    fn create_entry(&self, device: &Device, material: &Material, version: u64) -> MaterialEntry {
+        let buffer = create_buffer(
+            device,
+            &BufferDescriptor {
+                label: Some("material_buffer"),
+                size: std::mem::size_of::<MaterialUniform>() as BufferAddress,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: true,
+            },
+        );
+        buffer
+            .slice(..)
+            .get_mapped_range_mut()
+            .copy_from_slice(bytemuck::bytes_of(&MaterialUniform::new(material)));
+        buffer.unmap();
+
+        let (texture, sampler) = material
+            .base_color_texture
+            .and_then(|t| self.textures.get(t.0 as usize))
+            .map(|(view, sampler)| (view, &self.samplers[sampler]))
+            .unwrap_or((&self.white, &self.samplers[&SamplerSettings::default()]));
+
+        let bind_group = create_bind_group(
+            device,
+            &BindGroupDescriptor {
+                label: Some("material_bind_group"),
+                layout: &self.layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureView(texture),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: BindingResource::Sampler(sampler),
+                    },
+                ],
+            },
+        );
+
+        MaterialEntry {
+            buffer,
+            bind_group,
+            version,
+            texture: material.base_color_texture,
+        }
+    }
+
+    /// Falls back to the default material.
+    pub fn bind_group(&self, id: MaterialId) -> &BindGroup {
+        let entry = self.entries.get(id.0 as usize).unwrap_or(&self.entries[0]);
+        &entry.bind_group
+    }
+}
+
+/// Uploads every level, generating the mip chain if the data has none.
+fn upload_texture(device: &Device, queue: &Queue, data: &TextureData) -> TextureView {
+    let size = Extent3d {
+        width: data.width.max(1),
+        height: data.height.max(1),
+        depth_or_array_layers: 1,
+    };
+
+    let format = if data.srgb {
+        TextureFormat::Rgba8UnormSrgb
+    } else {
+        TextureFormat::Rgba8Unorm
+    };
+
+    let generated;
+    let mips = if data.mips.is_empty() {
+        generated = generate_mips(size.width, size.height, &data.pixels, data.srgb);
+        &generated
+    } else {
+        &data.mips
+    };
+
+    let texture = create_texture(
+        device,
+        &TextureDescriptor {
+            label: Some("material_texture"),
+            size,
+            mip_level_count: 1 + mips.len() as u32,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            view_formats: &[],
+        },
+    );
+
+    for (level, pixels) in std::iter::once(&data.pixels).chain(mips).enumerate() {
+        let level_size = size.mip_level_size(level as u32, TextureDimension::D2);
+
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: &texture,
+                mip_level: level as u32,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            pixels,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * level_size.width),
+                rows_per_image: Some(level_size.height),
+            },
+            level_size,
+        );
+    }
+
+    texture.create_view(&TextureViewDescriptor::default())
+}
@@ -0,0 +1,210 @@
+pub mod material_bindings;
+
+use crate::renderer::texture::TextureData;
+
+/// Index into a `MaterialLibrary`; `Renderable::material` holds the raw
+/// value. Unknown ids draw with the default material.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct MaterialId(pub u32);
+
+impl MaterialId {
+    /// Always present: plain white, opaque.
+    pub const DEFAULT: MaterialId = MaterialId(0);
+}
+
+/// Index into `MaterialLibrary::textures`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureId(pub u32);
+
+/// How a material's fragments combine with what is already drawn.
+/// Opaque draws go first, then blended ones without writing depth.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum BlendMode {
+    #[default]
+    Opaque,
+    /// Alpha blending with `base_color[3]` as alpha.
+    Blend,
+    /// Added on top (glows, holograms).
+    Additive,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 3] = [BlendMode::Opaque, BlendMode::Blend, BlendMode::Additive];
+
+    pub fn is_transparent(self) -> bool {
+        self != BlendMode::Opaque
+    }
+}
+
+/// Cel-style lighting in the manner of VRM's MToon: light bands between
+/// the base color and `shade_color` instead of PBR falloff, plus a rim.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ToonShading {
+    /// Linear RGB of the unlit side.
+    pub shade_color: [f32; 3],
+    /// Moves the lit/shade boundary; negative shrinks the lit side.
+    pub shading_shift: f32,
+    /// 0 = smooth like Lambert, 1 = a hard edge.
+    pub shading_toony: f32,
+    /// Linear RGB added at grazing angles.
+    pub rim_color: [f32; 3],
+    pub rim_fresnel_power: f32,
+}
+
+impl Default for ToonShading {
+    fn default() -> Self {
+        Self {
+            shade_color: [0.0; 3],
+            shading_shift: 0.0,
+            shading_toony: 0.9,
+            rim_color: [0.0; 3],
+            rim_fresnel_power: 5.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    pub name: String,
+    /// Linear RGBA; alpha is used by `BlendMode::Blend`.
+    pub base_color: [f32; 4],
+    /// Linear RGB added after shading.
+    pub emissive: [f32; 3],
+    pub roughness: f32,
+    pub metallic: f32,
+    /// Multiplies `base_color`, sampled at the mesh's UVs.
+    pub base_color_texture: Option<TextureId>,
+    pub blend: BlendMode,
+    /// Replaces PBR shading; roughness and metallic are then unused.
+    pub toon: Option<ToonShading>,
+}
+
+impl Material {
+    pub fn new(name: &str, base_color: [f32; 3]) -> Self {
+        Self {
+            name: name.to_string(),
+            base_color: [base_color[0], base_color[1], base_color[2], 1.0],
+            emissive: [0.0; 3],
+            roughness: 0.8,
+            metallic: 0.0,
+            base_color_texture: None,
+            blend: BlendMode::Opaque,
+            toon: None,
+        }
+    }
+
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.base_color[3] = alpha;
+        self
+    }
+
+    pub fn with_emissive(mut self, emissive: [f32; 3]) -> Self {
+        self.emissive = emissive;
+        self
+    }
+
+    pub fn with_roughness_metallic(mut self, roughness: f32, metallic: f32) -> Self {
+        self.roughness = roughness.clamp(0.0, 1.0);
+        self.metallic = metallic.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_base_color_texture(mut self, texture: TextureId) -> Self {
+        self.base_color_texture = Some(texture);
+        self
+    }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_toon(mut self, toon: ToonShading) -> Self {
+        self.toon = Some(toon);
+        self
+    }
+}
+
+/*
+   Every material in the scene, by id.
+
+   The renderer keeps a GPU copy (see `material_bindings`) and re-uploads
+   a material when its version changes, so edit through `get_mut`.
+*/
+pub struct MaterialLibrary {
+    materials: Vec<Material>,
+    versions: Vec<u64>,
+    textures: Vec<TextureData>,
+}
+
+impl MaterialLibrary {
+    pub fn new() -> Self {
+        Self {
+            materials: vec![Material::new("default", [1.0, 1.0, 1.0])],
+            versions: vec![1],
+            textures: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, material: Material) -> MaterialId {
+        self.materials.push(material);
+        self.versions.push(1);
+        MaterialId(self.materials.len() as u32 - 1)
+    }
+
+    /// Falls back to the default material for unknown ids.
+    pub fn get(&self, id: MaterialId) -> &Material {
+        self.materials.get(id.0 as usize).unwrap_or(&self.materials[0])
+    }
+
+    pub fn get_mut(&mut self, id: MaterialId) -> Option<&mut Material> {
+        let material = self.materials.get_mut(id.0 as usize)?;
+        self.versions[id.0 as usize] += 1;
+        Some(material)
+    }
+
+    pub fn find(&self, name: &str) -> Option<MaterialId> {
+        self.materials
+            .iter()
+            .position(|m| m.name == name)
+            .map(|i| MaterialId(i as u32))
+    }
+
+    /// `id` itself if it exists, otherwise the default.
+    pub fn resolve(&self, id: MaterialId) -> MaterialId {
+        if (id.0 as usize) < self.materials.len() {
+            id
+        } else {
+            MaterialId::DEFAULT
+        }
+    }
+
+    /// Bumped on every `get_mut`.
+    pub fn version(&self, id: MaterialId) -> u64 {
+        self.versions.get(id.0 as usize).copied().unwrap_or(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.materials.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.materials.is_empty()
+    }
+
+    /// Textures are immutable once added.
+    pub fn add_texture(&mut self, texture: TextureData) -> TextureId {
+        self.textures.push(texture);
+        TextureId(self.textures.len() as u32 - 1)
+    }
+
+    pub fn textures(&self) -> &[TextureData] {
+        &self.textures
+    }
+}
+
+impl Default for MaterialLibrary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,146 @@
+use glam::Mat4;
+
+use crate::renderer::mesh::{MeshId, MeshLibrary};
+use crate::renderer::resources::counters::create_bind_group;
+use crate::renderer::resources::dynamic::DynamicBuffer;
+
+/*
+   Everything that moves vertices on the GPU, in storage buffers: group
+   3 of the world pipelines and group 1 of the shadow pipelines.
+
+   - binding 0: the joint palette of every skinned prop this frame. Each
+     instance finds its joints from its `joint_offset`.
+   - binding 1: morph target deltas of every morphed mesh, uploaded once
+     like the meshes themselves. Vertex-major: for each vertex, a
+     position and a normal delta (as vec4s) per target.
+   - binding 2: the morph weights of every morphed prop this frame.
+     Each instance finds its deltas and weights from its `morph`.
+
+   So any number of characters share one bind group.
+*/
+pub struct DeformBindings {
+    pub layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+    palette: DynamicBuffer,
+    deltas: DynamicBuffer,
+    weights: DynamicBuffer,
+    delta_data: Vec<[f32; 4]>,
+    /// Where each mesh's deltas start (in vec4s), by id; `None` for
+    /// meshes without targets.
+    delta_offsets: Vec<Option<u32>>,
+}
+
+impl DeformBindings {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let storage = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("deform_layout"),
+            entries: &[storage(0), storage(1), storage(2)],
+        });
+
+        // room for a couple of characters before the first resize
+        let palette = DynamicBuffer::new(
+            device,
+            "joint_palette",
+            wgpu::BufferUsages::STORAGE,
+            std::mem::size_of::<Mat4>() as u64 * 256,
+        );
+        let deltas = DynamicBuffer::new(device, "morph_deltas", wgpu::BufferUsages::STORAGE, 16);
+        let weights = DynamicBuffer::new(device, "morph_weights", wgpu::BufferUsages::STORAGE, 4 * 256);
+        let bind_group = Self::create_bind_group(device, &layout, [&palette, &deltas, &weights]);
+
+        Self {
+            layout,
+            bind_group,
+            palette,
+            deltas,
+            weights,
+            delta_data: Vec::new(),
+            delta_offsets: Vec::new(),
+        }
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        buffers: [&DynamicBuffer; 3],
+    ) -> wgpu::BindGroup {
+        let entries: Vec<_> = buffers
+            .iter()
+            .enumerate()
+            .map(|(binding, b)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: b.buffer.as_entire_binding(),
+            })
+            .collect();
+
+        create_bind_group(
+            device,
+            &wgpu::BindGroupDescriptor {
+                label: Some("deform_bind_group"),
+                layout,
+                entries: &entries,
+            },
+        )
+    }
+
+    /// Uploads the deltas of meshes added since the last call, and this
+    /// frame's joint matrices and morph weights.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        library: &MeshLibrary,
+        joints: &[Mat4],
+        weights: &[f32],
+    ) {
+        let mut grew = false;
+
+        let known = self.delta_offsets.len();
+        for mesh in &library.meshes()[known..] {
+            if !mesh.is_morphed() {
+                self.delta_offsets.push(None);
+                continue;
+            }
+
+            self.delta_offsets.push(Some(self.delta_data.len() as u32));
+            for v in 0..mesh.vertices.len() {
+                for target in &mesh.morph_targets {
+                    let [x, y, z] = target.positions[v];
+                    let [nx, ny, nz] = target.normals.get(v).copied().unwrap_or_default();
+                    self.delta_data.push([x, y, z, 0.0]);
+                    self.delta_data.push([nx, ny, nz, 0.0]);
+                }
+            }
+        }
+        if self.delta_offsets[known..].iter().any(Option::is_some) {
+            grew |= self.deltas.write(device, queue, bytemuck::cast_slice(&self.delta_data));
+        }
+
+        let matrices: Vec<[[f32; 4]; 4]> = joints.iter().map(Mat4::to_cols_array_2d).collect();
+        grew |= self.palette.write(device, queue, bytemuck::cast_slice(&matrices));
+        grew |= self.weights.write(device, queue, bytemuck::cast_slice(weights));
+
+        if grew {
+            self.bind_group = Self::create_bind_group(
+                device,
+                &self.layout,
+                [&self.palette, &self.deltas, &self.weights],
+            );
+        }
+    }
+
+    /// Where `mesh`'s deltas start, if it has any and they are uploaded.
+    pub fn morph_offset(&self, mesh: MeshId) -> Option<u32> {
+        self.delta_offsets.get(mesh.0 as usize).copied().flatten()
+    }
+}
@@ -0,0 +1,45 @@
+use crate::renderer::mesh::{MeshId, MeshLibrary};
+use crate::renderer::resources::mesh::{Mesh, SkinVertex};
+
+/// GPU copy of a `MeshLibrary`: one vertex and index buffer per mesh
+/// (plus joint influences for skinned ones), uploaded the first frame
+/// the mesh exists.
+pub struct MeshBindings {
+    meshes: Vec<Mesh>,
+}
+
+impl MeshBindings {
+    pub fn new() -> Self {
+        Self { meshes: Vec::new() }
+    }
+
+    /// Uploads meshes added since the last call.
+    pub fn prepare(&mut self, device: &wgpu::Device, library: &MeshLibrary) {
+        for mesh in &library.meshes()[self.meshes.len()..] {
+            let mut gpu = Mesh::with_u32_indices(device, &mesh.vertices, &mesh.indices);
+
+            if mesh.is_skinned() {
+                let skin: Vec<SkinVertex> = mesh
+                    .joints
+                    .iter()
+                    .zip(&mesh.weights)
+                    .map(|(&joints, &weights)| SkinVertex { joints, weights })
+                    .collect();
+                gpu = gpu.with_skin(device, &skin);
+            }
+
+            self.meshes.push(gpu);
+        }
+    }
+
+    /// Falls back to the cube for ids not uploaded yet.
+    pub fn get(&self, id: MeshId) -> &Mesh {
+        self.meshes.get(id.0 as usize).unwrap_or(&self.meshes[0])
+    }
+}
+
+impl Default for MeshBindings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
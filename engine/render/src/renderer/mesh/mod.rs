@@ -0,0 +1,158 @@
+pub mod deform_bindings;
+pub mod mesh_bindings;
+pub mod morph;
+pub mod skin;
+
+use crate::renderer::resources::mesh::{capsule_mesh, cube_mesh, sphere_mesh, Vertex};
+
+/// Index into a `MeshLibrary`; `Renderable::mesh` holds the raw value.
+/// Unknown ids draw the cube.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct MeshId(pub u32);
+
+impl MeshId {
+    /// Always present: the unit cube centred on the origin.
+    pub const CUBE: MeshId = MeshId(0);
+    /// Always present: a sphere of diameter 1.
+    pub const SPHERE: MeshId = MeshId(1);
+    /// Always present: a capsule filling the unit cube along Y.
+    pub const CAPSULE: MeshId = MeshId(2);
+}
+
+/// Triangle list in the mesh's local space.
+#[derive(Clone, Default)]
+pub struct MeshData {
+    pub name: String,
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    /// Up to four joints per vertex (indices into the entity's `Skin`)
+    /// and their weights. Empty for rigid meshes.
+    pub joints: Vec<[u16; 4]>,
+    pub weights: Vec<[f32; 4]>,
+    /// Blend shapes, drawn with an entity's `MorphWeights`.
+    pub morph_targets: Vec<MorphTarget>,
+}
+
+/// Offsets from the base mesh at full weight, one per vertex.
+#[derive(Clone, Debug, Default)]
+pub struct MorphTarget {
+    pub name: String,
+    pub positions: Vec<[f32; 3]>,
+    /// Empty when the target leaves the normals alone.
+    pub normals: Vec<[f32; 3]>,
+}
+
+impl MeshData {
+    pub fn new(name: &str, vertices: Vec<Vertex>, indices: Vec<u32>) -> Self {
+        Self {
+            name: name.to_string(),
+            vertices,
+            indices,
+            joints: Vec::new(),
+            weights: Vec::new(),
+            morph_targets: Vec::new(),
+        }
+    }
+
+    pub fn with_skin(mut self, joints: Vec<[u16; 4]>, weights: Vec<[f32; 4]>) -> Self {
+        self.joints = joints;
+        self.weights = weights;
+        self
+    }
+
+    pub fn is_skinned(&self) -> bool {
+        !self.joints.is_empty()
+            && self.joints.len() == self.vertices.len()
+            && self.weights.len() == self.vertices.len()
+    }
+
+    pub fn with_morph_targets(mut self, targets: Vec<MorphTarget>) -> Self {
+        self.morph_targets = targets;
+        self
+    }
+
+    pub fn is_morphed(&self) -> bool {
+        !self.morph_targets.is_empty()
+            && self.morph_targets.iter().all(|t| {
+                t.positions.len() == self.vertices.len()
+                    && (t.normals.is_empty() || t.normals.len() == self.vertices.len())
+            })
+    }
+
+    /// Index of the named target, for its `MorphWeights` entry.
+    pub fn morph_target(&self, name: &str) -> Option<usize> {
+        self.morph_targets.iter().position(|t| t.name == name)
+    }
+}
+
+/*
+   Every mesh props can draw, by id.
+
+   The renderer uploads each mesh once (see `mesh_bindings`); meshes are
+   immutable after `add`, so replace one by adding a new mesh and
+   pointing the `Renderable` at it.
+*/
+pub struct MeshLibrary {
+    meshes: Vec<MeshData>,
+}
+
+impl MeshLibrary {
+    pub fn new() -> Self {
+        let builtin = |name: &str, (vertices, indices): (Vec<Vertex>, Vec<u16>)| {
+            MeshData::new(name, vertices, indices.into_iter().map(u32::from).collect())
+        };
+        let white = [1.0, 1.0, 1.0];
+
+        Self {
+            meshes: vec![
+                builtin("cube", cube_mesh(white)),
+                builtin("sphere", sphere_mesh(white)),
+                builtin("capsule", capsule_mesh(white)),
+            ],
+        }
+    }
+
+    pub fn add(&mut self, mesh: MeshData) -> MeshId {
+        self.meshes.push(mesh);
+        MeshId(self.meshes.len() as u32 - 1)
+    }
+
+    /// Falls back to the cube for unknown ids.
+    pub fn get(&self, id: MeshId) -> &MeshData {
+        self.meshes.get(id.0 as usize).unwrap_or(&self.meshes[0])
+    }
+
+    pub fn find(&self, name: &str) -> Option<MeshId> {
+        self.meshes
+            .iter()
+            .position(|m| m.name == name)
+            .map(|i| MeshId(i as u32))
+    }
+
+    /// `id` itself if it exists, otherwise the cube.
+    pub fn resolve(&self, id: MeshId) -> MeshId {
+        if (id.0 as usize) < self.meshes.len() {
+            id
+        } else {
+            MeshId::CUBE
+        }
+    }
+
+    pub fn meshes(&self) -> &[MeshData] {
+        &self.meshes
+    }
+
+    pub fn len(&self) -> usize {
+        self.meshes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.meshes.is_empty()
+    }
+}
+
+impl Default for MeshLibrary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
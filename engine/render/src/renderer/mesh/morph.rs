@@ -0,0 +1,41 @@
+/// Component for an entity drawing a mesh with morph targets: how much
+/// of each target (by index, as in `MeshData::morph_targets`) to blend
+/// in. Missing entries are zero. Set it from code, or let clips and VRM
+/// expressions drive it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MorphWeights {
+    pub weights: Vec<f32>,
+}
+
+impl MorphWeights {
+    pub fn new(weights: Vec<f32>) -> Self {
+        Self { weights }
+    }
+
+    /// All `count` targets at zero.
+    pub fn zeros(count: usize) -> Self {
+        Self::new(vec![0.0; count])
+    }
+
+    pub fn get(&self, target: usize) -> f32 {
+        self.weights.get(target).copied().unwrap_or(0.0)
+    }
+
+    /// Grows the list as needed.
+    pub fn set(&mut self, target: usize, weight: f32) {
+        if target >= self.weights.len() {
+            self.weights.resize(target + 1, 0.0);
+        }
+        self.weights[target] = weight;
+    }
+
+    /// Appends the weights of a mesh's `targets` targets.
+    pub fn write_weights(&self, targets: usize, weights: &mut Vec<f32>) {
+        weights.extend((0..targets).map(|i| self.get(i)));
+    }
+
+    /// Whether drawing with these weights differs from the base mesh.
+    pub fn is_active(&self) -> bool {
+        self.weights.iter().any(|&w| w != 0.0)
+    }
+}
@@ -0,0 +1,34 @@
+use engine_core::{EntityId, World};
+use glam::Mat4;
+
+/// Component for an entity drawing a skinned mesh: the joint entities
+/// its vertices' joint indices refer to, and for each one the inverse
+/// bind matrix taking the mesh from its bind pose into the joint's space.
+#[derive(Clone, Debug, Default)]
+pub struct Skin {
+    pub joints: Vec<EntityId>,
+    pub inverse_bind: Vec<Mat4>,
+}
+
+impl Skin {
+    /// Missing inverse bind matrices are the identity.
+    pub fn new(joints: Vec<EntityId>, mut inverse_bind: Vec<Mat4>) -> Self {
+        inverse_bind.resize(joints.len(), Mat4::IDENTITY);
+        Self {
+            joints,
+            inverse_bind,
+        }
+    }
+
+    /// Appends this frame's joint matrices (joint world matrix times
+    /// inverse bind), which take bind-pose vertices straight to world
+    /// space; the drawing entity's own transform does not apply.
+    pub fn write_palette(&self, world: &World, palette: &mut Vec<Mat4>) {
+        palette.extend(
+            self.joints
+                .iter()
+                .zip(&self.inverse_bind)
+                .map(|(&joint, inverse_bind)| world.world_matrix(joint) * *inverse_bind),
+        );
+    }
+}
@@ -0,0 +1,38 @@
+struct VertexInput {
+    @location(0) position : vec3<f32>,
+    @location(1) color : vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position : vec4<f32>,
+    @location(0) screen : vec4<f32>,
+    @location(1) tint : vec3<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> view_proj : mat4x4<f32>;
+
+@group(1) @binding(0)
+var mirror_texture : texture_2d<f32>;
+@group(1) @binding(1)
+var mirror_sampler : sampler;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out : VertexOutput;
+    out.clip_position = view_proj * vec4<f32>(in.position, 1.0);
+    out.screen = out.clip_position;
+    out.tint = in.color;
+    return out;
+}
+
+// The reflection was rendered with this view's projection, so the
+// texel to show is the one at this fragment's screen position.
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let ndc = in.screen.xy / in.screen.w;
+    let uv = vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+
+    let color = textureSample(mirror_texture, mirror_sampler, uv).rgb;
+    return vec4<f32>(color * in.tint, 1.0);
+}
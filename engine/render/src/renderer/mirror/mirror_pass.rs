@@ -0,0 +1,95 @@
+use engine_core::{EntityId, RenderLayers};
+use wgpu::*;
+
+use crate::renderer::context::RenderContext;
+use crate::renderer::frame::frame_resources::FrameResources;
+use crate::renderer::frame::render_pass::render_view;
+use crate::renderer::frame::view::RenderView;
+use crate::renderer::frame::{FrameScene, ViewPipelines};
+use crate::renderer::mirror::mirror_pipeline::MirrorPipeline;
+use crate::renderer::mirror::MirrorDraw;
+use crate::renderer::resources::dynamic::{BatchRange, GeometryBatch};
+use crate::renderer::resources::mesh::Vertex;
+
+/// Renders the reflection of every mirror `view` can see into the
+/// mirror's target, reusing the world pass with the reflected camera.
+/// Returns the mirrors that got a fresh reflection.
+pub fn render_reflections<'m>(
+    ctx: &RenderContext,
+    encoder: &mut CommandEncoder,
+    view: &RenderView,
+    scene: &FrameScene<'m>,
+    pipelines: &ViewPipelines,
+    resources: &FrameResources,
+) -> Vec<&'m MirrorDraw> {
+    // no mirrors inside mirrors, and no comfort vignette in the reflection
+    let reflected_scene = FrameScene {
+        mirrors: &[],
+        vignette: 0.0,
+        ..*scene
+    };
+
+    let mut drawn = Vec::new();
+
+    for mirror in scene.mirrors {
+        let Some(target) = resources.mirrors.target(mirror.id) else {
+            continue;
+        };
+        if !mirror.is_visible(view) {
+            continue;
+        }
+
+        let (view_m, proj_m) = mirror.reflect(view.view, view.proj);
+        let reflected = RenderView {
+            color: &target.color,
+            depth: &target.depth.view,
+            size: target.size,
+            layers: mirror.reflected_layers(view.layers),
+            view: view_m,
+            proj: proj_m,
+        };
+
+        render_view(ctx, encoder, &reflected, &reflected_scene, pipelines, resources);
+        drawn.push(mirror);
+    }
+
+    drawn
+}
+
+/// One quad per mirror, added to the frame's world batch so every view
+/// (and its reflections) draws the same upload.
+pub fn mirror_quads(
+    batch: &mut GeometryBatch<Vertex>,
+    mirrors: &[MirrorDraw],
+    draws: &mut Vec<(EntityId, BatchRange)>,
+) {
+    draws.clear();
+
+    for mirror in mirrors {
+        let color = mirror.tint;
+        let vertices = mirror.corners().map(|c| Vertex::new(c.into(), color));
+        let range = batch.push(RenderLayers::ALL, &vertices, &[0, 3, 2, 0, 2, 1]);
+
+        draws.push((mirror.id, range));
+    }
+}
+
+/// Draws the surfaces of the mirrors that got a reflection, inside a
+/// world pass that already has the camera bound at group 0.
+pub fn draw_mirrors<'a>(
+    pass: &mut RenderPass<'a>,
+    pipeline: &'a MirrorPipeline,
+    resources: &'a FrameResources,
+    mirrors: &[&MirrorDraw],
+) {
+    pass.set_pipeline(&pipeline.pipeline);
+
+    for mirror in mirrors {
+        let Some(target) = resources.mirrors.target(mirror.id) else {
+            continue;
+        };
+
+        pass.set_bind_group(1, &target.bind_group, &[]);
+        resources.draw_mirror_quad(pass, mirror.id);
+    }
+}
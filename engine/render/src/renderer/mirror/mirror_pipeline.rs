@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use engine_core::EntityId;
+use wgpu::*;
+
+use crate::renderer::context::depth::DepthTexture;
+use crate::renderer::resources::counters::{create_bind_group, create_texture};
+use crate::renderer::resources::mesh::Vertex;
+
+/// The reflection a mirror shows, rendered like any other view.
+pub struct MirrorTarget {
+    pub color: TextureView,
+    pub depth: DepthTexture,
+    pub bind_group: BindGroup,
+    pub size: (u32, u32),
+    pub format: TextureFormat,
+    /// The `MirrorTargets::prepare` that last asked for it.
+    used: u64,
+}
+
+/// Reflection textures, one per mirror picked this frame and per view
+/// size and color format. The headset and the window both render every
+/// frame at their own size and format, so each keeps its own targets;
+/// targets neither asked for in the last two `prepare`s are dropped.
+pub struct MirrorTargets {
+    pub layout: BindGroupLayout,
+    sampler: Sampler,
+    targets: HashMap<TargetKey, MirrorTarget>,
+    /// Size and format of the views being drawn since the last `prepare`.
+    current: ((u32, u32), TextureFormat),
+    /// Number of `prepare` calls so far.
+    generation: u64,
+}
+
+type TargetKey = (EntityId, (u32, u32), TextureFormat);
+
+impl MirrorTargets {
+    pub fn new(device: &Device) -> Self {
+        let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("mirror_texture_layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("mirror_sampler"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            ..Default::default()
+        });
+
+        Self {
+            layout,
+            sampler,
+            targets: HashMap::new(),
+            current: ((1, 1), TextureFormat::Rgba8UnormSrgb),
+            generation: 0,
+        }
+    }
+
+    /// The mirror's target for the views of the last `prepare`.
+    pub fn target(&self, id: EntityId) -> Option<&MirrorTarget> {
+        let (size, format) = self.current;
+        self.targets.get(&(id, size, format))
+    }
+
+    /// Makes sure every mirror in `ids` has a target of `size` and
+    /// `format`, and drops the targets no view has used lately.
+    pub fn prepare(&mut self, device: &Device, ids: &[EntityId], size: (u32, u32), format: TextureFormat) {
+        let size = (size.0.max(1), size.1.max(1));
+        self.current = (size, format);
+        self.generation += 1;
+
+        let generation = self.generation;
+        for &id in ids {
+            let mut target = match self.targets.remove(&(id, size, format)) {
+                Some(target) => target,
+                None => self.create_target(device, size, format),
+            };
+            target.used = generation;
+            self.targets.insert((id, size, format), target);
+        }
+
+        // the other path's targets were used by the previous `prepare`
+        self.targets.retain(|_, t| t.used + 1 >= generation);
+    }
+
+    fn create_target(&self, device: &Device, size: (u32, u32), format: TextureFormat) -> MirrorTarget {
+        let texture = create_texture(device, &TextureDescriptor {
+            label: Some("mirror_texture"),
+            size: Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let color = texture.create_view(&TextureViewDescriptor::default());
+
+        let bind_group = create_bind_group(device, &BindGroupDescriptor {
+            label: Some("mirror_bind_group"),
+            layout: &self.layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&color),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+
+        MirrorTarget {
+            color,
+            depth: DepthTexture::with_size(device, size.0, size.1),
+            bind_group,
+            size,
+            format,
+            used: 0,
+        }
+    }
+}
+
+/// Mirror surfaces: quads that show their reflection texture at the
+/// fragment's screen position.
+pub struct MirrorPipeline {
+    pub pipeline: RenderPipeline,
+}
+
+impl MirrorPipeline {
+    pub fn new(
+        device: &Device,
+        format: TextureFormat,
+        camera_layout: &BindGroupLayout,
+        texture_layout: &BindGroupLayout,
+    ) -> Self {
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("mirror_shader"),
+            source: ShaderSource::Wgsl(include_str!("mirror.wgsl").into()),
+        });
+
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("mirror_pipeline_layout"),
+            bind_group_layouts: &[camera_layout, texture_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("mirror_pipeline"),
+            layout: Some(&layout),
+
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::layout()],
+            },
+
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::REPLACE),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+
+            primitive: PrimitiveState::default(),
+            depth_stencil: Some(DepthStencilState {
+                format: TextureFormat::Depth24Plus,
+                depth_write_enabled: true,
+                depth_compare: CompareFunction::Less,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            multisample: MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self { pipeline }
+    }
+}
@@ -0,0 +1,168 @@
+pub mod mirror_pipeline;
+pub mod mirror_pass;
+
+use engine_core::{EntityId, RenderLayers};
+use glam::{Mat4, Vec2, Vec3, Vec4};
+
+use crate::renderer::frame::view::RenderView;
+
+/// Planar mirror component. The mirror is the entity's local XY plane,
+/// reflecting towards +Z; the entity's scale is ignored.
+#[derive(Clone, Copy, Debug)]
+pub struct Mirror {
+    /// Width and height in metres.
+    pub size: Vec2,
+    /// Multiplies the color of the reflection (slightly darker than 1
+    /// reads as glass).
+    pub tint: [f32; 3],
+    /// Layers the mirror surface itself is on.
+    pub layers: RenderLayers,
+    /// Layers the reflection may show. The reflection shows what the
+    /// viewer sees plus their own head, limited to these.
+    pub reflects: RenderLayers,
+}
+
+impl Mirror {
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            size,
+            tint: [0.92, 0.94, 0.96],
+            layers: RenderLayers::DEFAULT,
+            reflects: RenderLayers::ALL,
+        }
+    }
+}
+
+/// How much mirror rendering a frame may do.
+#[derive(Clone, Copy, Debug)]
+pub struct MirrorSettings {
+    /// Mirrors rendered per frame, nearest first; the rest are skipped.
+    pub max_per_frame: usize,
+    /// Reflection resolution relative to the view.
+    pub resolution_scale: f32,
+    /// Mirrors further away than this are not rendered.
+    pub max_distance: f32,
+}
+
+impl Default for MirrorSettings {
+    fn default() -> Self {
+        Self {
+            max_per_frame: 2,
+            resolution_scale: 0.5,
+            max_distance: 15.0,
+        }
+    }
+}
+
+/// A mirror picked for this frame, in world space.
+#[derive(Clone, Copy, Debug)]
+pub struct MirrorDraw {
+    pub id: EntityId,
+    /// Rigid placement of the mirror's centre.
+    pub transform: Mat4,
+    pub size: Vec2,
+    pub tint: [f32; 3],
+    pub layers: RenderLayers,
+    pub reflects: RenderLayers,
+}
+
+impl MirrorDraw {
+    pub fn normal(&self) -> Vec3 {
+        self.transform.transform_vector3(Vec3::Z).normalize()
+    }
+
+    pub fn center(&self) -> Vec3 {
+        self.transform.w_axis.truncate()
+    }
+
+    /// World-space plane (normal, -distance): positive in front.
+    pub fn plane(&self) -> Vec4 {
+        let n = self.normal();
+        n.extend(-n.dot(self.center()))
+    }
+
+    /// Top-left first, clockwise seen from the front.
+    pub fn corners(&self) -> [Vec3; 4] {
+        let half = self.size * 0.5;
+        [
+            Vec3::new(-half.x, half.y, 0.0),
+            Vec3::new(half.x, half.y, 0.0),
+            Vec3::new(half.x, -half.y, 0.0),
+            Vec3::new(-half.x, -half.y, 0.0),
+        ]
+        .map(|c| self.transform.transform_point3(c))
+    }
+
+    /// Whether a view can see the front of the mirror at all.
+    pub fn is_visible(&self, view: &RenderView) -> bool {
+        if !self.layers.intersects(view.layers) {
+            return false;
+        }
+
+        let eye = view.view.inverse().w_axis.truncate();
+        if self.plane().dot(eye.extend(1.0)) <= 0.0 {
+            return false;
+        }
+
+        // outside the frustum if every corner is past the same clip plane
+        let clip = self.corners().map(|c| view.view_proj() * c.extend(1.0));
+        let outside = |f: &dyn Fn(Vec4) -> bool| clip.iter().all(|&c| f(c));
+
+        !(outside(&|c| c.x < -c.w)
+            || outside(&|c| c.x > c.w)
+            || outside(&|c| c.y < -c.w)
+            || outside(&|c| c.y > c.w)
+            || outside(&|c| c.z > c.w))
+    }
+
+    /// Mask for the reflected view of a view with mask `view`: the local
+    /// head swaps in for the first-person cut.
+    pub fn reflected_layers(&self, view: RenderLayers) -> RenderLayers {
+        (view | RenderLayers::LOCAL_HEAD).without(RenderLayers::LOCAL_FIRST_PERSON) & self.reflects
+    }
+
+    /// View and projection of the reflected camera. The near plane is
+    /// moved onto the mirror so nothing behind it shows up in the
+    /// reflection.
+    pub fn reflect(&self, view: Mat4, proj: Mat4) -> (Mat4, Mat4) {
+        let reflected = view * reflection(self.plane());
+
+        let plane_view = reflected.inverse().transpose() * self.plane();
+        (reflected, oblique_near_plane(proj, plane_view))
+    }
+}
+
+/// Mirrors world space across `plane` (normal, -distance).
+pub fn reflection(plane: Vec4) -> Mat4 {
+    let n = plane.truncate();
+    let d = plane.w;
+
+    Mat4::from_cols(
+        Vec4::new(1.0 - 2.0 * n.x * n.x, -2.0 * n.y * n.x, -2.0 * n.z * n.x, 0.0),
+        Vec4::new(-2.0 * n.x * n.y, 1.0 - 2.0 * n.y * n.y, -2.0 * n.z * n.y, 0.0),
+        Vec4::new(-2.0 * n.x * n.z, -2.0 * n.y * n.z, 1.0 - 2.0 * n.z * n.z, 0.0),
+        Vec4::new(-2.0 * d * n.x, -2.0 * d * n.y, -2.0 * d * n.z, 1.0),
+    )
+}
+
+/// Replaces the near plane of a right-handed, 0..1 depth projection with
+/// `plane` (view space, the kept side positive), keeping the far plane
+/// as close to the original as possible (Lengyel's oblique frustum).
+pub fn oblique_near_plane(proj: Mat4, plane: Vec4) -> Mat4 {
+    let inv = proj.inverse();
+
+    // far corner of the frustum opposite the plane
+    let clip_plane = inv.transpose() * plane;
+    let corner = Vec4::new(clip_plane.x.signum(), clip_plane.y.signum(), 1.0, 1.0);
+    let q = inv * corner;
+
+    // row 3 is (0, 0, -1, 0) for a perspective projection: w = -z
+    let row = plane * (-q.z / plane.dot(q));
+
+    let mut m = proj;
+    m.x_axis.z = row.x;
+    m.y_axis.z = row.y;
+    m.z_axis.z = row.z;
+    m.w_axis.z = row.w;
+    m
+}
@@ -1,62 +1,202 @@
-use glam::Vec3;
+use std::path::Path;
+
+use engine_core::{EntityId, RenderLayers, Renderable, Transform, World};
+use glam::{Mat4, Quat, Vec2, Vec3};
 use winit::window::Window;
 
 pub mod context;
+pub mod environment;
 pub mod frame;
+pub mod lighting;
+pub mod material;
+pub mod mesh;
+pub mod mirror;
+pub mod panel;
 pub mod pipeline;
 pub mod resources;
+pub mod shadow;
 pub mod uniforms;
 pub mod skybox; // <-- ADD
+pub mod texture;
+pub mod vignette;
+
+use crate::animation::{animate, AnimationParams, Animations, Animator};
+use crate::avatar::vrm::VrmAvatar;
+use crate::avatar::{load_default_avatar, AvatarBody, AvatarFile, BodyTargets, DefinitionError, Joint};
+use crate::interaction::{integrate_motion, GrabInput, GrabVolume, Grabbable, Interaction, RestSurface};
+use crate::locomotion::{CharacterController, Ground, Locomotion, LocomotionInput};
+use crate::model::{load_model, Model, ModelError};
+use crate::ui::{PointerSource, Ray, SettingsMenu, Ui, UiPointer};
+use crate::xr::{Boundary, Hand, HandSkeleton, PoseFrame, TrackedPose};
 
+use context::device::RenderDevice;
 use context::RenderContext;
-use frame::FrameRenderer;
+use frame::render_pass::polyline;
+use frame::view::RenderView;
+use frame::{FrameRenderer, FrameScene, SceneMesh};
+use lighting::lights::{DirectionalLight, LightDraw, PointLight, SpotLight};
+use lighting::Lighting;
+use material::{BlendMode, Material, MaterialId, MaterialLibrary};
+use mesh::morph::MorphWeights;
+use mesh::skin::Skin;
+use mesh::{MeshId, MeshLibrary};
+use mirror::{Mirror, MirrorDraw, MirrorSettings};
+use resources::counters::GpuCounters;
+use resources::hand::hand_mesh;
+use resources::mesh::Vertex;
+use shadow::ShadowSettings;
+use skybox::Sky;
 use uniforms::camera::OrbitCamera;
 
 #[derive(Clone, Copy)]
 pub struct Prop {
     pub position: Vec3,
+    pub rotation: Quat,
     pub scale: Vec3,
+    /// Multiplies the material's base color (hover/grab highlights).
+    pub color: [f32; 3],
+    pub mesh: MeshId,
+    pub material: MaterialId,
+    pub layers: RenderLayers,
+    /// First of the prop's joint matrices in `FrameScene::joints`, for
+    /// skinned meshes; the transform above then applies after skinning.
+    pub joints: Option<u32>,
+    /// First of the prop's target weights in `FrameScene::morph_weights`,
+    /// for morphed meshes.
+    pub morph_weights: Option<u32>,
+}
+
+impl Prop {
+    pub fn new(position: Vec3, scale: Vec3) -> Self {
+        Self {
+            position,
+            rotation: Quat::IDENTITY,
+            scale,
+            color: [1.0, 1.0, 1.0],
+            mesh: MeshId::CUBE,
+            material: MaterialId::DEFAULT,
+            layers: RenderLayers::DEFAULT,
+            joints: None,
+            morph_weights: None,
+        }
+    }
+}
+
+/// Materials the built-in scene uses.
+struct SceneMaterials {
+    avatar: MaterialId,
+    skin: MaterialId,
 }
 
 pub struct Renderer {
     ctx: RenderContext,
     frame: FrameRenderer,
 
-    avatar_pos: Vec3,
-    avatar_yaw: f32,
-    velocity: Vec3,
-    grounded: bool,
+    controller: CharacterController,
+    ground: Ground,
+    pub locomotion: Locomotion,
+    pub boundary: Boundary,
 
-    jump_count: u8,
-    max_jumps: u8,
+    pub world: World,
+    pub materials: MaterialLibrary,
+    pub meshes: MeshLibrary,
+    pub lighting: Lighting,
+    pub shadows: ShadowSettings,
+    pub sky: Sky,
+    scene_materials: SceneMaterials,
+    pub interaction: Interaction,
+    pub ui: Ui,
+    settings_menu: SettingsMenu,
+    /// Desktop mouse pointer for the next update; replaces the hand lasers.
+    mouse: Option<UiPointer>,
+    /// Tracked hands in world space, for drawing.
+    hands: [HandSkeleton; 2],
 
     pub camera: OrbitCamera,
-    avatar_parts: Vec<Prop>,
+    pub avatar: AvatarBody,
+    /// Definition file of the part body, reloaded when it changes.
+    pub avatar_file: Option<AvatarFile>,
+    /// Loaded VRM drawn instead of the part body.
+    pub vrm: Option<VrmAvatar>,
+    /// What the avatar looks at while nothing tracks its head; the
+    /// desktop camera when `None`.
+    pub point_of_interest: Option<Vec3>,
+    pub mirrors: MirrorSettings,
+
+    /// Layers the desktop window shows. It is a third-person camera, so
+    /// the local head is visible.
+    pub desktop_layers: RenderLayers,
+    /// Layers the headset eyes show; the user's own head is hidden and
+    /// first-person-only geometry shown instead.
+    pub eye_layers: RenderLayers,
+
+    /// GPU objects created by the last `render`/`render_stereo` call.
+    frame_allocations: GpuCounters,
 }
 
 impl Renderer {
     pub async fn new(window: &Window) -> Self {
         let ctx = RenderContext::new(window).await;
+        Self::with_context(ctx)
+    }
+
+    /// Renderer on a device the caller already created (see `xr::openxr`).
+    pub fn with_device(window: &Window, device: RenderDevice) -> Self {
+        Self::with_context(RenderContext::with_device(window, device))
+    }
+
+    fn with_context(ctx: RenderContext) -> Self {
         let frame = FrameRenderer::new(&ctx);
 
-        let avatar_parts = vec![
-            Prop { position: Vec3::new(0.0, 0.9, 0.0), scale: Vec3::new(0.6, 0.9, 0.3) },
-            Prop { position: Vec3::new(0.0, 1.6, 0.0), scale: Vec3::splat(0.35) },
-            Prop { position: Vec3::new(-0.15, 0.3, 0.0), scale: Vec3::new(0.2, 0.6, 0.2) },
-            Prop { position: Vec3::new(0.15, 0.3, 0.0), scale: Vec3::new(0.2, 0.6, 0.2) },
-        ];
+        let mut world = World::new();
+        let interaction = Interaction::new(&mut world);
+
+        let mut materials = MaterialLibrary::new();
+        let scene_materials = SceneMaterials {
+            // part colors come from the avatar definition
+            avatar: materials.add(Material::new("avatar", [1.0, 1.0, 1.0])),
+            skin: materials.add(Material::new("skin", [0.9, 0.8, 0.7])),
+        };
+        spawn_props(&mut world, &mut materials);
+
+        // left of the table, turned towards the spawn point
+        let mut ui = Ui::new();
+        let settings_menu = SettingsMenu::new(
+            &mut ui,
+            Mat4::from_rotation_translation(
+                Quat::from_rotation_y(0.9_f32.atan2(-0.7)),
+                Vec3::new(-0.9, 1.3, 0.7),
+            ),
+        );
 
         Self {
             ctx,
             frame,
-            avatar_pos: Vec3::ZERO,
-            avatar_yaw: 0.0,
-            velocity: Vec3::ZERO,
-            grounded: true,
-            jump_count: 0,
-            max_jumps: 2,
+            controller: CharacterController::new(),
+            ground: Ground::floor_grid(),
+            locomotion: Locomotion::default(),
+            boundary: Boundary::default(),
+            world,
+            materials,
+            meshes: MeshLibrary::new(),
+            lighting: Lighting::default(),
+            shadows: ShadowSettings::default(),
+            sky: Sky::default(),
+            scene_materials,
+            interaction,
+            ui,
+            settings_menu,
+            mouse: None,
+            hands: Default::default(),
             camera: OrbitCamera::new(),
-            avatar_parts,
+            avatar: AvatarBody::new(&load_default_avatar()),
+            avatar_file: None,
+            vrm: None,
+            point_of_interest: None,
+            mirrors: MirrorSettings::default(),
+            desktop_layers: RenderLayers::DEFAULT | RenderLayers::LOCAL_HEAD | RenderLayers::GIZMOS,
+            eye_layers: RenderLayers::DEFAULT | RenderLayers::LOCAL_FIRST_PERSON,
+            frame_allocations: GpuCounters::default(),
         }
     }
 
@@ -64,58 +204,567 @@ impl Renderer {
         self.ctx.resize(width, height);
     }
 
-    pub fn update(&mut self, dt: f32, input: Vec3, jump: bool) {
-        let speed = 4.0;
+    pub fn update(&mut self, dt: f32, input: &LocomotionInput, poses: &PoseFrame) {
+        self.update_ui(poses);
+
+        // pointing at a panel takes over the right stick (scrolling)
+        let mut input = *input;
+        if self.ui.is_pointing(PointerSource::Hand(Hand::Right)) {
+            input.aim = None;
+        }
+
+        let turn_before = self.controller.turn_yaw;
+
+        self.locomotion.update(dt, &input, &mut self.controller, &self.ground);
+
+        // turning swings the desktop camera along with the play area
+        self.camera.yaw += self.controller.turn_yaw - turn_before;
+        self.camera.target = self.controller.position;
 
-        let yaw = self.camera.yaw;
-        let forward = Vec3::new(yaw.sin(), 0.0, yaw.cos());
-        let right = Vec3::new(forward.z, 0.0, -forward.x);
+        // animated models play along with the character's movement
+        let params = AnimationParams::from_controller(&self.controller);
+        animate(&mut self.world, dt, &params);
 
-        let mut move_dir = Vec3::ZERO;
-        move_dir += forward * -input.z;
-        move_dir += right * -input.x;
+        let to_world = self.controller.tracking_to_world();
+        let to_world_pose = |p: &TrackedPose| {
+            let (_, rotation, position) = (to_world * p.matrix()).to_scale_rotation_translation();
+            TrackedPose { position, orientation: rotation, ..*p }
+        };
 
-        if move_dir.length_squared() > 0.0001 {
-            move_dir = move_dir.normalize();
-            self.avatar_yaw = move_dir.x.atan2(move_dir.z);
-            self.avatar_pos += move_dir * speed * dt;
+        // tracked hands drive the arms from the wrist
+        let hand_target = |hand: Hand| {
+            let tracked = poses.hand(hand);
+            if tracked.valid {
+                to_world_pose(&tracked.wrist())
+            } else {
+                to_world_pose(&poses.controller(hand).pose)
+            }
+        };
+
+        let targets = BodyTargets {
+            head: to_world_pose(&poses.head),
+            left: hand_target(Hand::Left),
+            right: hand_target(Hand::Right),
+        };
+        if targets.head.valid {
+            self.avatar.update(dt, &targets, &self.ground);
+        } else {
+            // no tracking: walk along with the character instead
+            let look_at = self
+                .point_of_interest
+                .unwrap_or_else(|| self.camera.view_matrix().inverse().w_axis.truncate());
+            self.avatar.animate(
+                dt,
+                &params,
+                self.controller.position,
+                self.controller.yaw,
+                Some(look_at),
+            );
+        }
+        if let Some(vrm) = &mut self.vrm {
+            vrm.update(&mut self.world, &self.avatar, dt);
         }
 
-        if jump && self.jump_count < self.max_jumps {
-            self.velocity.y = 5.0;
-            self.grounded = false;
-            self.jump_count += 1;
+        self.hands = [&poses.left_hand, &poses.right_hand].map(|h| h.transformed(to_world));
+
+        self.interaction.update(
+            &mut self.world,
+            to_world,
+            [
+                GrabInput::from_frame(poses, Hand::Left),
+                GrabInput::from_frame(poses, Hand::Right),
+            ],
+            dt,
+        );
+        integrate_motion(&mut self.world, &self.ground, dt);
+
+        self.boundary.update(poses);
+    }
+
+    fn update_ui(&mut self, poses: &PoseFrame) {
+        let to_world = self.controller.tracking_to_world();
+
+        let pointers: Vec<UiPointer> = match self.mouse.take() {
+            Some(mouse) => vec![mouse],
+            None => [Hand::Left, Hand::Right]
+                .into_iter()
+                .filter_map(|hand| {
+                    UiPointer::from_hand(hand, poses.hand(hand), to_world)
+                        .or_else(|| UiPointer::from_controller(hand, poses.controller(hand), to_world))
+                })
+                .collect(),
+        };
+
+        let events = self.ui.update(&pointers);
+        self.settings_menu.update(
+            &mut self.ui,
+            &events,
+            &mut self.locomotion.settings,
+            &mut self.boundary,
+        );
+    }
+
+    /// Points the desktop mouse into the scene for the next `update`,
+    /// instead of the hand lasers.
+    pub fn set_mouse_pointer(&mut self, pointer: UiPointer) {
+        self.mouse = Some(pointer);
+    }
+
+    /// World-space ray under a window pixel, from the desktop camera.
+    pub fn mouse_ray(&self, cursor: Vec2) -> Ray {
+        let (w, h) = (self.ctx.surface.config.width as f32, self.ctx.surface.config.height as f32);
+        let ndc = Vec2::new(cursor.x / w * 2.0 - 1.0, 1.0 - cursor.y / h * 2.0);
+
+        let inv = (self.camera.proj_matrix(w / h) * self.camera.view_matrix()).inverse();
+        let near = inv.project_point3(ndc.extend(0.0));
+        let far = inv.project_point3(ndc.extend(1.0));
+
+        Ray::new(near, far - near)
+    }
+
+    pub fn device(&self) -> &RenderDevice {
+        &self.ctx.device
+    }
+
+    /// Places the tracking-space origin (the play area) in the world.
+    /// The play area travels with the character.
+    pub fn tracking_to_world(&self) -> Mat4 {
+        self.controller.tracking_to_world()
+    }
+
+    /// Props for the avatar body, the world's renderables and untracked
+    /// hands; skinned renderables append their joint matrices to `joints`
+    /// and morphed ones their target weights to `morph_weights`.
+    fn avatar_world_props(&self, joints: &mut Vec<Mat4>, morph_weights: &mut Vec<f32>) -> Vec<Prop> {
+        // a VRM draws itself through the world
+        let parts = if self.vrm.is_some() { Vec::new() } else { self.avatar.parts() };
+
+        let mut props: Vec<Prop> = parts
+            .into_iter()
+            .map(|p| Prop {
+                position: p.position,
+                rotation: p.rotation,
+                scale: p.scale,
+                color: p.color,
+                mesh: p.shape.mesh(),
+                material: self.scene_materials.avatar,
+                layers: if p.joint == Joint::Head {
+                    RenderLayers::LOCAL_HEAD
+                } else {
+                    RenderLayers::DEFAULT
+                },
+                joints: None,
+                morph_weights: None,
+            })
+            .collect();
+
+        for (id, renderable) in self.world.query::<Renderable>() {
+            let mesh = self.meshes.get(MeshId(renderable.mesh));
+
+            // skinning puts the mesh in world space
+            let skin = self.world.get::<Skin>(id).filter(|_| mesh.is_skinned());
+            let t = match skin {
+                Some(_) => Transform::IDENTITY,
+                None => self.world.world_transform(id),
+            };
+            let skin_joints = skin.map(|skin| {
+                let offset = joints.len() as u32;
+                skin.write_palette(&self.world, joints);
+                offset
+            });
+            // all-zero weights draw the base mesh for free
+            let morph_offset = self
+                .world
+                .get::<MorphWeights>(id)
+                .filter(|w| mesh.is_morphed() && w.is_active())
+                .map(|w| {
+                    let offset = morph_weights.len() as u32;
+                    w.write_weights(mesh.morph_targets.len(), morph_weights);
+                    offset
+                });
+
+            let color = if self.interaction.is_held(id) {
+                [1.4, 1.1, 0.6]
+            } else if self.interaction.is_hovered(id) {
+                [1.3, 1.25, 1.0]
+            } else {
+                [1.0, 1.0, 1.0]
+            };
+
+            props.push(Prop {
+                position: t.position,
+                rotation: t.rotation,
+                scale: t.scale,
+                color,
+                mesh: MeshId(renderable.mesh),
+                material: MaterialId(renderable.material),
+                layers: renderable.layers,
+                joints: skin_joints,
+                morph_weights: morph_offset,
+            });
         }
 
-        self.velocity.y -= 9.8 * dt;
-        self.avatar_pos.y += self.velocity.y * dt;
+        for (hand, tracked) in self.interaction.hands.iter().zip(&self.hands) {
+            if tracked.valid {
+                continue;
+            }
 
-        if self.avatar_pos.y <= 0.0 {
-            self.avatar_pos.y = 0.0;
-            self.velocity.y = 0.0;
-            self.grounded = true;
-            self.jump_count = 0;
+            let t = self.world.world_transform(hand.entity);
+            props.push(Prop {
+                position: t.position,
+                rotation: t.rotation,
+                scale: Vec3::new(0.06, 0.04, 0.1),
+                color: [1.0, 1.0, 1.0],
+                mesh: MeshId::CUBE,
+                material: self.scene_materials.skin,
+                layers: RenderLayers::DEFAULT,
+                joints: None,
+                morph_weights: None,
+            });
         }
 
-        self.camera.target = self.avatar_pos;
+        props
     }
 
-    pub fn render(&mut self) {
-        let avatar_world_props: Vec<Prop> = self
-            .avatar_parts
+    fn hand_meshes(&self) -> Vec<SceneMesh> {
+        self.hands
             .iter()
-            .map(|p| Prop {
-                position: self.avatar_pos + p.position,
-                scale: p.scale,
+            .filter(|h| h.valid)
+            .map(|h| {
+                let (vertices, indices) = hand_mesh(h, [0.9, 0.8, 0.7]);
+                SceneMesh::new(RenderLayers::DEFAULT, vertices, indices)
+            })
+            .collect()
+    }
+
+    /// Play-area boundary, UI lasers and the teleport arc with its target
+    /// marker (green when the target is walkable), plus the tracking-space
+    /// origin on the debug layer.
+    fn scene_lines(&self) -> Vec<SceneMesh> {
+        let to_world = self.controller.tracking_to_world();
+        let mut lines = self.boundary.lines(to_world);
+
+        for (from, to) in self.ui.lasers() {
+            lines.extend(polyline(&[from, to], [0.6, 0.8, 1.0]));
+        }
+
+        if let Some(arc) = &self.locomotion.arc {
+            let color = if arc.is_valid() { [0.2, 0.9, 0.3] } else { [0.9, 0.2, 0.2] };
+            lines.extend(polyline(&arc.points, color));
+
+            if let Some(target) = arc.target {
+                let ring: Vec<Vec3> = (0..=16)
+                    .map(|i| {
+                        let a = i as f32 / 16.0 * std::f32::consts::TAU;
+                        target + Vec3::new(a.cos() * 0.3, 0.01, a.sin() * 0.3)
+                    })
+                    .collect();
+                lines.extend(polyline(&ring, color));
+            }
+        }
+
+        let origin = to_world.transform_point3(Vec3::ZERO);
+        let debug: Vec<Vertex> = [
+            (Vec3::X, [1.0, 0.2, 0.2]),
+            (Vec3::Y, [0.2, 1.0, 0.2]),
+            (Vec3::Z, [0.2, 0.4, 1.0]),
+        ]
+        .into_iter()
+        .flat_map(|(axis, color)| polyline(&[origin, to_world.transform_point3(axis * 0.3)], color))
+        .collect();
+
+        vec![
+            SceneMesh::lines(RenderLayers::DEFAULT, lines),
+            SceneMesh::lines(RenderLayers::DEBUG, debug),
+        ]
+    }
+
+    /// Mirrors in front of `eye` and within range, nearest first, capped
+    /// at `mirrors.max_per_frame`.
+    fn visible_mirrors(&self, eye: Vec3) -> Vec<MirrorDraw> {
+        let mut mirrors: Vec<(f32, MirrorDraw)> = self
+            .world
+            .query::<Mirror>()
+            .into_iter()
+            .map(|(id, mirror)| {
+                let t = self.world.world_transform(id);
+                MirrorDraw {
+                    id,
+                    transform: Mat4::from_rotation_translation(t.rotation, t.position),
+                    size: mirror.size,
+                    tint: mirror.tint,
+                    layers: mirror.layers,
+                    reflects: mirror.reflects,
+                }
             })
+            .filter(|m| m.plane().dot(eye.extend(1.0)) > 0.0)
+            .map(|m| (m.center().distance(eye), m))
+            .filter(|(d, _)| *d <= self.mirrors.max_distance)
             .collect();
 
-        self.frame.render(
-            &mut self.ctx,
-            &self.camera,
-            self.avatar_pos,
-            self.avatar_yaw,
-            &avatar_world_props,
+        mirrors.sort_by(|a, b| a.0.total_cmp(&b.0));
+        mirrors.truncate(self.mirrors.max_per_frame);
+        mirrors.into_iter().map(|(_, m)| m).collect()
+    }
+
+    /// Point and spot lights in world space.
+    fn scene_lights(&self) -> Vec<LightDraw> {
+        let points = self
+            .world
+            .query::<PointLight>()
+            .into_iter()
+            .map(|(id, light)| LightDraw::point(self.world.world_matrix(id), light));
+        let spots = self
+            .world
+            .query::<SpotLight>()
+            .into_iter()
+            .map(|(id, light)| LightDraw::spot(self.world.world_matrix(id), light));
+
+        points.chain(spots).collect()
+    }
+
+    /// `lighting` with its sun replaced by the oldest `DirectionalLight`
+    /// entity, if there is one.
+    fn scene_lighting(&self) -> Lighting {
+        let sun = self
+            .world
+            .query::<DirectionalLight>()
+            .into_iter()
+            .min_by_key(|(id, _)| *id);
+
+        match sun {
+            Some((id, light)) => Lighting {
+                sun_direction: self.world.world_transform(id).rotation * Vec3::Z,
+                sun_color: light.color,
+                sun_intensity: light.intensity,
+                ..self.lighting
+            },
+            None => self.lighting,
+        }
+    }
+
+    pub fn render(&mut self) {
+        let (mut joints, mut morph_weights) = (Vec::new(), Vec::new());
+        let props = self.avatar_world_props(&mut joints, &mut morph_weights);
+        let lighting = self.scene_lighting();
+        let lights = self.scene_lights();
+        let lines = self.scene_lines();
+        let meshes = self.hand_meshes();
+        let mirrors = self.visible_mirrors(self.camera.view_matrix().inverse().w_axis.truncate());
+
+        let scene = FrameScene {
+            props: &props,
+            materials: &self.materials,
+            mesh_library: &self.meshes,
+            lighting: &lighting,
+            lights: &lights,
+            shadows: &self.shadows,
+            sky: &self.sky,
+            lines: &lines,
+            joints: &joints,
+            morph_weights: &morph_weights,
+            meshes: &meshes,
+            panels: &self.ui.panels,
+            mirrors: &mirrors,
+            mirror_scale: self.mirrors.resolution_scale,
+            vignette: self.locomotion.vignette(),
+        };
+
+        let before = GpuCounters::now();
+        self.frame.render(&mut self.ctx, &self.camera, self.desktop_layers, &scene);
+        self.frame_allocations = GpuCounters::now().since(&before);
+    }
+
+    /// Renders one view per eye into external targets of `format`.
+    pub fn render_stereo(&mut self, format: wgpu::TextureFormat, views: &[RenderView]) {
+        let (mut joints, mut morph_weights) = (Vec::new(), Vec::new());
+        let props = self.avatar_world_props(&mut joints, &mut morph_weights);
+        let lighting = self.scene_lighting();
+        let lights = self.scene_lights();
+        let lines = self.scene_lines();
+        let meshes = self.hand_meshes();
+
+        // mirrors are picked once for both eyes
+        let eye = views
+            .iter()
+            .map(|v| v.view.inverse().w_axis.truncate())
+            .sum::<Vec3>()
+            / views.len().max(1) as f32;
+        let mirrors = self.visible_mirrors(eye);
+
+        let scene = FrameScene {
+            props: &props,
+            materials: &self.materials,
+            mesh_library: &self.meshes,
+            lighting: &lighting,
+            lights: &lights,
+            shadows: &self.shadows,
+            sky: &self.sky,
+            lines: &lines,
+            joints: &joints,
+            morph_weights: &morph_weights,
+            meshes: &meshes,
+            panels: &self.ui.panels,
+            mirrors: &mirrors,
+            mirror_scale: self.mirrors.resolution_scale,
+            vignette: self.locomotion.vignette(),
+        };
+
+        let before = GpuCounters::now();
+        self.frame.render_stereo(&self.ctx, format, views, &scene);
+        self.frame_allocations = GpuCounters::now().since(&before);
+    }
+
+    /// Imports a glTF/GLB file into the world with its root at `transform`.
+    /// Models with clips get an `Animator` (see `Animator::for_clips`).
+    pub fn load_model(&mut self, path: impl AsRef<Path>, transform: Transform) -> Result<Model, ModelError> {
+        let model = load_model(path, &mut self.world, &mut self.meshes, &mut self.materials)?;
+        self.world.set_transform(model.root, transform);
+
+        let animator = self
+            .world
+            .get::<Animations>(model.root)
+            .and_then(|clips| Animator::for_clips(clips, &self.world));
+        if let Some(animator) = animator {
+            self.world.insert(model.root, animator);
+        }
+
+        Ok(model)
+    }
+
+    /// Replaces the part body (or the previous VRM) with a VRM avatar
+    /// and refits the body's proportions to it.
+    pub fn load_avatar(&mut self, path: impl AsRef<Path>) -> Result<&VrmAvatar, ModelError> {
+        let vrm = VrmAvatar::load(path, &mut self.world, &mut self.meshes, &mut self.materials)?;
+
+        if let Some(old) = self.vrm.take() {
+            self.world.despawn(old.model.root);
+        }
+        self.avatar.proportions = vrm.proportions();
+        self.avatar_file = None;
+
+        Ok(self.vrm.insert(vrm))
+    }
+
+    /// Builds the part body from a definition file (replacing any VRM)
+    /// and reloads it whenever the file changes.
+    pub fn load_avatar_definition(&mut self, path: impl AsRef<Path>) -> Result<(), DefinitionError> {
+        let (file, def) = AvatarFile::open(path)?;
+
+        if let Some(old) = self.vrm.take() {
+            self.world.despawn(old.model.root);
+        }
+        self.avatar.set_definition(def);
+        self.avatar_file = Some(file);
+        Ok(())
+    }
+
+    /// Checks the avatar definition file and rebuilds the part body when
+    /// it changed. Returns the new avatar's name, or why the change was
+    /// rejected (the current avatar stays); `None` when nothing changed.
+    pub fn reload_avatar_definition(&mut self, dt: f32) -> Option<Result<String, DefinitionError>> {
+        let reloaded = self.avatar_file.as_mut()?.poll(dt)?;
+
+        Some(reloaded.map(|def| {
+            let name = def.name.clone();
+            self.avatar.set_definition(def);
+            name
+        }))
+    }
+
+    /// Sets the weight of the named morph target of the mesh `entity`
+    /// draws, giving it `MorphWeights` if it has none. Returns false if
+    /// the mesh has no such target.
+    pub fn set_morph_weight(&mut self, entity: EntityId, target: &str, weight: f32) -> bool {
+        let Some(renderable) = self.world.get::<Renderable>(entity) else {
+            return false;
+        };
+        let mesh = self.meshes.get(MeshId(renderable.mesh));
+        let (Some(index), count) = (mesh.morph_target(target), mesh.morph_targets.len()) else {
+            return false;
+        };
+
+        if self.world.get::<MorphWeights>(entity).is_none() {
+            self.world.insert(entity, MorphWeights::zeros(count));
+        }
+        if let Some(weights) = self.world.get_mut::<MorphWeights>(entity) {
+            weights.set(index, weight);
+        }
+        true
+    }
+
+    /// Buffers, bind groups and textures the last rendered frame created.
+    /// Zero once the scene is steady; anything else is a resource being
+    /// rebuilt every frame, a buffer growing or a new panel/mirror target.
+    pub fn frame_allocations(&self) -> GpuCounters {
+        self.frame_allocations
+    }
+}
+
+/// Table with a few things to pick up in front of the spawn point, and
+/// a mirror behind it.
+fn spawn_props(world: &mut World, materials: &mut MaterialLibrary) {
+    let table_top = materials.add(Material::new("table", [0.5, 0.5, 0.55]).with_roughness_metallic(0.4, 0.6));
+    let wood = materials.add(Material::new("wood", [0.75, 0.55, 0.35]));
+    let glass = materials.add(
+        Material::new("glass", [0.6, 0.85, 1.0])
+            .with_alpha(0.4)
+            .with_roughness_metallic(0.1, 0.0)
+            .with_blend(BlendMode::Blend),
+    );
+
+    let table = world.spawn(
+        "table",
+        Transform::from_position(Vec3::new(0.0, 0.5, 0.6)).with_scale(Vec3::new(1.2, 1.0, 0.6)),
+    );
+    world.insert(table, Renderable::new(MeshId::CUBE.0, table_top.0));
+    world.insert(table, RestSurface::UNIT_CUBE);
+
+    // the last cube is glass
+    for (i, x) in [-0.35_f32, -0.15, 0.05].into_iter().enumerate() {
+        let cube = world.spawn(
+            &format!("cube_{}", i),
+            Transform::from_position(Vec3::new(x, 1.05, 0.45)).with_scale(Vec3::splat(0.1)),
         );
+        let material = if i == 2 { glass } else { wood };
+        world.insert(cube, Renderable::new(MeshId::CUBE.0, material.0));
+        world.insert(cube, Grabbable::new(GrabVolume::UNIT_CUBE));
     }
+
+    // long plank with a handle at each end, for two-handed grabs
+    let plank = world.spawn(
+        "plank",
+        Transform::from_position(Vec3::new(0.35, 1.02, 0.6)).with_scale(Vec3::new(0.08, 0.04, 0.6)),
+    );
+    world.insert(plank, Renderable::new(MeshId::CUBE.0, wood.0));
+    world.insert(
+        plank,
+        Grabbable::new(GrabVolume::UNIT_CUBE)
+            .with_point("near", Transform::from_position(Vec3::new(0.0, 0.0, -0.4)), 0.08)
+            .with_point("far", Transform::from_position(Vec3::new(0.0, 0.0, 0.4)), 0.08)
+            .two_handed(),
+    );
+
+    // facing back towards the spawn point
+    let mirror = world.spawn(
+        "mirror",
+        Transform::from_position_rotation(
+            Vec3::new(0.0, 1.3, 2.2),
+            Quat::from_rotation_y(std::f32::consts::PI),
+        ),
+    );
+    world.insert(mirror, Mirror::new(Vec2::new(1.6, 2.0)));
+
+    // a warm lamp over the table and a magenta spot on the plank
+    let lamp = world.spawn("lamp", Transform::from_position(Vec3::new(-0.4, 1.6, 0.7)));
+    world.insert(lamp, PointLight::new([1.0, 0.75, 0.45], 0.8, 3.0));
+
+    let spot = world.spawn(
+        "spot",
+        Transform::from_position_rotation(
+            Vec3::new(0.5, 2.0, 0.6),
+            Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2),
+        ),
+    );
+    world.insert(spot, SpotLight::new([1.0, 0.3, 0.8], 2.0, 4.0, 0.4));
 }
@@ -0,0 +1,2 @@
+pub mod panel_pipeline;
+pub mod panel_pass;
@@ -0,0 +1,30 @@
+struct VertexInput {
+    @location(0) position : vec3<f32>,
+    @location(1) uv : vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position : vec4<f32>,
+    @location(0) uv : vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> view_proj : mat4x4<f32>;
+
+@group(1) @binding(0)
+var panel_texture : texture_2d<f32>;
+@group(1) @binding(1)
+var panel_sampler : sampler;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out : VertexOutput;
+    out.clip_position = view_proj * vec4<f32>(in.position, 1.0);
+    out.uv = in.uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(panel_texture, panel_sampler, in.uv);
+}
@@ -0,0 +1,43 @@
+use engine_core::RenderLayers;
+use wgpu::*;
+
+use crate::renderer::frame::frame_resources::FrameResources;
+use crate::renderer::panel::panel_pipeline::{PanelPipeline, PanelVertex};
+use crate::renderer::resources::dynamic::{BatchRange, GeometryBatch};
+use crate::ui::{Panel, PanelId};
+
+/// One quad per panel, on the panel's layers, added to `batch` once per
+/// frame for every view to draw.
+pub fn panel_quads(
+    batch: &mut GeometryBatch<PanelVertex>,
+    panels: &[Panel],
+    draws: &mut Vec<(PanelId, BatchRange)>,
+) {
+    batch.clear();
+    draws.clear();
+
+    for panel in panels {
+        let [tl, tr, br, bl] = panel.corners().map(|c| c.to_array());
+        let vertices = [
+            PanelVertex { position: tl, uv: [0.0, 0.0] },
+            PanelVertex { position: tr, uv: [1.0, 0.0] },
+            PanelVertex { position: br, uv: [1.0, 1.0] },
+            PanelVertex { position: bl, uv: [0.0, 1.0] },
+        ];
+
+        let range = batch.push(panel.layers, &vertices, &[0, 3, 2, 0, 2, 1]);
+        draws.push((panel.id, range));
+    }
+}
+
+/// Draws the panel quads on `layers` inside a world pass that already
+/// has the camera bound at group 0.
+pub fn draw_panels<'a>(
+    pass: &mut RenderPass<'a>,
+    pipeline: &'a PanelPipeline,
+    resources: &'a FrameResources,
+    layers: RenderLayers,
+) {
+    pass.set_pipeline(&pipeline.pipeline);
+    resources.draw_panel_quads(pass, layers);
+}
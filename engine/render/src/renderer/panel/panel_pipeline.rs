@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use bytemuck::{Pod, Zeroable};
+use engine_core::RenderLayers;
+use wgpu::*;
+
+use crate::renderer::pipeline::overlay_pipeline::create_overlay_pipeline;
+use crate::renderer::resources::counters::{create_bind_group, create_texture};
+use crate::renderer::resources::dynamic::GeometryBatch;
+use crate::renderer::resources::mesh::Vertex;
+use crate::ui::{Panel, PanelId};
+
+/// Panels are drawn into their own textures in this format, whatever
+/// the view they end up in.
+pub const PANEL_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct PanelVertex {
+    pub position: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+impl PanelVertex {
+    pub fn layout() -> VertexBufferLayout<'static> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<PanelVertex>() as BufferAddress,
+            step_mode: VertexStepMode::Vertex,
+            attributes: &[
+                VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: VertexFormat::Float32x3,
+                },
+                VertexAttribute {
+                    offset: 12,
+                    shader_location: 1,
+                    format: VertexFormat::Float32x2,
+                },
+            ],
+        }
+    }
+}
+
+/// One panel's offscreen texture and the version last drawn into it.
+pub struct PanelTarget {
+    pub view: TextureView,
+    pub bind_group: BindGroup,
+    pub size: (u32, u32),
+    pub version: u64,
+}
+
+/// Offscreen panel textures, shared by every view format. Panels are
+/// redrawn only when their version changes.
+pub struct PanelTextures {
+    pub layout: BindGroupLayout,
+    sampler: Sampler,
+    canvas: RenderPipeline,
+    /// Every stale canvas of a frame, uploaded together.
+    canvases: GeometryBatch<Vertex>,
+    targets: HashMap<PanelId, PanelTarget>,
+}
+
+impl PanelTextures {
+    pub fn new(device: &Device) -> Self {
+        let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("panel_texture_layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("panel_sampler"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+
+        // canvas vertices are already in clip space, like the compass
+        let canvas = create_overlay_pipeline(device, PANEL_FORMAT, PrimitiveTopology::TriangleList);
+
+        Self {
+            layout,
+            sampler,
+            canvas,
+            canvases: GeometryBatch::new(device, "panel_canvas_batch"),
+            targets: HashMap::new(),
+        }
+    }
+
+    pub fn target(&self, id: PanelId) -> Option<&PanelTarget> {
+        self.targets.get(&id)
+    }
+
+    /// Creates, resizes and redraws panel textures, and frees the ones
+    /// whose panel is gone. Submits its own commands.
+    pub fn prepare(&mut self, device: &Device, queue: &Queue, panels: &[Panel]) {
+        self.targets.retain(|id, _| panels.iter().any(|p| p.id == *id));
+
+        let stale: Vec<&Panel> = panels
+            .iter()
+            .filter(|p| self.targets.get(&p.id).map(|t| t.version) != Some(p.version()))
+            .collect();
+
+        if stale.is_empty() {
+            return;
+        }
+
+        self.canvases.clear();
+        let mut ranges = Vec::with_capacity(stale.len());
+
+        for panel in &stale {
+            let size = (panel.resolution.x.max(1), panel.resolution.y.max(1));
+            if self.targets.get(&panel.id).map(|t| t.size) != Some(size) {
+                let target = self.create_target(device, size);
+                self.targets.insert(panel.id, target);
+            }
+
+            let canvas = panel.draw();
+            ranges.push(self.canvases.push(RenderLayers::ALL, &canvas.vertices, &canvas.indices));
+        }
+
+        self.canvases.upload(device, queue);
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("panel_encoder"),
+        });
+
+        for (panel, range) in stale.iter().zip(&ranges) {
+            let target = self.targets.get_mut(&panel.id).unwrap();
+
+            {
+                let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                    label: Some("panel_canvas_pass"),
+                    color_attachments: &[Some(RenderPassColorAttachment {
+                        view: &target.view,
+                        resolve_target: None,
+                        ops: Operations {
+                            load: LoadOp::Clear(Color::TRANSPARENT),
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                });
+
+                pass.set_pipeline(&self.canvas);
+                self.canvases.bind(&mut pass);
+                self.canvases.draw(&mut pass, range);
+            }
+
+            target.version = panel.version();
+        }
+
+        queue.submit(Some(encoder.finish()));
+    }
+
+    fn create_target(&self, device: &Device, size: (u32, u32)) -> PanelTarget {
+        let texture = create_texture(device, &TextureDescriptor {
+            label: Some("panel_texture"),
+            size: Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: PANEL_FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&TextureViewDescriptor::default());
+
+        let bind_group = create_bind_group(device, &BindGroupDescriptor {
+            label: Some("panel_bind_group"),
+            layout: &self.layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+
+        PanelTarget {
+            view,
+            bind_group,
+            size,
+            version: 0,
+        }
+    }
+}
+
+/// Textured, depth-tested quads showing the panel textures in the world.
+pub struct PanelPipeline {
+    pub pipeline: RenderPipeline,
+}
+
+impl PanelPipeline {
+    pub fn new(
+        device: &Device,
+        format: TextureFormat,
+        camera_layout: &BindGroupLayout,
+        texture_layout: &BindGroupLayout,
+    ) -> Self {
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("panel_shader"),
+            source: ShaderSource::Wgsl(include_str!("panel.wgsl").into()),
+        });
+
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("panel_pipeline_layout"),
+            bind_group_layouts: &[camera_layout, texture_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("panel_pipeline"),
+            layout: Some(&layout),
+
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[PanelVertex::layout()],
+            },
+
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+
+            primitive: PrimitiveState::default(),
+            depth_stencil: Some(DepthStencilState {
+                format: TextureFormat::Depth24Plus,
+                depth_write_enabled: true,
+                depth_compare: CompareFunction::Less,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            multisample: MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self { pipeline }
+    }
+}
@@ -1,38 +0,0 @@
-pub struct BindGroupLayouts {
-    pub camera: wgpu::BindGroupLayout,
-    pub material: wgpu::BindGroupLayout,
-}
-
-impl BindGroupLayouts {
-    pub fn new(device: &wgpu::Device) -> Self {
-        let camera = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
-            label: Some("camera_layout"),
-        });
-
-        let material = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
-            label: Some("material_layout"),
-        });
-
-        Self { camera, material }
-    }
-}
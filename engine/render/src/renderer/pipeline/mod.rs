@@ -1,4 +1,5 @@
 use crate::renderer::context::device::RenderDevice;
+use crate::renderer::material::BlendMode;
 
 pub mod pipeline;
 pub mod overlay_pipeline;
@@ -7,7 +8,19 @@ use pipeline::create_pipeline;
 use overlay_pipeline::create_overlay_pipeline;
 
 pub struct RenderPipelineBundle {
+    /// Opaque materials.
     pub main: wgpu::RenderPipeline,
+    /// `BlendMode::Blend` materials; depth-tested but not written.
+    pub blend: wgpu::RenderPipeline,
+    /// `BlendMode::Additive` materials.
+    pub additive: wgpu::RenderPipeline,
+    /// The same three for skinned meshes. Every world pipeline has the
+    /// deform bindings (joint palette and morph targets) at group 3.
+    pub skinned_main: wgpu::RenderPipeline,
+    pub skinned_blend: wgpu::RenderPipeline,
+    pub skinned_additive: wgpu::RenderPipeline,
+    /// Same shader as `main`, drawn as a line list (grid, debug arcs).
+    pub lines: wgpu::RenderPipeline,
     pub overlay: wgpu::RenderPipeline,
 }
 
@@ -16,18 +29,83 @@ impl RenderPipelineBundle {
         device: &RenderDevice,
         config: &wgpu::SurfaceConfiguration,
         camera_layout: &wgpu::BindGroupLayout,
+        material_layout: &wgpu::BindGroupLayout,
+        lighting_layout: &wgpu::BindGroupLayout,
+        deform_layout: &wgpu::BindGroupLayout,
     ) -> Self {
-        let main = create_pipeline(
-            &device.device,
-            config,
+        Self::with_format(
+            device,
+            config.format,
             camera_layout,
+            material_layout,
+            lighting_layout,
+            deform_layout,
+        )
+    }
+
+    pub fn with_format(
+        device: &RenderDevice,
+        format: wgpu::TextureFormat,
+        camera_layout: &wgpu::BindGroupLayout,
+        material_layout: &wgpu::BindGroupLayout,
+        lighting_layout: &wgpu::BindGroupLayout,
+        deform_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let layouts = [camera_layout, material_layout, lighting_layout, deform_layout];
+        let triangles = |blend, skinned| {
+            create_pipeline(
+                &device.device,
+                format,
+                &layouts,
+                wgpu::PrimitiveTopology::TriangleList,
+                blend,
+                skinned,
+            )
+        };
+
+        let main = triangles(BlendMode::Opaque, false);
+        let blend = triangles(BlendMode::Blend, false);
+        let additive = triangles(BlendMode::Additive, false);
+        let skinned_main = triangles(BlendMode::Opaque, true);
+        let skinned_blend = triangles(BlendMode::Blend, true);
+        let skinned_additive = triangles(BlendMode::Additive, true);
+
+        let lines = create_pipeline(
+            &device.device,
+            format,
+            &layouts,
+            wgpu::PrimitiveTopology::LineList,
+            BlendMode::Opaque,
+            false,
         );
 
         let overlay = create_overlay_pipeline(
             &device.device,
-            config,
+            format,
+            wgpu::PrimitiveTopology::LineList, // IMPORTANT for compass
         );
 
-        Self { main, overlay }
+        Self {
+            main,
+            blend,
+            additive,
+            skinned_main,
+            skinned_blend,
+            skinned_additive,
+            lines,
+            overlay,
+        }
+    }
+
+    /// The triangle pipeline for a material's blend mode.
+    pub fn for_blend(&self, blend: BlendMode, skinned: bool) -> &wgpu::RenderPipeline {
+        match (blend, skinned) {
+            (BlendMode::Opaque, false) => &self.main,
+            (BlendMode::Blend, false) => &self.blend,
+            (BlendMode::Additive, false) => &self.additive,
+            (BlendMode::Opaque, true) => &self.skinned_main,
+            (BlendMode::Blend, true) => &self.skinned_blend,
+            (BlendMode::Additive, true) => &self.skinned_additive,
+        }
     }
 }
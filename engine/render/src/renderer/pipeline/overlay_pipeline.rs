@@ -2,7 +2,8 @@ use crate::renderer::resources::mesh::Vertex;
 
 pub fn create_overlay_pipeline(
     device: &wgpu::Device,
-    config: &wgpu::SurfaceConfiguration,
+    format: wgpu::TextureFormat,
+    topology: wgpu::PrimitiveTopology,
 ) -> wgpu::RenderPipeline {
     let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: Some("overlay_shader"),
@@ -31,14 +32,14 @@ pub fn create_overlay_pipeline(
             module: &shader,
             entry_point: "fs_main",
             targets: &[Some(wgpu::ColorTargetState {
-                format: config.format,
+                format,
                 blend: Some(wgpu::BlendState::ALPHA_BLENDING),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
         }),
 
         primitive: wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::LineList, // IMPORTANT for compass
+            topology,
             ..Default::default()
         },
 
@@ -1,9 +1,14 @@
-use crate::renderer::resources::mesh::Vertex;
+use crate::renderer::material::BlendMode;
+use crate::renderer::resources::instance::Instance;
+use crate::renderer::resources::mesh::{SkinVertex, Vertex};
 
 pub fn create_pipeline(
     device: &wgpu::Device,
-    config: &wgpu::SurfaceConfiguration,
-    camera_layout: &wgpu::BindGroupLayout,
+    format: wgpu::TextureFormat,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    topology: wgpu::PrimitiveTopology,
+    blend: BlendMode,
+    skinned: bool,
 ) -> wgpu::RenderPipeline {
     let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: Some("main_shader"),
@@ -13,35 +18,60 @@ pub fn create_pipeline(
     let pipeline_layout = device.create_pipeline_layout(
         &wgpu::PipelineLayoutDescriptor {
             label: Some("main_pipeline_layout"),
-            bind_group_layouts: &[camera_layout],
+            bind_group_layouts,
             push_constant_ranges: &[],
         },
     );
 
+    // transparent surfaces are depth-tested against opaque ones but do
+    // not hide each other
+    let (blend_state, depth_write) = match blend {
+        BlendMode::Opaque => (wgpu::BlendState::REPLACE, true),
+        BlendMode::Blend => (wgpu::BlendState::ALPHA_BLENDING, false),
+        BlendMode::Additive => (
+            wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::OVER,
+            },
+            false,
+        ),
+    };
+
+    let buffers = [Vertex::layout(), Instance::layout(), SkinVertex::layout()];
+
     device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
         label: Some("main_pipeline"),
         layout: Some(&pipeline_layout),
 
+        // skinned meshes read joint influences from slot 2 and the joint
+        // palette from group 3
         vertex: wgpu::VertexState {
             module: &shader,
-            entry_point: "vs_main",
-            buffers: &[Vertex::layout()],
+            entry_point: if skinned { "vs_skinned" } else { "vs_main" },
+            buffers: if skinned { &buffers } else { &buffers[..2] },
         },
 
         fragment: Some(wgpu::FragmentState {
             module: &shader,
             entry_point: "fs_main",
             targets: &[Some(wgpu::ColorTargetState {
-                format: config.format,
-                blend: Some(wgpu::BlendState::REPLACE),
+                format,
+                blend: Some(blend_state),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
         }),
 
-        primitive: wgpu::PrimitiveState::default(),
+        primitive: wgpu::PrimitiveState {
+            topology,
+            ..Default::default()
+        },
         depth_stencil: Some(wgpu::DepthStencilState {
             format: wgpu::TextureFormat::Depth24Plus,
-            depth_write_enabled: true,
+            depth_write_enabled: depth_write,
             depth_compare: wgpu::CompareFunction::Less,
             stencil: wgpu::StencilState::default(),
             bias: wgpu::DepthBiasState::default(),
@@ -1,25 +1,440 @@
 struct VertexInput {
     @location(0) position : vec3<f32>,
     @location(1) color : vec3<f32>,
+    @location(2) normal : vec3<f32>,
+    @location(8) uv : vec2<f32>,
+};
+
+// per instance (vertex buffer slot 1)
+struct InstanceInput {
+    @location(3) model_0 : vec4<f32>,
+    @location(4) model_1 : vec4<f32>,
+    @location(5) model_2 : vec4<f32>,
+    @location(6) model_3 : vec4<f32>,
+    @location(7) color : vec3<f32>,
+    // first delta, first weight and target count; no targets = no morph
+    @location(12) morph : vec3<u32>,
+};
+
+// skinned meshes only (vertex buffer slot 2)
+struct SkinInput {
+    @location(9) joints : vec4<u32>,
+    @location(10) weights : vec4<f32>,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position : vec4<f32>,
     @location(0) color : vec3<f32>,
+    @location(1) world_position : vec3<f32>,
+    // zero for unlit geometry
+    @location(2) normal : vec3<f32>,
+    @location(3) uv : vec2<f32>,
+};
+
+struct Camera {
+    view_proj : mat4x4<f32>,
+    position : vec4<f32>,
+    inv_view_proj : mat4x4<f32>,
+    forward : vec3<f32>,
+    cluster_offset : u32,
 };
 
 @group(0) @binding(0)
-var<uniform> view_proj : mat4x4<f32>;
+var<uniform> camera : Camera;
+
+struct Material {
+    base_color : vec4<f32>,
+    emissive : vec3<f32>,
+    roughness : f32,
+    metallic : f32,
+    has_base_color_texture : u32,
+    // toon shading (MToon-like) when non-zero
+    toon : u32,
+    shading_shift : f32,
+    shade_color : vec3<f32>,
+    shading_toony : f32,
+    rim_color : vec3<f32>,
+    rim_fresnel_power : f32,
+};
+
+@group(1) @binding(0)
+var<uniform> material : Material;
+
+@group(1) @binding(1)
+var base_color_texture : texture_2d<f32>;
+
+@group(1) @binding(2)
+var base_color_sampler : sampler;
+
+struct Lighting {
+    sun_direction : vec3<f32>,
+    sun_intensity : f32,
+    sun_color : vec3<f32>,
+    ambient_intensity : f32,
+};
+
+@group(2) @binding(0)
+var<uniform> lighting : Lighting;
+
+struct Shadow {
+    view_proj : array<mat4x4<f32>, 4>,
+    // world-space size of one texel, per cascade
+    texel_size : vec4<f32>,
+    cascade_count : u32,
+    pcf_radius : u32,
+    debug : u32,
+    _pad0 : u32,
+    normal_bias : f32,
+    inv_resolution : f32,
+    _pad1 : vec2<f32>,
+};
+
+@group(2) @binding(1)
+var<uniform> shadow : Shadow;
+
+@group(2) @binding(2)
+var shadow_map : texture_depth_2d_array;
+
+@group(2) @binding(3)
+var shadow_sampler : sampler_comparison;
+
+// the sky prefiltered by roughness (one mip per step) and its irradiance
+@group(2) @binding(4)
+var specular_map : texture_cube<f32>;
+
+@group(2) @binding(5)
+var irradiance_map : texture_cube<f32>;
+
+@group(2) @binding(6)
+var environment_sampler : sampler;
+
+struct Light {
+    position : vec3<f32>,
+    range : f32,
+    radiance : vec3<f32>,
+    // below -1 for point lights
+    cos_outer : f32,
+    direction : vec3<f32>,
+    cos_inner : f32,
+};
+
+@group(2) @binding(7)
+var<storage, read> lights : array<Light>;
+
+// per view, from camera.cluster_offset: an (offset, count) pair per
+// cluster, then the light indices
+@group(2) @binding(8)
+var<storage, read> clusters : array<u32>;
+
+// joint matrices of every skinned instance this frame; an instance's
+// joints start at its joint_offset
+@group(3) @binding(0)
+var<storage, read> joint_palette : array<mat4x4<f32>>;
+
+// morph target deltas of every morphed mesh, vertex-major: a position
+// and a normal delta per target per vertex
+@group(3) @binding(1)
+var<storage, read> morph_deltas : array<vec4<f32>>;
+
+// morph weights of every morphed instance this frame
+@group(3) @binding(2)
+var<storage, read> morph_weights : array<f32>;
+
+// must match lighting/clusters.rs
+const CLUSTERS_X : u32 = 16u;
+const CLUSTERS_Y : u32 = 9u;
+const CLUSTERS_Z : u32 = 24u;
+const CLUSTER_NEAR : f32 = 0.1;
+const CLUSTER_FAR : f32 = 60.0;
+
+const PI : f32 = 3.14159265;
+
+@vertex
+fn vs_main(
+    in: VertexInput,
+    instance: InstanceInput,
+    @builtin(vertex_index) vertex: u32,
+) -> VertexOutput {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    return transform_vertex(morph_vertex(in, vertex, instance.morph), model, instance.color);
+}
 
 @vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
+fn vs_skinned(
+    in: VertexInput,
+    skin: SkinInput,
+    instance: InstanceInput,
+    @location(11) joint_offset: u32,
+    @builtin(vertex_index) vertex: u32,
+) -> VertexOutput {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    let morphed = morph_vertex(in, vertex, instance.morph);
+    return transform_vertex(morphed, model * skin_matrix(skin, joint_offset), instance.color);
+}
+
+/// The vertex with the instance's weighted morph targets added, before
+/// skinning.
+fn morph_vertex(in : VertexInput, vertex : u32, morph : vec3<u32>) -> VertexInput {
+    var out = in;
+    let base = morph.x + vertex * morph.z * 2u;
+    for (var i = 0u; i < morph.z; i = i + 1u) {
+        let w = morph_weights[morph.y + i];
+        if (w != 0.0) {
+            out.position = out.position + morph_deltas[base + i * 2u].xyz * w;
+            out.normal = out.normal + morph_deltas[base + i * 2u + 1u].xyz * w;
+        }
+    }
+    return out;
+}
+
+/// Blend of up to four joint matrices; weights are renormalised so
+/// exporters rounding them do not shrink the mesh.
+fn skin_matrix(skin : SkinInput, offset : u32) -> mat4x4<f32> {
+    let w = skin.weights / max(dot(skin.weights, vec4<f32>(1.0)), 1e-5);
+    return joint_palette[offset + skin.joints.x] * w.x
+        + joint_palette[offset + skin.joints.y] * w.y
+        + joint_palette[offset + skin.joints.z] * w.z
+        + joint_palette[offset + skin.joints.w] * w.w;
+}
+
+fn transform_vertex(in : VertexInput, model : mat4x4<f32>, color : vec3<f32>) -> VertexOutput {
+    let world = model * vec4<f32>(in.position, 1.0);
+
+    // inverse transpose of the upper 3x3 (up to scale) keeps normals
+    // perpendicular under non-uniform scale
+    let m0 = model[0].xyz;
+    let m1 = model[1].xyz;
+    let m2 = model[2].xyz;
+    let cofactor = mat3x3<f32>(cross(m1, m2), cross(m2, m0), cross(m0, m1));
+    let normal = cofactor * in.normal * sign(dot(m0, cross(m1, m2)));
+
     var out : VertexOutput;
-    out.clip_position = view_proj * vec4<f32>(in.position, 1.0);
-    out.color = in.color;
+    out.clip_position = camera.view_proj * world;
+    out.color = in.color * color;
+    out.world_position = world.xyz;
+    out.normal = normal;
+    out.uv = in.uv;
     return out;
 }
 
+/// Index of the first (sharpest) cascade covering `p`, or
+/// `cascade_count` if none does.
+fn shadow_cascade(p : vec3<f32>) -> u32 {
+    for (var i = 0u; i < shadow.cascade_count; i = i + 1u) {
+        let clip = shadow.view_proj[i] * vec4<f32>(p, 1.0);
+        if (all(abs(clip.xy) < vec2<f32>(0.98)) && clip.z > 0.0 && clip.z < 1.0) {
+            return i;
+        }
+    }
+    return shadow.cascade_count;
+}
+
+/// Fraction of sunlight reaching `p`: 1 = lit, 0 = shadowed.
+fn sun_visibility(p : vec3<f32>, n : vec3<f32>, cascade : u32) -> f32 {
+    if (cascade >= shadow.cascade_count) {
+        return 1.0;
+    }
+
+    // push the receiver off its surface to avoid acne
+    let offset = n * shadow.texel_size[cascade] * shadow.normal_bias;
+    let clip = shadow.view_proj[cascade] * vec4<f32>(p + offset, 1.0);
+    let uv = clip.xy * vec2<f32>(0.5, -0.5) + 0.5;
+
+    let radius = i32(shadow.pcf_radius);
+    var lit = 0.0;
+    for (var y = -radius; y <= radius; y = y + 1) {
+        for (var x = -radius; x <= radius; x = x + 1) {
+            let tap = uv + vec2<f32>(f32(x), f32(y)) * shadow.inv_resolution;
+            lit = lit + textureSampleCompareLevel(shadow_map, shadow_sampler, tap, i32(cascade), clip.z);
+        }
+    }
+
+    let taps = f32((2 * radius + 1) * (2 * radius + 1));
+    return lit / taps;
+}
+
+fn cascade_tint(cascade : u32) -> vec3<f32> {
+    switch (cascade) {
+        case 0u: { return vec3<f32>(1.0, 0.4, 0.4); }
+        case 1u: { return vec3<f32>(0.4, 1.0, 0.4); }
+        case 2u: { return vec3<f32>(0.4, 0.4, 1.0); }
+        case 3u: { return vec3<f32>(1.0, 1.0, 0.4); }
+        default: { return vec3<f32>(1.0); }
+    }
+}
+
+fn distribution_ggx(n_dot_h : f32, a : f32) -> f32 {
+    let a2 = a * a;
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * d * d);
+}
+
+/// Smith-GGX geometry term divided by `4 n.l n.v`.
+fn visibility_smith(n_dot_v : f32, n_dot_l : f32, roughness : f32) -> f32 {
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    let g_v = n_dot_v / (n_dot_v * (1.0 - k) + k);
+    let g_l = n_dot_l / (n_dot_l * (1.0 - k) + k);
+    return g_v * g_l / max(4.0 * n_dot_v * n_dot_l, 1e-4);
+}
+
+fn fresnel_schlick(cos_theta : f32, f0 : vec3<f32>) -> vec3<f32> {
+    return f0 + (vec3<f32>(1.0) - f0) * pow(1.0 - cos_theta, 5.0);
+}
+
+fn fresnel_schlick_roughness(cos_theta : f32, f0 : vec3<f32>, roughness : f32) -> vec3<f32> {
+    return f0 + (max(vec3<f32>(1.0 - roughness), f0) - f0) * pow(1.0 - cos_theta, 5.0);
+}
+
+/// Analytic fit of the split-sum environment BRDF (Karis 2014), in
+/// place of a lookup texture.
+fn environment_brdf(f0 : vec3<f32>, roughness : f32, n_dot_v : f32) -> vec3<f32> {
+    let c0 = vec4<f32>(-1.0, -0.0275, -0.572, 0.022);
+    let c1 = vec4<f32>(1.0, 0.0425, 1.04, -0.04);
+    let r = roughness * c0 + c1;
+    let a004 = min(r.x * r.x, exp2(-9.28 * n_dot_v)) * r.x + r.y;
+    let ab = vec2<f32>(-1.04, 1.04) * a004 + r.zw;
+    return f0 * ab.x + vec3<f32>(ab.y);
+}
+
+/// What the BRDF needs to know about the shaded point.
+struct Surface {
+    diffuse_color : vec3<f32>,
+    f0 : vec3<f32>,
+    roughness : f32,
+    n : vec3<f32>,
+    v : vec3<f32>,
+    n_dot_v : f32,
+};
+
+fn surface(albedo : vec3<f32>, n : vec3<f32>, v : vec3<f32>) -> Surface {
+    var s : Surface;
+    s.diffuse_color = albedo * (1.0 - material.metallic);
+    s.f0 = mix(vec3<f32>(0.04), albedo, material.metallic);
+    s.roughness = clamp(material.roughness, 0.04, 1.0);
+    s.n = n;
+    s.v = v;
+    s.n_dot_v = max(dot(n, v), 1e-4);
+    return s;
+}
+
+/// Metallic/roughness PBR (GGX specular, Lambert diffuse) for light of
+/// `radiance` arriving from `l`. A radiance of 1 from straight above
+/// lights a white surface to 1.
+fn direct_light(s : Surface, l : vec3<f32>, radiance : vec3<f32>) -> vec3<f32> {
+    let h = normalize(l + s.v);
+    let n_dot_l = max(dot(s.n, l), 0.0);
+    let n_dot_h = max(dot(s.n, h), 0.0);
+    let a = s.roughness * s.roughness;
+
+    let f = fresnel_schlick(max(dot(h, s.v), 0.0), s.f0);
+    let specular = distribution_ggx(n_dot_h, a) * visibility_smith(s.n_dot_v, n_dot_l, s.roughness) * f;
+    let diffuse = (vec3<f32>(1.0) - f) * s.diffuse_color / PI;
+
+    return (diffuse + specular) * radiance * PI * n_dot_l;
+}
+
+/// MToon-style banding: the base color where lit, the shade color
+/// elsewhere, with a boundary as hard as `shading_toony`. `visibility`
+/// (shadows) darkens towards the shade color rather than black.
+fn toon_light(s : Surface, l : vec3<f32>, radiance : vec3<f32>, visibility : f32) -> vec3<f32> {
+    let edge = 1.0 - material.shading_toony;
+    let lit = smoothstep(-edge, edge, dot(s.n, l) + material.shading_shift) * visibility;
+    return mix(material.shade_color * s.diffuse_color, s.diffuse_color, lit) * radiance;
+}
+
+/// Light from one direction in the material's shading model.
+fn shade(s : Surface, l : vec3<f32>, radiance : vec3<f32>, visibility : f32) -> vec3<f32> {
+    if (material.toon != 0u) {
+        return toon_light(s, l, radiance, visibility);
+    }
+    return direct_light(s, l, radiance * visibility);
+}
+
+/// Image-based light from the sky. Toon materials only take the
+/// diffuse part, plus their rim.
+fn ambient_light(s : Surface) -> vec3<f32> {
+    if (material.toon != 0u) {
+        let irradiance = textureSampleLevel(irradiance_map, environment_sampler, s.n, 0.0).rgb;
+        let rim = pow(1.0 - s.n_dot_v, material.rim_fresnel_power);
+        return s.diffuse_color * irradiance + material.rim_color * rim;
+    }
+
+    let f = fresnel_schlick_roughness(s.n_dot_v, s.f0, s.roughness);
+    let irradiance = textureSampleLevel(irradiance_map, environment_sampler, s.n, 0.0).rgb;
+
+    let r = reflect(-s.v, s.n);
+    let lod = s.roughness * f32(textureNumLevels(specular_map) - 1u);
+    let prefiltered = textureSampleLevel(specular_map, environment_sampler, r, lod).rgb;
+
+    return (vec3<f32>(1.0) - f) * s.diffuse_color * irradiance
+        + prefiltered * environment_brdf(s.f0, s.roughness, s.n_dot_v);
+}
+
+/// Index of the froxel holding `p` in the current view.
+fn cluster_index(p : vec3<f32>) -> u32 {
+    let clip = camera.view_proj * vec4<f32>(p, 1.0);
+    let ndc = clamp(clip.xy / clip.w, vec2<f32>(-1.0), vec2<f32>(1.0));
+    let x = min(u32((ndc.x * 0.5 + 0.5) * f32(CLUSTERS_X)), CLUSTERS_X - 1u);
+    let y = min(u32((ndc.y * 0.5 + 0.5) * f32(CLUSTERS_Y)), CLUSTERS_Y - 1u);
+
+    let depth = max(dot(p - camera.position.xyz, camera.forward), CLUSTER_NEAR);
+    let t = log(depth / CLUSTER_NEAR) / log(CLUSTER_FAR / CLUSTER_NEAR);
+    let z = min(u32(t * f32(CLUSTERS_Z)), CLUSTERS_Z - 1u);
+
+    return (z * CLUSTERS_Y + y) * CLUSTERS_X + x;
+}
+
+/// Point and spot lights whose range reaches `p`'s cluster.
+fn local_lights(s : Surface, p : vec3<f32>) -> vec3<f32> {
+    let cluster = camera.cluster_offset + cluster_index(p) * 2u;
+    let first = camera.cluster_offset + clusters[cluster];
+    let count = clusters[cluster + 1u];
+
+    var color = vec3<f32>(0.0);
+    for (var i = 0u; i < count; i = i + 1u) {
+        let light = lights[clusters[first + i]];
+        let to_light = light.position - p;
+        let distance2 = max(dot(to_light, to_light), 1e-4);
+        let l = to_light * inverseSqrt(distance2);
+
+        // inverse square, windowed to reach zero at the range
+        let ratio = distance2 / (light.range * light.range);
+        let window = clamp(1.0 - ratio * ratio, 0.0, 1.0);
+        var attenuation = window * window / distance2;
+
+        if (light.cos_outer >= -1.0) {
+            attenuation = attenuation * smoothstep(light.cos_outer, light.cos_inner, dot(-l, light.direction));
+        }
+
+        color = color + shade(s, l, light.radiance * attenuation, 1.0);
+    }
+    return color;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(in.color, 1.0);
+    // white when the material has no texture
+    let texel = textureSample(base_color_texture, base_color_sampler, in.uv);
+    let albedo = in.color * material.base_color.rgb * texel.rgb;
+
+    var color = albedo;
+    if (dot(in.normal, in.normal) > 1e-8) {
+        let n = normalize(in.normal);
+        let v = normalize(camera.position.xyz - in.world_position);
+        let s = surface(albedo, n, v);
+
+        let cascade = shadow_cascade(in.world_position);
+        let sun = lighting.sun_color * lighting.sun_intensity;
+        let visibility = sun_visibility(in.world_position, n, cascade);
+
+        color = shade(s, normalize(lighting.sun_direction), sun, visibility)
+            + local_lights(s, in.world_position)
+            + ambient_light(s) * lighting.ambient_intensity;
+
+        if (shadow.debug != 0u) {
+            color = color * cascade_tint(cascade);
+        }
+    }
+
+    return vec4<f32>(color + material.emissive, material.base_color.a * texel.a);
 }
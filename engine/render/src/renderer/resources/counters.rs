@@ -0,0 +1,78 @@
+use std::cell::Cell;
+use std::thread::LocalKey;
+
+use wgpu::util::DeviceExt;
+
+/*
+   Every buffer, bind group and texture the renderer creates goes
+   through the helpers below, so frame cost that scales with allocations
+   shows up in `GpuCounters`. A steady-state frame should create none.
+
+   The totals are per thread: a renderer creates everything on the thread
+   it renders on, so its snapshots only see its own work even while other
+   renderers (parallel tests) allocate.
+*/
+
+thread_local! {
+    static BUFFERS: Cell<u64> = const { Cell::new(0) };
+    static BIND_GROUPS: Cell<u64> = const { Cell::new(0) };
+    static TEXTURES: Cell<u64> = const { Cell::new(0) };
+}
+
+fn bump(counter: &'static LocalKey<Cell<u64>>) {
+    counter.with(|c| c.set(c.get() + 1));
+}
+
+/// Running totals of GPU objects created on this thread; subtract two
+/// snapshots to get the cost of what happened in between.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GpuCounters {
+    pub buffers: u64,
+    pub bind_groups: u64,
+    pub textures: u64,
+}
+
+impl GpuCounters {
+    pub fn now() -> Self {
+        Self {
+            buffers: BUFFERS.with(Cell::get),
+            bind_groups: BIND_GROUPS.with(Cell::get),
+            textures: TEXTURES.with(Cell::get),
+        }
+    }
+
+    pub fn since(&self, earlier: &GpuCounters) -> Self {
+        Self {
+            buffers: self.buffers - earlier.buffers,
+            bind_groups: self.bind_groups - earlier.bind_groups,
+            textures: self.textures - earlier.textures,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+pub fn create_buffer(device: &wgpu::Device, desc: &wgpu::BufferDescriptor) -> wgpu::Buffer {
+    bump(&BUFFERS);
+    device.create_buffer(desc)
+}
+
+pub fn create_buffer_init(
+    device: &wgpu::Device,
+    desc: &wgpu::util::BufferInitDescriptor,
+) -> wgpu::Buffer {
+    bump(&BUFFERS);
+    device.create_buffer_init(desc)
+}
+
+pub fn create_bind_group(device: &wgpu::Device, desc: &wgpu::BindGroupDescriptor) -> wgpu::BindGroup {
+    bump(&BIND_GROUPS);
+    device.create_bind_group(desc)
+}
+
+pub fn create_texture(device: &wgpu::Device, desc: &wgpu::TextureDescriptor) -> wgpu::Texture {
+    bump(&TEXTURES);
+    device.create_texture(desc)
+}
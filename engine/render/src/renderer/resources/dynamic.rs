@@ -0,0 +1,146 @@
+use std::marker::PhantomData;
+
+use bytemuck::Pod;
+use engine_core::RenderLayers;
+
+use crate::renderer::resources::counters::create_buffer;
+
+/* =========================================================
+   DYNAMIC BUFFER
+   ========================================================= */
+
+/// A GPU buffer rewritten with `queue.write_buffer`. It only grows
+/// (to the next power of two), so once a scene has been seen it is
+/// never reallocated.
+pub struct DynamicBuffer {
+    pub buffer: wgpu::Buffer,
+    capacity: u64,
+    usage: wgpu::BufferUsages,
+    label: &'static str,
+}
+
+impl DynamicBuffer {
+    pub fn new(device: &wgpu::Device, label: &'static str, usage: wgpu::BufferUsages, capacity: u64) -> Self {
+        let usage = usage | wgpu::BufferUsages::COPY_DST;
+        let capacity = capacity.max(wgpu::COPY_BUFFER_ALIGNMENT);
+
+        Self {
+            buffer: Self::allocate(device, label, usage, capacity),
+            capacity,
+            usage,
+            label,
+        }
+    }
+
+    fn allocate(device: &wgpu::Device, label: &'static str, usage: wgpu::BufferUsages, size: u64) -> wgpu::Buffer {
+        create_buffer(
+            device,
+            &wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage,
+                mapped_at_creation: false,
+            },
+        )
+    }
+
+    /// Makes room for `size` bytes. Returns true if the buffer was
+    /// replaced (anything bound to the old one must be rebuilt).
+    pub fn reserve(&mut self, device: &wgpu::Device, size: u64) -> bool {
+        if size <= self.capacity {
+            return false;
+        }
+
+        self.capacity = size.next_power_of_two();
+        self.buffer = Self::allocate(device, self.label, self.usage, self.capacity);
+        true
+    }
+
+    /// Writes `data` at offset 0; `data` must be a multiple of 4 bytes.
+    pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[u8]) -> bool {
+        let grew = self.reserve(device, data.len() as u64);
+        if !data.is_empty() {
+            queue.write_buffer(&self.buffer, 0, data);
+        }
+        grew
+    }
+}
+
+/* =========================================================
+   GEOMETRY BATCH
+   ========================================================= */
+
+/// Part of a batch drawn with one `draw_indexed`.
+#[derive(Clone, Copy, Debug)]
+pub struct BatchRange {
+    pub layers: RenderLayers,
+    pub first_index: u32,
+    pub index_count: u32,
+    pub base_vertex: i32,
+}
+
+/// All of a frame's CPU-built geometry of one vertex type in one vertex
+/// and one index buffer. Filled once per frame and uploaded once; every
+/// view then draws the ranges it wants, so nothing is rewritten between
+/// views (writes would all land before the first pass runs).
+pub struct GeometryBatch<V: Pod> {
+    vertices: Vec<V>,
+    indices: Vec<u16>,
+    vertex_buffer: DynamicBuffer,
+    index_buffer: DynamicBuffer,
+    _vertex: PhantomData<V>,
+}
+
+impl<V: Pod> GeometryBatch<V> {
+    pub fn new(device: &wgpu::Device, label: &'static str) -> Self {
+        Self {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            vertex_buffer: DynamicBuffer::new(device, label, wgpu::BufferUsages::VERTEX, 4096),
+            index_buffer: DynamicBuffer::new(device, label, wgpu::BufferUsages::INDEX, 1024),
+            _vertex: PhantomData,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+    }
+
+    /// Adds a mesh; indices are relative to its own vertices.
+    pub fn push(&mut self, layers: RenderLayers, vertices: &[V], indices: &[u16]) -> BatchRange {
+        let range = BatchRange {
+            layers,
+            first_index: self.indices.len() as u32,
+            index_count: indices.len() as u32,
+            base_vertex: self.vertices.len() as i32,
+        };
+
+        self.vertices.extend_from_slice(vertices);
+        self.indices.extend_from_slice(indices);
+        range
+    }
+
+    pub fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        // write_buffer wants whole 4-byte words
+        if self.indices.len() % 2 == 1 {
+            self.indices.push(0);
+        }
+
+        self.vertex_buffer.write(device, queue, bytemuck::cast_slice(&self.vertices));
+        self.index_buffer.write(device, queue, bytemuck::cast_slice(&self.indices));
+    }
+
+    pub fn bind<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        pass.set_vertex_buffer(0, self.vertex_buffer.buffer.slice(..));
+        pass.set_index_buffer(self.index_buffer.buffer.slice(..), wgpu::IndexFormat::Uint16);
+    }
+
+    /// Draws one range; call `bind` first.
+    pub fn draw(&self, pass: &mut wgpu::RenderPass, range: &BatchRange) {
+        if range.index_count > 0 {
+            let indices = range.first_index..range.first_index + range.index_count;
+            pass.draw_indexed(indices, range.base_vertex, 0..1);
+        }
+    }
+}
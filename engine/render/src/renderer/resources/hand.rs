@@ -0,0 +1,51 @@
+use glam::{Quat, Vec3};
+
+use crate::renderer::resources::mesh::{cube_mesh, Vertex};
+use crate::xr::{HandJoint, HandSkeleton};
+
+/* =========================================================
+   HAND (PROCEDURAL)
+   ========================================================= */
+
+/// One box per finger bone, as thick as the joint radii, plus a palm
+/// slab. `hand` is expected in world space already.
+pub fn hand_mesh(hand: &HandSkeleton, color: [f32; 3]) -> (Vec<Vertex>, Vec<u16>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    if !hand.valid {
+        return (vertices, indices);
+    }
+
+    let mut push_box = |center: Vec3, rotation: Quat, scale: Vec3| {
+        let (verts, inds) = cube_mesh(color);
+        let base = vertices.len() as u16;
+
+        vertices.extend(verts.into_iter().map(|mut v| {
+            v.position = (center + rotation * (Vec3::from(v.position) * scale)).into();
+            // box faces stay axis aligned, so scaling keeps their normals
+            v.normal = (rotation * Vec3::from(v.normal)).into();
+            v
+        }));
+        indices.extend(inds.into_iter().map(|i| base + i));
+    };
+
+    for (a, b) in HandSkeleton::bones() {
+        let (a, b) = (hand.joint(a), hand.joint(b));
+        let dir = b.position - a.position;
+        let width = (a.radius + b.radius).max(0.008);
+
+        // align the box's -Z with the bone, keep +Y on the back of the hand
+        let rotation = Quat::from_rotation_arc(Vec3::NEG_Z, dir.normalize_or_zero());
+        push_box(
+            (a.position + b.position) * 0.5,
+            rotation,
+            Vec3::new(width, width * 0.8, dir.length()),
+        );
+    }
+
+    let palm = hand.joint(HandJoint::Palm);
+    push_box(palm.position, palm.orientation, Vec3::new(0.07, 0.02, 0.08));
+
+    (vertices, indices)
+}
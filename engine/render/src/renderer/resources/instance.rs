@@ -0,0 +1,104 @@
+use bytemuck::{Pod, Zeroable};
+use glam::Mat4;
+
+/* =========================================================
+   INSTANCE
+   ========================================================= */
+
+/// Per-instance data for the world pipeline: the model matrix, a
+/// color multiplied into the vertex colors, for skinned meshes where the
+/// instance's joint matrices start in the frame's palette and, for
+/// morphed ones, where its targets' deltas and weights start. Read from
+/// vertex buffer slot 1, so one mesh is drawn many times in a single call.
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct Instance {
+    pub model: [[f32; 4]; 4],
+    pub color: [f32; 3],
+    pub joint_offset: u32,
+    /// First delta, first weight and target count; zero targets draws
+    /// the base mesh.
+    pub morph: [u32; 3],
+}
+
+impl Instance {
+    /// World-space geometry drawn as is.
+    pub const IDENTITY: Instance = Instance {
+        model: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+        color: [1.0, 1.0, 1.0],
+        joint_offset: 0,
+        morph: [0; 3],
+    };
+
+    pub fn new(model: Mat4, color: [f32; 3]) -> Self {
+        Self {
+            model: model.to_cols_array_2d(),
+            color,
+            joint_offset: 0,
+            morph: [0; 3],
+        }
+    }
+
+    pub fn with_joint_offset(mut self, offset: u32) -> Self {
+        self.joint_offset = offset;
+        self
+    }
+
+    pub fn with_morph(mut self, deltas: u32, weights: u32, targets: u32) -> Self {
+        self.morph = [deltas, weights, targets];
+        self
+    }
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                // model matrix, one column per location
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: 16,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: 32,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: 48,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                // color
+                wgpu::VertexAttribute {
+                    offset: 64,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                // joint offset (skinned pipelines only)
+                wgpu::VertexAttribute {
+                    offset: 76,
+                    shader_location: 11,
+                    format: wgpu::VertexFormat::Uint32,
+                },
+                // morph targets
+                wgpu::VertexAttribute {
+                    offset: 80,
+                    shader_location: 12,
+                    format: wgpu::VertexFormat::Uint32x3,
+                },
+            ],
+        }
+    }
+}
@@ -1,5 +1,10 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::ops::Range;
+
 use bytemuck::{Pod, Zeroable};
-use wgpu::util::DeviceExt;
+use glam::Vec3;
+
+use crate::renderer::resources::counters::create_buffer_init;
 
 /* =========================================================
    VERTEX
@@ -10,9 +15,38 @@ use wgpu::util::DeviceExt;
 pub struct Vertex {
     pub position: [f32; 3],
     pub color: [f32; 3],
+    /// Zero for unlit geometry (lines, gizmos, UI).
+    pub normal: [f32; 3],
+    /// Texture coordinates, (0, 0) at the top left of the image.
+    pub uv: [f32; 2],
 }
 
 impl Vertex {
+    /// Unlit vertex: drawn in its flat color.
+    pub fn new(position: [f32; 3], color: [f32; 3]) -> Self {
+        Self {
+            position,
+            color,
+            normal: [0.0; 3],
+            uv: [0.0; 2],
+        }
+    }
+
+    /// Vertex shaded by the scene lights.
+    pub fn lit(position: [f32; 3], normal: [f32; 3], color: [f32; 3]) -> Self {
+        Self {
+            position,
+            color,
+            normal,
+            uv: [0.0; 2],
+        }
+    }
+
+    pub fn with_uv(mut self, uv: [f32; 2]) -> Self {
+        self.uv = uv;
+        self
+    }
+
     pub fn layout() -> wgpu::VertexBufferLayout<'static> {
         wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
@@ -30,6 +64,51 @@ impl Vertex {
                     shader_location: 1,
                     format: wgpu::VertexFormat::Float32x3,
                 },
+                // normal
+                wgpu::VertexAttribute {
+                    offset: 24,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                // uv (after the instance attributes)
+                wgpu::VertexAttribute {
+                    offset: 36,
+                    shader_location: 8,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+            ],
+        }
+    }
+}
+
+/// Joint influences of a skinned vertex, in a second vertex buffer
+/// (slot 2) next to its `Vertex`.
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct SkinVertex {
+    /// Into the drawing prop's joint palette.
+    pub joints: [u16; 4],
+    pub weights: [f32; 4],
+}
+
+impl SkinVertex {
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SkinVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                // joints
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 9,
+                    format: wgpu::VertexFormat::Uint16x4,
+                },
+                // weights
+                wgpu::VertexAttribute {
+                    offset: 8,
+                    shader_location: 10,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
             ],
         }
     }
@@ -39,10 +118,15 @@ impl Vertex {
    MESH
    ========================================================= */
 
+/// Geometry that does not change (floor grid, library meshes); uploaded
+/// once.
 pub struct Mesh {
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
     pub index_count: u32,
+    pub index_format: wgpu::IndexFormat,
+    /// Joint influences, for skinned meshes.
+    pub skin_buffer: Option<wgpu::Buffer>,
 }
 
 impl Mesh {
@@ -51,7 +135,39 @@ impl Mesh {
         vertices: &[Vertex],
         indices: &[u16],
     ) -> Self {
-        let vertex_buffer = device.create_buffer_init(
+        Self::from_bytes(
+            device,
+            vertices,
+            bytemuck::cast_slice(indices),
+            indices.len() as u32,
+            wgpu::IndexFormat::Uint16,
+        )
+    }
+
+    /// For meshes with more than 65536 vertices (imported models).
+    pub fn with_u32_indices(
+        device: &wgpu::Device,
+        vertices: &[Vertex],
+        indices: &[u32],
+    ) -> Self {
+        Self::from_bytes(
+            device,
+            vertices,
+            bytemuck::cast_slice(indices),
+            indices.len() as u32,
+            wgpu::IndexFormat::Uint32,
+        )
+    }
+
+    fn from_bytes(
+        device: &wgpu::Device,
+        vertices: &[Vertex],
+        indices: &[u8],
+        index_count: u32,
+        index_format: wgpu::IndexFormat,
+    ) -> Self {
+        let vertex_buffer = create_buffer_init(
+            device,
             &wgpu::util::BufferInitDescriptor {
                 label: Some("vertex_buffer"),
                 contents: bytemuck::cast_slice(vertices),
@@ -59,10 +175,11 @@ impl Mesh {
             },
         );
 
-        let index_buffer = device.create_buffer_init(
+        let index_buffer = create_buffer_init(
+            device,
             &wgpu::util::BufferInitDescriptor {
                 label: Some("index_buffer"),
-                contents: bytemuck::cast_slice(indices),
+                contents: indices,
                 usage: wgpu::BufferUsages::INDEX,
             },
         );
@@ -70,14 +187,42 @@ impl Mesh {
         Self {
             vertex_buffer,
             index_buffer,
-            index_count: indices.len() as u32,
+            index_count,
+            index_format,
+            skin_buffer: None,
         }
     }
 
+    /// Adds one joint influence per vertex, for the skinned pipelines.
+    pub fn with_skin(mut self, device: &wgpu::Device, skin: &[SkinVertex]) -> Self {
+        self.skin_buffer = Some(create_buffer_init(
+            device,
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("skin_buffer"),
+                contents: bytemuck::cast_slice(skin),
+                usage: wgpu::BufferUsages::VERTEX,
+            },
+        ));
+        self
+    }
+
+    pub fn is_skinned(&self) -> bool {
+        self.skin_buffer.is_some()
+    }
+
     pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        self.draw_instanced(pass, 0..1);
+    }
+
+    /// Draws the mesh once per instance in `instances`, read from
+    /// whatever is bound at vertex buffer slot 1.
+    pub fn draw_instanced<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, instances: Range<u32>) {
         pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-        pass.draw_indexed(0..self.index_count, 0, 0..1);
+        if let Some(skin) = &self.skin_buffer {
+            pass.set_vertex_buffer(2, skin.slice(..));
+        }
+        pass.set_index_buffer(self.index_buffer.slice(..), self.index_format);
+        pass.draw_indexed(0..self.index_count, 0, instances);
     }
 }
 
@@ -99,14 +244,8 @@ pub fn floor_mesh() -> (Vec<Vertex>, Vec<u16>) {
     for x in -size..=size {
         let x = x as f32 * spacing;
 
-        vertices.push(Vertex {
-            position: [x, 0.0, -size as f32 * spacing],
-            color: grid_color,
-        });
-        vertices.push(Vertex {
-            position: [x, 0.0, size as f32 * spacing],
-            color: grid_color,
-        });
+        vertices.push(Vertex::new([x, 0.0, -size as f32 * spacing], grid_color));
+        vertices.push(Vertex::new([x, 0.0, size as f32 * spacing], grid_color));
 
         indices.push(i);
         indices.push(i + 1);
@@ -117,14 +256,8 @@ pub fn floor_mesh() -> (Vec<Vertex>, Vec<u16>) {
     for z in -size..=size {
         let z = z as f32 * spacing;
 
-        vertices.push(Vertex {
-            position: [-size as f32 * spacing, 0.0, z],
-            color: grid_color,
-        });
-        vertices.push(Vertex {
-            position: [size as f32 * spacing, 0.0, z],
-            color: grid_color,
-        });
+        vertices.push(Vertex::new([-size as f32 * spacing, 0.0, z], grid_color));
+        vertices.push(Vertex::new([size as f32 * spacing, 0.0, z], grid_color));
 
         indices.push(i);
         indices.push(i + 1);
@@ -138,26 +271,94 @@ pub fn floor_mesh() -> (Vec<Vertex>, Vec<u16>) {
    CUBE (TRIANGLES)
    ========================================================= */
 
+/// Unit cube centred on the origin, four vertices per face so each face
+/// has its own normal.
 pub fn cube_mesh(color: [f32; 3]) -> (Vec<Vertex>, Vec<u16>) {
-    let vertices = vec![
-        Vertex { position: [-0.5, -0.5, -0.5], color },
-        Vertex { position: [ 0.5, -0.5, -0.5], color },
-        Vertex { position: [ 0.5,  0.5, -0.5], color },
-        Vertex { position: [-0.5,  0.5, -0.5], color },
-        Vertex { position: [-0.5, -0.5,  0.5], color },
-        Vertex { position: [ 0.5, -0.5,  0.5], color },
-        Vertex { position: [ 0.5,  0.5,  0.5], color },
-        Vertex { position: [-0.5,  0.5,  0.5], color },
+    // normal, then two axes spanning the face (u x v = normal)
+    let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+        ([ 1.0,  0.0,  0.0], [ 0.0,  0.0, -1.0], [ 0.0,  1.0,  0.0]),
+        ([-1.0,  0.0,  0.0], [ 0.0,  0.0,  1.0], [ 0.0,  1.0,  0.0]),
+        ([ 0.0,  1.0,  0.0], [ 1.0,  0.0,  0.0], [ 0.0,  0.0, -1.0]),
+        ([ 0.0, -1.0,  0.0], [ 1.0,  0.0,  0.0], [ 0.0,  0.0,  1.0]),
+        ([ 0.0,  0.0,  1.0], [ 1.0,  0.0,  0.0], [ 0.0,  1.0,  0.0]),
+        ([ 0.0,  0.0, -1.0], [-1.0,  0.0,  0.0], [ 0.0,  1.0,  0.0]),
     ];
 
-    let indices: Vec<u16> = vec![
-        0, 1, 2, 2, 3, 0,
-        4, 5, 6, 6, 7, 4,
-        0, 4, 7, 7, 3, 0,
-        1, 5, 6, 6, 2, 1,
-        3, 2, 6, 6, 7, 3,
-        0, 1, 5, 5, 4, 0,
-    ];
+    let mut vertices = Vec::with_capacity(24);
+    let mut indices = Vec::with_capacity(36);
+
+    for (n, u, v) in faces {
+        let base = vertices.len() as u16;
+
+        for (su, sv) in [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)] {
+            let position = [
+                n[0] * 0.5 + u[0] * su + v[0] * sv,
+                n[1] * 0.5 + u[1] * su + v[1] * sv,
+                n[2] * 0.5 + u[2] * su + v[2] * sv,
+            ];
+            vertices.push(Vertex::lit(position, n, color).with_uv([su + 0.5, 0.5 - sv]));
+        }
+
+        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+
+    (vertices, indices)
+}
+
+/* =========================================================
+   SPHERE AND CAPSULE (TRIANGLES)
+   ========================================================= */
+
+/// Sphere of diameter 1 centred on the origin.
+pub fn sphere_mesh(color: [f32; 3]) -> (Vec<Vertex>, Vec<u16>) {
+    rounded_mesh(color, 0.5)
+}
+
+/// Capsule filling the unit cube along Y: a cylinder of diameter 1 with
+/// rounded ends a quarter of its height each, so it stays a capsule
+/// when stretched.
+pub fn capsule_mesh(color: [f32; 3]) -> (Vec<Vertex>, Vec<u16>) {
+    rounded_mesh(color, 0.25)
+}
+
+/// Surface of revolution about Y filling the unit cube: a cylinder of
+/// radius 0.5 closed by half-ellipsoid caps `cap` tall (0.5 makes a
+/// sphere, with a cylinder of no height).
+fn rounded_mesh(color: [f32; 3], cap: f32) -> (Vec<Vertex>, Vec<u16>) {
+    const SEGMENTS: u16 = 24;
+    const CAP_RINGS: u16 = 8;
+
+    // top pole to the top equator, then the bottom equator to the pole
+    let rings: Vec<(f32, f32)> = (0..=CAP_RINGS)
+        .map(|r| (0.5 - cap, r as f32 / CAP_RINGS as f32 * FRAC_PI_2))
+        .chain((0..=CAP_RINGS).map(|r| (cap - 0.5, FRAC_PI_2 + r as f32 / CAP_RINGS as f32 * FRAC_PI_2)))
+        .collect();
+
+    let mut vertices = Vec::with_capacity(rings.len() * (SEGMENTS as usize + 1));
+    let mut indices = Vec::new();
+
+    for (i, &(centre, theta)) in rings.iter().enumerate() {
+        let (sin_t, cos_t) = theta.sin_cos();
+
+        for s in 0..=SEGMENTS {
+            let phi = s as f32 / SEGMENTS as f32 * TAU;
+            let (sin_p, cos_p) = phi.sin_cos();
+
+            let position = [0.5 * sin_t * cos_p, centre + cap * cos_t, 0.5 * sin_t * sin_p];
+            let normal = Vec3::new(sin_t * cos_p / 0.5, cos_t / cap, sin_t * sin_p / 0.5).normalize();
+            let uv = [s as f32 / SEGMENTS as f32, i as f32 / (rings.len() - 1) as f32];
+            vertices.push(Vertex::lit(position, normal.to_array(), color).with_uv(uv));
+        }
+    }
+
+    let stride = SEGMENTS + 1;
+    for ring in 0..rings.len() as u16 - 1 {
+        for s in 0..SEGMENTS {
+            let a = ring * stride + s;
+            let (b, c, d) = (a + 1, a + stride, a + stride + 1);
+            indices.extend_from_slice(&[a, b, d, a, d, c]);
+        }
+    }
 
     (vertices, indices)
 }
@@ -1 +1,6 @@
+pub mod counters;
+pub mod dynamic;
+pub mod instance;
 pub mod mesh;
+pub mod hand;
+pub mod uniform_ring;
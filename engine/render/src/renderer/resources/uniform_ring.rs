@@ -0,0 +1,103 @@
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+use bytemuck::Pod;
+
+use crate::renderer::resources::counters::{create_bind_group, create_buffer};
+
+/// A uniform buffer with one slot per use in a frame (e.g. one camera
+/// per view), bound once with a dynamic offset per slot. `reset` at the
+/// start of the frame, `reserve` enough slots, then `push` each value.
+pub struct UniformRing<T: Pod> {
+    pub buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+    stride: u64,
+    capacity: u32,
+    next: Cell<u32>,
+    label: &'static str,
+    _value: PhantomData<T>,
+}
+
+impl<T: Pod> UniformRing<T> {
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, label: &'static str, capacity: u32) -> Self {
+        let align = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let size = std::mem::size_of::<T>() as u64;
+        let stride = size.div_ceil(align) * align;
+
+        let (buffer, bind_group) = Self::allocate(device, layout, label, stride, capacity);
+
+        Self {
+            buffer,
+            bind_group,
+            stride,
+            capacity,
+            next: Cell::new(0),
+            label,
+            _value: PhantomData,
+        }
+    }
+
+    fn allocate(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        label: &'static str,
+        stride: u64,
+        capacity: u32,
+    ) -> (wgpu::Buffer, wgpu::BindGroup) {
+        let buffer = create_buffer(
+            device,
+            &wgpu::BufferDescriptor {
+                label: Some(label),
+                size: stride * capacity as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        );
+
+        let bind_group = create_bind_group(
+            device,
+            &wgpu::BindGroupDescriptor {
+                label: Some(label),
+                layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<T>() as u64),
+                    }),
+                }],
+            },
+        );
+
+        (buffer, bind_group)
+    }
+
+    pub fn reset(&self) {
+        self.next.set(0);
+    }
+
+    /// Grows (rarely) so `slots` pushes fit this frame. Call after
+    /// `reset`, before any `push`.
+    pub fn reserve(&mut self, device: &wgpu::Device, layout: &wgpu::BindGroupLayout, slots: u32) {
+        if slots <= self.capacity {
+            return;
+        }
+
+        self.capacity = slots.next_power_of_two();
+        let (buffer, bind_group) = Self::allocate(device, layout, self.label, self.stride, self.capacity);
+        self.buffer = buffer;
+        self.bind_group = bind_group;
+    }
+
+    /// Writes the next slot and returns its dynamic offset. Wraps (and
+    /// overwrites) if more slots are pushed than were reserved.
+    pub fn push(&self, queue: &wgpu::Queue, value: &T) -> u32 {
+        let slot = self.next.get() % self.capacity;
+        self.next.set(self.next.get() + 1);
+
+        let offset = slot as u64 * self.stride;
+        queue.write_buffer(&self.buffer, offset, bytemuck::bytes_of(value));
+        offset as u32
+    }
+}
@@ -0,0 +1,144 @@
+pub mod shadow_pipeline;
+pub mod shadow_pass;
+
+use engine_core::RenderLayers;
+use glam::{Mat4, Vec3, Vec4};
+
+/// Most cascades a shadow map can have.
+pub const MAX_CASCADES: usize = 4;
+
+/// Sun shadows: a cascaded shadow map covering the main view out to
+/// `max_distance`, sampled with PCF.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowSettings {
+    pub enabled: bool,
+    /// Size of each cascade's map in texels.
+    pub resolution: u32,
+    /// 1..=`MAX_CASCADES`; more cascades keep distant shadows sharp.
+    pub cascades: usize,
+    /// Shadows end this far from the viewer.
+    pub max_distance: f32,
+    /// Blend between uniform (0) and logarithmic (1) cascade splits.
+    pub split_lambda: f32,
+    /// Rasterizer depth bias in depth units, and per unit of slope.
+    pub depth_bias: i32,
+    pub slope_bias: f32,
+    /// Receiver offset along its normal, in shadow-map texels.
+    pub normal_bias: f32,
+    /// PCF kernel radius in texels: 0 = one tap, 1 = 3x3, 2 = 5x5.
+    pub pcf_radius: u32,
+    /// What casts shadows. The local head is hidden in the headset but
+    /// still casts.
+    pub casters: RenderLayers,
+    /// Tints the world by cascade.
+    pub debug_cascades: bool,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            resolution: 2048,
+            cascades: 3,
+            max_distance: 30.0,
+            split_lambda: 0.75,
+            depth_bias: 2,
+            slope_bias: 2.0,
+            normal_bias: 1.5,
+            pcf_radius: 1,
+            casters: RenderLayers::DEFAULT | RenderLayers::LOCAL_HEAD,
+            debug_cascades: false,
+        }
+    }
+}
+
+/// One slice of the view frustum as seen from the sun.
+#[derive(Clone, Copy, Debug)]
+pub struct Cascade {
+    pub view_proj: Mat4,
+    /// View distance where this cascade ends.
+    pub far: f32,
+    /// World-space size of one shadow-map texel.
+    pub texel_size: f32,
+}
+
+/// Far distance of each cascade, mixing uniform and logarithmic splits.
+pub fn cascade_splits(near: f32, far: f32, count: usize, lambda: f32) -> Vec<f32> {
+    (1..=count)
+        .map(|i| {
+            let t = i as f32 / count as f32;
+            let uniform = near + (far - near) * t;
+            let log = near * (far / near).powf(t);
+            uniform + (log - uniform) * lambda
+        })
+        .collect()
+}
+
+/// Fits one orthographic sun camera around each slice of the view
+/// frustum. Slices are bounded by spheres, and the spheres snapped to
+/// whole texels, so shadows don't shimmer as the view moves or turns.
+pub fn fit_cascades(settings: &ShadowSettings, sun_direction: Vec3, view: Mat4, proj: Mat4) -> Vec<Cascade> {
+    let to_sun = sun_direction.normalize_or_zero();
+    if to_sun == Vec3::ZERO {
+        return Vec::new();
+    }
+
+    let inv_proj = proj.inverse();
+    let inv_view = view.inverse();
+
+    // near-plane corners in view space; rays from the eye run through them
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(x, y)| {
+        let p = inv_proj * Vec4::new(x, y, 0.0, 1.0);
+        p.truncate() / p.w
+    });
+    let near = -corners[0].z;
+    if near <= 0.0 {
+        return Vec::new();
+    }
+
+    let count = settings.cascades.clamp(1, MAX_CASCADES);
+    let resolution = settings.resolution.max(1) as f32;
+    let splits = cascade_splits(near, settings.max_distance.max(near * 2.0), count, settings.split_lambda);
+
+    // casters between the sun and the slice must land in the map too
+    let caster_margin = 30.0;
+    let up = if to_sun.y.abs() > 0.99 { Vec3::Z } else { Vec3::Y };
+
+    let mut start = near;
+    splits
+        .into_iter()
+        .map(|end| {
+            let points: Vec<Vec3> = [start, end]
+                .into_iter()
+                .flat_map(|d| corners.map(|c| inv_view.transform_point3(c * (d / near))))
+                .collect();
+            start = end;
+
+            let center = points.iter().copied().sum::<Vec3>() / points.len() as f32;
+            let radius = points.iter().map(|p| p.distance(center)).fold(0.0, f32::max);
+            let radius = (radius * 16.0).ceil() / 16.0;
+
+            let light_view = Mat4::look_at_rh(center + to_sun * (radius + caster_margin), center, up);
+            let mut light_proj = Mat4::orthographic_rh(
+                -radius,
+                radius,
+                -radius,
+                radius,
+                0.0,
+                radius * 2.0 + caster_margin,
+            );
+
+            // move the map in whole texels only
+            let origin = (light_proj * light_view).project_point3(Vec3::ZERO) * resolution * 0.5;
+            let snapped = origin.round();
+            light_proj.w_axis.x += (snapped.x - origin.x) * 2.0 / resolution;
+            light_proj.w_axis.y += (snapped.y - origin.y) * 2.0 / resolution;
+
+            Cascade {
+                view_proj: light_proj * light_view,
+                far: end,
+                texel_size: radius * 2.0 / resolution,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,68 @@
+struct VertexInput {
+    @location(0) position : vec3<f32>,
+};
+
+struct SkinInput {
+    @location(9) joints : vec4<u32>,
+    @location(10) weights : vec4<f32>,
+};
+
+struct InstanceInput {
+    @location(3) model_0 : vec4<f32>,
+    @location(4) model_1 : vec4<f32>,
+    @location(5) model_2 : vec4<f32>,
+    @location(6) model_3 : vec4<f32>,
+    @location(12) morph : vec3<u32>,
+};
+
+// the cascade's sun camera
+@group(0) @binding(0)
+var<uniform> view_proj : mat4x4<f32>;
+
+// see the world shader
+@group(1) @binding(0)
+var<storage, read> joint_palette : array<mat4x4<f32>>;
+
+@group(1) @binding(1)
+var<storage, read> morph_deltas : array<vec4<f32>>;
+
+@group(1) @binding(2)
+var<storage, read> morph_weights : array<f32>;
+
+fn morph_position(position : vec3<f32>, vertex : u32, morph : vec3<u32>) -> vec3<f32> {
+    var p = position;
+    let base = morph.x + vertex * morph.z * 2u;
+    for (var i = 0u; i < morph.z; i = i + 1u) {
+        p = p + morph_deltas[base + i * 2u].xyz * morph_weights[morph.y + i];
+    }
+    return p;
+}
+
+@vertex
+fn vs_main(
+    in: VertexInput,
+    instance: InstanceInput,
+    @builtin(vertex_index) vertex: u32,
+) -> @builtin(position) vec4<f32> {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    let position = morph_position(in.position, vertex, instance.morph);
+    return view_proj * model * vec4<f32>(position, 1.0);
+}
+
+@vertex
+fn vs_skinned(
+    in: VertexInput,
+    skin: SkinInput,
+    instance: InstanceInput,
+    @location(11) joint_offset: u32,
+    @builtin(vertex_index) vertex: u32,
+) -> @builtin(position) vec4<f32> {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    let w = skin.weights / max(dot(skin.weights, vec4<f32>(1.0)), 1e-5);
+    let skinning = joint_palette[joint_offset + skin.joints.x] * w.x
+        + joint_palette[joint_offset + skin.joints.y] * w.y
+        + joint_palette[joint_offset + skin.joints.z] * w.z
+        + joint_palette[joint_offset + skin.joints.w] * w.w;
+    let position = morph_position(in.position, vertex, instance.morph);
+    return view_proj * model * skinning * vec4<f32>(position, 1.0);
+}
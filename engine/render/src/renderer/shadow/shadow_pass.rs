@@ -0,0 +1,39 @@
+use engine_core::RenderLayers;
+use glam::Vec3;
+use wgpu::*;
+
+use crate::renderer::context::RenderContext;
+use crate::renderer::frame::frame_resources::FrameResources;
+use crate::renderer::uniforms::camera::CameraUniform;
+
+/// Renders every cascade of the sun shadow map. Runs once per frame,
+/// before any view; eyes and mirror reflections all sample the result.
+pub fn render_shadows(
+    ctx: &RenderContext,
+    encoder: &mut CommandEncoder,
+    resources: &FrameResources,
+    casters: RenderLayers,
+) {
+    let shadows = &resources.shadows;
+
+    for (cascade, target) in shadows.cascades.iter().zip(&shadows.layers) {
+        let camera = CameraUniform::new(cascade.view_proj, Vec3::ZERO);
+        let offset = resources.cameras.push(&ctx.device.queue, &camera);
+
+        let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("shadow_pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: target,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+
+        pass.set_bind_group(0, &resources.cameras.bind_group, &[offset]);
+        resources.draw_shadow_casters(&mut pass, casters);
+    }
+}
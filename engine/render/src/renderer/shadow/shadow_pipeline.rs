@@ -0,0 +1,248 @@
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec3};
+use wgpu::*;
+
+use crate::renderer::resources::counters::{create_buffer, create_texture};
+use crate::renderer::resources::instance::Instance;
+use crate::renderer::resources::mesh::{SkinVertex, Vertex};
+use crate::renderer::shadow::{fit_cascades, Cascade, ShadowSettings, MAX_CASCADES};
+
+pub const SHADOW_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct ShadowUniform {
+    pub view_proj: [[[f32; 4]; 4]; MAX_CASCADES],
+    pub texel_size: [f32; MAX_CASCADES],
+    /// 0 when shadows are off.
+    pub cascade_count: u32,
+    pub pcf_radius: u32,
+    pub debug: u32,
+    pub _pad0: u32,
+    pub normal_bias: f32,
+    pub inv_resolution: f32,
+    pub _pad1: [f32; 2],
+}
+
+/*
+   The cascaded sun shadow map: a depth texture array with one layer per
+   cascade, the depth-only pipeline that renders into it, and the
+   uniform telling the world shader how to sample it.
+
+   The texture and pipeline are rebuilt when resolution, cascade count
+   or bias change; `generation` tells bind groups holding the texture to
+   follow.
+*/
+pub struct ShadowMaps {
+    pub pipeline: RenderPipeline,
+    /// For skinned meshes. Both have the deform bindings (joint palette
+    /// and morph targets) at group 1.
+    pub skinned_pipeline: RenderPipeline,
+    pub buffer: Buffer,
+    pub sampler: Sampler,
+    /// All layers, for sampling.
+    pub view: TextureView,
+    /// One per cascade, for rendering.
+    pub layers: Vec<TextureView>,
+    pub cascades: Vec<Cascade>,
+    pub generation: u64,
+    key: (u32, usize, i32, u32),
+}
+
+impl ShadowMaps {
+    pub fn new(
+        device: &Device,
+        camera_layout: &BindGroupLayout,
+        deform_layout: &BindGroupLayout,
+        settings: &ShadowSettings,
+    ) -> Self {
+        let key = Self::key(settings);
+        let (view, layers) = Self::create_texture(device, key.0, key.1);
+
+        let buffer = create_buffer(
+            device,
+            &BufferDescriptor {
+                label: Some("shadow_buffer"),
+                size: std::mem::size_of::<ShadowUniform>() as BufferAddress,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        );
+
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("shadow_sampler"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            compare: Some(CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        Self {
+            pipeline: create_shadow_pipeline(device, &[camera_layout, deform_layout], settings, false),
+            skinned_pipeline: create_shadow_pipeline(device, &[camera_layout, deform_layout], settings, true),
+            buffer,
+            sampler,
+            view,
+            layers,
+            cascades: Vec::new(),
+            generation: 0,
+            key,
+        }
+    }
+
+    fn key(settings: &ShadowSettings) -> (u32, usize, i32, u32) {
+        (
+            settings.resolution.clamp(1, 8192),
+            settings.cascades.clamp(1, MAX_CASCADES),
+            settings.depth_bias,
+            settings.slope_bias.to_bits(),
+        )
+    }
+
+    fn create_texture(device: &Device, resolution: u32, cascades: usize) -> (TextureView, Vec<TextureView>) {
+        let texture = create_texture(
+            device,
+            &TextureDescriptor {
+                label: Some("shadow_map"),
+                size: Extent3d {
+                    width: resolution,
+                    height: resolution,
+                    depth_or_array_layers: cascades as u32,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: SHADOW_FORMAT,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        );
+
+        let view = texture.create_view(&TextureViewDescriptor {
+            label: Some("shadow_map_view"),
+            dimension: Some(TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+
+        let layers = (0..cascades as u32)
+            .map(|layer| {
+                texture.create_view(&TextureViewDescriptor {
+                    label: Some("shadow_cascade_view"),
+                    dimension: Some(TextureViewDimension::D2),
+                    base_array_layer: layer,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        (view, layers)
+    }
+
+    /// Rebuilds the map and pipeline if the settings call for it.
+    pub fn prepare(
+        &mut self,
+        device: &Device,
+        camera_layout: &BindGroupLayout,
+        deform_layout: &BindGroupLayout,
+        settings: &ShadowSettings,
+    ) {
+        let key = Self::key(settings);
+        if key == self.key {
+            return;
+        }
+
+        if (key.0, key.1) != (self.key.0, self.key.1) {
+            let (view, layers) = Self::create_texture(device, key.0, key.1);
+            self.view = view;
+            self.layers = layers;
+            self.generation += 1;
+        }
+        if (key.2, key.3) != (self.key.2, self.key.3) {
+            self.pipeline = create_shadow_pipeline(device, &[camera_layout, deform_layout], settings, false);
+            self.skinned_pipeline =
+                create_shadow_pipeline(device, &[camera_layout, deform_layout], settings, true);
+        }
+
+        self.key = key;
+    }
+
+    /// Fits the cascades to the main view and uploads them.
+    pub fn update(&mut self, queue: &Queue, settings: &ShadowSettings, sun_direction: Vec3, view: Mat4, proj: Mat4) {
+        self.cascades = if settings.enabled {
+            fit_cascades(settings, sun_direction, view, proj)
+        } else {
+            Vec::new()
+        };
+
+        let mut uniform = ShadowUniform {
+            view_proj: [Mat4::IDENTITY.to_cols_array_2d(); MAX_CASCADES],
+            texel_size: [0.0; MAX_CASCADES],
+            cascade_count: self.cascades.len() as u32,
+            pcf_radius: settings.pcf_radius.min(3),
+            debug: settings.debug_cascades as u32,
+            _pad0: 0,
+            normal_bias: settings.normal_bias,
+            inv_resolution: 1.0 / self.key.0 as f32,
+            _pad1: [0.0; 2],
+        };
+
+        for (i, cascade) in self.cascades.iter().enumerate() {
+            uniform.view_proj[i] = cascade.view_proj.to_cols_array_2d();
+            uniform.texel_size[i] = cascade.texel_size;
+        }
+
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&uniform));
+    }
+}
+
+/// Depth-only pass of the world geometry from the sun.
+fn create_shadow_pipeline(
+    device: &Device,
+    bind_group_layouts: &[&BindGroupLayout],
+    settings: &ShadowSettings,
+    skinned: bool,
+) -> RenderPipeline {
+    let shader = device.create_shader_module(ShaderModuleDescriptor {
+        label: Some("shadow_shader"),
+        source: ShaderSource::Wgsl(include_str!("shadow.wgsl").into()),
+    });
+
+    let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+        label: Some("shadow_pipeline_layout"),
+        bind_group_layouts,
+        push_constant_ranges: &[],
+    });
+
+    let buffers = [Vertex::layout(), Instance::layout(), SkinVertex::layout()];
+
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("shadow_pipeline"),
+        layout: Some(&layout),
+
+        vertex: VertexState {
+            module: &shader,
+            entry_point: if skinned { "vs_skinned" } else { "vs_main" },
+            buffers: if skinned { &buffers } else { &buffers[..2] },
+        },
+
+        fragment: None,
+
+        primitive: PrimitiveState::default(),
+        depth_stencil: Some(DepthStencilState {
+            format: SHADOW_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::LessEqual,
+            stencil: StencilState::default(),
+            bias: DepthBiasState {
+                constant: settings.depth_bias,
+                slope_scale: settings.slope_bias,
+                clamp: 0.0,
+            },
+        }),
+        multisample: MultisampleState::default(),
+        multiview: None,
+    })
+}
@@ -1,2 +1,59 @@
 pub mod skybox_pipeline;
 pub mod skybox_pass;
+
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
+
+/// The procedural sky. Its sun follows `Lighting::sun_direction`. The
+/// sky is baked into the environment cubemaps, which also light the
+/// world, so editing it re-bakes them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sky {
+    /// Linear RGB.
+    pub horizon_color: [f32; 3],
+    pub zenith_color: [f32; 3],
+    pub ground_color: [f32; 3],
+    /// Higher = smaller sun disc.
+    pub sun_sharpness: f32,
+    pub sun_glow: f32,
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Self {
+            horizon_color: [0.85, 0.9, 1.0],
+            zenith_color: [0.15, 0.35, 0.65],
+            ground_color: [0.25, 0.22, 0.2],
+            sun_sharpness: 256.0,
+            sun_glow: 1.2,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct SkyUniform {
+    pub sun_direction: [f32; 3],
+    pub sun_sharpness: f32,
+    pub horizon_color: [f32; 3],
+    pub sun_glow: f32,
+    pub zenith_color: [f32; 3],
+    pub _pad0: f32,
+    pub ground_color: [f32; 3],
+    pub _pad1: f32,
+}
+
+impl SkyUniform {
+    pub fn new(sky: &Sky, sun_direction: Vec3) -> Self {
+        Self {
+            sun_direction: sun_direction.normalize_or_zero().to_array(),
+            sun_sharpness: sky.sun_sharpness,
+            horizon_color: sky.horizon_color,
+            sun_glow: sky.sun_glow,
+            zenith_color: sky.zenith_color,
+            _pad0: 0.0,
+            ground_color: sky.ground_color,
+            _pad1: 0.0,
+        }
+    }
+}
@@ -1,6 +1,22 @@
+struct Camera {
+    view_proj : mat4x4<f32>,
+    position : vec4<f32>,
+    inv_view_proj : mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera : Camera;
+
+// the procedural sky, baked by the environment pass
+@group(1) @binding(0)
+var sky : texture_cube<f32>;
+
+@group(1) @binding(1)
+var sky_sampler : sampler;
+
 struct VSOut {
     @builtin(position) pos: vec4<f32>,
-    @location(0) dir: vec3<f32>,
+    @location(0) ndc: vec2<f32>,
 };
 
 @vertex
@@ -14,22 +30,15 @@ fn vs_main(@builtin(vertex_index) i: u32) -> VSOut {
     let p = positions[i];
     var out: VSOut;
     out.pos = vec4<f32>(p, 0.0, 1.0);
-    out.dir = normalize(vec3<f32>(p.x, -p.y, 1.0));
+    out.ndc = p;
     return out;
 }
 
 @fragment
 fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
-    let t = clamp(in.dir.y * 0.5 + 0.5, 0.0, 1.0);
-
-    let horizon = vec3<f32>(0.85, 0.9, 1.0);
-    let zenith  = vec3<f32>(0.15, 0.35, 0.65);
-
-    let sky = mix(horizon, zenith, t);
-
-    // subtle sun
-    let sun_dir = normalize(vec3<f32>(0.3, 0.7, 0.6));
-    let sun = pow(max(dot(in.dir, sun_dir), 0.0), 256.0);
+    // any point on the pixel's ray, minus the eye
+    let point = camera.inv_view_proj * vec4<f32>(in.ndc, 0.5, 1.0);
+    let dir = normalize(point.xyz / point.w - camera.position.xyz);
 
-    return vec4<f32>(sky + sun * 1.2, 1.0);
+    return vec4<f32>(textureSampleLevel(sky, sky_sampler, dir, 0.0).rgb, 1.0);
 }
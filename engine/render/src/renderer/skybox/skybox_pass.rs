@@ -1,10 +1,18 @@
 use wgpu::*;
+use crate::renderer::environment::environment_pipeline::SkyEnvironment;
 use crate::renderer::skybox::skybox_pipeline::SkyboxPipeline;
 
+/// Draws the baked sky behind everything, seen from the camera at
+/// `camera_offset` in `cameras`.
 pub fn draw_skybox<'a>(
     pass: &mut RenderPass<'a>,
     skybox: &'a SkyboxPipeline,
+    cameras: &'a BindGroup,
+    camera_offset: u32,
+    environment: &'a SkyEnvironment,
 ) {
     pass.set_pipeline(&skybox.pipeline);
+    pass.set_bind_group(0, cameras, &[camera_offset]);
+    pass.set_bind_group(1, &environment.bind_group, &[]);
     pass.draw(0..3, 0..1); // fullscreen triangle
 }
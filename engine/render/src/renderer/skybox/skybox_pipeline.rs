@@ -5,7 +5,12 @@ pub struct SkyboxPipeline {
 }
 
 impl SkyboxPipeline {
-    pub fn new(device: &Device, format: TextureFormat) -> Self {
+    pub fn new(
+        device: &Device,
+        format: TextureFormat,
+        camera_layout: &BindGroupLayout,
+        environment_layout: &BindGroupLayout,
+    ) -> Self {
         let shader = device.create_shader_module(ShaderModuleDescriptor {
             label: Some("skybox_shader"),
             source: ShaderSource::Wgsl(include_str!("skybox.wgsl").into()),
@@ -13,7 +18,7 @@ impl SkyboxPipeline {
 
         let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: Some("skybox_pipeline_layout"),
-            bind_group_layouts: &[],
+            bind_group_layouts: &[camera_layout, environment_layout],
             push_constant_ranges: &[],
         });
 
@@ -0,0 +1,53 @@
+use crate::renderer::texture::{TextureData, TextureError};
+
+/// Any 8/16-bit PNG, expanded to RGBA8.
+pub fn decode_png(bytes: &[u8], srgb: bool) -> Result<TextureData, TextureError> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info().map_err(TextureError::Png)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(TextureError::Png)?;
+    buffer.truncate(info.buffer_size());
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => expand(&buffer, 3, |p| [p[0], p[1], p[2], 255]),
+        png::ColorType::GrayscaleAlpha => expand(&buffer, 2, |p| [p[0], p[0], p[0], p[1]]),
+        png::ColorType::Grayscale => expand(&buffer, 1, |p| [p[0], p[0], p[0], 255]),
+        png::ColorType::Indexed => {
+            return Err(TextureError::Unsupported("unexpanded indexed PNG".to_string()))
+        }
+    };
+
+    Ok(TextureData::new(info.width, info.height, pixels, srgb))
+}
+
+/// Baseline and progressive JPEG, expanded to RGBA8.
+pub fn decode_jpeg(bytes: &[u8], srgb: bool) -> Result<TextureData, TextureError> {
+    let mut decoder = jpeg_decoder::Decoder::new(bytes);
+    let buffer = decoder.decode().map_err(TextureError::Jpeg)?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| TextureError::Unsupported("JPEG without a frame".to_string()))?;
+
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => expand(&buffer, 3, |p| [p[0], p[1], p[2], 255]),
+        jpeg_decoder::PixelFormat::L8 => expand(&buffer, 1, |p| [p[0], p[0], p[0], 255]),
+        jpeg_decoder::PixelFormat::CMYK32 => expand(&buffer, 4, |p| {
+            let k = 255 - p[3] as u32;
+            let channel = |c: u8| ((255 - c as u32) * k / 255) as u8;
+            [channel(p[0]), channel(p[1]), channel(p[2]), 255]
+        }),
+        jpeg_decoder::PixelFormat::L16 => {
+            return Err(TextureError::Unsupported("16-bit JPEG".to_string()))
+        }
+    };
+
+    Ok(TextureData::new(info.width as u32, info.height as u32, pixels, srgb))
+}
+
+/// Converts tightly packed pixels of `channels` bytes to RGBA8.
+pub fn expand(pixels: &[u8], channels: usize, to_rgba: impl Fn(&[u8]) -> [u8; 4]) -> Vec<u8> {
+    pixels.chunks_exact(channels).flat_map(to_rgba).collect()
+}
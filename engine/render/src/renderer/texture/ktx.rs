@@ -0,0 +1,69 @@
+use std::io::Read;
+
+use ktx2::{Format, SupercompressionScheme};
+
+use crate::renderer::texture::decode::expand;
+use crate::renderer::texture::{TextureData, TextureError};
+
+/// A 2D KTX2 texture of 8-bit RGB(A), uncompressed or Zstandard
+/// supercompressed, with whatever mip levels it carries. Block-compressed
+/// and Basis Universal payloads are rejected.
+pub fn decode_ktx2(bytes: &[u8]) -> Result<TextureData, TextureError> {
+    let reader = ktx2::Reader::new(bytes).map_err(|e| TextureError::Ktx2(format!("{:?}", e)))?;
+    let header = reader.header();
+
+    if header.face_count != 1 || header.layer_count > 1 || header.pixel_depth > 1 {
+        return Err(TextureError::Unsupported(
+            "KTX2 cubemaps, arrays and 3D textures".to_string(),
+        ));
+    }
+
+    let (channels, srgb) = match header.format {
+        Some(Format::R8G8B8A8_UNORM) => (4, false),
+        Some(Format::R8G8B8A8_SRGB) => (4, true),
+        Some(Format::R8G8B8_UNORM) => (3, false),
+        Some(Format::R8G8B8_SRGB) => (3, true),
+        other => {
+            return Err(TextureError::Unsupported(format!("KTX2 format {:?}", other)));
+        }
+    };
+
+    let mut levels = Vec::with_capacity(header.level_count.max(1) as usize);
+    for (level, data) in reader.levels().enumerate() {
+        let data = match header.supercompression_scheme {
+            None => data.to_vec(),
+            Some(SupercompressionScheme::Zstandard) => {
+                let mut out = Vec::new();
+                ruzstd::StreamingDecoder::new(data)
+                    .map_err(|e| TextureError::Ktx2(format!("level {}: {}", level, e)))?
+                    .read_to_end(&mut out)?;
+                out
+            }
+            Some(other) => {
+                return Err(TextureError::Unsupported(format!("KTX2 supercompression {:?}", other)));
+            }
+        };
+
+        let width = (header.pixel_width >> level).max(1) as usize;
+        let height = (header.pixel_height.max(1) >> level).max(1) as usize;
+        if data.len() < width * height * channels {
+            return Err(TextureError::Ktx2(format!("level {} is truncated", level)));
+        }
+
+        let data = &data[..width * height * channels];
+        levels.push(if channels == 4 {
+            data.to_vec()
+        } else {
+            expand(data, 3, |p| [p[0], p[1], p[2], 255])
+        });
+    }
+
+    if levels.is_empty() {
+        return Err(TextureError::Ktx2("no levels".to_string()));
+    }
+
+    let pixels = levels.remove(0);
+    let mut texture = TextureData::new(header.pixel_width, header.pixel_height.max(1), pixels, srgb);
+    texture.mips = levels;
+    Ok(texture)
+}
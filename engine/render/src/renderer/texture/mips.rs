@@ -0,0 +1,69 @@
+/// The mip chain below an RGBA8 image, down to 1x1. Each level averages
+/// 2x2 texels of the one above; sRGB color is averaged in linear space so
+/// mips do not darken.
+pub fn generate_mips(width: u32, height: u32, pixels: &[u8], srgb: bool) -> Vec<Vec<u8>> {
+    let to_linear: Vec<f32> = (0..256)
+        .map(|v| {
+            let c = v as f32 / 255.0;
+            if !srgb {
+                c
+            } else if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        })
+        .collect();
+
+    let to_byte = |c: f32| {
+        let c = if !srgb {
+            c
+        } else if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+        (c.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+    };
+
+    let mut mips = Vec::new();
+    let (mut w, mut h) = (width.max(1) as usize, height.max(1) as usize);
+    let mut above = pixels.to_vec();
+
+    while w > 1 || h > 1 {
+        let (nw, nh) = ((w / 2).max(1), (h / 2).max(1));
+        let mut level = Vec::with_capacity(nw * nh * 4);
+
+        for y in 0..nh {
+            for x in 0..nw {
+                let texels = [
+                    (2 * x, 2 * y),
+                    ((2 * x + 1).min(w - 1), 2 * y),
+                    (2 * x, (2 * y + 1).min(h - 1)),
+                    ((2 * x + 1).min(w - 1), (2 * y + 1).min(h - 1)),
+                ];
+
+                for channel in 0..4 {
+                    let sum: f32 = texels
+                        .iter()
+                        .map(|(tx, ty)| above[(ty * w + tx) * 4 + channel])
+                        .map(|v| if channel == 3 { v as f32 / 255.0 } else { to_linear[v as usize] })
+                        .sum();
+                    let average = sum / 4.0;
+
+                    level.push(if channel == 3 {
+                        (average * 255.0 + 0.5) as u8
+                    } else {
+                        to_byte(average)
+                    });
+                }
+            }
+        }
+
+        (w, h) = (nw, nh);
+        above.clone_from(&level);
+        mips.push(level);
+    }
+
+    mips
+}
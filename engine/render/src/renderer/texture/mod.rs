@@ -0,0 +1,155 @@
+pub mod decode;
+pub mod ktx;
+pub mod mips;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use wgpu::{AddressMode, FilterMode};
+
+/// RGBA8 pixels for a material texture.
+#[derive(Clone, Debug)]
+pub struct TextureData {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    /// Color data (base color, emissive) is sRGB; masks and normal maps
+    /// are linear. Decides both the GPU format and how mips average.
+    pub srgb: bool,
+    /// Smaller levels, each half the size of the one before. Generated
+    /// on upload when empty.
+    pub mips: Vec<Vec<u8>>,
+    pub sampler: SamplerSettings,
+}
+
+impl TextureData {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>, srgb: bool) -> Self {
+        Self {
+            width,
+            height,
+            pixels,
+            srgb,
+            mips: Vec::new(),
+            sampler: SamplerSettings::default(),
+        }
+    }
+
+    pub fn with_sampler(mut self, sampler: SamplerSettings) -> Self {
+        self.sampler = sampler;
+        self
+    }
+}
+
+/// How a texture is filtered and wrapped. Materials sharing settings
+/// share a sampler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SamplerSettings {
+    pub address_u: AddressMode,
+    pub address_v: AddressMode,
+    pub mag_filter: FilterMode,
+    pub min_filter: FilterMode,
+    pub mipmap_filter: FilterMode,
+    /// 1 = off. Only applies when every filter is linear.
+    pub anisotropy: u16,
+}
+
+impl SamplerSettings {
+    /// Crisp texels for pixel art and lookup tables.
+    pub fn nearest() -> Self {
+        Self {
+            mag_filter: FilterMode::Nearest,
+            min_filter: FilterMode::Nearest,
+            mipmap_filter: FilterMode::Nearest,
+            anisotropy: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn clamped(mut self) -> Self {
+        self.address_u = AddressMode::ClampToEdge;
+        self.address_v = AddressMode::ClampToEdge;
+        self
+    }
+
+    pub fn descriptor(&self) -> wgpu::SamplerDescriptor<'static> {
+        let linear = [self.mag_filter, self.min_filter, self.mipmap_filter]
+            .iter()
+            .all(|f| *f == FilterMode::Linear);
+
+        wgpu::SamplerDescriptor {
+            label: Some("texture_sampler"),
+            address_mode_u: self.address_u,
+            address_mode_v: self.address_v,
+            mag_filter: self.mag_filter,
+            min_filter: self.min_filter,
+            mipmap_filter: self.mipmap_filter,
+            anisotropy_clamp: if linear { self.anisotropy.clamp(1, 16) } else { 1 },
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for SamplerSettings {
+    fn default() -> Self {
+        Self {
+            address_u: AddressMode::Repeat,
+            address_v: AddressMode::Repeat,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            anisotropy: 8,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TextureError {
+    Io(io::Error),
+    Png(png::DecodingError),
+    Jpeg(jpeg_decoder::Error),
+    Ktx2(String),
+    Unsupported(String),
+    UnknownFormat,
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextureError::Io(e) => write!(f, "{}", e),
+            TextureError::Png(e) => write!(f, "png: {}", e),
+            TextureError::Jpeg(e) => write!(f, "jpeg: {}", e),
+            TextureError::Ktx2(msg) => write!(f, "ktx2: {}", msg),
+            TextureError::Unsupported(what) => write!(f, "unsupported: {}", what),
+            TextureError::UnknownFormat => write!(f, "not a PNG, JPEG or KTX2 image"),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+impl From<io::Error> for TextureError {
+    fn from(e: io::Error) -> Self {
+        TextureError::Io(e)
+    }
+}
+
+/// Loads a PNG, JPEG or KTX2 file. `srgb` says whether it holds color;
+/// KTX2 files carry their own color space and ignore it.
+pub fn load_texture(path: impl AsRef<Path>, srgb: bool) -> Result<TextureData, TextureError> {
+    decode_texture(&fs::read(path)?, srgb)
+}
+
+/// Decodes an image in memory, telling the format from its signature.
+pub fn decode_texture(bytes: &[u8], srgb: bool) -> Result<TextureData, TextureError> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        decode::decode_png(bytes, srgb)
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        decode::decode_jpeg(bytes, srgb)
+    } else if bytes.starts_with(b"\xabKTX 20\xbb\r\n\x1a\n") {
+        ktx::decode_ktx2(bytes)
+    } else {
+        Err(TextureError::UnknownFormat)
+    }
+}
@@ -4,6 +4,32 @@ use glam::{Mat4, Vec3};
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct CameraUniform {
     pub view_proj: [[f32; 4]; 4],
+    /// World-space eye position (w unused), for specular highlights.
+    pub position: [f32; 4],
+    /// Clip to world, for rays through the screen (the skybox).
+    pub inv_view_proj: [[f32; 4]; 4],
+    /// View direction, for the depth of light clusters.
+    pub forward: [f32; 3],
+    /// Start of the view's light clusters, see `LightClusters::push`.
+    pub cluster_offset: u32,
+}
+
+impl CameraUniform {
+    pub fn new(view_proj: Mat4, position: Vec3) -> Self {
+        Self {
+            view_proj: view_proj.to_cols_array_2d(),
+            position: position.extend(1.0).to_array(),
+            inv_view_proj: view_proj.inverse().to_cols_array_2d(),
+            forward: [0.0; 3],
+            cluster_offset: 0,
+        }
+    }
+
+    pub fn with_clusters(mut self, forward: Vec3, cluster_offset: u32) -> Self {
+        self.forward = forward.to_array();
+        self.cluster_offset = cluster_offset;
+        self
+    }
 }
 
 pub struct OrbitCamera {
@@ -48,6 +74,10 @@ impl OrbitCamera {
         }
     }
 
+    pub fn proj_matrix(&self, aspect: f32) -> Mat4 {
+        Mat4::perspective_rh(45.0_f32.to_radians(), aspect, 0.1, 100.0)
+    }
+
     pub fn view_matrix(&self) -> Mat4 {
         let dir = Vec3::new(
             self.pitch.cos() * self.yaw.sin(),
@@ -59,3 +89,40 @@ impl OrbitCamera {
         Mat4::look_at_rh(pos, self.target, Vec3::Y)
     }
 }
+
+/// Camera component, looking down the entity's local -Z (glTF cameras).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SceneCamera {
+    Perspective {
+        /// Vertical field of view in radians.
+        yfov: f32,
+        /// Width over height; the view's own when `None`.
+        aspect: Option<f32>,
+        znear: f32,
+        /// Infinite when `None`.
+        zfar: Option<f32>,
+    },
+    Orthographic {
+        /// Half the width and height of the view volume.
+        xmag: f32,
+        ymag: f32,
+        znear: f32,
+        zfar: f32,
+    },
+}
+
+impl SceneCamera {
+    pub fn proj_matrix(&self, view_aspect: f32) -> Mat4 {
+        match *self {
+            SceneCamera::Perspective { yfov, aspect, znear, zfar: Some(zfar) } => {
+                Mat4::perspective_rh(yfov, aspect.unwrap_or(view_aspect), znear, zfar)
+            }
+            SceneCamera::Perspective { yfov, aspect, znear, zfar: None } => {
+                Mat4::perspective_infinite_rh(yfov, aspect.unwrap_or(view_aspect), znear)
+            }
+            SceneCamera::Orthographic { xmag, ymag, znear, zfar } => {
+                Mat4::orthographic_rh(-xmag, xmag, -ymag, ymag, znear, zfar)
+            }
+        }
+    }
+}
@@ -2,7 +2,6 @@ pub mod camera;
 pub mod model;
 pub mod player;
 pub mod time;
-pub mod material;
 
 pub use camera::CameraUniform;
 pub use model::ModelUniform;
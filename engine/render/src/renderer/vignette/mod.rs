@@ -0,0 +1,2 @@
+pub mod vignette_pipeline;
+pub mod vignette_pass;
@@ -0,0 +1,37 @@
+struct Vignette {
+    strength: f32,
+    inner: f32,
+    outer: f32,
+    _pad: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> vignette: Vignette;
+
+struct VSOut {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) i: u32) -> VSOut {
+    var positions = array<vec2<f32>, 3>(
+        vec2<f32>(-1.0, -3.0),
+        vec2<f32>( 3.0,  1.0),
+        vec2<f32>(-1.0,  1.0),
+    );
+
+    let p = positions[i];
+    var out: VSOut;
+    out.pos = vec4<f32>(p, 0.0, 1.0);
+    out.ndc = p;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let r = length(in.ndc);
+    let a = smoothstep(vignette.inner, vignette.outer, r) * vignette.strength;
+
+    return vec4<f32>(0.0, 0.0, 0.0, a);
+}
@@ -0,0 +1,38 @@
+use wgpu::*;
+
+use crate::renderer::vignette::vignette_pipeline::{VignettePipeline, VignetteUniform};
+
+pub fn draw_vignette(
+    encoder: &mut CommandEncoder,
+    queue: &Queue,
+    target: &TextureView,
+    vignette: &VignettePipeline,
+    strength: f32,
+) {
+    if strength <= 0.001 {
+        return;
+    }
+
+    queue.write_buffer(
+        &vignette.buffer,
+        0,
+        bytemuck::bytes_of(&VignetteUniform::from_strength(strength)),
+    );
+
+    let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some("vignette_pass"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Load,
+                store: true,
+            },
+        })],
+        depth_stencil_attachment: None,
+    });
+
+    pass.set_pipeline(&vignette.pipeline);
+    pass.set_bind_group(0, &vignette.bind_group, &[]);
+    pass.draw(0..3, 0..1); // fullscreen triangle
+}
@@ -0,0 +1,113 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::*;
+
+use crate::renderer::resources::counters::{create_bind_group, create_buffer};
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct VignetteUniform {
+    pub strength: f32,
+    pub inner: f32,
+    pub outer: f32,
+    pub _pad: f32,
+}
+
+impl VignetteUniform {
+    /// Radii (in NDC) shrink as the strength goes up.
+    pub fn from_strength(strength: f32) -> Self {
+        let strength = strength.clamp(0.0, 1.0);
+        let inner = 1.1 - strength * 0.65;
+
+        Self {
+            strength,
+            inner,
+            outer: inner + 0.35,
+            _pad: 0.0,
+        }
+    }
+}
+
+/// Comfort vignette: a fullscreen triangle darkening the periphery,
+/// alpha-blended over the finished view.
+pub struct VignettePipeline {
+    pub pipeline: RenderPipeline,
+    pub buffer: Buffer,
+    pub bind_group: BindGroup,
+}
+
+impl VignettePipeline {
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("vignette_shader"),
+            source: ShaderSource::Wgsl(include_str!("vignette.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("vignette_layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let buffer = create_buffer(device, &BufferDescriptor {
+            label: Some("vignette_buffer"),
+            size: std::mem::size_of::<VignetteUniform>() as BufferAddress,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = create_bind_group(device, &BindGroupDescriptor {
+            label: Some("vignette_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("vignette_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("vignette_pipeline"),
+            layout: Some(&layout),
+
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            buffer,
+            bind_group,
+        }
+    }
+}
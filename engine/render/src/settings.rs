@@ -0,0 +1,289 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use glam::Vec2;
+
+use crate::locomotion::{LocomotionMode, LocomotionSettings, TurnMode};
+use crate::xr::{Boundary, PlayArea};
+
+/*
+   User settings are plain text, one `key = value` per line:
+
+     locomotion.mode = smooth | teleport
+     locomotion.turn = snap <degrees> | smooth <degrees/s> | off
+     locomotion.teleport_range = <metres>
+     locomotion.vignette = true | false
+     locomotion.vignette_strength = <0..1>
+     boundary.enabled = true | false
+     boundary.warn_distance = <metres>
+     boundary.points = x z, x z, x z, ...
+
+   Lines starting with '#' are comments; unknown keys are skipped (and
+   listed in `warnings`) so older builds can read newer files.
+*/
+
+const HEADER: &str = "# u-vr user settings v1";
+
+/// Everything that is per user rather than per world.
+#[derive(Clone, Debug, Default)]
+pub struct UserSettings {
+    pub locomotion: LocomotionSettings,
+    pub boundary: Boundary,
+    /// What was skipped when loading (unknown keys).
+    pub warnings: Vec<String>,
+}
+
+impl UserSettings {
+    /// `<config dir>/u-vr/users/<user>.cfg`; fails for names that are
+    /// not a plain file name (see `is_valid_user`).
+    pub fn path(user: &str) -> io::Result<PathBuf> {
+        if !Self::is_valid_user(user) {
+            let message = format!("invalid user name '{}'", user);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+            .unwrap_or_else(|| PathBuf::from("."));
+
+        Ok(base.join("u-vr").join("users").join(format!("{}.cfg", user)))
+    }
+
+    /// Whether `user` stays inside the users directory: not empty, no
+    /// path separators (or drive colons) and no `..`.
+    pub fn is_valid_user(user: &str) -> bool {
+        !user.is_empty() && !user.contains("..") && !user.contains(['/', '\\', ':', '\0'])
+    }
+
+    /// Name used when none is given: the OS account.
+    pub fn default_user() -> String {
+        std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "default".to_string())
+    }
+
+    /// Loads the user's settings, or defaults if they have none yet. A
+    /// file that exists but can't be read or parsed is an error, so it is
+    /// never mistaken for a fresh user and saved over.
+    pub fn load_for(user: &str) -> io::Result<Self> {
+        match Self::path(user).and_then(Self::load) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            result => result,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_text(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        if let Some(dir) = path.as_ref().parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_text())
+    }
+
+    pub fn save_for(&self, user: &str) -> io::Result<()> {
+        self.save(Self::path(user)?)
+    }
+
+    /// Moves the user's settings file to `<user>.cfg.bak`, so one that
+    /// failed to load survives saving new settings in its place.
+    pub fn back_up(user: &str) -> io::Result<PathBuf> {
+        let path = Self::path(user)?;
+        let backup = path.with_extension("cfg.bak");
+        fs::rename(&path, &backup)?;
+        Ok(backup)
+    }
+
+    /* ================= TEXT ================= */
+
+    pub fn to_text(&self) -> String {
+        let l = &self.locomotion;
+        let b = &self.boundary;
+
+        let mut out = String::new();
+        out.push_str(HEADER);
+        out.push('\n');
+
+        let mode = match l.mode {
+            LocomotionMode::Smooth => "smooth",
+            LocomotionMode::Teleport => "teleport",
+        };
+        let turn = match l.turn {
+            TurnMode::Snap { degrees } => format!("snap {}", degrees),
+            TurnMode::Smooth { degrees_per_second } => format!("smooth {}", degrees_per_second),
+            TurnMode::Disabled => "off".to_string(),
+        };
+        let points: Vec<String> = b
+            .area
+            .points
+            .iter()
+            .map(|p| format!("{} {}", p.x, p.y))
+            .collect();
+
+        let _ = writeln!(out, "locomotion.mode = {}", mode);
+        let _ = writeln!(out, "locomotion.turn = {}", turn);
+        let _ = writeln!(out, "locomotion.teleport_range = {}", l.teleport_range);
+        let _ = writeln!(out, "locomotion.vignette = {}", l.vignette);
+        let _ = writeln!(out, "locomotion.vignette_strength = {}", l.vignette_strength);
+        let _ = writeln!(out, "boundary.enabled = {}", b.enabled);
+        let _ = writeln!(out, "boundary.warn_distance = {}", b.warn_distance);
+        let _ = writeln!(out, "boundary.points = {}", points.join(", "));
+
+        out
+    }
+
+    pub fn from_text(text: &str) -> io::Result<Self> {
+        let mut settings = Self::default();
+
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            settings.apply(line).map_err(|msg| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", n + 1, msg))
+            })?;
+        }
+
+        Ok(settings)
+    }
+
+    fn apply(&mut self, line: &str) -> Result<(), String> {
+        let (key, value) = line
+            .split_once('=')
+            .map(|(k, v)| (k.trim(), v.trim()))
+            .ok_or_else(|| format!("expected 'key = value', found '{}'", line))?;
+
+        let l = &mut self.locomotion;
+        let b = &mut self.boundary;
+
+        match key {
+            "locomotion.mode" => {
+                l.mode = match value {
+                    "smooth" => LocomotionMode::Smooth,
+                    "teleport" => LocomotionMode::Teleport,
+                    _ => return Err(format!("unknown locomotion mode '{}'", value)),
+                }
+            }
+            "locomotion.turn" => {
+                let mut words = value.split_whitespace();
+                l.turn = match (words.next(), words.next()) {
+                    (Some("snap"), Some(d)) => TurnMode::Snap { degrees: number(d)? },
+                    (Some("smooth"), Some(d)) => TurnMode::Smooth { degrees_per_second: number(d)? },
+                    (Some("off"), None) => TurnMode::Disabled,
+                    _ => return Err(format!("invalid turn mode '{}'", value)),
+                }
+            }
+            "locomotion.teleport_range" => l.teleport_range = number(value)?,
+            "locomotion.vignette" => l.vignette = boolean(value)?,
+            "locomotion.vignette_strength" => l.vignette_strength = number(value)?.clamp(0.0, 1.0),
+            "boundary.enabled" => b.enabled = boolean(value)?,
+            "boundary.warn_distance" => b.warn_distance = number(value)?,
+            "boundary.points" => {
+                let points = value
+                    .split(',')
+                    .map(|p| {
+                        let xz: Vec<f32> = p.split_whitespace().map(number).collect::<Result<_, _>>()?;
+                        match xz[..] {
+                            [x, z] => Ok(Vec2::new(x, z)),
+                            _ => Err(format!("expected 'x z', found '{}'", p.trim())),
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if points.len() < 3 {
+                    return Err("boundary needs at least 3 points".to_string());
+                }
+                b.area = PlayArea { points };
+            }
+            _ => self.warnings.push(format!("unknown setting '{}' skipped", key)),
+        }
+
+        Ok(())
+    }
+}
+
+/// A finite number; "nan" and "inf" parse as floats but are never
+/// valid settings.
+fn number(s: &str) -> Result<f32, String> {
+    s.parse::<f32>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| format!("invalid number '{}'", s))
+}
+
+fn boolean(s: &str) -> Result<bool, String> {
+    match s {
+        "true" | "on" | "yes" => Ok(true),
+        "false" | "off" | "no" => Ok(false),
+        _ => Err(format!("invalid boolean '{}'", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_survive_a_save_and_load() {
+        let mut settings = UserSettings::default();
+        settings.locomotion.mode = LocomotionMode::Teleport;
+        settings.locomotion.turn = TurnMode::Smooth { degrees_per_second: 120.0 };
+        settings.locomotion.teleport_range = 7.5;
+        settings.locomotion.vignette = true;
+        settings.locomotion.vignette_strength = 0.25;
+        settings.boundary.enabled = false;
+        settings.boundary.warn_distance = 0.6;
+        settings.boundary.area = PlayArea::rectangle(3.0, 2.5);
+
+        let path = std::env::temp_dir().join(format!("u-vr-settings-{}.cfg", std::process::id()));
+        settings.save(&path).unwrap();
+        let loaded = UserSettings::load(&path);
+        let _ = fs::remove_file(&path);
+        let loaded = loaded.unwrap();
+
+        let (l, b) = (&loaded.locomotion, &loaded.boundary);
+        assert_eq!(l.mode, LocomotionMode::Teleport);
+        assert_eq!(l.turn, TurnMode::Smooth { degrees_per_second: 120.0 });
+        assert_eq!(l.teleport_range, 7.5);
+        assert!(l.vignette);
+        assert_eq!(l.vignette_strength, 0.25);
+        assert!(!b.enabled);
+        assert_eq!(b.warn_distance, 0.6);
+        assert_eq!(b.area, PlayArea::rectangle(3.0, 2.5));
+    }
+
+    #[test]
+    fn bad_lines_are_reported_and_unknown_keys_skipped() {
+        let text = "# comment\nlocomotion.turn = off\nfuture.key = 1\n";
+        let settings = UserSettings::from_text(text).unwrap();
+        assert_eq!(settings.locomotion.turn, TurnMode::Disabled);
+        assert_eq!(settings.warnings, ["unknown setting 'future.key' skipped"]);
+
+        let error = |text: &str| UserSettings::from_text(text).err().unwrap().to_string();
+        assert_eq!(error("\nlocomotion.mode = fly"), "line 2: unknown locomotion mode 'fly'");
+        assert_eq!(error("locomotion.vignette"), "line 1: expected 'key = value', found 'locomotion.vignette'");
+        assert_eq!(error("boundary.points = 0 0, 1 1"), "line 1: boundary needs at least 3 points");
+        assert_eq!(error("boundary.points = 0 0, 1, 1 1"), "line 1: expected 'x z', found '1'");
+        assert_eq!(error("locomotion.teleport_range = far"), "line 1: invalid number 'far'");
+        assert_eq!(error("locomotion.teleport_range = NaN"), "line 1: invalid number 'NaN'");
+        assert_eq!(error("locomotion.turn = snap inf"), "line 1: invalid number 'inf'");
+    }
+
+    #[test]
+    fn user_names_cannot_leave_the_users_directory() {
+        for name in ["alice", "Bob Smith", "player.2"] {
+            assert!(UserSettings::path(name).is_ok(), "{}", name);
+        }
+        for name in ["", "..", "../../x", "a/b", "a\\b", "C:evil", "..hidden"] {
+            assert!(UserSettings::path(name).is_err(), "{}", name);
+        }
+    }
+}
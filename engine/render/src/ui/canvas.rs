@@ -0,0 +1,138 @@
+use glam::Vec2;
+
+use crate::renderer::resources::mesh::Vertex;
+
+use super::font::{glyph, GLYPH_HEIGHT, GLYPH_SPACING, GLYPH_WIDTH};
+
+/// Axis-aligned rectangle in panel pixels, y down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+        Self { x, y, w, h }
+    }
+
+    pub fn contains(&self, p: Vec2) -> bool {
+        p.x >= self.x && p.x < self.x + self.w && p.y >= self.y && p.y < self.y + self.h
+    }
+
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let x0 = self.x.max(other.x);
+        let y0 = self.y.max(other.y);
+        let x1 = (self.x + self.w).min(other.x + other.w);
+        let y1 = (self.y + self.h).min(other.y + other.h);
+
+        (x1 > x0 && y1 > y0).then(|| Rect::new(x0, y0, x1 - x0, y1 - y0))
+    }
+
+    pub fn inset(&self, by: f32) -> Rect {
+        Rect::new(self.x + by, self.y + by, self.w - by * 2.0, self.h - by * 2.0)
+    }
+}
+
+/// Flat-colored triangles in panel pixels, converted to clip space as
+/// they are added so they can go through the overlay shader unchanged.
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u16>,
+    clip: Rect,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            clip: Rect::new(0.0, 0.0, width as f32, height as f32),
+        }
+    }
+
+    /// Restricts drawing to `clip` (clamped to the canvas); `None` resets.
+    pub fn set_clip(&mut self, clip: Option<Rect>) {
+        let full = Rect::new(0.0, 0.0, self.width as f32, self.height as f32);
+        self.clip = match clip {
+            Some(c) => c.intersect(&full).unwrap_or(Rect::new(0.0, 0.0, 0.0, 0.0)),
+            None => full,
+        };
+    }
+
+    pub fn rect(&mut self, rect: Rect, color: [f32; 3]) {
+        let Some(r) = rect.intersect(&self.clip) else {
+            return;
+        };
+        if self.vertices.len() + 4 > u16::MAX as usize {
+            return;
+        }
+
+        let to_ndc = |x: f32, y: f32| {
+            [
+                x / self.width as f32 * 2.0 - 1.0,
+                1.0 - y / self.height as f32 * 2.0,
+                0.0,
+            ]
+        };
+
+        let i = self.vertices.len() as u16;
+        self.vertices.extend_from_slice(&[
+            Vertex::new(to_ndc(r.x, r.y), color),
+            Vertex::new(to_ndc(r.x, r.y + r.h), color),
+            Vertex::new(to_ndc(r.x + r.w, r.y + r.h), color),
+            Vertex::new(to_ndc(r.x + r.w, r.y), color),
+        ]);
+        self.indices.extend_from_slice(&[i, i + 1, i + 2, i, i + 2, i + 3]);
+    }
+
+    /// Draws `text` with its top-left corner at (x, y), `scale` pixels
+    /// per font pixel. Each run of lit pixels in a row is one quad.
+    pub fn text(&mut self, x: f32, y: f32, scale: f32, text: &str, color: [f32; 3]) {
+        let advance = (GLYPH_WIDTH + GLYPH_SPACING) as f32 * scale;
+
+        for (n, c) in text.chars().enumerate() {
+            let gx = x + n as f32 * advance;
+
+            for (row, bits) in glyph(c).iter().enumerate() {
+                let gy = y + row as f32 * scale;
+                let mut col = 0;
+
+                while col < GLYPH_WIDTH {
+                    let lit = |col: u32| bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0;
+
+                    if !lit(col) {
+                        col += 1;
+                        continue;
+                    }
+
+                    let start = col;
+                    while col < GLYPH_WIDTH && lit(col) {
+                        col += 1;
+                    }
+
+                    self.rect(
+                        Rect::new(
+                            gx + start as f32 * scale,
+                            gy,
+                            (col - start) as f32 * scale,
+                            scale,
+                        ),
+                        color,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Height of a line of text at `scale`.
+    pub fn line_height(scale: f32) -> f32 {
+        GLYPH_HEIGHT as f32 * scale
+    }
+}
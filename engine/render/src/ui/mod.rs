@@ -0,0 +1,177 @@
+pub mod canvas;
+pub mod font;
+pub mod panel;
+pub mod pointer;
+pub mod settings_menu;
+
+pub use canvas::{Canvas, Rect};
+pub use panel::{Panel, PanelHit, PanelId, Widget, WidgetKind};
+pub use pointer::{PointerSource, Ray, UiPointer};
+pub use settings_menu::SettingsMenu;
+
+use glam::Vec3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UiEvent {
+    /// Pressed and released on the same widget.
+    Click { panel: PanelId, widget: &'static str },
+}
+
+struct PointerState {
+    source: PointerSource,
+    ray: Ray,
+    down: bool,
+    /// Widget the button went down on.
+    pressed: Option<(PanelId, usize)>,
+    hit: Option<PanelHit>,
+}
+
+/*
+   World-space UI: owns the panels and turns pointer rays into hover,
+   click and scroll. Pointers are matched frame to frame by source, so a
+   press and release from the same laser (or the mouse) make one click.
+*/
+pub struct Ui {
+    pub panels: Vec<Panel>,
+    pointers: Vec<PointerState>,
+    next_id: u32,
+}
+
+impl Ui {
+    /// Pixels scrolled per unit of wheel / full stick per frame.
+    const SCROLL_SPEED: f32 = 24.0;
+
+    pub fn new() -> Self {
+        Self {
+            panels: Vec::new(),
+            pointers: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    pub fn add(&mut self, mut panel: Panel) -> PanelId {
+        let id = PanelId(self.next_id);
+        self.next_id += 1;
+
+        panel.id = id;
+        self.panels.push(panel);
+        id
+    }
+
+    pub fn remove(&mut self, id: PanelId) {
+        self.panels.retain(|p| p.id != id);
+    }
+
+    pub fn panel(&self, id: PanelId) -> Option<&Panel> {
+        self.panels.iter().find(|p| p.id == id)
+    }
+
+    pub fn panel_mut(&mut self, id: PanelId) -> Option<&mut Panel> {
+        self.panels.iter_mut().find(|p| p.id == id)
+    }
+
+    /// Closest panel hit along a ray.
+    pub fn raycast(&self, ray: &Ray) -> Option<PanelHit> {
+        self.panels
+            .iter()
+            .filter_map(|p| p.hit(ray))
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /// Whether `source` was over a panel on the last update.
+    pub fn is_pointing(&self, source: PointerSource) -> bool {
+        self.pointers.iter().any(|p| p.source == source && p.hit.is_some())
+    }
+
+    /// Ray origin to hit point for every laser touching a panel.
+    pub fn lasers(&self) -> Vec<(Vec3, Vec3)> {
+        self.pointers
+            .iter()
+            .filter(|p| p.source != PointerSource::Mouse)
+            .filter_map(|p| p.hit.map(|hit| (p.ray.origin, hit.point)))
+            .collect()
+    }
+
+    pub fn update(&mut self, pointers: &[UiPointer]) -> Vec<UiEvent> {
+        let mut events = Vec::new();
+
+        // pointers that went away this frame drop their press
+        self.pointers.retain(|s| pointers.iter().any(|p| p.source == s.source));
+
+        for pointer in pointers {
+            let hit = self.raycast(&pointer.ray);
+            let target = hit.and_then(|h| h.widget.map(|w| (h.panel, w)));
+
+            let index = match self.pointers.iter().position(|s| s.source == pointer.source) {
+                Some(i) => i,
+                None => {
+                    self.pointers.push(PointerState {
+                        source: pointer.source,
+                        ray: pointer.ray,
+                        down: false,
+                        pressed: None,
+                        hit: None,
+                    });
+                    self.pointers.len() - 1
+                }
+            };
+            let state = &mut self.pointers[index];
+
+            if pointer.click && !state.down {
+                state.pressed = target;
+            } else if !pointer.click && state.down {
+                if let Some((panel, widget)) = state.pressed.take() {
+                    if target == Some((panel, widget)) {
+                        let widget = self.panel(panel).unwrap().widgets()[widget].id;
+                        events.push(UiEvent::Click { panel, widget });
+                    }
+                }
+            }
+
+            let state = &mut self.pointers[index];
+            state.down = pointer.click;
+            state.ray = pointer.ray;
+            state.hit = hit;
+
+            if let Some(hit) = hit {
+                if pointer.scroll != 0.0 {
+                    let panel = self.panel_mut(hit.panel).unwrap();
+                    panel.scroll_by(-pointer.scroll * Self::SCROLL_SPEED);
+                }
+            }
+        }
+
+        /* ---------- highlights ---------- */
+
+        for panel in &mut self.panels {
+            let mut hovered = Vec::new();
+            let mut pressed = Vec::new();
+            let mut cursors = Vec::new();
+
+            for state in &self.pointers {
+                let Some(hit) = state.hit.filter(|h| h.panel == panel.id) else {
+                    continue;
+                };
+
+                cursors.push(hit.pixel);
+                if let Some(w) = hit.widget {
+                    if state.down && state.pressed == Some((panel.id, w)) {
+                        pressed.push(w);
+                    } else {
+                        hovered.push(w);
+                    }
+                }
+            }
+
+            panel.set_pointers(hovered, pressed, cursors);
+        }
+
+        events
+    }
+}
+
+impl Default for Ui {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,352 @@
+use engine_core::RenderLayers;
+use glam::{Mat4, UVec2, Vec2, Vec3};
+
+use super::canvas::{Canvas, Rect};
+use super::font::text_width;
+use super::pointer::Ray;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PanelId(pub u32);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WidgetKind {
+    Label,
+    Button,
+    Toggle(bool),
+}
+
+#[derive(Clone, Debug)]
+pub struct Widget {
+    pub id: &'static str,
+    pub label: String,
+    pub kind: WidgetKind,
+}
+
+impl Widget {
+    pub fn is_interactive(&self) -> bool {
+        !matches!(self.kind, WidgetKind::Label)
+    }
+}
+
+/// Where a ray meets a panel.
+#[derive(Clone, Copy, Debug)]
+pub struct PanelHit {
+    pub panel: PanelId,
+    /// World-space point on the panel.
+    pub point: Vec3,
+    /// Panel pixel, y down from the top-left corner.
+    pub pixel: Vec2,
+    pub distance: f32,
+    /// Index of the interactive widget under the pixel.
+    pub widget: Option<usize>,
+}
+
+/*
+   A flat UI panel in the world.
+
+   Widgets are a single scrolling column below a fixed title bar. The
+   panel is drawn into its own texture (see `renderer::panel`) whenever
+   `version` changes, and shown as a quad centred on `transform`, facing
+   its local +Z.
+*/
+pub struct Panel {
+    pub id: PanelId,
+    pub title: String,
+    transform: Mat4,
+    /// Size in metres.
+    pub size: Vec2,
+    /// Texture size in pixels.
+    pub resolution: UVec2,
+    pub layers: RenderLayers,
+
+    widgets: Vec<Widget>,
+    scroll: f32,
+
+    hovered: Vec<usize>,
+    pressed: Vec<usize>,
+    cursors: Vec<Vec2>,
+
+    version: u64,
+}
+
+impl Panel {
+    pub const TITLE_HEIGHT: f32 = 56.0;
+    pub const ROW_HEIGHT: f32 = 56.0;
+    pub const PADDING: f32 = 16.0;
+    pub const SCROLLBAR: f32 = 10.0;
+    pub const TEXT_SCALE: f32 = 3.0;
+
+    pub fn new(title: &str, size: Vec2, resolution: UVec2) -> Self {
+        Self {
+            id: PanelId(0),
+            title: title.to_string(),
+            transform: Mat4::IDENTITY,
+            size,
+            resolution,
+            layers: RenderLayers::DEFAULT,
+            widgets: Vec::new(),
+            scroll: 0.0,
+            hovered: Vec::new(),
+            pressed: Vec::new(),
+            cursors: Vec::new(),
+            version: 1,
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Mat4) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_label(self, id: &'static str, label: &str) -> Self {
+        self.with_widget(id, label, WidgetKind::Label)
+    }
+
+    pub fn with_button(self, id: &'static str, label: &str) -> Self {
+        self.with_widget(id, label, WidgetKind::Button)
+    }
+
+    pub fn with_toggle(self, id: &'static str, label: &str, on: bool) -> Self {
+        self.with_widget(id, label, WidgetKind::Toggle(on))
+    }
+
+    fn with_widget(mut self, id: &'static str, label: &str, kind: WidgetKind) -> Self {
+        self.widgets.push(Widget { id, label: label.to_string(), kind });
+        self
+    }
+
+    /* ================= STATE ================= */
+
+    /// Bumped on every visible change; the renderer redraws on mismatch.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn touch(&mut self) {
+        self.version += 1;
+    }
+
+    pub fn transform(&self) -> Mat4 {
+        self.transform
+    }
+
+    /// Rigid placement only; scale goes in `size`.
+    pub fn set_transform(&mut self, transform: Mat4) {
+        self.transform = transform;
+    }
+
+    pub fn widgets(&self) -> &[Widget] {
+        &self.widgets
+    }
+
+    pub fn widget(&self, id: &str) -> Option<&Widget> {
+        self.widgets.iter().find(|w| w.id == id)
+    }
+
+    pub fn set_label(&mut self, id: &str, label: &str) {
+        if let Some(i) = self.widgets.iter().position(|w| w.id == id) {
+            if self.widgets[i].label != label {
+                self.widgets[i].label = label.to_string();
+                self.touch();
+            }
+        }
+    }
+
+    pub fn set_toggle(&mut self, id: &str, on: bool) {
+        if let Some(i) = self.widgets.iter().position(|w| w.id == id) {
+            if self.widgets[i].kind != WidgetKind::Toggle(on) {
+                self.widgets[i].kind = WidgetKind::Toggle(on);
+                self.touch();
+            }
+        }
+    }
+
+    /// Hover/press highlights and pointer cursors, in panel pixels.
+    pub fn set_pointers(&mut self, hovered: Vec<usize>, pressed: Vec<usize>, cursors: Vec<Vec2>) {
+        if hovered != self.hovered || pressed != self.pressed || cursors != self.cursors {
+            self.hovered = hovered;
+            self.pressed = pressed;
+            self.cursors = cursors;
+            self.touch();
+        }
+    }
+
+    /* ================= LAYOUT ================= */
+
+    fn viewport(&self) -> Rect {
+        Rect::new(
+            0.0,
+            Self::TITLE_HEIGHT,
+            self.resolution.x as f32,
+            self.resolution.y as f32 - Self::TITLE_HEIGHT,
+        )
+    }
+
+    fn content_height(&self) -> f32 {
+        Self::PADDING * 2.0 + self.widgets.len() as f32 * Self::ROW_HEIGHT
+    }
+
+    fn max_scroll(&self) -> f32 {
+        (self.content_height() - self.viewport().h).max(0.0)
+    }
+
+    /// Scrolls the widget column by `pixels`, positive moves content up.
+    pub fn scroll_by(&mut self, pixels: f32) {
+        let scroll = (self.scroll + pixels).clamp(0.0, self.max_scroll());
+        if scroll != self.scroll {
+            self.scroll = scroll;
+            self.touch();
+        }
+    }
+
+    /// Widget rectangle in panel pixels, after scrolling.
+    pub fn widget_rect(&self, index: usize) -> Rect {
+        Rect::new(
+            Self::PADDING,
+            Self::TITLE_HEIGHT + Self::PADDING + index as f32 * Self::ROW_HEIGHT - self.scroll,
+            self.resolution.x as f32 - Self::PADDING * 2.0 - Self::SCROLLBAR,
+            Self::ROW_HEIGHT - 8.0,
+        )
+    }
+
+    /// Interactive widget under a panel pixel.
+    pub fn widget_at(&self, pixel: Vec2) -> Option<usize> {
+        if !self.viewport().contains(pixel) {
+            return None;
+        }
+
+        (0..self.widgets.len())
+            .find(|&i| self.widgets[i].is_interactive() && self.widget_rect(i).contains(pixel))
+    }
+
+    /* ================= HIT TEST ================= */
+
+    /// Intersects a world-space ray with the front of the panel.
+    pub fn hit(&self, ray: &Ray) -> Option<PanelHit> {
+        let to_local = self.transform.inverse();
+        let origin = to_local.transform_point3(ray.origin);
+        let direction = to_local.transform_vector3(ray.direction);
+
+        // only rays travelling into the front face
+        if direction.z >= -1e-6 {
+            return None;
+        }
+
+        let distance = -origin.z / direction.z;
+        if distance < 0.0 {
+            return None;
+        }
+
+        let local = origin + direction * distance;
+        let half = self.size * 0.5;
+        if local.x.abs() > half.x || local.y.abs() > half.y {
+            return None;
+        }
+
+        let pixel = Vec2::new(
+            (local.x / self.size.x + 0.5) * self.resolution.x as f32,
+            (0.5 - local.y / self.size.y) * self.resolution.y as f32,
+        );
+
+        Some(PanelHit {
+            panel: self.id,
+            point: ray.at(distance),
+            pixel,
+            distance,
+            widget: self.widget_at(pixel),
+        })
+    }
+
+    /// World-space corners, top-left first, clockwise.
+    pub fn corners(&self) -> [Vec3; 4] {
+        let half = self.size * 0.5;
+        [
+            Vec3::new(-half.x, half.y, 0.0),
+            Vec3::new(half.x, half.y, 0.0),
+            Vec3::new(half.x, -half.y, 0.0),
+            Vec3::new(-half.x, -half.y, 0.0),
+        ]
+        .map(|c| self.transform.transform_point3(c))
+    }
+
+    /* ================= DRAW ================= */
+
+    pub fn draw(&self) -> Canvas {
+        let (w, h) = (self.resolution.x as f32, self.resolution.y as f32);
+        let scale = Self::TEXT_SCALE;
+        let text_h = Canvas::line_height(scale);
+
+        let mut canvas = Canvas::new(self.resolution.x, self.resolution.y);
+
+        canvas.rect(Rect::new(0.0, 0.0, w, h), [0.08, 0.09, 0.12]);
+        canvas.rect(Rect::new(0.0, 0.0, w, Self::TITLE_HEIGHT), [0.16, 0.2, 0.3]);
+        canvas.text(
+            Self::PADDING,
+            (Self::TITLE_HEIGHT - text_h) * 0.5,
+            scale,
+            &self.title,
+            [0.95, 0.95, 1.0],
+        );
+
+        /* ---------- widgets ---------- */
+
+        let viewport = self.viewport();
+        canvas.set_clip(Some(viewport));
+
+        for (i, widget) in self.widgets.iter().enumerate() {
+            let rect = self.widget_rect(i);
+            if rect.intersect(&viewport).is_none() {
+                continue;
+            }
+
+            let text_y = rect.y + (rect.h - text_h) * 0.5;
+
+            if widget.is_interactive() {
+                let background = if self.pressed.contains(&i) {
+                    [0.35, 0.45, 0.7]
+                } else if self.hovered.contains(&i) {
+                    [0.25, 0.3, 0.45]
+                } else {
+                    [0.15, 0.17, 0.22]
+                };
+                canvas.rect(rect, background);
+            }
+
+            canvas.text(rect.x + 12.0, text_y, scale, &widget.label, [0.9, 0.9, 0.92]);
+
+            if let WidgetKind::Toggle(on) = widget.kind {
+                let size = rect.h - 20.0;
+                let knob = Rect::new(rect.x + rect.w - size - 12.0, rect.y + 10.0, size, size);
+                canvas.rect(knob, [0.5, 0.5, 0.55]);
+                let fill = if on { [0.3, 0.85, 0.45] } else { [0.12, 0.12, 0.15] };
+                canvas.rect(knob.inset(3.0), fill);
+            } else if widget.kind == WidgetKind::Button {
+                let arrow = ">";
+                let x = rect.x + rect.w - 12.0 - text_width(arrow, scale);
+                canvas.text(x, text_y, scale, arrow, [0.6, 0.6, 0.7]);
+            }
+        }
+
+        /* ---------- scrollbar ---------- */
+
+        let max_scroll = self.max_scroll();
+        if max_scroll > 0.0 {
+            let track = Rect::new(w - Self::SCROLLBAR - 4.0, viewport.y + 4.0, Self::SCROLLBAR, viewport.h - 8.0);
+            let thumb_h = track.h * viewport.h / self.content_height();
+            let thumb_y = track.y + (track.h - thumb_h) * self.scroll / max_scroll;
+
+            canvas.rect(track, [0.12, 0.13, 0.17]);
+            canvas.rect(Rect::new(track.x, thumb_y, track.w, thumb_h), [0.4, 0.45, 0.55]);
+        }
+
+        /* ---------- pointer cursors ---------- */
+
+        canvas.set_clip(None);
+        for c in &self.cursors {
+            canvas.rect(Rect::new(c.x - 5.0, c.y - 5.0, 10.0, 10.0), [0.1, 0.1, 0.1]);
+            canvas.rect(Rect::new(c.x - 3.0, c.y - 3.0, 6.0, 6.0), [1.0, 1.0, 1.0]);
+        }
+
+        canvas
+    }
+}
@@ -0,0 +1,86 @@
+use glam::{Mat4, Vec3};
+
+use crate::xr::{ControllerState, Hand, HandSkeleton, TrackedPose};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ray {
+    pub origin: Vec3,
+    /// Unit length.
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize_or_zero(),
+        }
+    }
+
+    /// Along the pose's forward (-Z) axis.
+    pub fn from_pose(pose: &TrackedPose) -> Self {
+        Self::new(pose.position, pose.forward())
+    }
+
+    pub fn transformed(&self, m: Mat4) -> Self {
+        Self::new(m.transform_point3(self.origin), m.transform_vector3(self.direction))
+    }
+
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerSource {
+    Mouse,
+    Hand(Hand),
+}
+
+/// One pointer for one frame: a world-space ray, whether its button is
+/// held and how far it scrolled (positive = wheel up / stick forward).
+#[derive(Clone, Copy, Debug)]
+pub struct UiPointer {
+    pub source: PointerSource,
+    pub ray: Ray,
+    pub click: bool,
+    pub scroll: f32,
+}
+
+impl UiPointer {
+    const CLICK_THRESHOLD: f32 = 0.6;
+    /// Thumbstick deflection ignored when scrolling.
+    const DEADZONE: f32 = 0.2;
+
+    /// Laser from the controller's aim pose; the trigger clicks and the
+    /// thumbstick scrolls.
+    pub fn from_controller(hand: Hand, c: &ControllerState, tracking_to_world: Mat4) -> Option<Self> {
+        if !c.pose.valid {
+            return None;
+        }
+
+        let stick = c.thumbstick.y;
+        let scroll = if stick.abs() > Self::DEADZONE { stick } else { 0.0 };
+
+        Some(Self {
+            source: PointerSource::Hand(hand),
+            ray: Ray::from_pose(&c.pose).transformed(tracking_to_world),
+            click: c.trigger > Self::CLICK_THRESHOLD,
+            scroll,
+        })
+    }
+
+    /// Laser along a tracked hand's palm, clicked by pinching.
+    pub fn from_hand(hand: Hand, skeleton: &HandSkeleton, tracking_to_world: Mat4) -> Option<Self> {
+        if !skeleton.valid {
+            return None;
+        }
+
+        Some(Self {
+            source: PointerSource::Hand(hand),
+            ray: Ray::from_pose(&skeleton.palm()).transformed(tracking_to_world),
+            click: skeleton.gestures().pinch > Self::CLICK_THRESHOLD,
+            scroll: 0.0,
+        })
+    }
+}
@@ -0,0 +1,105 @@
+use glam::{Mat4, UVec2, Vec2};
+
+use crate::locomotion::{LocomotionMode, LocomotionSettings, TurnMode};
+use crate::xr::Boundary;
+
+use super::{Panel, PanelId, Ui, UiEvent};
+
+/// Comfort and play-area options as a world-space panel.
+pub struct SettingsMenu {
+    pub panel: PanelId,
+}
+
+impl SettingsMenu {
+    pub fn new(ui: &mut Ui, transform: Mat4) -> Self {
+        let panel = Panel::new("SETTINGS", Vec2::new(0.6, 0.45), UVec2::new(512, 384))
+            .with_transform(transform)
+            .with_toggle("teleport", "Teleport", false)
+            .with_button("turn", "Turn")
+            .with_button("range", "Range")
+            .with_toggle("vignette", "Vignette", false)
+            .with_button("vignette_strength", "Strength")
+            .with_toggle("boundary", "Boundary", true)
+            .with_button("warn_distance", "Warn at");
+
+        Self { panel: ui.add(panel) }
+    }
+
+    /// Applies clicks on this menu, then refreshes every row from the
+    /// settings so changes made elsewhere show up too.
+    pub fn update(
+        &self,
+        ui: &mut Ui,
+        events: &[UiEvent],
+        locomotion: &mut LocomotionSettings,
+        boundary: &mut Boundary,
+    ) {
+        for event in events {
+            let UiEvent::Click { panel, widget } = *event;
+            if panel != self.panel {
+                continue;
+            }
+
+            match widget {
+                "teleport" => {
+                    locomotion.mode = match locomotion.mode {
+                        LocomotionMode::Smooth => LocomotionMode::Teleport,
+                        LocomotionMode::Teleport => LocomotionMode::Smooth,
+                    }
+                }
+                "turn" => {
+                    locomotion.turn = match locomotion.turn {
+                        TurnMode::Snap { degrees } if degrees < 45.0 => TurnMode::Snap { degrees: 45.0 },
+                        TurnMode::Snap { .. } => TurnMode::Smooth { degrees_per_second: 90.0 },
+                        TurnMode::Smooth { degrees_per_second } if degrees_per_second < 180.0 => {
+                            TurnMode::Smooth { degrees_per_second: 180.0 }
+                        }
+                        TurnMode::Smooth { .. } => TurnMode::Disabled,
+                        TurnMode::Disabled => TurnMode::Snap { degrees: 30.0 },
+                    }
+                }
+                "range" => locomotion.teleport_range = next_step(locomotion.teleport_range, &[5.0, 10.0, 15.0]),
+                "vignette" => locomotion.vignette = !locomotion.vignette,
+                "vignette_strength" => {
+                    locomotion.vignette_strength = next_step(locomotion.vignette_strength, &[0.3, 0.5, 0.7, 0.9])
+                }
+                "boundary" => boundary.enabled = !boundary.enabled,
+                "warn_distance" => boundary.warn_distance = next_step(boundary.warn_distance, &[0.2, 0.4, 0.6]),
+                _ => {}
+            }
+        }
+
+        let Some(panel) = ui.panel_mut(self.panel) else {
+            return;
+        };
+
+        let turn = match locomotion.turn {
+            TurnMode::Snap { degrees } => format!("Turn: snap {}", degrees),
+            TurnMode::Smooth { degrees_per_second } => format!("Turn: smooth {}/s", degrees_per_second),
+            TurnMode::Disabled => "Turn: off".to_string(),
+        };
+
+        panel.set_toggle("teleport", locomotion.mode == LocomotionMode::Teleport);
+        panel.set_label("turn", &turn);
+        panel.set_label("range", &format!("Teleport range: {} m", locomotion.teleport_range));
+        panel.set_toggle("vignette", locomotion.vignette);
+        panel.set_label(
+            "vignette_strength",
+            &format!("Vignette: {}%", (locomotion.vignette_strength * 100.0).round()),
+        );
+        panel.set_toggle("boundary", boundary.enabled);
+        panel.set_label(
+            "warn_distance",
+            &format!("Warn at: {} cm", (boundary.warn_distance * 100.0).round()),
+        );
+    }
+}
+
+/// The first step above `value`, wrapping to the first.
+fn next_step(value: f32, steps: &[f32]) -> f32 {
+    steps
+        .iter()
+        .copied()
+        .find(|&s| s > value + 1e-3)
+        .unwrap_or(steps[0])
+}
@@ -0,0 +1,212 @@
+use glam::{Mat4, Vec2, Vec3};
+
+use super::pose::PoseFrame;
+use crate::renderer::resources::mesh::Vertex;
+
+/// Play-area polygon on the floor of tracking space, as (x, z) corners in
+/// order. The user is expected to stay inside it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayArea {
+    pub points: Vec<Vec2>,
+}
+
+impl PlayArea {
+    /// Centred `width` x `depth` rectangle.
+    pub fn rectangle(width: f32, depth: f32) -> Self {
+        let (w, d) = (width * 0.5, depth * 0.5);
+        Self {
+            points: vec![
+                Vec2::new(-w, -d),
+                Vec2::new(w, -d),
+                Vec2::new(w, d),
+                Vec2::new(-w, d),
+            ],
+        }
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        let n = self.points.len();
+        (0..n).map(move |i| (self.points[i], self.points[(i + 1) % n]))
+    }
+
+    pub fn contains(&self, p: Vec2) -> bool {
+        // even-odd ray cast along +x
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            if (a.y > p.y) != (b.y > p.y) {
+                let x = a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if p.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    /// Closest point on the outline to `p`.
+    pub fn closest_point(&self, p: Vec2) -> Vec2 {
+        self.edges()
+            .map(|(a, b)| {
+                let ab = b - a;
+                let t = ((p - a).dot(ab) / ab.length_squared().max(1e-6)).clamp(0.0, 1.0);
+                a + ab * t
+            })
+            .min_by(|x, y| x.distance_squared(p).total_cmp(&y.distance_squared(p)))
+            .unwrap_or(p)
+    }
+
+    /// Distance to the outline, positive inside and negative outside.
+    pub fn signed_distance(&self, p: Vec2) -> f32 {
+        let d = self.closest_point(p).distance(p);
+        if self.contains(p) {
+            d
+        } else {
+            -d
+        }
+    }
+}
+
+/* =========================================================
+   BOUNDARY
+   ========================================================= */
+
+#[derive(Clone, Debug)]
+pub struct Boundary {
+    pub area: PlayArea,
+    pub enabled: bool,
+    /// The wall starts showing this far (metres) from the edge.
+    pub warn_distance: f32,
+    pub wall_height: f32,
+
+    visibility: f32,
+    closest: Vec2,
+}
+
+impl Boundary {
+    pub const GRID_SPACING: f32 = 0.25;
+
+    pub fn new(area: PlayArea) -> Self {
+        Self {
+            area,
+            enabled: true,
+            warn_distance: 0.4,
+            wall_height: 2.5,
+            visibility: 0.0,
+            closest: Vec2::ZERO,
+        }
+    }
+
+    /// 0 when every tracked point is well inside, 1 at or past the edge.
+    pub fn visibility(&self) -> f32 {
+        if self.enabled {
+            self.visibility
+        } else {
+            0.0
+        }
+    }
+
+    /// Checks the head and both hands (tracked hands when present).
+    pub fn update(&mut self, poses: &PoseFrame) {
+        if self.area.points.len() < 3 {
+            self.visibility = 0.0;
+            return;
+        }
+
+        let hands = [
+            (poses.left_hand.palm(), poses.left.pose),
+            (poses.right_hand.palm(), poses.right.pose),
+        ]
+        .map(|(hand, controller)| if hand.valid { hand } else { controller });
+
+        let nearest = [poses.head, hands[0], hands[1]]
+            .iter()
+            .filter(|p| p.valid)
+            .map(|p| {
+                let xz = Vec2::new(p.position.x, p.position.z);
+                (self.area.signed_distance(xz), xz)
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        match nearest {
+            Some((distance, xz)) => {
+                self.visibility = 1.0 - (distance / self.warn_distance.max(1e-3)).clamp(0.0, 1.0);
+                self.closest = self.area.closest_point(xz);
+            }
+            None => self.visibility = 0.0,
+        }
+    }
+
+    /// Line-list vertices in world space: a faint floor outline, plus a
+    /// wall grid around the nearest edge point that grows and brightens
+    /// with `visibility`.
+    pub fn lines(&self, tracking_to_world: Mat4) -> Vec<Vertex> {
+        let mut vertices = Vec::new();
+        if !self.enabled || self.area.points.len() < 3 {
+            return vertices;
+        }
+
+        let mut push = |a: Vec3, b: Vec3, color: [f32; 3]| {
+            vertices.push(Vertex::new(tracking_to_world.transform_point3(a).into(), color));
+            vertices.push(Vertex::new(tracking_to_world.transform_point3(b).into(), color));
+        };
+
+        let floor = Vec3::Y * 0.01;
+        let outline = [0.2, 0.45, 0.6];
+        for (a, b) in self.area.edges() {
+            push(Vec3::new(a.x, 0.0, a.y) + floor, Vec3::new(b.x, 0.0, b.y) + floor, outline);
+        }
+
+        let v = self.visibility;
+        if v <= 0.0 {
+            return vertices;
+        }
+
+        let bright = [0.3 + 0.2 * v, 0.5 + 0.5 * v, 0.6 + 0.4 * v];
+        let radius = 0.5 + 1.5 * v;
+        let top = self.wall_height * (0.4 + 0.6 * v);
+
+        for (a, b) in self.area.edges() {
+            let len = a.distance(b);
+            if len < 1e-4 {
+                continue;
+            }
+            let dir = (b - a) / len;
+
+            // the part of this edge inside the reveal radius
+            let t = (self.closest - a).dot(dir).clamp(0.0, len);
+            let off = (self.closest - (a + dir * t)).length();
+            if off >= radius {
+                continue;
+            }
+            let half = (radius * radius - off * off).sqrt();
+            let (t0, t1) = ((t - half).max(0.0), (t + half).min(len));
+
+            let at = |t: f32, y: f32| {
+                let p = a + dir * t;
+                Vec3::new(p.x, y, p.y)
+            };
+
+            // vertical posts
+            let mut s = (t0 / Self::GRID_SPACING).ceil() * Self::GRID_SPACING;
+            while s <= t1 {
+                push(at(s, 0.0), at(s, top), bright);
+                s += Self::GRID_SPACING;
+            }
+
+            // horizontal rails
+            let mut y = 0.0;
+            while y <= top {
+                push(at(t0, y), at(t1, y), bright);
+                y += Self::GRID_SPACING;
+            }
+        }
+
+        vertices
+    }
+}
+
+impl Default for Boundary {
+    fn default() -> Self {
+        Self::new(PlayArea::rectangle(2.0, 2.0))
+    }
+}
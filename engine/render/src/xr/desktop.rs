@@ -0,0 +1,285 @@
+use std::collections::HashSet;
+
+use glam::{Quat, Vec2, Vec3};
+use winit::event::*;
+
+use super::hand::HandSkeleton;
+use super::pose::{ControllerState, Hand, PoseFrame, PoseProvider, TrackedPose};
+
+/// Keyboard/mouse stand-in for a headset and two controllers.
+///
+/// This owns the WASD/mouse state that `app::run` used to track by hand.
+/// The head looks around with the middle mouse button (same as the orbit
+/// camera), WASD drives the left thumbstick, Q/E push the right thumbstick
+/// left/right (turning) and T pushes it forward (teleport aim). Space and R
+/// are the right primary/secondary buttons, and the left/right mouse
+/// buttons are the right trigger/grip, so the left button clicks UI and
+/// the right one grabs.
+/// Scroll wheel zooms the camera, or pushes the right hand in and out
+/// while the grip is held. The cursor position is kept for UI pointing.
+/// H toggles emulated hand tracking, where the grip curls the right
+/// hand's fingers and the trigger pinches.
+pub struct DesktopPoseProvider {
+    pressed: HashSet<VirtualKeyCode>,
+    jump_requested: bool,
+    hand_tracking: bool,
+
+    mouse_dx: f32,
+    mouse_dy: f32,
+    scroll: f32,
+    cursor: Option<Vec2>,
+
+    middle_mouse_held: bool,
+    left_mouse_held: bool,
+    right_mouse_held: bool,
+
+    look_yaw: f32,
+    look_pitch: f32,
+    reach: f32,
+
+    pub eye_height: f32,
+
+    time: f32,
+    last: Option<PoseFrame>,
+}
+
+impl DesktopPoseProvider {
+    pub fn new() -> Self {
+        Self {
+            pressed: HashSet::new(),
+            jump_requested: false,
+            hand_tracking: false,
+            mouse_dx: 0.0,
+            mouse_dy: 0.0,
+            scroll: 0.0,
+            cursor: None,
+            middle_mouse_held: false,
+            left_mouse_held: false,
+            right_mouse_held: false,
+            look_yaw: 0.0,
+            look_pitch: 0.0,
+            reach: 0.45,
+            eye_height: 1.6,
+            time: 0.0,
+            last: None,
+        }
+    }
+
+    /* ================= EVENTS ================= */
+
+    pub fn handle_window_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput { input, .. } => {
+                if let Some(key) = input.virtual_keycode {
+                    match input.state {
+                        ElementState::Pressed => {
+                            if key == VirtualKeyCode::Space {
+                                self.jump_requested = true;
+                            }
+                            if key == VirtualKeyCode::H && !self.pressed.contains(&key) {
+                                self.hand_tracking = !self.hand_tracking;
+                            }
+                            self.pressed.insert(key);
+                        }
+                        ElementState::Released => {
+                            self.pressed.remove(&key);
+                        }
+                    }
+                }
+            }
+
+            WindowEvent::MouseInput { state, button, .. } => {
+                let held = *state == ElementState::Pressed;
+                match button {
+                    MouseButton::Middle => self.middle_mouse_held = held,
+                    MouseButton::Left => self.left_mouse_held = held,
+                    MouseButton::Right => self.right_mouse_held = held,
+                    _ => {}
+                }
+            }
+
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = Some(Vec2::new(position.x as f32, position.y as f32));
+            }
+
+            WindowEvent::CursorLeft { .. } => self.cursor = None,
+
+            WindowEvent::MouseWheel { delta, .. } => {
+                let amount = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(p) => p.y as f32 * 0.01,
+                };
+
+                if self.right_mouse_held {
+                    self.reach = (self.reach + amount * 0.05).clamp(0.2, 0.8);
+                } else {
+                    self.scroll += amount;
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    pub fn handle_device_event(&mut self, event: &DeviceEvent) {
+        if let DeviceEvent::MouseMotion { delta } = event {
+            if self.middle_mouse_held {
+                self.mouse_dx += delta.0 as f32;
+                self.mouse_dy += delta.1 as f32;
+            }
+        }
+    }
+
+    /* ================= DESKTOP STATE ================= */
+
+    pub fn middle_mouse_held(&self) -> bool {
+        self.middle_mouse_held
+    }
+
+    pub fn left_mouse_held(&self) -> bool {
+        self.left_mouse_held
+    }
+
+    /// Cursor in window pixels, while it is inside the window.
+    pub fn cursor(&self) -> Option<Vec2> {
+        self.cursor
+    }
+
+    /// WASD as a movement vector (x = strafe, z = forward is negative).
+    pub fn move_input(&self) -> Vec3 {
+        let mut input = Vec3::ZERO;
+
+        if self.pressed.contains(&VirtualKeyCode::W) {
+            input.z -= 1.0;
+        }
+        if self.pressed.contains(&VirtualKeyCode::S) {
+            input.z += 1.0;
+        }
+        if self.pressed.contains(&VirtualKeyCode::A) {
+            input.x -= 1.0;
+        }
+        if self.pressed.contains(&VirtualKeyCode::D) {
+            input.x += 1.0;
+        }
+
+        input
+    }
+
+    /// Q/E as the turn axis, T as forward (teleport aim).
+    fn turn_stick(&self) -> Vec2 {
+        let mut stick = Vec2::ZERO;
+
+        if self.pressed.contains(&VirtualKeyCode::Q) {
+            stick.x -= 1.0;
+        }
+        if self.pressed.contains(&VirtualKeyCode::E) {
+            stick.x += 1.0;
+        }
+        if self.pressed.contains(&VirtualKeyCode::T) {
+            stick.y += 1.0;
+        }
+
+        stick
+    }
+
+    /// Returns and clears the jump edge triggered by Space.
+    pub fn take_jump(&mut self) -> bool {
+        std::mem::take(&mut self.jump_requested)
+    }
+
+    /// Returns and clears the accumulated mouse motion and scroll.
+    pub fn take_mouse(&mut self) -> (f32, f32, f32) {
+        let sensitivity = 0.005;
+        self.look_yaw += self.mouse_dx * sensitivity;
+        self.look_pitch = (self.look_pitch - self.mouse_dy * sensitivity).clamp(-1.4, 1.4);
+
+        let out = (self.mouse_dx, self.mouse_dy, self.scroll);
+        self.mouse_dx = 0.0;
+        self.mouse_dy = 0.0;
+        self.scroll = 0.0;
+        out
+    }
+
+    /* ================= EMULATED POSES ================= */
+
+    /// Body yaw matching the orbit camera, which starts out looking down +Z.
+    fn facing(&self) -> Quat {
+        Quat::from_rotation_y(self.look_yaw + std::f32::consts::PI)
+    }
+
+    fn head_pose(&self) -> TrackedPose {
+        let orientation = self.facing() * Quat::from_rotation_x(self.look_pitch);
+
+        TrackedPose::new(Vec3::new(0.0, self.eye_height, 0.0), orientation)
+    }
+
+    fn hand_pose(&self, head: &TrackedPose, side: f32, reach: f32) -> TrackedPose {
+        // hands follow the head yaw only, held below and in front of the eyes
+        let body = self.facing();
+        let offset = Vec3::new(0.2 * side, -0.35, 0.0);
+        let position = head.position + body * offset + head.forward() * reach;
+
+        TrackedPose::new(position, head.orientation)
+    }
+}
+
+impl Default for DesktopPoseProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PoseProvider for DesktopPoseProvider {
+    fn sample(&mut self, dt: f32) -> PoseFrame {
+        self.time += dt;
+
+        let head = self.head_pose();
+        let stick = self.move_input();
+
+        let left = ControllerState {
+            pose: self.hand_pose(&head, -1.0, 0.35),
+            thumbstick: Vec2::new(stick.x, -stick.z).normalize_or_zero(),
+            menu: self.pressed.contains(&VirtualKeyCode::Escape),
+            ..Default::default()
+        };
+
+        let right = ControllerState {
+            pose: self.hand_pose(&head, 1.0, self.reach),
+            trigger: if self.left_mouse_held { 1.0 } else { 0.0 },
+            grip: if self.right_mouse_held { 1.0 } else { 0.0 },
+            thumbstick: self.turn_stick(),
+            primary: self.pressed.contains(&VirtualKeyCode::Space),
+            secondary: self.pressed.contains(&VirtualKeyCode::R),
+            ..Default::default()
+        };
+
+        let (left_hand, right_hand) = if self.hand_tracking {
+            (
+                HandSkeleton::procedural(Hand::Left, left.pose, 0.0, 0.0),
+                HandSkeleton::procedural(Hand::Right, right.pose, right.grip, right.trigger),
+            )
+        } else {
+            Default::default()
+        };
+
+        let mut frame = PoseFrame {
+            time: self.time,
+            head,
+            left,
+            right,
+            left_hand,
+            right_hand,
+        };
+
+        if let Some(prev) = &self.last {
+            frame = frame.with_velocity_from(prev, dt);
+        }
+
+        self.last = Some(frame);
+        frame
+    }
+
+    fn name(&self) -> &str {
+        "desktop"
+    }
+}
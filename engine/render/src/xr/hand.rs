@@ -0,0 +1,365 @@
+use glam::{Mat4, Quat, Vec3};
+
+use super::pose::{Hand, TrackedPose};
+
+/* =========================================================
+   JOINTS
+   ========================================================= */
+
+/// The 26 joints of the standard hand skeleton, in the order runtimes
+/// report them (palm, wrist, then thumb to little finger, base to tip).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HandJoint {
+    Palm,
+    Wrist,
+
+    ThumbMetacarpal,
+    ThumbProximal,
+    ThumbDistal,
+    ThumbTip,
+
+    IndexMetacarpal,
+    IndexProximal,
+    IndexIntermediate,
+    IndexDistal,
+    IndexTip,
+
+    MiddleMetacarpal,
+    MiddleProximal,
+    MiddleIntermediate,
+    MiddleDistal,
+    MiddleTip,
+
+    RingMetacarpal,
+    RingProximal,
+    RingIntermediate,
+    RingDistal,
+    RingTip,
+
+    LittleMetacarpal,
+    LittleProximal,
+    LittleIntermediate,
+    LittleDistal,
+    LittleTip,
+}
+
+impl HandJoint {
+    pub const COUNT: usize = 26;
+
+    pub const THUMB: [HandJoint; 4] = [
+        HandJoint::ThumbMetacarpal,
+        HandJoint::ThumbProximal,
+        HandJoint::ThumbDistal,
+        HandJoint::ThumbTip,
+    ];
+
+    /// Index, middle, ring, little; metacarpal to tip.
+    pub const FINGERS: [[HandJoint; 5]; 4] = [
+        [
+            HandJoint::IndexMetacarpal,
+            HandJoint::IndexProximal,
+            HandJoint::IndexIntermediate,
+            HandJoint::IndexDistal,
+            HandJoint::IndexTip,
+        ],
+        [
+            HandJoint::MiddleMetacarpal,
+            HandJoint::MiddleProximal,
+            HandJoint::MiddleIntermediate,
+            HandJoint::MiddleDistal,
+            HandJoint::MiddleTip,
+        ],
+        [
+            HandJoint::RingMetacarpal,
+            HandJoint::RingProximal,
+            HandJoint::RingIntermediate,
+            HandJoint::RingDistal,
+            HandJoint::RingTip,
+        ],
+        [
+            HandJoint::LittleMetacarpal,
+            HandJoint::LittleProximal,
+            HandJoint::LittleIntermediate,
+            HandJoint::LittleDistal,
+            HandJoint::LittleTip,
+        ],
+    ];
+
+    pub fn from_index(i: usize) -> Option<HandJoint> {
+        HandSkeleton::ORDER.get(i).copied()
+    }
+}
+
+/* =========================================================
+   SKELETON
+   ========================================================= */
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HandJointPose {
+    pub position: Vec3,
+    pub orientation: Quat,
+    pub radius: f32,
+}
+
+impl Default for HandJointPose {
+    fn default() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            orientation: Quat::IDENTITY,
+            radius: 0.01,
+        }
+    }
+}
+
+/// One tracked hand. Joint axes follow the usual convention: -Z points
+/// along the bone towards the fingertip, +Y out of the back of the hand.
+#[derive(Clone, Copy, Debug)]
+pub struct HandSkeleton {
+    pub joints: [HandJointPose; HandJoint::COUNT],
+    pub valid: bool,
+}
+
+impl Default for HandSkeleton {
+    fn default() -> Self {
+        Self {
+            joints: [HandJointPose::default(); HandJoint::COUNT],
+            valid: false,
+        }
+    }
+}
+
+impl HandSkeleton {
+    pub const ORDER: [HandJoint; HandJoint::COUNT] = [
+        HandJoint::Palm,
+        HandJoint::Wrist,
+        HandJoint::ThumbMetacarpal,
+        HandJoint::ThumbProximal,
+        HandJoint::ThumbDistal,
+        HandJoint::ThumbTip,
+        HandJoint::IndexMetacarpal,
+        HandJoint::IndexProximal,
+        HandJoint::IndexIntermediate,
+        HandJoint::IndexDistal,
+        HandJoint::IndexTip,
+        HandJoint::MiddleMetacarpal,
+        HandJoint::MiddleProximal,
+        HandJoint::MiddleIntermediate,
+        HandJoint::MiddleDistal,
+        HandJoint::MiddleTip,
+        HandJoint::RingMetacarpal,
+        HandJoint::RingProximal,
+        HandJoint::RingIntermediate,
+        HandJoint::RingDistal,
+        HandJoint::RingTip,
+        HandJoint::LittleMetacarpal,
+        HandJoint::LittleProximal,
+        HandJoint::LittleIntermediate,
+        HandJoint::LittleDistal,
+        HandJoint::LittleTip,
+    ];
+
+    pub fn joint(&self, joint: HandJoint) -> &HandJointPose {
+        &self.joints[joint as usize]
+    }
+
+    pub fn joint_mut(&mut self, joint: HandJoint) -> &mut HandJointPose {
+        &mut self.joints[joint as usize]
+    }
+
+    pub fn palm(&self) -> TrackedPose {
+        if !self.valid {
+            return TrackedPose::INVALID;
+        }
+        let p = self.joint(HandJoint::Palm);
+        TrackedPose::new(p.position, p.orientation)
+    }
+
+    pub fn wrist(&self) -> TrackedPose {
+        if !self.valid {
+            return TrackedPose::INVALID;
+        }
+        let p = self.joint(HandJoint::Wrist);
+        TrackedPose::new(p.position, p.orientation)
+    }
+
+    /// Wrist through each finger chain, as (from, to) joint pairs.
+    pub fn bones() -> Vec<(HandJoint, HandJoint)> {
+        let mut bones = vec![(HandJoint::Wrist, HandJoint::ThumbMetacarpal)];
+        bones.extend(HandJoint::THUMB.windows(2).map(|w| (w[0], w[1])));
+
+        for finger in HandJoint::FINGERS {
+            bones.push((HandJoint::Wrist, finger[0]));
+            bones.extend(finger.windows(2).map(|w| (w[0], w[1])));
+        }
+
+        bones
+    }
+
+    pub fn transformed(&self, m: Mat4) -> Self {
+        let (_, rotation, _) = m.to_scale_rotation_translation();
+        let mut out = *self;
+
+        for j in &mut out.joints {
+            j.position = m.transform_point3(j.position);
+            j.orientation = rotation * j.orientation;
+        }
+
+        out
+    }
+
+    pub fn lerp(&self, other: &HandSkeleton, t: f32) -> Self {
+        // a hand that drops out doesn't fade, it disappears
+        if !self.valid || !other.valid {
+            return if t < 0.5 { *self } else { *other };
+        }
+
+        let mut out = *self;
+        for (j, o) in out.joints.iter_mut().zip(&other.joints) {
+            j.position = j.position.lerp(o.position, t);
+            j.orientation = j.orientation.slerp(o.orientation, t);
+            j.radius += (o.radius - j.radius) * t;
+        }
+        out
+    }
+
+    /* ================= GESTURES ================= */
+
+    pub fn gestures(&self) -> HandGestures {
+        if !self.valid {
+            return HandGestures::default();
+        }
+
+        let remap = |d: f32, closed: f32, open: f32| 1.0 - ((d - closed) / (open - closed)).clamp(0.0, 1.0);
+
+        let thumb = self.joint(HandJoint::ThumbTip).position;
+        let index = self.joint(HandJoint::IndexTip).position;
+        let pinch = remap(thumb.distance(index), 0.015, 0.05);
+
+        let palm = self.joint(HandJoint::Palm).position;
+        let curl: f32 = HandJoint::FINGERS
+            .iter()
+            .map(|f| self.joint(f[4]).position.distance(palm))
+            .sum::<f32>()
+            / 4.0;
+        let grab = remap(curl, 0.045, 0.09);
+
+        HandGestures {
+            pinch,
+            grab,
+            pinch_point: (thumb + index) * 0.5,
+        }
+    }
+
+    /* ================= PROCEDURAL ================= */
+
+    /// A plausible hand around `palm` (tracking or world space), with
+    /// fingers curled by `curl` and the thumb brought to the index by
+    /// `pinch`. Used by the desktop emulator and scripted sessions.
+    pub fn procedural(hand: Hand, palm: TrackedPose, curl: f32, pinch: f32) -> Self {
+        let mut s = Self {
+            valid: palm.valid,
+            ..Default::default()
+        };
+
+        // thumb side: -X on the right hand, +X on the left
+        let side = match hand {
+            Hand::Left => 1.0,
+            Hand::Right => -1.0,
+        };
+        let at = |local: Vec3| palm.position + palm.orientation * local;
+
+        *s.joint_mut(HandJoint::Palm) = HandJointPose {
+            position: palm.position,
+            orientation: palm.orientation,
+            radius: 0.02,
+        };
+
+        let wrist = Vec3::new(0.0, 0.0, 0.045);
+        *s.joint_mut(HandJoint::Wrist) = HandJointPose {
+            position: at(wrist),
+            orientation: palm.orientation,
+            radius: 0.02,
+        };
+
+        /* ================= FINGERS ================= */
+
+        let spread = [0.024, 0.006, -0.011, -0.026];
+        let lengths: [[f32; 4]; 4] = [
+            [0.045, 0.040, 0.025, 0.020],
+            [0.045, 0.045, 0.028, 0.021],
+            [0.043, 0.042, 0.026, 0.020],
+            [0.040, 0.033, 0.020, 0.018],
+        ];
+
+        for (f, finger) in HandJoint::FINGERS.iter().enumerate() {
+            let knuckle = Vec3::new(spread[f] * side, 0.0, -0.035);
+
+            // metacarpal sits between wrist and knuckle
+            let meta = wrist.lerp(knuckle, 0.35);
+            *s.joint_mut(finger[0]) = HandJointPose {
+                position: at(meta),
+                orientation: palm.orientation,
+                radius: 0.011,
+            };
+
+            let mut p = knuckle;
+            let mut bend = Quat::IDENTITY;
+
+            for (k, len) in lengths[f][1..].iter().enumerate() {
+                // each phalanx rolls further towards the palm (-Y)
+                bend *= Quat::from_rotation_x(-curl * 75f32.to_radians());
+                let orientation = palm.orientation * bend;
+
+                *s.joint_mut(finger[k + 1]) = HandJointPose {
+                    position: at(p),
+                    orientation,
+                    radius: 0.01 - k as f32 * 0.001,
+                };
+
+                p += bend * Vec3::new(0.0, 0.0, -len);
+            }
+
+            *s.joint_mut(finger[4]) = HandJointPose {
+                position: at(p),
+                orientation: palm.orientation * bend,
+                radius: 0.007,
+            };
+        }
+
+        /* ================= THUMB ================= */
+
+        let index_tip = s.joint(HandJoint::IndexTip).position;
+        let dir = Quat::from_rotation_y(side * 40f32.to_radians());
+        let mut p = Vec3::new(0.02 * side, -0.012, 0.03);
+
+        for (k, (joint, len)) in HandJoint::THUMB
+            .iter()
+            .zip([0.035, 0.032, 0.028, 0.0])
+            .enumerate()
+        {
+            let pull = pinch * k as f32 / 3.0;
+            let orientation = palm.orientation * dir;
+
+            *s.joint_mut(*joint) = HandJointPose {
+                position: at(p).lerp(index_tip, pull),
+                orientation,
+                radius: 0.011 - k as f32 * 0.001,
+            };
+
+            p += dir * Vec3::new(0.0, 0.0, -len);
+        }
+
+        s
+    }
+}
+
+/// Continuous gesture strengths, 0 (open) to 1 (fully closed).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HandGestures {
+    /// Thumb tip against index tip.
+    pub pinch: f32,
+    /// Fingertips curled into the palm.
+    pub grab: f32,
+    /// Between thumb and index tips, where a pinch holds things.
+    pub pinch_point: Vec3,
+}
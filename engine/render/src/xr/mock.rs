@@ -0,0 +1,93 @@
+use super::pose::{PoseFrame, PoseProvider};
+
+/// Plays back a hand-authored list of keyframes, interpolating between
+/// them. Useful for driving interactions from tests and demos.
+pub struct ScriptedPoseProvider {
+    keys: Vec<PoseFrame>,
+    time: f32,
+    looping: bool,
+    last: Option<PoseFrame>,
+}
+
+impl ScriptedPoseProvider {
+    pub fn new(mut keys: Vec<PoseFrame>) -> Self {
+        keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        Self {
+            keys,
+            time: 0.0,
+            looping: false,
+            last: None,
+        }
+    }
+
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn push(&mut self, key: PoseFrame) {
+        let at = self.keys.partition_point(|k| k.time <= key.time);
+        self.keys.insert(at, key);
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keys.last().map(|k| k.time).unwrap_or(0.0)
+    }
+
+    pub fn finished(&self) -> bool {
+        !self.looping && self.time >= self.duration()
+    }
+
+    pub fn reset(&mut self) {
+        self.time = 0.0;
+        self.last = None;
+    }
+
+    fn evaluate(&self, time: f32) -> PoseFrame {
+        let (first, last) = match (self.keys.first(), self.keys.last()) {
+            (Some(f), Some(l)) => (f, l),
+            _ => return PoseFrame { time, ..Default::default() },
+        };
+
+        if time <= first.time {
+            return PoseFrame { time, ..*first };
+        }
+        if time >= last.time {
+            return PoseFrame { time, ..*last };
+        }
+
+        let next = self.keys.partition_point(|k| k.time <= time);
+        let a = &self.keys[next - 1];
+        let b = &self.keys[next];
+        let span = b.time - a.time;
+        let t = if span > 0.0 { (time - a.time) / span } else { 1.0 };
+
+        PoseFrame { time, ..a.lerp(b, t) }
+    }
+}
+
+impl PoseProvider for ScriptedPoseProvider {
+    fn sample(&mut self, dt: f32) -> PoseFrame {
+        self.time += dt;
+
+        let duration = self.duration();
+        if self.looping && duration > 0.0 && self.time > duration {
+            self.time %= duration;
+            // don't difference across the wrap, it would read as a teleport
+            self.last = None;
+        }
+
+        let mut frame = self.evaluate(self.time);
+        if let Some(prev) = &self.last {
+            frame = frame.with_velocity_from(prev, dt);
+        }
+
+        self.last = Some(frame);
+        frame
+    }
+
+    fn name(&self) -> &str {
+        "scripted"
+    }
+}
@@ -0,0 +1,16 @@
+pub mod pose;
+pub mod hand;
+pub mod mock;
+pub mod replay;
+pub mod desktop;
+pub mod boundary;
+
+#[cfg(any(windows, target_os = "linux"))]
+pub mod openxr;
+
+pub use hand::{HandGestures, HandJoint, HandJointPose, HandSkeleton};
+pub use pose::{ControllerState, Hand, PoseFrame, PoseProvider, TrackedPose};
+pub use mock::ScriptedPoseProvider;
+pub use replay::{PoseRecorder, ReplayPoseProvider};
+pub use desktop::DesktopPoseProvider;
+pub use boundary::{Boundary, PlayArea};
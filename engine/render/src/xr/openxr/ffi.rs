@@ -0,0 +1,843 @@
+//! Hand-written subset of the OpenXR 1.0 C API: only the types, enums
+//! and entry points the backend calls. Everything is resolved at runtime
+//! through `xrGetInstanceProcAddr`, so nothing links against the loader.
+
+#![allow(non_camel_case_types, non_snake_case, dead_code)]
+
+use std::ffi::{c_char, c_void};
+
+pub type XrResult = i32;
+pub type XrStructureType = i32;
+pub type XrBool32 = u32;
+pub type XrTime = i64;
+pub type XrDuration = i64;
+pub type XrSystemId = u64;
+pub type XrPath = u64;
+pub type XrFlags64 = u64;
+
+pub type XrInstance = u64;
+pub type XrSession = u64;
+pub type XrSpace = u64;
+pub type XrActionSet = u64;
+pub type XrAction = u64;
+pub type XrSwapchain = u64;
+pub type XrHandTrackerEXT = u64;
+
+pub const XR_NULL_HANDLE: u64 = 0;
+pub const XR_NULL_PATH: XrPath = 0;
+pub const XR_TRUE: XrBool32 = 1;
+pub const XR_INFINITE_DURATION: XrDuration = 0x7fff_ffff_ffff_ffff;
+
+pub const XR_MAX_EXTENSION_NAME_SIZE: usize = 128;
+pub const XR_MAX_APPLICATION_NAME_SIZE: usize = 128;
+pub const XR_MAX_ENGINE_NAME_SIZE: usize = 128;
+pub const XR_MAX_ACTION_SET_NAME_SIZE: usize = 64;
+pub const XR_MAX_ACTION_NAME_SIZE: usize = 64;
+pub const XR_MAX_LOCALIZED_ACTION_SET_NAME_SIZE: usize = 128;
+pub const XR_MAX_LOCALIZED_ACTION_NAME_SIZE: usize = 128;
+
+pub const fn xr_make_version(major: u64, minor: u64, patch: u64) -> u64 {
+    (major << 48) | (minor << 32) | patch
+}
+
+pub const XR_CURRENT_API_VERSION: u64 = xr_make_version(1, 0, 0);
+
+/* =========================================================
+   RESULTS
+   ========================================================= */
+
+pub const XR_SUCCESS: XrResult = 0;
+pub const XR_TIMEOUT_EXPIRED: XrResult = 1;
+pub const XR_SESSION_LOSS_PENDING: XrResult = 3;
+pub const XR_EVENT_UNAVAILABLE: XrResult = 4;
+pub const XR_SESSION_NOT_FOCUSED: XrResult = 8;
+pub const XR_FRAME_DISCARDED: XrResult = 9;
+pub const XR_ERROR_RUNTIME_FAILURE: XrResult = -2;
+pub const XR_ERROR_FORM_FACTOR_UNAVAILABLE: XrResult = -35;
+
+/* =========================================================
+   STRUCTURE TYPES
+   ========================================================= */
+
+pub const XR_TYPE_EXTENSION_PROPERTIES: XrStructureType = 2;
+pub const XR_TYPE_INSTANCE_CREATE_INFO: XrStructureType = 3;
+pub const XR_TYPE_SYSTEM_GET_INFO: XrStructureType = 4;
+pub const XR_TYPE_VIEW_LOCATE_INFO: XrStructureType = 6;
+pub const XR_TYPE_VIEW: XrStructureType = 7;
+pub const XR_TYPE_SESSION_CREATE_INFO: XrStructureType = 8;
+pub const XR_TYPE_SWAPCHAIN_CREATE_INFO: XrStructureType = 9;
+pub const XR_TYPE_SESSION_BEGIN_INFO: XrStructureType = 10;
+pub const XR_TYPE_VIEW_STATE: XrStructureType = 11;
+pub const XR_TYPE_FRAME_END_INFO: XrStructureType = 12;
+pub const XR_TYPE_EVENT_DATA_BUFFER: XrStructureType = 16;
+pub const XR_TYPE_EVENT_DATA_INSTANCE_LOSS_PENDING: XrStructureType = 17;
+pub const XR_TYPE_EVENT_DATA_SESSION_STATE_CHANGED: XrStructureType = 18;
+pub const XR_TYPE_ACTION_STATE_BOOLEAN: XrStructureType = 23;
+pub const XR_TYPE_ACTION_STATE_FLOAT: XrStructureType = 24;
+pub const XR_TYPE_ACTION_STATE_VECTOR2F: XrStructureType = 25;
+pub const XR_TYPE_ACTION_SET_CREATE_INFO: XrStructureType = 28;
+pub const XR_TYPE_ACTION_CREATE_INFO: XrStructureType = 29;
+pub const XR_TYPE_FRAME_WAIT_INFO: XrStructureType = 33;
+pub const XR_TYPE_COMPOSITION_LAYER_PROJECTION: XrStructureType = 35;
+pub const XR_TYPE_REFERENCE_SPACE_CREATE_INFO: XrStructureType = 37;
+pub const XR_TYPE_ACTION_SPACE_CREATE_INFO: XrStructureType = 38;
+pub const XR_TYPE_VIEW_CONFIGURATION_VIEW: XrStructureType = 41;
+pub const XR_TYPE_SPACE_LOCATION: XrStructureType = 42;
+pub const XR_TYPE_SPACE_VELOCITY: XrStructureType = 43;
+pub const XR_TYPE_FRAME_STATE: XrStructureType = 44;
+pub const XR_TYPE_FRAME_BEGIN_INFO: XrStructureType = 46;
+pub const XR_TYPE_COMPOSITION_LAYER_PROJECTION_VIEW: XrStructureType = 48;
+pub const XR_TYPE_INTERACTION_PROFILE_SUGGESTED_BINDING: XrStructureType = 51;
+pub const XR_TYPE_SWAPCHAIN_IMAGE_ACQUIRE_INFO: XrStructureType = 55;
+pub const XR_TYPE_SWAPCHAIN_IMAGE_WAIT_INFO: XrStructureType = 56;
+pub const XR_TYPE_SWAPCHAIN_IMAGE_RELEASE_INFO: XrStructureType = 57;
+pub const XR_TYPE_ACTION_STATE_GET_INFO: XrStructureType = 58;
+pub const XR_TYPE_SESSION_ACTION_SETS_ATTACH_INFO: XrStructureType = 60;
+pub const XR_TYPE_ACTIONS_SYNC_INFO: XrStructureType = 61;
+
+// XR_KHR_vulkan_enable2
+pub const XR_TYPE_GRAPHICS_BINDING_VULKAN2_KHR: XrStructureType = 1000025000;
+pub const XR_TYPE_SWAPCHAIN_IMAGE_VULKAN2_KHR: XrStructureType = 1000025001;
+pub const XR_TYPE_GRAPHICS_REQUIREMENTS_VULKAN2_KHR: XrStructureType = 1000025002;
+pub const XR_TYPE_VULKAN_INSTANCE_CREATE_INFO_KHR: XrStructureType = 1000090000;
+pub const XR_TYPE_VULKAN_DEVICE_CREATE_INFO_KHR: XrStructureType = 1000090001;
+pub const XR_TYPE_VULKAN_GRAPHICS_DEVICE_GET_INFO_KHR: XrStructureType = 1000090003;
+
+// XR_EXT_hand_tracking
+pub const XR_TYPE_HAND_TRACKER_CREATE_INFO_EXT: XrStructureType = 1000051001;
+pub const XR_TYPE_HAND_JOINTS_LOCATE_INFO_EXT: XrStructureType = 1000051002;
+pub const XR_TYPE_HAND_JOINT_LOCATIONS_EXT: XrStructureType = 1000051003;
+
+pub const XR_KHR_VULKAN_ENABLE2_EXTENSION_NAME: &str = "XR_KHR_vulkan_enable2";
+pub const XR_MND_HEADLESS_EXTENSION_NAME: &str = "XR_MND_headless";
+pub const XR_EXT_HAND_TRACKING_EXTENSION_NAME: &str = "XR_EXT_hand_tracking";
+
+/* =========================================================
+   ENUMS / FLAGS
+   ========================================================= */
+
+pub const XR_FORM_FACTOR_HEAD_MOUNTED_DISPLAY: i32 = 1;
+pub const XR_VIEW_CONFIGURATION_TYPE_PRIMARY_STEREO: i32 = 2;
+pub const XR_ENVIRONMENT_BLEND_MODE_OPAQUE: i32 = 1;
+
+pub const XR_REFERENCE_SPACE_TYPE_VIEW: i32 = 1;
+pub const XR_REFERENCE_SPACE_TYPE_LOCAL: i32 = 2;
+pub const XR_REFERENCE_SPACE_TYPE_STAGE: i32 = 3;
+
+pub const XR_SESSION_STATE_IDLE: i32 = 1;
+pub const XR_SESSION_STATE_READY: i32 = 2;
+pub const XR_SESSION_STATE_SYNCHRONIZED: i32 = 3;
+pub const XR_SESSION_STATE_VISIBLE: i32 = 4;
+pub const XR_SESSION_STATE_FOCUSED: i32 = 5;
+pub const XR_SESSION_STATE_STOPPING: i32 = 6;
+pub const XR_SESSION_STATE_LOSS_PENDING: i32 = 7;
+pub const XR_SESSION_STATE_EXITING: i32 = 8;
+
+pub const XR_ACTION_TYPE_BOOLEAN_INPUT: i32 = 1;
+pub const XR_ACTION_TYPE_FLOAT_INPUT: i32 = 2;
+pub const XR_ACTION_TYPE_VECTOR2F_INPUT: i32 = 3;
+pub const XR_ACTION_TYPE_POSE_INPUT: i32 = 4;
+
+pub const XR_SWAPCHAIN_USAGE_COLOR_ATTACHMENT_BIT: XrFlags64 = 0x1;
+pub const XR_SWAPCHAIN_USAGE_SAMPLED_BIT: XrFlags64 = 0x20;
+
+pub const XR_SPACE_LOCATION_ORIENTATION_VALID_BIT: XrFlags64 = 0x1;
+pub const XR_SPACE_LOCATION_POSITION_VALID_BIT: XrFlags64 = 0x2;
+pub const XR_SPACE_VELOCITY_LINEAR_VALID_BIT: XrFlags64 = 0x1;
+pub const XR_SPACE_VELOCITY_ANGULAR_VALID_BIT: XrFlags64 = 0x2;
+
+/* =========================================================
+   MATH
+   ========================================================= */
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct XrVector2f {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct XrVector3f {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct XrQuaternionf {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct XrPosef {
+    pub orientation: XrQuaternionf,
+    pub position: XrVector3f,
+}
+
+impl XrPosef {
+    pub const IDENTITY: Self = Self {
+        orientation: XrQuaternionf { x: 0.0, y: 0.0, z: 0.0, w: 1.0 },
+        position: XrVector3f { x: 0.0, y: 0.0, z: 0.0 },
+    };
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct XrFovf {
+    pub angle_left: f32,
+    pub angle_right: f32,
+    pub angle_up: f32,
+    pub angle_down: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct XrOffset2Di {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct XrExtent2Di {
+    pub width: i32,
+    pub height: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct XrRect2Di {
+    pub offset: XrOffset2Di,
+    pub extent: XrExtent2Di,
+}
+
+/* =========================================================
+   INSTANCE / SYSTEM
+   ========================================================= */
+
+#[repr(C)]
+pub struct XrExtensionProperties {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub extension_name: [c_char; XR_MAX_EXTENSION_NAME_SIZE],
+    pub extension_version: u32,
+}
+
+#[repr(C)]
+pub struct XrApplicationInfo {
+    pub application_name: [c_char; XR_MAX_APPLICATION_NAME_SIZE],
+    pub application_version: u32,
+    pub engine_name: [c_char; XR_MAX_ENGINE_NAME_SIZE],
+    pub engine_version: u32,
+    pub api_version: u64,
+}
+
+#[repr(C)]
+pub struct XrInstanceCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub create_flags: XrFlags64,
+    pub application_info: XrApplicationInfo,
+    pub enabled_api_layer_count: u32,
+    pub enabled_api_layer_names: *const *const c_char,
+    pub enabled_extension_count: u32,
+    pub enabled_extension_names: *const *const c_char,
+}
+
+#[repr(C)]
+pub struct XrSystemGetInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub form_factor: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XrViewConfigurationView {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub recommended_image_rect_width: u32,
+    pub max_image_rect_width: u32,
+    pub recommended_image_rect_height: u32,
+    pub max_image_rect_height: u32,
+    pub recommended_swapchain_sample_count: u32,
+    pub max_swapchain_sample_count: u32,
+}
+
+/* =========================================================
+   SESSION / EVENTS
+   ========================================================= */
+
+#[repr(C)]
+pub struct XrSessionCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub create_flags: XrFlags64,
+    pub system_id: XrSystemId,
+}
+
+#[repr(C)]
+pub struct XrSessionBeginInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub primary_view_configuration_type: i32,
+}
+
+#[repr(C)]
+pub struct XrEventDataBuffer {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub varying: [u8; 4000],
+}
+
+#[repr(C)]
+pub struct XrEventDataSessionStateChanged {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub session: XrSession,
+    pub state: i32,
+    pub time: XrTime,
+}
+
+#[repr(C)]
+pub struct XrReferenceSpaceCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub reference_space_type: i32,
+    pub pose_in_reference_space: XrPosef,
+}
+
+#[repr(C)]
+pub struct XrSpaceLocation {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub location_flags: XrFlags64,
+    pub pose: XrPosef,
+}
+
+#[repr(C)]
+pub struct XrSpaceVelocity {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub velocity_flags: XrFlags64,
+    pub linear_velocity: XrVector3f,
+    pub angular_velocity: XrVector3f,
+}
+
+/* =========================================================
+   FRAMES / VIEWS
+   ========================================================= */
+
+#[repr(C)]
+pub struct XrFrameWaitInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+}
+
+#[repr(C)]
+pub struct XrFrameState {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub predicted_display_time: XrTime,
+    pub predicted_display_period: XrDuration,
+    pub should_render: XrBool32,
+}
+
+#[repr(C)]
+pub struct XrFrameBeginInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+}
+
+#[repr(C)]
+pub struct XrViewLocateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub view_configuration_type: i32,
+    pub display_time: XrTime,
+    pub space: XrSpace,
+}
+
+#[repr(C)]
+pub struct XrViewState {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub view_state_flags: XrFlags64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XrView {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub pose: XrPosef,
+    pub fov: XrFovf,
+}
+
+#[repr(C)]
+pub struct XrSwapchainSubImage {
+    pub swapchain: XrSwapchain,
+    pub image_rect: XrRect2Di,
+    pub image_array_index: u32,
+}
+
+#[repr(C)]
+pub struct XrCompositionLayerProjectionView {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub pose: XrPosef,
+    pub fov: XrFovf,
+    pub sub_image: XrSwapchainSubImage,
+}
+
+#[repr(C)]
+pub struct XrCompositionLayerProjection {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub layer_flags: XrFlags64,
+    pub space: XrSpace,
+    pub view_count: u32,
+    pub views: *const XrCompositionLayerProjectionView,
+}
+
+#[repr(C)]
+pub struct XrFrameEndInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub display_time: XrTime,
+    pub environment_blend_mode: i32,
+    pub layer_count: u32,
+    pub layers: *const *const XrCompositionLayerProjection,
+}
+
+/* =========================================================
+   SWAPCHAINS
+   ========================================================= */
+
+#[repr(C)]
+pub struct XrSwapchainCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub create_flags: XrFlags64,
+    pub usage_flags: XrFlags64,
+    pub format: i64,
+    pub sample_count: u32,
+    pub width: u32,
+    pub height: u32,
+    pub face_count: u32,
+    pub array_size: u32,
+    pub mip_count: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XrSwapchainImageVulkanKHR {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub image: u64,
+}
+
+#[repr(C)]
+pub struct XrSwapchainImageAcquireInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+}
+
+#[repr(C)]
+pub struct XrSwapchainImageWaitInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub timeout: XrDuration,
+}
+
+#[repr(C)]
+pub struct XrSwapchainImageReleaseInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+}
+
+/* =========================================================
+   ACTIONS
+   ========================================================= */
+
+#[repr(C)]
+pub struct XrActionSetCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub action_set_name: [c_char; XR_MAX_ACTION_SET_NAME_SIZE],
+    pub localized_action_set_name: [c_char; XR_MAX_LOCALIZED_ACTION_SET_NAME_SIZE],
+    pub priority: u32,
+}
+
+#[repr(C)]
+pub struct XrActionCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub action_name: [c_char; XR_MAX_ACTION_NAME_SIZE],
+    pub action_type: i32,
+    pub count_subaction_paths: u32,
+    pub subaction_paths: *const XrPath,
+    pub localized_action_name: [c_char; XR_MAX_LOCALIZED_ACTION_NAME_SIZE],
+}
+
+#[repr(C)]
+pub struct XrActionSuggestedBinding {
+    pub action: XrAction,
+    pub binding: XrPath,
+}
+
+#[repr(C)]
+pub struct XrInteractionProfileSuggestedBinding {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub interaction_profile: XrPath,
+    pub count_suggested_bindings: u32,
+    pub suggested_bindings: *const XrActionSuggestedBinding,
+}
+
+#[repr(C)]
+pub struct XrSessionActionSetsAttachInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub count_action_sets: u32,
+    pub action_sets: *const XrActionSet,
+}
+
+#[repr(C)]
+pub struct XrActiveActionSet {
+    pub action_set: XrActionSet,
+    pub subaction_path: XrPath,
+}
+
+#[repr(C)]
+pub struct XrActionsSyncInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub count_active_action_sets: u32,
+    pub active_action_sets: *const XrActiveActionSet,
+}
+
+#[repr(C)]
+pub struct XrActionStateGetInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub action: XrAction,
+    pub subaction_path: XrPath,
+}
+
+#[repr(C)]
+pub struct XrActionStateBoolean {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub current_state: XrBool32,
+    pub changed_since_last_sync: XrBool32,
+    pub last_change_time: XrTime,
+    pub is_active: XrBool32,
+}
+
+#[repr(C)]
+pub struct XrActionStateFloat {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub current_state: f32,
+    pub changed_since_last_sync: XrBool32,
+    pub last_change_time: XrTime,
+    pub is_active: XrBool32,
+}
+
+#[repr(C)]
+pub struct XrActionStateVector2f {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub current_state: XrVector2f,
+    pub changed_since_last_sync: XrBool32,
+    pub last_change_time: XrTime,
+    pub is_active: XrBool32,
+}
+
+#[repr(C)]
+pub struct XrActionSpaceCreateInfo {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub action: XrAction,
+    pub subaction_path: XrPath,
+    pub pose_in_action_space: XrPosef,
+}
+
+/* =========================================================
+   VULKAN (XR_KHR_vulkan_enable2)
+   ========================================================= */
+
+#[repr(C)]
+pub struct XrGraphicsRequirementsVulkanKHR {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub min_api_version_supported: u64,
+    pub max_api_version_supported: u64,
+}
+
+#[repr(C)]
+pub struct XrVulkanInstanceCreateInfoKHR {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub system_id: XrSystemId,
+    pub create_flags: XrFlags64,
+    pub pfn_get_instance_proc_addr: *const c_void,
+    pub vulkan_create_info: *const c_void,
+    pub vulkan_allocator: *const c_void,
+}
+
+#[repr(C)]
+pub struct XrVulkanGraphicsDeviceGetInfoKHR {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub system_id: XrSystemId,
+    pub vulkan_instance: *const c_void,
+}
+
+#[repr(C)]
+pub struct XrVulkanDeviceCreateInfoKHR {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub system_id: XrSystemId,
+    pub create_flags: XrFlags64,
+    pub pfn_get_instance_proc_addr: *const c_void,
+    pub vulkan_physical_device: *const c_void,
+    pub vulkan_create_info: *const c_void,
+    pub vulkan_allocator: *const c_void,
+}
+
+#[repr(C)]
+pub struct XrGraphicsBindingVulkanKHR {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub instance: *const c_void,
+    pub physical_device: *const c_void,
+    pub device: *const c_void,
+    pub queue_family_index: u32,
+    pub queue_index: u32,
+}
+
+/* =========================================================
+   HAND TRACKING
+   ========================================================= */
+
+pub const XR_HAND_LEFT_EXT: i32 = 1;
+pub const XR_HAND_RIGHT_EXT: i32 = 2;
+pub const XR_HAND_JOINT_SET_DEFAULT_EXT: i32 = 0;
+pub const XR_HAND_JOINT_COUNT_EXT: usize = 26;
+
+#[repr(C)]
+pub struct XrHandTrackerCreateInfoEXT {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub hand: i32,
+    pub hand_joint_set: i32,
+}
+
+#[repr(C)]
+pub struct XrHandJointsLocateInfoEXT {
+    pub ty: XrStructureType,
+    pub next: *const c_void,
+    pub base_space: XrSpace,
+    pub time: XrTime,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct XrHandJointLocationEXT {
+    pub location_flags: XrFlags64,
+    pub pose: XrPosef,
+    pub radius: f32,
+}
+
+#[repr(C)]
+pub struct XrHandJointLocationsEXT {
+    pub ty: XrStructureType,
+    pub next: *mut c_void,
+    pub is_active: XrBool32,
+    pub joint_count: u32,
+    pub joint_locations: *mut XrHandJointLocationEXT,
+}
+
+/* =========================================================
+   ENTRY POINTS
+   ========================================================= */
+
+pub type PFN_xrVoidFunction = Option<unsafe extern "system" fn()>;
+
+pub type PFN_xrGetInstanceProcAddr = unsafe extern "system" fn(
+    XrInstance,
+    *const c_char,
+    *mut PFN_xrVoidFunction,
+) -> XrResult;
+
+pub type PFN_xrEnumerateInstanceExtensionProperties = unsafe extern "system" fn(
+    *const c_char,
+    u32,
+    *mut u32,
+    *mut XrExtensionProperties,
+) -> XrResult;
+
+pub type PFN_xrCreateInstance =
+    unsafe extern "system" fn(*const XrInstanceCreateInfo, *mut XrInstance) -> XrResult;
+pub type PFN_xrDestroyInstance = unsafe extern "system" fn(XrInstance) -> XrResult;
+pub type PFN_xrGetSystem =
+    unsafe extern "system" fn(XrInstance, *const XrSystemGetInfo, *mut XrSystemId) -> XrResult;
+pub type PFN_xrEnumerateViewConfigurationViews = unsafe extern "system" fn(
+    XrInstance,
+    XrSystemId,
+    i32,
+    u32,
+    *mut u32,
+    *mut XrViewConfigurationView,
+) -> XrResult;
+pub type PFN_xrStringToPath =
+    unsafe extern "system" fn(XrInstance, *const c_char, *mut XrPath) -> XrResult;
+pub type PFN_xrPollEvent =
+    unsafe extern "system" fn(XrInstance, *mut XrEventDataBuffer) -> XrResult;
+
+pub type PFN_xrCreateSession =
+    unsafe extern "system" fn(XrInstance, *const XrSessionCreateInfo, *mut XrSession) -> XrResult;
+pub type PFN_xrDestroySession = unsafe extern "system" fn(XrSession) -> XrResult;
+pub type PFN_xrBeginSession =
+    unsafe extern "system" fn(XrSession, *const XrSessionBeginInfo) -> XrResult;
+pub type PFN_xrEndSession = unsafe extern "system" fn(XrSession) -> XrResult;
+pub type PFN_xrRequestExitSession = unsafe extern "system" fn(XrSession) -> XrResult;
+
+pub type PFN_xrCreateReferenceSpace = unsafe extern "system" fn(
+    XrSession,
+    *const XrReferenceSpaceCreateInfo,
+    *mut XrSpace,
+) -> XrResult;
+pub type PFN_xrCreateActionSpace = unsafe extern "system" fn(
+    XrSession,
+    *const XrActionSpaceCreateInfo,
+    *mut XrSpace,
+) -> XrResult;
+pub type PFN_xrDestroySpace = unsafe extern "system" fn(XrSpace) -> XrResult;
+pub type PFN_xrLocateSpace =
+    unsafe extern "system" fn(XrSpace, XrSpace, XrTime, *mut XrSpaceLocation) -> XrResult;
+
+pub type PFN_xrWaitFrame = unsafe extern "system" fn(
+    XrSession,
+    *const XrFrameWaitInfo,
+    *mut XrFrameState,
+) -> XrResult;
+pub type PFN_xrBeginFrame =
+    unsafe extern "system" fn(XrSession, *const XrFrameBeginInfo) -> XrResult;
+pub type PFN_xrEndFrame = unsafe extern "system" fn(XrSession, *const XrFrameEndInfo) -> XrResult;
+pub type PFN_xrLocateViews = unsafe extern "system" fn(
+    XrSession,
+    *const XrViewLocateInfo,
+    *mut XrViewState,
+    u32,
+    *mut u32,
+    *mut XrView,
+) -> XrResult;
+
+pub type PFN_xrEnumerateSwapchainFormats =
+    unsafe extern "system" fn(XrSession, u32, *mut u32, *mut i64) -> XrResult;
+pub type PFN_xrCreateSwapchain = unsafe extern "system" fn(
+    XrSession,
+    *const XrSwapchainCreateInfo,
+    *mut XrSwapchain,
+) -> XrResult;
+pub type PFN_xrDestroySwapchain = unsafe extern "system" fn(XrSwapchain) -> XrResult;
+pub type PFN_xrEnumerateSwapchainImages = unsafe extern "system" fn(
+    XrSwapchain,
+    u32,
+    *mut u32,
+    *mut XrSwapchainImageVulkanKHR,
+) -> XrResult;
+pub type PFN_xrAcquireSwapchainImage = unsafe extern "system" fn(
+    XrSwapchain,
+    *const XrSwapchainImageAcquireInfo,
+    *mut u32,
+) -> XrResult;
+pub type PFN_xrWaitSwapchainImage =
+    unsafe extern "system" fn(XrSwapchain, *const XrSwapchainImageWaitInfo) -> XrResult;
+pub type PFN_xrReleaseSwapchainImage =
+    unsafe extern "system" fn(XrSwapchain, *const XrSwapchainImageReleaseInfo) -> XrResult;
+
+pub type PFN_xrCreateActionSet = unsafe extern "system" fn(
+    XrInstance,
+    *const XrActionSetCreateInfo,
+    *mut XrActionSet,
+) -> XrResult;
+pub type PFN_xrCreateAction =
+    unsafe extern "system" fn(XrActionSet, *const XrActionCreateInfo, *mut XrAction) -> XrResult;
+pub type PFN_xrSuggestInteractionProfileBindings = unsafe extern "system" fn(
+    XrInstance,
+    *const XrInteractionProfileSuggestedBinding,
+) -> XrResult;
+pub type PFN_xrAttachSessionActionSets =
+    unsafe extern "system" fn(XrSession, *const XrSessionActionSetsAttachInfo) -> XrResult;
+pub type PFN_xrSyncActions =
+    unsafe extern "system" fn(XrSession, *const XrActionsSyncInfo) -> XrResult;
+pub type PFN_xrGetActionStateBoolean = unsafe extern "system" fn(
+    XrSession,
+    *const XrActionStateGetInfo,
+    *mut XrActionStateBoolean,
+) -> XrResult;
+pub type PFN_xrGetActionStateFloat = unsafe extern "system" fn(
+    XrSession,
+    *const XrActionStateGetInfo,
+    *mut XrActionStateFloat,
+) -> XrResult;
+pub type PFN_xrGetActionStateVector2f = unsafe extern "system" fn(
+    XrSession,
+    *const XrActionStateGetInfo,
+    *mut XrActionStateVector2f,
+) -> XrResult;
+
+pub type PFN_xrGetVulkanGraphicsRequirements2KHR = unsafe extern "system" fn(
+    XrInstance,
+    XrSystemId,
+    *mut XrGraphicsRequirementsVulkanKHR,
+) -> XrResult;
+pub type PFN_xrCreateVulkanInstanceKHR = unsafe extern "system" fn(
+    XrInstance,
+    *const XrVulkanInstanceCreateInfoKHR,
+    *mut *const c_void,
+    *mut i32,
+) -> XrResult;
+pub type PFN_xrGetVulkanGraphicsDevice2KHR = unsafe extern "system" fn(
+    XrInstance,
+    *const XrVulkanGraphicsDeviceGetInfoKHR,
+    *mut *const c_void,
+) -> XrResult;
+pub type PFN_xrCreateVulkanDeviceKHR = unsafe extern "system" fn(
+    XrInstance,
+    *const XrVulkanDeviceCreateInfoKHR,
+    *mut *const c_void,
+    *mut i32,
+) -> XrResult;
+
+pub type PFN_xrCreateHandTrackerEXT = unsafe extern "system" fn(
+    XrSession,
+    *const XrHandTrackerCreateInfoEXT,
+    *mut XrHandTrackerEXT,
+) -> XrResult;
+pub type PFN_xrDestroyHandTrackerEXT = unsafe extern "system" fn(XrHandTrackerEXT) -> XrResult;
+pub type PFN_xrLocateHandJointsEXT = unsafe extern "system" fn(
+    XrHandTrackerEXT,
+    *const XrHandJointsLocateInfoEXT,
+    *mut XrHandJointLocationsEXT,
+) -> XrResult;
+
+/// Copies `s` into a fixed-size, NUL-terminated C char array.
+pub fn fixed_str<const N: usize>(s: &str) -> [c_char; N] {
+    let mut out = [0 as c_char; N];
+    for (dst, src) in out.iter_mut().zip(s.bytes().take(N - 1)) {
+        *dst = src as c_char;
+    }
+    out
+}
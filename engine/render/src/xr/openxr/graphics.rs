@@ -0,0 +1,296 @@
+use std::ffi::{c_void, CString};
+use std::ptr;
+
+use ash::vk::{self, Handle};
+use wgpu_hal::api::Vulkan as V;
+
+use crate::renderer::context::device::RenderDevice;
+
+use super::ffi::*;
+use super::instance::{check, OpenXrInstance};
+use super::XrError;
+
+const VK_TARGET_VERSION: u32 = vk::make_api_version(0, 1, 1, 0);
+const XR_TARGET_VERSION: u64 = xr_make_version(1, 1, 0);
+
+/// Raw Vulkan handles the session binding needs, alongside the wgpu
+/// device built on top of them.
+pub struct VulkanBinding {
+    pub instance: *const c_void,
+    pub physical_device: *const c_void,
+    pub device: *const c_void,
+    pub queue_family_index: u32,
+}
+
+/// Lets the runtime pick (and extend) the Vulkan instance and device,
+/// then wraps them in wgpu so the regular renderer can draw into the
+/// headset swapchain.
+pub fn create_render_device(xr: &OpenXrInstance) -> Result<(RenderDevice, VulkanBinding), XrError> {
+    let fns = xr.vulkan.as_ref().ok_or(XrError::Unsupported("vulkan binding"))?;
+
+    /* ================= REQUIREMENTS ================= */
+
+    let mut reqs = XrGraphicsRequirementsVulkanKHR {
+        ty: XR_TYPE_GRAPHICS_REQUIREMENTS_VULKAN2_KHR,
+        next: ptr::null_mut(),
+        min_api_version_supported: 0,
+        max_api_version_supported: 0,
+    };
+    check("xrGetVulkanGraphicsRequirements2KHR", unsafe {
+        (fns.get_graphics_requirements)(xr.handle, xr.system, &mut reqs)
+    })?;
+
+    if XR_TARGET_VERSION < reqs.min_api_version_supported
+        || (XR_TARGET_VERSION >> 48) > (reqs.max_api_version_supported >> 48)
+    {
+        return Err(XrError::Unsupported("runtime Vulkan version range"));
+    }
+
+    /* ================= INSTANCE ================= */
+
+    let entry = unsafe { ash::Entry::load() }.map_err(|e| XrError::Vulkan(e.to_string()))?;
+    let flags = wgpu_hal::InstanceFlags::empty();
+
+    let extensions = <V as wgpu_hal::Api>::Instance::required_extensions(&entry, VK_TARGET_VERSION, flags)
+        .map_err(|e| XrError::Vulkan(e.to_string()))?;
+
+    let vk_instance = unsafe {
+        let extension_ptrs: Vec<_> = extensions.iter().map(|s| s.as_ptr()).collect();
+        let app_name = CString::new("U-VR").unwrap();
+        let app_info = vk::ApplicationInfo::builder()
+            .application_name(&app_name)
+            .application_version(1)
+            .engine_name(&app_name)
+            .engine_version(1)
+            .api_version(VK_TARGET_VERSION);
+        let create_info = vk::InstanceCreateInfo::builder()
+            .application_info(&app_info)
+            .enabled_extension_names(&extension_ptrs);
+
+        let info = XrVulkanInstanceCreateInfoKHR {
+            ty: XR_TYPE_VULKAN_INSTANCE_CREATE_INFO_KHR,
+            next: ptr::null(),
+            system_id: xr.system,
+            create_flags: 0,
+            pfn_get_instance_proc_addr: entry.static_fn().get_instance_proc_addr as *const c_void,
+            vulkan_create_info: &*create_info as *const _ as *const c_void,
+            vulkan_allocator: ptr::null(),
+        };
+
+        let mut raw = ptr::null();
+        let mut vk_result = 0;
+        check("xrCreateVulkanInstanceKHR", (fns.create_vulkan_instance)(
+            xr.handle,
+            &info,
+            &mut raw,
+            &mut vk_result,
+        ))?;
+        if vk_result != 0 {
+            return Err(XrError::Vulkan(format!("vkCreateInstance returned {}", vk_result)));
+        }
+
+        ash::Instance::load(entry.static_fn(), vk::Instance::from_raw(raw as u64))
+    };
+
+    /* ================= PHYSICAL DEVICE ================= */
+
+    let vk_physical_device = unsafe {
+        let info = XrVulkanGraphicsDeviceGetInfoKHR {
+            ty: XR_TYPE_VULKAN_GRAPHICS_DEVICE_GET_INFO_KHR,
+            next: ptr::null(),
+            system_id: xr.system,
+            vulkan_instance: vk_instance.handle().as_raw() as *const c_void,
+        };
+
+        let mut raw = ptr::null();
+        check("xrGetVulkanGraphicsDevice2KHR", (fns.get_graphics_device)(xr.handle, &info, &mut raw))?;
+        vk::PhysicalDevice::from_raw(raw as u64)
+    };
+
+    let properties = unsafe { vk_instance.get_physical_device_properties(vk_physical_device) };
+    if properties.api_version < VK_TARGET_VERSION {
+        return Err(XrError::Unsupported("physical device Vulkan 1.1"));
+    }
+
+    let queue_family_index = unsafe {
+        vk_instance.get_physical_device_queue_family_properties(vk_physical_device)
+    }
+    .iter()
+    .position(|f| f.queue_flags.contains(vk::QueueFlags::GRAPHICS))
+    .ok_or(XrError::Unsupported("graphics queue"))? as u32;
+
+    /* ================= WGPU ON TOP ================= */
+
+    let hal_instance = unsafe {
+        <V as wgpu_hal::Api>::Instance::from_raw(
+            entry.clone(),
+            vk_instance.clone(),
+            VK_TARGET_VERSION,
+            0,
+            extensions,
+            flags,
+            false,
+            // the runtime created the instance, it outlives wgpu's copy
+            Some(Box::new(())),
+        )
+    }
+    .map_err(|e| XrError::Vulkan(e.to_string()))?;
+
+    let exposed = hal_instance
+        .expose_adapter(vk_physical_device)
+        .ok_or(XrError::Unsupported("wgpu adapter for the runtime's GPU"))?;
+
+    let features = wgpu::Features::empty();
+    let device_extensions = exposed.adapter.required_device_extensions(features);
+
+    let (open_device, vk_device) = unsafe {
+        let extension_ptrs: Vec<_> = device_extensions.iter().map(|s| s.as_ptr()).collect();
+        let mut phd_features = exposed.adapter.physical_device_features(&device_extensions, features);
+
+        let queue_info = vk::DeviceQueueCreateInfo::builder()
+            .queue_family_index(queue_family_index)
+            .queue_priorities(&[1.0])
+            .build();
+        let queue_infos = [queue_info];
+
+        let create_info = phd_features.add_to_device_create_builder(
+            vk::DeviceCreateInfo::builder()
+                .queue_create_infos(&queue_infos)
+                .enabled_extension_names(&extension_ptrs),
+        );
+
+        let info = XrVulkanDeviceCreateInfoKHR {
+            ty: XR_TYPE_VULKAN_DEVICE_CREATE_INFO_KHR,
+            next: ptr::null(),
+            system_id: xr.system,
+            create_flags: 0,
+            pfn_get_instance_proc_addr: entry.static_fn().get_instance_proc_addr as *const c_void,
+            vulkan_physical_device: vk_physical_device.as_raw() as *const c_void,
+            vulkan_create_info: &*create_info as *const _ as *const c_void,
+            vulkan_allocator: ptr::null(),
+        };
+
+        let mut raw = ptr::null();
+        let mut vk_result = 0;
+        check("xrCreateVulkanDeviceKHR", (fns.create_vulkan_device)(
+            xr.handle,
+            &info,
+            &mut raw,
+            &mut vk_result,
+        ))?;
+        if vk_result != 0 {
+            return Err(XrError::Vulkan(format!("vkCreateDevice returned {}", vk_result)));
+        }
+
+        let vk_device = ash::Device::load(vk_instance.fp_v1_0(), vk::Device::from_raw(raw as u64));
+        let handle = vk_device.handle();
+
+        let open_device = exposed
+            .adapter
+            .device_from_raw(
+                vk_device,
+                true,
+                &device_extensions,
+                features,
+                queue_family_index,
+                0,
+            )
+            .map_err(|e| XrError::Vulkan(e.to_string()))?;
+
+        (open_device, handle)
+    };
+
+    let instance = unsafe { wgpu::Instance::from_hal::<V>(hal_instance) };
+    let adapter = unsafe { instance.create_adapter_from_hal(exposed) };
+    let (device, queue) = unsafe {
+        adapter.create_device_from_hal(
+            open_device,
+            &wgpu::DeviceDescriptor {
+                label: Some("openxr_device"),
+                features,
+                limits: wgpu::Limits::default(),
+            },
+            None,
+        )
+    }
+    .map_err(|e| XrError::Vulkan(e.to_string()))?;
+
+    let binding = VulkanBinding {
+        instance: vk_instance.handle().as_raw() as *const c_void,
+        physical_device: vk_physical_device.as_raw() as *const c_void,
+        device: vk_device.as_raw() as *const c_void,
+        queue_family_index,
+    };
+
+    Ok((
+        RenderDevice {
+            instance,
+            adapter,
+            device,
+            queue,
+        },
+        binding,
+    ))
+}
+
+/// Maps the swapchain formats we can render to onto their Vulkan values.
+pub fn vk_format(format: wgpu::TextureFormat) -> Option<i64> {
+    match format {
+        wgpu::TextureFormat::Rgba8UnormSrgb => Some(vk::Format::R8G8B8A8_SRGB.as_raw() as i64),
+        wgpu::TextureFormat::Bgra8UnormSrgb => Some(vk::Format::B8G8R8A8_SRGB.as_raw() as i64),
+        wgpu::TextureFormat::Rgba8Unorm => Some(vk::Format::R8G8B8A8_UNORM.as_raw() as i64),
+        wgpu::TextureFormat::Bgra8Unorm => Some(vk::Format::B8G8R8A8_UNORM.as_raw() as i64),
+        _ => None,
+    }
+}
+
+/// Wraps one runtime-owned swapchain image as a wgpu texture.
+///
+/// # Safety
+/// `image` must be a live `VkImage` created on `device` with the given
+/// size, format and layer count.
+pub unsafe fn wrap_swapchain_image(
+    device: &wgpu::Device,
+    image: u64,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    layers: u32,
+) -> wgpu::Texture {
+    let size = wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: layers,
+    };
+
+    let hal_texture = <V as wgpu_hal::Api>::Device::texture_from_raw(
+        vk::Image::from_raw(image),
+        &wgpu_hal::TextureDescriptor {
+            label: Some("xr_swapchain_image"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu_hal::TextureUses::COLOR_TARGET,
+            memory_flags: wgpu_hal::MemoryFlags::empty(),
+            view_formats: vec![],
+        },
+        // the runtime owns and destroys the image
+        Some(Box::new(())),
+    );
+
+    device.create_texture_from_hal::<V>(
+        hal_texture,
+        &wgpu::TextureDescriptor {
+            label: Some("xr_swapchain_image"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+    )
+}
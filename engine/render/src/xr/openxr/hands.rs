@@ -0,0 +1,115 @@
+use std::ptr;
+
+use glam::{Quat, Vec3};
+
+use crate::xr::hand::{HandJointPose, HandSkeleton};
+use crate::xr::pose::Hand;
+
+use super::ffi::*;
+use super::instance::{check, OpenXrInstance};
+use super::XrError;
+
+/// One `XrHandTrackerEXT` per hand (XR_EXT_hand_tracking). The runtime's
+/// joint order matches [`HandSkeleton::ORDER`].
+pub struct HandTrackers {
+    trackers: [XrHandTrackerEXT; 2],
+}
+
+impl HandTrackers {
+    /// `Ok(None)` when the runtime has no hand tracking, an error when it
+    /// has the extension but the system can't track hands.
+    pub fn new(xr: &OpenXrInstance, session: XrSession) -> Result<Option<Self>, XrError> {
+        let Some(fns) = &xr.hand_tracking else {
+            return Ok(None);
+        };
+
+        let mut trackers = [XR_NULL_HANDLE; 2];
+
+        for (tracker, hand) in trackers.iter_mut().zip([XR_HAND_LEFT_EXT, XR_HAND_RIGHT_EXT]) {
+            let info = XrHandTrackerCreateInfoEXT {
+                ty: XR_TYPE_HAND_TRACKER_CREATE_INFO_EXT,
+                next: ptr::null(),
+                hand,
+                hand_joint_set: XR_HAND_JOINT_SET_DEFAULT_EXT,
+            };
+
+            let created = check("xrCreateHandTrackerEXT", unsafe {
+                (fns.create_hand_tracker)(session, &info, tracker)
+            });
+
+            if let Err(e) = created {
+                // the extension is there but the system can't track hands
+                for t in trackers.iter().filter(|t| **t != XR_NULL_HANDLE) {
+                    unsafe { (fns.destroy_hand_tracker)(*t) };
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(Some(Self { trackers }))
+    }
+
+    pub fn locate(&self, xr: &OpenXrInstance, hand: Hand, base: XrSpace, time: XrTime) -> HandSkeleton {
+        let Some(fns) = &xr.hand_tracking else {
+            return HandSkeleton::default();
+        };
+
+        let mut joints = [XrHandJointLocationEXT {
+            location_flags: 0,
+            pose: XrPosef::IDENTITY,
+            radius: 0.0,
+        }; XR_HAND_JOINT_COUNT_EXT];
+
+        let info = XrHandJointsLocateInfoEXT {
+            ty: XR_TYPE_HAND_JOINTS_LOCATE_INFO_EXT,
+            next: ptr::null(),
+            base_space: base,
+            time,
+        };
+        let mut locations = XrHandJointLocationsEXT {
+            ty: XR_TYPE_HAND_JOINT_LOCATIONS_EXT,
+            next: ptr::null_mut(),
+            is_active: 0,
+            joint_count: joints.len() as u32,
+            joint_locations: joints.as_mut_ptr(),
+        };
+
+        let r = unsafe {
+            (fns.locate_hand_joints)(self.trackers[hand as usize], &info, &mut locations)
+        };
+
+        let valid_bits = XR_SPACE_LOCATION_ORIENTATION_VALID_BIT | XR_SPACE_LOCATION_POSITION_VALID_BIT;
+        let all_valid = joints.iter().all(|j| j.location_flags & valid_bits == valid_bits);
+
+        if r != XR_SUCCESS || locations.is_active == 0 || !all_valid {
+            return HandSkeleton::default();
+        }
+
+        let mut skeleton = HandSkeleton {
+            valid: true,
+            ..Default::default()
+        };
+
+        for (out, j) in skeleton.joints.iter_mut().zip(&joints) {
+            let (p, q) = (j.pose.position, j.pose.orientation);
+            *out = HandJointPose {
+                position: Vec3::new(p.x, p.y, p.z),
+                orientation: Quat::from_xyzw(q.x, q.y, q.z, q.w),
+                radius: j.radius,
+            };
+        }
+
+        skeleton
+    }
+
+    pub fn destroy(&mut self, xr: &OpenXrInstance) {
+        if let Some(fns) = &xr.hand_tracking {
+            for t in &mut self.trackers {
+                if *t != XR_NULL_HANDLE {
+                    unsafe { (fns.destroy_hand_tracker)(*t) };
+                    *t = XR_NULL_HANDLE;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,363 @@
+use std::ptr;
+
+use glam::{Quat, Vec2, Vec3};
+
+use crate::xr::pose::{ControllerState, Hand, TrackedPose};
+
+use super::ffi::*;
+use super::instance::{check, OpenXrInstance};
+use super::XrError;
+
+/// The "gameplay" action set: one action per logical input, with a
+/// subaction path per hand so left and right are read separately.
+pub struct ActionInput {
+    pub set: XrActionSet,
+    hands: [XrPath; 2],
+
+    pose: XrAction,
+    trigger: XrAction,
+    grip: XrAction,
+    thumbstick: XrAction,
+    primary: XrAction,
+    secondary: XrAction,
+    thumbstick_click: XrAction,
+    menu: XrAction,
+
+    // created once the session exists
+    hand_spaces: [XrSpace; 2],
+}
+
+impl ActionInput {
+    /// Creates the actions and suggests bindings for every supported
+    /// controller; also returns the profiles the runtime rejected.
+    pub fn new(xr: &OpenXrInstance) -> Result<(Self, Vec<String>), XrError> {
+        let info = XrActionSetCreateInfo {
+            ty: XR_TYPE_ACTION_SET_CREATE_INFO,
+            next: ptr::null(),
+            action_set_name: fixed_str("gameplay"),
+            localized_action_set_name: fixed_str("Gameplay"),
+            priority: 0,
+        };
+
+        let mut set = XR_NULL_HANDLE;
+        check("xrCreateActionSet", unsafe {
+            (xr.fns.create_action_set)(xr.handle, &info, &mut set)
+        })?;
+
+        let hands = [xr.path("/user/hand/left")?, xr.path("/user/hand/right")?];
+
+        let action = |name: &str, label: &str, ty: i32| -> Result<XrAction, XrError> {
+            let info = XrActionCreateInfo {
+                ty: XR_TYPE_ACTION_CREATE_INFO,
+                next: ptr::null(),
+                action_name: fixed_str(name),
+                action_type: ty,
+                count_subaction_paths: hands.len() as u32,
+                subaction_paths: hands.as_ptr(),
+                localized_action_name: fixed_str(label),
+            };
+
+            let mut out = XR_NULL_HANDLE;
+            check("xrCreateAction", unsafe {
+                (xr.fns.create_action)(set, &info, &mut out)
+            })?;
+            Ok(out)
+        };
+
+        let input = Self {
+            set,
+            hands,
+            pose: action("hand_pose", "Hand Pose", XR_ACTION_TYPE_POSE_INPUT)?,
+            trigger: action("trigger", "Trigger", XR_ACTION_TYPE_FLOAT_INPUT)?,
+            grip: action("grip", "Grip", XR_ACTION_TYPE_FLOAT_INPUT)?,
+            thumbstick: action("thumbstick", "Thumbstick", XR_ACTION_TYPE_VECTOR2F_INPUT)?,
+            primary: action("primary", "Primary Button", XR_ACTION_TYPE_BOOLEAN_INPUT)?,
+            secondary: action("secondary", "Secondary Button", XR_ACTION_TYPE_BOOLEAN_INPUT)?,
+            thumbstick_click: action("thumbstick_click", "Thumbstick Click", XR_ACTION_TYPE_BOOLEAN_INPUT)?,
+            menu: action("menu", "Menu", XR_ACTION_TYPE_BOOLEAN_INPUT)?,
+            hand_spaces: [XR_NULL_HANDLE; 2],
+        };
+
+        let skipped = input.suggest_bindings(xr);
+
+        Ok((input, skipped))
+    }
+
+    /* ================= BINDINGS ================= */
+
+    fn suggest_bindings(&self, xr: &OpenXrInstance) -> Vec<String> {
+        let both = |suffix: &str, action: XrAction| {
+            ["left", "right"]
+                .map(|hand| (format!("/user/hand/{}/input/{}", hand, suffix), action))
+                .to_vec()
+        };
+
+        let simple = [
+            both("grip/pose", self.pose),
+            both("select/click", self.trigger),
+            both("menu/click", self.menu),
+        ]
+        .concat();
+
+        let touch = [
+            both("grip/pose", self.pose),
+            both("trigger/value", self.trigger),
+            both("squeeze/value", self.grip),
+            both("thumbstick", self.thumbstick),
+            both("thumbstick/click", self.thumbstick_click),
+            vec![
+                ("/user/hand/left/input/x/click".to_string(), self.primary),
+                ("/user/hand/left/input/y/click".to_string(), self.secondary),
+                ("/user/hand/left/input/menu/click".to_string(), self.menu),
+                ("/user/hand/right/input/a/click".to_string(), self.primary),
+                ("/user/hand/right/input/b/click".to_string(), self.secondary),
+            ],
+        ]
+        .concat();
+
+        let index = [
+            both("grip/pose", self.pose),
+            both("trigger/value", self.trigger),
+            both("squeeze/value", self.grip),
+            both("thumbstick", self.thumbstick),
+            both("thumbstick/click", self.thumbstick_click),
+            both("a/click", self.primary),
+            both("b/click", self.secondary),
+        ]
+        .concat();
+
+        let profiles = [
+            ("/interaction_profiles/khr/simple_controller", simple),
+            ("/interaction_profiles/oculus/touch_controller", touch),
+            ("/interaction_profiles/valve/index_controller", index),
+        ];
+
+        // a runtime may not know every profile; skip those it rejects
+        profiles
+            .into_iter()
+            .filter_map(|(profile, bindings)| {
+                let e = suggest_profile(xr, profile, &bindings).err()?;
+                Some(format!("skipped {}: {}", profile, e))
+            })
+            .collect()
+    }
+
+    /* ================= SESSION ================= */
+
+    pub fn attach(&mut self, xr: &OpenXrInstance, session: XrSession) -> Result<(), XrError> {
+        let info = XrSessionActionSetsAttachInfo {
+            ty: XR_TYPE_SESSION_ACTION_SETS_ATTACH_INFO,
+            next: ptr::null(),
+            count_action_sets: 1,
+            action_sets: &self.set,
+        };
+        check("xrAttachSessionActionSets", unsafe {
+            (xr.fns.attach_session_action_sets)(session, &info)
+        })?;
+
+        for (space, hand) in self.hand_spaces.iter_mut().zip(self.hands) {
+            let info = XrActionSpaceCreateInfo {
+                ty: XR_TYPE_ACTION_SPACE_CREATE_INFO,
+                next: ptr::null(),
+                action: self.pose,
+                subaction_path: hand,
+                pose_in_action_space: XrPosef::IDENTITY,
+            };
+            check("xrCreateActionSpace", unsafe {
+                (xr.fns.create_action_space)(session, &info, space)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    pub fn sync(&self, xr: &OpenXrInstance, session: XrSession) -> Result<(), XrError> {
+        let active = XrActiveActionSet {
+            action_set: self.set,
+            subaction_path: XR_NULL_PATH,
+        };
+        let info = XrActionsSyncInfo {
+            ty: XR_TYPE_ACTIONS_SYNC_INFO,
+            next: ptr::null(),
+            count_active_action_sets: 1,
+            active_action_sets: &active,
+        };
+
+        // SESSION_NOT_FOCUSED is a success code; states just read inactive
+        check("xrSyncActions", unsafe { (xr.fns.sync_actions)(session, &info) })?;
+        Ok(())
+    }
+
+    pub fn controller(
+        &self,
+        xr: &OpenXrInstance,
+        session: XrSession,
+        base: XrSpace,
+        time: XrTime,
+        hand: Hand,
+    ) -> ControllerState {
+        let i = match hand {
+            Hand::Left => 0,
+            Hand::Right => 1,
+        };
+        let sub = self.hands[i];
+
+        let mut state = ControllerState {
+            pose: locate(xr, self.hand_spaces[i], base, time),
+            trigger: get_float(xr, session, self.trigger, sub),
+            grip: get_float(xr, session, self.grip, sub),
+            thumbstick: get_vec2(xr, session, self.thumbstick, sub),
+            ..Default::default()
+        };
+
+        state.primary = get_bool(xr, session, self.primary, sub);
+        state.secondary = get_bool(xr, session, self.secondary, sub);
+        state.thumbstick_click = get_bool(xr, session, self.thumbstick_click, sub);
+        state.menu = get_bool(xr, session, self.menu, sub);
+
+        state
+    }
+
+    pub fn destroy_spaces(&mut self, xr: &OpenXrInstance) {
+        for space in &mut self.hand_spaces {
+            if *space != XR_NULL_HANDLE {
+                unsafe { (xr.fns.destroy_space)(*space) };
+                *space = XR_NULL_HANDLE;
+            }
+        }
+    }
+}
+
+fn suggest_profile(
+    xr: &OpenXrInstance,
+    profile: &str,
+    bindings: &[(String, XrAction)],
+) -> Result<(), XrError> {
+    let suggested = bindings
+        .iter()
+        .map(|(path, action)| {
+            Ok(XrActionSuggestedBinding {
+                action: *action,
+                binding: xr.path(path)?,
+            })
+        })
+        .collect::<Result<Vec<_>, XrError>>()?;
+
+    let info = XrInteractionProfileSuggestedBinding {
+        ty: XR_TYPE_INTERACTION_PROFILE_SUGGESTED_BINDING,
+        next: ptr::null(),
+        interaction_profile: xr.path(profile)?,
+        count_suggested_bindings: suggested.len() as u32,
+        suggested_bindings: suggested.as_ptr(),
+    };
+
+    check("xrSuggestInteractionProfileBindings", unsafe {
+        (xr.fns.suggest_interaction_profile_bindings)(xr.handle, &info)
+    })?;
+    Ok(())
+}
+
+/* =========================================================
+   STATE QUERIES
+   ========================================================= */
+
+fn get_info(action: XrAction, subaction_path: XrPath) -> XrActionStateGetInfo {
+    XrActionStateGetInfo {
+        ty: XR_TYPE_ACTION_STATE_GET_INFO,
+        next: ptr::null(),
+        action,
+        subaction_path,
+    }
+}
+
+fn get_float(xr: &OpenXrInstance, session: XrSession, action: XrAction, sub: XrPath) -> f32 {
+    let mut state = XrActionStateFloat {
+        ty: XR_TYPE_ACTION_STATE_FLOAT,
+        next: ptr::null_mut(),
+        current_state: 0.0,
+        changed_since_last_sync: 0,
+        last_change_time: 0,
+        is_active: 0,
+    };
+    let r = unsafe { (xr.fns.get_action_state_float)(session, &get_info(action, sub), &mut state) };
+    if r == XR_SUCCESS && state.is_active == XR_TRUE {
+        state.current_state
+    } else {
+        0.0
+    }
+}
+
+fn get_bool(xr: &OpenXrInstance, session: XrSession, action: XrAction, sub: XrPath) -> bool {
+    let mut state = XrActionStateBoolean {
+        ty: XR_TYPE_ACTION_STATE_BOOLEAN,
+        next: ptr::null_mut(),
+        current_state: 0,
+        changed_since_last_sync: 0,
+        last_change_time: 0,
+        is_active: 0,
+    };
+    let r = unsafe { (xr.fns.get_action_state_boolean)(session, &get_info(action, sub), &mut state) };
+    r == XR_SUCCESS && state.is_active == XR_TRUE && state.current_state == XR_TRUE
+}
+
+fn get_vec2(xr: &OpenXrInstance, session: XrSession, action: XrAction, sub: XrPath) -> Vec2 {
+    let mut state = XrActionStateVector2f {
+        ty: XR_TYPE_ACTION_STATE_VECTOR2F,
+        next: ptr::null_mut(),
+        current_state: XrVector2f::default(),
+        changed_since_last_sync: 0,
+        last_change_time: 0,
+        is_active: 0,
+    };
+    let r = unsafe { (xr.fns.get_action_state_vector2f)(session, &get_info(action, sub), &mut state) };
+    if r == XR_SUCCESS && state.is_active == XR_TRUE {
+        Vec2::new(state.current_state.x, state.current_state.y)
+    } else {
+        Vec2::ZERO
+    }
+}
+
+/// Locates `space` in `base` at `time`, including velocity when reported.
+pub fn locate(xr: &OpenXrInstance, space: XrSpace, base: XrSpace, time: XrTime) -> TrackedPose {
+    if space == XR_NULL_HANDLE || time == 0 {
+        return TrackedPose::INVALID;
+    }
+
+    let mut velocity = XrSpaceVelocity {
+        ty: XR_TYPE_SPACE_VELOCITY,
+        next: ptr::null_mut(),
+        velocity_flags: 0,
+        linear_velocity: XrVector3f::default(),
+        angular_velocity: XrVector3f::default(),
+    };
+    let mut location = XrSpaceLocation {
+        ty: XR_TYPE_SPACE_LOCATION,
+        next: &mut velocity as *mut _ as *mut _,
+        location_flags: 0,
+        pose: XrPosef::IDENTITY,
+    };
+
+    let r = unsafe { (xr.fns.locate_space)(space, base, time, &mut location) };
+    let valid_bits = XR_SPACE_LOCATION_ORIENTATION_VALID_BIT | XR_SPACE_LOCATION_POSITION_VALID_BIT;
+    if r != XR_SUCCESS || location.location_flags & valid_bits != valid_bits {
+        return TrackedPose::INVALID;
+    }
+
+    let mut pose = pose_from_xr(&location.pose);
+    if velocity.velocity_flags & XR_SPACE_VELOCITY_LINEAR_VALID_BIT != 0 {
+        let v = velocity.linear_velocity;
+        pose.linear_velocity = Vec3::new(v.x, v.y, v.z);
+    }
+    if velocity.velocity_flags & XR_SPACE_VELOCITY_ANGULAR_VALID_BIT != 0 {
+        let v = velocity.angular_velocity;
+        pose.angular_velocity = Vec3::new(v.x, v.y, v.z);
+    }
+
+    pose
+}
+
+pub fn pose_from_xr(p: &XrPosef) -> TrackedPose {
+    let q = p.orientation;
+    let v = p.position;
+    TrackedPose::new(Vec3::new(v.x, v.y, v.z), Quat::from_xyzw(q.x, q.y, q.z, q.w))
+}
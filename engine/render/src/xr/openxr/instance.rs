@@ -0,0 +1,375 @@
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use super::ffi::*;
+use super::XrError;
+
+#[cfg(windows)]
+const LOADER_NAMES: &[&str] = &["openxr_loader.dll"];
+#[cfg(not(windows))]
+const LOADER_NAMES: &[&str] = &["libopenxr_loader.so.1", "libopenxr_loader.so"];
+
+/// Resolves one entry point by name into a typed function pointer.
+macro_rules! load_fn {
+    ($get:expr, $instance:expr, $name:literal, $ty:ty) => {{
+        let mut f: PFN_xrVoidFunction = None;
+        let r = unsafe { ($get)($instance, concat!($name, "\0").as_ptr() as *const c_char, &mut f) };
+        match f {
+            Some(f) if r == XR_SUCCESS => unsafe { std::mem::transmute::<unsafe extern "system" fn(), $ty>(f) },
+            _ => return Err(XrError::MissingFunction($name)),
+        }
+    }};
+}
+
+/* =========================================================
+   FUNCTION TABLE
+   ========================================================= */
+
+pub struct InstanceFns {
+    pub destroy_instance: PFN_xrDestroyInstance,
+    pub get_system: PFN_xrGetSystem,
+    pub enumerate_view_configuration_views: PFN_xrEnumerateViewConfigurationViews,
+    pub string_to_path: PFN_xrStringToPath,
+    pub poll_event: PFN_xrPollEvent,
+
+    pub create_session: PFN_xrCreateSession,
+    pub destroy_session: PFN_xrDestroySession,
+    pub begin_session: PFN_xrBeginSession,
+    pub end_session: PFN_xrEndSession,
+    pub request_exit_session: PFN_xrRequestExitSession,
+
+    pub create_reference_space: PFN_xrCreateReferenceSpace,
+    pub create_action_space: PFN_xrCreateActionSpace,
+    pub destroy_space: PFN_xrDestroySpace,
+    pub locate_space: PFN_xrLocateSpace,
+
+    pub wait_frame: PFN_xrWaitFrame,
+    pub begin_frame: PFN_xrBeginFrame,
+    pub end_frame: PFN_xrEndFrame,
+    pub locate_views: PFN_xrLocateViews,
+
+    pub enumerate_swapchain_formats: PFN_xrEnumerateSwapchainFormats,
+    pub create_swapchain: PFN_xrCreateSwapchain,
+    pub destroy_swapchain: PFN_xrDestroySwapchain,
+    pub enumerate_swapchain_images: PFN_xrEnumerateSwapchainImages,
+    pub acquire_swapchain_image: PFN_xrAcquireSwapchainImage,
+    pub wait_swapchain_image: PFN_xrWaitSwapchainImage,
+    pub release_swapchain_image: PFN_xrReleaseSwapchainImage,
+
+    pub create_action_set: PFN_xrCreateActionSet,
+    pub create_action: PFN_xrCreateAction,
+    pub suggest_interaction_profile_bindings: PFN_xrSuggestInteractionProfileBindings,
+    pub attach_session_action_sets: PFN_xrAttachSessionActionSets,
+    pub sync_actions: PFN_xrSyncActions,
+    pub get_action_state_boolean: PFN_xrGetActionStateBoolean,
+    pub get_action_state_float: PFN_xrGetActionStateFloat,
+    pub get_action_state_vector2f: PFN_xrGetActionStateVector2f,
+}
+
+pub struct HandTrackingFns {
+    pub create_hand_tracker: PFN_xrCreateHandTrackerEXT,
+    pub destroy_hand_tracker: PFN_xrDestroyHandTrackerEXT,
+    pub locate_hand_joints: PFN_xrLocateHandJointsEXT,
+}
+
+pub struct VulkanFns {
+    pub get_graphics_requirements: PFN_xrGetVulkanGraphicsRequirements2KHR,
+    pub create_vulkan_instance: PFN_xrCreateVulkanInstanceKHR,
+    pub get_graphics_device: PFN_xrGetVulkanGraphicsDevice2KHR,
+    pub create_vulkan_device: PFN_xrCreateVulkanDeviceKHR,
+}
+
+/* =========================================================
+   INSTANCE
+   ========================================================= */
+
+/// Owns the dynamically loaded OpenXR loader, the `XrInstance` and the
+/// HMD system id.
+pub struct OpenXrInstance {
+    pub handle: XrInstance,
+    pub system: XrSystemId,
+    pub fns: InstanceFns,
+    pub vulkan: Option<VulkanFns>,
+    /// Present when the runtime exposes `XR_EXT_hand_tracking`.
+    pub hand_tracking: Option<HandTrackingFns>,
+    pub headless: bool,
+
+    // keeps the entry points above valid
+    _loader: libloading::Library,
+}
+
+impl OpenXrInstance {
+    pub fn new(headless: bool) -> Result<Self, XrError> {
+        let loader = LOADER_NAMES
+            .iter()
+            .find_map(|name| unsafe { libloading::Library::new(name) }.ok())
+            .ok_or(XrError::LoaderNotFound)?;
+
+        let get: PFN_xrGetInstanceProcAddr = unsafe {
+            *loader
+                .get::<PFN_xrGetInstanceProcAddr>(b"xrGetInstanceProcAddr\0")
+                .map_err(|_| XrError::MissingFunction("xrGetInstanceProcAddr"))?
+        };
+
+        let enumerate_extensions: PFN_xrEnumerateInstanceExtensionProperties =
+            load_fn!(get, XR_NULL_HANDLE, "xrEnumerateInstanceExtensionProperties", PFN_xrEnumerateInstanceExtensionProperties);
+        let create_instance: PFN_xrCreateInstance =
+            load_fn!(get, XR_NULL_HANDLE, "xrCreateInstance", PFN_xrCreateInstance);
+
+        /* ================= EXTENSIONS ================= */
+
+        let available = available_extensions(enumerate_extensions)?;
+        let wanted = if headless {
+            XR_MND_HEADLESS_EXTENSION_NAME
+        } else {
+            XR_KHR_VULKAN_ENABLE2_EXTENSION_NAME
+        };
+
+        if !available.iter().any(|e| e == wanted) {
+            return Err(XrError::MissingExtension(wanted));
+        }
+
+        // optional: articulated hands when the runtime has them
+        let hands = available.iter().any(|e| e == XR_EXT_HAND_TRACKING_EXTENSION_NAME);
+
+        let wanted = CString::new(wanted).unwrap();
+        let hand_ext = CString::new(XR_EXT_HAND_TRACKING_EXTENSION_NAME).unwrap();
+
+        let mut extension_names = vec![wanted.as_ptr()];
+        if hands {
+            extension_names.push(hand_ext.as_ptr());
+        }
+
+        /* ================= CREATE ================= */
+
+        let info = XrInstanceCreateInfo {
+            ty: XR_TYPE_INSTANCE_CREATE_INFO,
+            next: ptr::null(),
+            create_flags: 0,
+            application_info: XrApplicationInfo {
+                application_name: fixed_str("U-VR"),
+                application_version: 1,
+                engine_name: fixed_str("U-VR"),
+                engine_version: 1,
+                api_version: XR_CURRENT_API_VERSION,
+            },
+            enabled_api_layer_count: 0,
+            enabled_api_layer_names: ptr::null(),
+            enabled_extension_count: extension_names.len() as u32,
+            enabled_extension_names: extension_names.as_ptr(),
+        };
+
+        let mut handle = XR_NULL_HANDLE;
+        check("xrCreateInstance", unsafe { create_instance(&info, &mut handle) })?;
+
+        let destroy_instance: PFN_xrDestroyInstance = load_fn!(get, handle, "xrDestroyInstance", PFN_xrDestroyInstance);
+
+        let loaded = load_instance_fns(get, handle).and_then(|fns| {
+            let vulkan = if headless {
+                None
+            } else {
+                Some(load_vulkan_fns(get, handle)?)
+            };
+            let hand_tracking = if hands {
+                Some(load_hand_tracking_fns(get, handle)?)
+            } else {
+                None
+            };
+            Ok((fns, vulkan, hand_tracking))
+        });
+
+        let (fns, vulkan, hand_tracking) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                // nothing owns the handle yet
+                unsafe { destroy_instance(handle) };
+                return Err(e);
+            }
+        };
+
+        let mut instance = Self {
+            handle,
+            system: 0,
+            fns,
+            vulkan,
+            hand_tracking,
+            headless,
+            _loader: loader,
+        };
+
+        /* ================= SYSTEM ================= */
+
+        let system_info = XrSystemGetInfo {
+            ty: XR_TYPE_SYSTEM_GET_INFO,
+            next: ptr::null(),
+            form_factor: XR_FORM_FACTOR_HEAD_MOUNTED_DISPLAY,
+        };
+
+        check("xrGetSystem", unsafe {
+            (instance.fns.get_system)(handle, &system_info, &mut instance.system)
+        })?;
+
+        Ok(instance)
+    }
+
+    pub fn path(&self, path: &str) -> Result<XrPath, XrError> {
+        let c = CString::new(path).unwrap();
+        let mut out = XR_NULL_PATH;
+        check("xrStringToPath", unsafe {
+            (self.fns.string_to_path)(self.handle, c.as_ptr(), &mut out)
+        })?;
+        Ok(out)
+    }
+
+    pub fn view_configuration_views(&self) -> Result<Vec<XrViewConfigurationView>, XrError> {
+        let empty = XrViewConfigurationView {
+            ty: XR_TYPE_VIEW_CONFIGURATION_VIEW,
+            next: ptr::null_mut(),
+            recommended_image_rect_width: 0,
+            max_image_rect_width: 0,
+            recommended_image_rect_height: 0,
+            max_image_rect_height: 0,
+            recommended_swapchain_sample_count: 0,
+            max_swapchain_sample_count: 0,
+        };
+
+        let mut count = 0;
+        check("xrEnumerateViewConfigurationViews", unsafe {
+            (self.fns.enumerate_view_configuration_views)(
+                self.handle,
+                self.system,
+                XR_VIEW_CONFIGURATION_TYPE_PRIMARY_STEREO,
+                0,
+                &mut count,
+                ptr::null_mut(),
+            )
+        })?;
+
+        let mut views = vec![empty; count as usize];
+        check("xrEnumerateViewConfigurationViews", unsafe {
+            (self.fns.enumerate_view_configuration_views)(
+                self.handle,
+                self.system,
+                XR_VIEW_CONFIGURATION_TYPE_PRIMARY_STEREO,
+                count,
+                &mut count,
+                views.as_mut_ptr(),
+            )
+        })?;
+
+        Ok(views)
+    }
+}
+
+impl Drop for OpenXrInstance {
+    fn drop(&mut self) {
+        unsafe { (self.fns.destroy_instance)(self.handle) };
+    }
+}
+
+pub fn check(call: &'static str, result: XrResult) -> Result<XrResult, XrError> {
+    if result < 0 {
+        Err(XrError::Call { call, result })
+    } else {
+        Ok(result)
+    }
+}
+
+fn available_extensions(
+    enumerate: PFN_xrEnumerateInstanceExtensionProperties,
+) -> Result<Vec<String>, XrError> {
+    let mut count = 0;
+    check("xrEnumerateInstanceExtensionProperties", unsafe {
+        enumerate(ptr::null(), 0, &mut count, ptr::null_mut())
+    })?;
+
+    let mut props: Vec<XrExtensionProperties> = (0..count)
+        .map(|_| XrExtensionProperties {
+            ty: XR_TYPE_EXTENSION_PROPERTIES,
+            next: ptr::null_mut(),
+            extension_name: [0; XR_MAX_EXTENSION_NAME_SIZE],
+            extension_version: 0,
+        })
+        .collect();
+
+    check("xrEnumerateInstanceExtensionProperties", unsafe {
+        enumerate(ptr::null(), count, &mut count, props.as_mut_ptr())
+    })?;
+
+    Ok(props
+        .iter()
+        .take(count as usize)
+        .map(|p| unsafe { CStr::from_ptr(p.extension_name.as_ptr()) }
+            .to_string_lossy()
+            .into_owned())
+        .collect())
+}
+
+fn load_instance_fns(
+    get: PFN_xrGetInstanceProcAddr,
+    instance: XrInstance,
+) -> Result<InstanceFns, XrError> {
+    Ok(InstanceFns {
+        destroy_instance: load_fn!(get, instance, "xrDestroyInstance", PFN_xrDestroyInstance),
+        get_system: load_fn!(get, instance, "xrGetSystem", PFN_xrGetSystem),
+        enumerate_view_configuration_views: load_fn!(get, instance, "xrEnumerateViewConfigurationViews", PFN_xrEnumerateViewConfigurationViews),
+        string_to_path: load_fn!(get, instance, "xrStringToPath", PFN_xrStringToPath),
+        poll_event: load_fn!(get, instance, "xrPollEvent", PFN_xrPollEvent),
+
+        create_session: load_fn!(get, instance, "xrCreateSession", PFN_xrCreateSession),
+        destroy_session: load_fn!(get, instance, "xrDestroySession", PFN_xrDestroySession),
+        begin_session: load_fn!(get, instance, "xrBeginSession", PFN_xrBeginSession),
+        end_session: load_fn!(get, instance, "xrEndSession", PFN_xrEndSession),
+        request_exit_session: load_fn!(get, instance, "xrRequestExitSession", PFN_xrRequestExitSession),
+
+        create_reference_space: load_fn!(get, instance, "xrCreateReferenceSpace", PFN_xrCreateReferenceSpace),
+        create_action_space: load_fn!(get, instance, "xrCreateActionSpace", PFN_xrCreateActionSpace),
+        destroy_space: load_fn!(get, instance, "xrDestroySpace", PFN_xrDestroySpace),
+        locate_space: load_fn!(get, instance, "xrLocateSpace", PFN_xrLocateSpace),
+
+        wait_frame: load_fn!(get, instance, "xrWaitFrame", PFN_xrWaitFrame),
+        begin_frame: load_fn!(get, instance, "xrBeginFrame", PFN_xrBeginFrame),
+        end_frame: load_fn!(get, instance, "xrEndFrame", PFN_xrEndFrame),
+        locate_views: load_fn!(get, instance, "xrLocateViews", PFN_xrLocateViews),
+
+        enumerate_swapchain_formats: load_fn!(get, instance, "xrEnumerateSwapchainFormats", PFN_xrEnumerateSwapchainFormats),
+        create_swapchain: load_fn!(get, instance, "xrCreateSwapchain", PFN_xrCreateSwapchain),
+        destroy_swapchain: load_fn!(get, instance, "xrDestroySwapchain", PFN_xrDestroySwapchain),
+        enumerate_swapchain_images: load_fn!(get, instance, "xrEnumerateSwapchainImages", PFN_xrEnumerateSwapchainImages),
+        acquire_swapchain_image: load_fn!(get, instance, "xrAcquireSwapchainImage", PFN_xrAcquireSwapchainImage),
+        wait_swapchain_image: load_fn!(get, instance, "xrWaitSwapchainImage", PFN_xrWaitSwapchainImage),
+        release_swapchain_image: load_fn!(get, instance, "xrReleaseSwapchainImage", PFN_xrReleaseSwapchainImage),
+
+        create_action_set: load_fn!(get, instance, "xrCreateActionSet", PFN_xrCreateActionSet),
+        create_action: load_fn!(get, instance, "xrCreateAction", PFN_xrCreateAction),
+        suggest_interaction_profile_bindings: load_fn!(get, instance, "xrSuggestInteractionProfileBindings", PFN_xrSuggestInteractionProfileBindings),
+        attach_session_action_sets: load_fn!(get, instance, "xrAttachSessionActionSets", PFN_xrAttachSessionActionSets),
+        sync_actions: load_fn!(get, instance, "xrSyncActions", PFN_xrSyncActions),
+        get_action_state_boolean: load_fn!(get, instance, "xrGetActionStateBoolean", PFN_xrGetActionStateBoolean),
+        get_action_state_float: load_fn!(get, instance, "xrGetActionStateFloat", PFN_xrGetActionStateFloat),
+        get_action_state_vector2f: load_fn!(get, instance, "xrGetActionStateVector2f", PFN_xrGetActionStateVector2f),
+    })
+}
+
+fn load_vulkan_fns(
+    get: PFN_xrGetInstanceProcAddr,
+    instance: XrInstance,
+) -> Result<VulkanFns, XrError> {
+    Ok(VulkanFns {
+        get_graphics_requirements: load_fn!(get, instance, "xrGetVulkanGraphicsRequirements2KHR", PFN_xrGetVulkanGraphicsRequirements2KHR),
+        create_vulkan_instance: load_fn!(get, instance, "xrCreateVulkanInstanceKHR", PFN_xrCreateVulkanInstanceKHR),
+        get_graphics_device: load_fn!(get, instance, "xrGetVulkanGraphicsDevice2KHR", PFN_xrGetVulkanGraphicsDevice2KHR),
+        create_vulkan_device: load_fn!(get, instance, "xrCreateVulkanDeviceKHR", PFN_xrCreateVulkanDeviceKHR),
+    })
+}
+
+fn load_hand_tracking_fns(
+    get: PFN_xrGetInstanceProcAddr,
+    instance: XrInstance,
+) -> Result<HandTrackingFns, XrError> {
+    Ok(HandTrackingFns {
+        create_hand_tracker: load_fn!(get, instance, "xrCreateHandTrackerEXT", PFN_xrCreateHandTrackerEXT),
+        destroy_hand_tracker: load_fn!(get, instance, "xrDestroyHandTrackerEXT", PFN_xrDestroyHandTrackerEXT),
+        locate_hand_joints: load_fn!(get, instance, "xrLocateHandJointsEXT", PFN_xrLocateHandJointsEXT),
+    })
+}
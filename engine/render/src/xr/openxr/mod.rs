@@ -0,0 +1,67 @@
+//! OpenXR runtime backend.
+//!
+//! The loader is opened at runtime, so builds and machines without an
+//! OpenXR runtime still work: `OpenXrBackend::new` just returns an error
+//! and the app stays in desktop mode. With `XrOptions::headless` the
+//! session is created through `XR_MND_headless` (e.g. Monado's headless
+//! driver) and only supplies poses, which is enough to exercise session
+//! lifecycle, spaces and actions without a headset.
+
+pub mod ffi;
+pub mod graphics;
+pub mod hands;
+pub mod input;
+pub mod instance;
+pub mod session;
+
+use std::fmt;
+
+pub use session::{FrameTiming, OpenXrBackend};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferenceSpace {
+    /// Seated/standing origin at the initial head position.
+    Local,
+    /// Room-scale origin on the floor; falls back to `Local` if missing.
+    Stage,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct XrOptions {
+    pub headless: bool,
+    pub reference_space: ReferenceSpace,
+}
+
+impl Default for XrOptions {
+    fn default() -> Self {
+        Self {
+            headless: false,
+            reference_space: ReferenceSpace::Stage,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum XrError {
+    LoaderNotFound,
+    MissingFunction(&'static str),
+    MissingExtension(&'static str),
+    Unsupported(&'static str),
+    Call { call: &'static str, result: ffi::XrResult },
+    Vulkan(String),
+}
+
+impl fmt::Display for XrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            XrError::LoaderNotFound => write!(f, "no OpenXR loader found"),
+            XrError::MissingFunction(name) => write!(f, "runtime does not provide {}", name),
+            XrError::MissingExtension(name) => write!(f, "runtime does not support {}", name),
+            XrError::Unsupported(what) => write!(f, "unsupported: {}", what),
+            XrError::Call { call, result } => write!(f, "{} failed with XrResult {}", call, result),
+            XrError::Vulkan(msg) => write!(f, "vulkan: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for XrError {}
@@ -0,0 +1,698 @@
+use std::ptr;
+
+use glam::Mat4;
+
+use crate::renderer::context::depth::DepthTexture;
+use crate::renderer::context::device::RenderDevice;
+use crate::renderer::frame::view::{fov_projection, RenderView};
+use crate::renderer::Renderer;
+use crate::xr::pose::{Hand, PoseFrame, PoseProvider, TrackedPose};
+
+use super::ffi::*;
+use super::hands::HandTrackers;
+use super::graphics::{create_render_device, vk_format, wrap_swapchain_image, VulkanBinding};
+use super::input::{locate, pose_from_xr, ActionInput};
+use super::instance::{check, OpenXrInstance};
+use super::{ReferenceSpace, XrError, XrOptions};
+
+const NEAR: f32 = 0.05;
+const FAR: f32 = 100.0;
+
+// used to put a LOCAL space origin on the floor
+const DEFAULT_EYE_HEIGHT: f32 = 1.6;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FrameTiming {
+    pub predicted_display_time: XrTime,
+    pub predicted_display_period: XrDuration,
+    pub should_render: bool,
+}
+
+/* =========================================================
+   SWAPCHAIN
+   ========================================================= */
+
+/// One two-layer swapchain; eye `i` renders into array layer `i`.
+struct Swapchain {
+    handle: XrSwapchain,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    images: Vec<wgpu::Texture>,
+    depth: DepthTexture,
+}
+
+impl Swapchain {
+    fn new(
+        xr: &OpenXrInstance,
+        session: XrSession,
+        device: &RenderDevice,
+        views: &[XrViewConfigurationView],
+    ) -> Result<Self, XrError> {
+        let view = views.first().ok_or(XrError::Unsupported("stereo view configuration"))?;
+        let width = view.recommended_image_rect_width;
+        let height = view.recommended_image_rect_height;
+        let layers = views.len() as u32;
+
+        /* ================= FORMAT ================= */
+
+        let mut count = 0;
+        check("xrEnumerateSwapchainFormats", unsafe {
+            (xr.fns.enumerate_swapchain_formats)(session, 0, &mut count, ptr::null_mut())
+        })?;
+        let mut formats = vec![0i64; count as usize];
+        check("xrEnumerateSwapchainFormats", unsafe {
+            (xr.fns.enumerate_swapchain_formats)(session, count, &mut count, formats.as_mut_ptr())
+        })?;
+
+        let format = [
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            wgpu::TextureFormat::Bgra8UnormSrgb,
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureFormat::Bgra8Unorm,
+        ]
+        .into_iter()
+        .find(|f| vk_format(*f).is_some_and(|vk| formats.contains(&vk)))
+        .ok_or(XrError::Unsupported("swapchain color format"))?;
+
+        /* ================= CREATE ================= */
+
+        let info = XrSwapchainCreateInfo {
+            ty: XR_TYPE_SWAPCHAIN_CREATE_INFO,
+            next: ptr::null(),
+            create_flags: 0,
+            usage_flags: XR_SWAPCHAIN_USAGE_COLOR_ATTACHMENT_BIT | XR_SWAPCHAIN_USAGE_SAMPLED_BIT,
+            format: vk_format(format).unwrap(),
+            sample_count: 1,
+            width,
+            height,
+            face_count: 1,
+            array_size: layers,
+            mip_count: 1,
+        };
+
+        let mut handle = XR_NULL_HANDLE;
+        check("xrCreateSwapchain", unsafe {
+            (xr.fns.create_swapchain)(session, &info, &mut handle)
+        })?;
+
+        let mut count = 0;
+        check("xrEnumerateSwapchainImages", unsafe {
+            (xr.fns.enumerate_swapchain_images)(handle, 0, &mut count, ptr::null_mut())
+        })?;
+        let empty = XrSwapchainImageVulkanKHR {
+            ty: XR_TYPE_SWAPCHAIN_IMAGE_VULKAN2_KHR,
+            next: ptr::null_mut(),
+            image: 0,
+        };
+        let mut raw_images = vec![empty; count as usize];
+        check("xrEnumerateSwapchainImages", unsafe {
+            (xr.fns.enumerate_swapchain_images)(handle, count, &mut count, raw_images.as_mut_ptr())
+        })?;
+
+        let images = raw_images
+            .iter()
+            .map(|img| unsafe {
+                wrap_swapchain_image(&device.device, img.image, format, width, height, layers)
+            })
+            .collect();
+
+        Ok(Self {
+            handle,
+            format,
+            width,
+            height,
+            images,
+            depth: DepthTexture::with_size(&device.device, width, height),
+        })
+    }
+
+    fn acquire(&self, xr: &OpenXrInstance) -> Result<usize, XrError> {
+        let acquire = XrSwapchainImageAcquireInfo {
+            ty: XR_TYPE_SWAPCHAIN_IMAGE_ACQUIRE_INFO,
+            next: ptr::null(),
+        };
+        let mut index = 0;
+        check("xrAcquireSwapchainImage", unsafe {
+            (xr.fns.acquire_swapchain_image)(self.handle, &acquire, &mut index)
+        })?;
+
+        let wait = XrSwapchainImageWaitInfo {
+            ty: XR_TYPE_SWAPCHAIN_IMAGE_WAIT_INFO,
+            next: ptr::null(),
+            timeout: XR_INFINITE_DURATION,
+        };
+        let waited = check("xrWaitSwapchainImage", unsafe {
+            (xr.fns.wait_swapchain_image)(self.handle, &wait)
+        });
+        if let Err(e) = waited {
+            // an acquired image that is never released blocks every
+            // later acquire
+            let _ = self.release(xr);
+            return Err(e);
+        }
+
+        Ok(index as usize)
+    }
+
+    fn release(&self, xr: &OpenXrInstance) -> Result<(), XrError> {
+        let info = XrSwapchainImageReleaseInfo {
+            ty: XR_TYPE_SWAPCHAIN_IMAGE_RELEASE_INFO,
+            next: ptr::null(),
+        };
+        check("xrReleaseSwapchainImage", unsafe {
+            (xr.fns.release_swapchain_image)(self.handle, &info)
+        })?;
+        Ok(())
+    }
+}
+
+/* =========================================================
+   BACKEND
+   ========================================================= */
+
+/// A running OpenXR session: lifecycle, spaces, frame timing, the
+/// headset swapchain and controller actions. Also a [`PoseProvider`].
+pub struct OpenXrBackend {
+    session: XrSession,
+    state: i32,
+    running: bool,
+    exit_requested: bool,
+
+    space: XrSpace,
+    view_space: XrSpace,
+    pub reference_space: ReferenceSpace,
+    /// What the runtime didn't support and was left out (interaction
+    /// profiles, hand tracking).
+    pub warnings: Vec<String>,
+
+    input: ActionInput,
+    hands: Option<HandTrackers>,
+    view_config: Vec<XrViewConfigurationView>,
+    swapchain: Option<Swapchain>,
+
+    timing: Option<FrameTiming>,
+    first_time: Option<XrTime>,
+    last: Option<PoseFrame>,
+
+    // dropped last, after everything above is destroyed
+    instance: OpenXrInstance,
+}
+
+impl OpenXrBackend {
+    /// Starts a session. Unless headless, also returns the device the
+    /// renderer must use, since the runtime chooses the GPU.
+    pub fn new(options: &XrOptions) -> Result<(Self, Option<RenderDevice>), XrError> {
+        let instance = OpenXrInstance::new(options.headless)?;
+        let view_config = instance.view_configuration_views()?;
+        let (input, warnings) = ActionInput::new(&instance)?;
+
+        let (device, binding) = if options.headless {
+            (None, None)
+        } else {
+            let (device, binding) = create_render_device(&instance)?;
+            (Some(device), Some(binding))
+        };
+
+        let session = create_session(&instance, binding.as_ref())?;
+
+        let mut backend = Self {
+            session,
+            state: XR_SESSION_STATE_IDLE,
+            running: false,
+            exit_requested: false,
+            space: XR_NULL_HANDLE,
+            view_space: XR_NULL_HANDLE,
+            reference_space: options.reference_space,
+            warnings,
+            input,
+            hands: None,
+            view_config,
+            swapchain: None,
+            timing: None,
+            first_time: None,
+            last: None,
+            instance,
+        };
+
+        // from here on Drop cleans up the session if anything fails
+        backend.input.attach(&backend.instance, session)?;
+        backend.hands = match HandTrackers::new(&backend.instance, session) {
+            Ok(hands) => hands,
+            Err(e) => {
+                backend.warnings.push(format!("hand tracking unavailable: {}", e));
+                None
+            }
+        };
+
+        backend.create_spaces()?;
+
+        if let Some(device) = &device {
+            backend.swapchain = Some(Swapchain::new(
+                &backend.instance,
+                session,
+                device,
+                &backend.view_config,
+            )?);
+        }
+
+        Ok((backend, device))
+    }
+
+    fn create_spaces(&mut self) -> Result<(), XrError> {
+        let create = |ty: i32, offset_y: f32| -> Result<XrSpace, XrError> {
+            let mut pose = XrPosef::IDENTITY;
+            pose.position.y = offset_y;
+
+            let info = XrReferenceSpaceCreateInfo {
+                ty: XR_TYPE_REFERENCE_SPACE_CREATE_INFO,
+                next: ptr::null(),
+                reference_space_type: ty,
+                pose_in_reference_space: pose,
+            };
+            let mut space = XR_NULL_HANDLE;
+            check("xrCreateReferenceSpace", unsafe {
+                (self.instance.fns.create_reference_space)(self.session, &info, &mut space)
+            })?;
+            Ok(space)
+        };
+
+        self.view_space = create(XR_REFERENCE_SPACE_TYPE_VIEW, 0.0)?;
+
+        let stage = match self.reference_space {
+            ReferenceSpace::Stage => create(XR_REFERENCE_SPACE_TYPE_STAGE, 0.0).ok(),
+            ReferenceSpace::Local => None,
+        };
+
+        self.space = match stage {
+            Some(space) => space,
+            None => {
+                // LOCAL starts at the head; drop the origin to the floor
+                self.reference_space = ReferenceSpace::Local;
+                create(XR_REFERENCE_SPACE_TYPE_LOCAL, -DEFAULT_EYE_HEIGHT)?
+            }
+        };
+
+        Ok(())
+    }
+
+    /* ================= LIFECYCLE ================= */
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn exit_requested(&self) -> bool {
+        self.exit_requested
+    }
+
+    /// Asks the runtime to wind the session down; it will move through
+    /// STOPPING and EXITING, handled in `poll_events`.
+    pub fn request_exit(&mut self) {
+        if self.running {
+            unsafe { (self.instance.fns.request_exit_session)(self.session) };
+        } else {
+            self.exit_requested = true;
+        }
+    }
+
+    pub fn poll_events(&mut self) -> Result<(), XrError> {
+        loop {
+            let mut event = XrEventDataBuffer {
+                ty: XR_TYPE_EVENT_DATA_BUFFER,
+                next: ptr::null(),
+                varying: [0; 4000],
+            };
+
+            let r = check("xrPollEvent", unsafe {
+                (self.instance.fns.poll_event)(self.instance.handle, &mut event)
+            })?;
+            if r == XR_EVENT_UNAVAILABLE {
+                return Ok(());
+            }
+
+            match event.ty {
+                XR_TYPE_EVENT_DATA_SESSION_STATE_CHANGED => {
+                    let changed = unsafe {
+                        &*(&event as *const XrEventDataBuffer as *const XrEventDataSessionStateChanged)
+                    };
+                    self.on_state_changed(changed.state)?;
+                }
+                XR_TYPE_EVENT_DATA_INSTANCE_LOSS_PENDING => {
+                    self.exit_requested = true;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn on_state_changed(&mut self, state: i32) -> Result<(), XrError> {
+        self.state = state;
+
+        match state {
+            XR_SESSION_STATE_READY => {
+                let info = XrSessionBeginInfo {
+                    ty: XR_TYPE_SESSION_BEGIN_INFO,
+                    next: ptr::null(),
+                    primary_view_configuration_type: XR_VIEW_CONFIGURATION_TYPE_PRIMARY_STEREO,
+                };
+                check("xrBeginSession", unsafe {
+                    (self.instance.fns.begin_session)(self.session, &info)
+                })?;
+                self.running = true;
+            }
+            XR_SESSION_STATE_STOPPING => {
+                check("xrEndSession", unsafe { (self.instance.fns.end_session)(self.session) })?;
+                self.running = false;
+                self.timing = None;
+            }
+            XR_SESSION_STATE_EXITING | XR_SESSION_STATE_LOSS_PENDING => {
+                self.running = false;
+                self.exit_requested = true;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    pub fn focused(&self) -> bool {
+        self.state == XR_SESSION_STATE_FOCUSED
+    }
+
+    /* ================= FRAME LOOP ================= */
+
+    /// Blocks until the runtime wants the next frame, then begins it.
+    /// Returns `None` while the session is not running.
+    pub fn begin_frame(&mut self) -> Result<Option<FrameTiming>, XrError> {
+        if !self.running {
+            return Ok(None);
+        }
+
+        let wait = XrFrameWaitInfo {
+            ty: XR_TYPE_FRAME_WAIT_INFO,
+            next: ptr::null(),
+        };
+        let mut state = XrFrameState {
+            ty: XR_TYPE_FRAME_STATE,
+            next: ptr::null_mut(),
+            predicted_display_time: 0,
+            predicted_display_period: 0,
+            should_render: 0,
+        };
+        check("xrWaitFrame", unsafe {
+            (self.instance.fns.wait_frame)(self.session, &wait, &mut state)
+        })?;
+
+        let begin = XrFrameBeginInfo {
+            ty: XR_TYPE_FRAME_BEGIN_INFO,
+            next: ptr::null(),
+        };
+        check("xrBeginFrame", unsafe {
+            (self.instance.fns.begin_frame)(self.session, &begin)
+        })?;
+
+        let timing = FrameTiming {
+            predicted_display_time: state.predicted_display_time,
+            predicted_display_period: state.predicted_display_period,
+            should_render: state.should_render == XR_TRUE,
+        };
+        self.timing = Some(timing);
+        self.first_time.get_or_insert(state.predicted_display_time);
+
+        Ok(Some(timing))
+    }
+
+    /// Renders both eyes (if the runtime asked for it) and submits the frame.
+    /// A begun frame is always ended, with no layers if rendering failed.
+    pub fn end_frame(&mut self, renderer: &mut Renderer) -> Result<(), XrError> {
+        let timing = match self.timing.take() {
+            Some(t) => t,
+            None => return Ok(()),
+        };
+
+        let rendered = if timing.should_render {
+            self.render_views(renderer, timing.predicted_display_time)
+        } else {
+            Ok(Vec::new())
+        };
+        let no_views = Vec::new();
+        let projection_views = rendered.as_ref().unwrap_or(&no_views);
+
+        let layer = XrCompositionLayerProjection {
+            ty: XR_TYPE_COMPOSITION_LAYER_PROJECTION,
+            next: ptr::null(),
+            layer_flags: 0,
+            space: self.space,
+            view_count: projection_views.len() as u32,
+            views: projection_views.as_ptr(),
+        };
+        let layers = [&layer as *const XrCompositionLayerProjection];
+
+        let info = XrFrameEndInfo {
+            ty: XR_TYPE_FRAME_END_INFO,
+            next: ptr::null(),
+            display_time: timing.predicted_display_time,
+            environment_blend_mode: XR_ENVIRONMENT_BLEND_MODE_OPAQUE,
+            layer_count: if projection_views.is_empty() { 0 } else { 1 },
+            layers: layers.as_ptr(),
+        };
+
+        let ended = check("xrEndFrame", unsafe { (self.instance.fns.end_frame)(self.session, &info) });
+        rendered?;
+        ended?;
+        Ok(())
+    }
+
+    /// Renders the eyes into the next swapchain image and returns the
+    /// projection views showing them.
+    fn render_views(
+        &self,
+        renderer: &mut Renderer,
+        time: XrTime,
+    ) -> Result<Vec<XrCompositionLayerProjectionView>, XrError> {
+        let xr = &self.instance;
+        let Some(swapchain) = &self.swapchain else {
+            return Ok(Vec::new());
+        };
+
+        let views = self.locate_views(time)?;
+        let index = swapchain.acquire(xr)?;
+        let image = &swapchain.images[index];
+
+        let targets: Vec<wgpu::TextureView> = (0..views.len() as u32)
+            .map(|layer| {
+                image.create_view(&wgpu::TextureViewDescriptor {
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_array_layer: layer,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        let to_world = renderer.tracking_to_world();
+        let layers = renderer.eye_layers;
+        let render_views: Vec<RenderView> = views
+            .iter()
+            .zip(&targets)
+            .map(|(v, target)| {
+                let eye = pose_from_xr(&v.pose).matrix();
+                RenderView {
+                    color: target,
+                    depth: &swapchain.depth.view,
+                    size: (swapchain.width, swapchain.height),
+                    layers,
+                    view: (to_world * eye).inverse(),
+                    proj: fov_projection(
+                        v.fov.angle_left,
+                        v.fov.angle_right,
+                        v.fov.angle_up,
+                        v.fov.angle_down,
+                        NEAR,
+                        FAR,
+                    ),
+                }
+            })
+            .collect();
+
+        renderer.render_stereo(swapchain.format, &render_views);
+        swapchain.release(xr)?;
+
+        let mut projection_views = Vec::new();
+        for (i, v) in views.iter().enumerate() {
+            projection_views.push(XrCompositionLayerProjectionView {
+                ty: XR_TYPE_COMPOSITION_LAYER_PROJECTION_VIEW,
+                next: ptr::null(),
+                pose: v.pose,
+                fov: v.fov,
+                sub_image: XrSwapchainSubImage {
+                    swapchain: swapchain.handle,
+                    image_rect: XrRect2Di {
+                        offset: XrOffset2Di { x: 0, y: 0 },
+                        extent: XrExtent2Di {
+                            width: swapchain.width as i32,
+                            height: swapchain.height as i32,
+                        },
+                    },
+                    image_array_index: i as u32,
+                },
+            });
+        }
+
+        Ok(projection_views)
+    }
+
+    fn locate_views(&self, time: XrTime) -> Result<Vec<XrView>, XrError> {
+        let info = XrViewLocateInfo {
+            ty: XR_TYPE_VIEW_LOCATE_INFO,
+            next: ptr::null(),
+            view_configuration_type: XR_VIEW_CONFIGURATION_TYPE_PRIMARY_STEREO,
+            display_time: time,
+            space: self.space,
+        };
+        let mut state = XrViewState {
+            ty: XR_TYPE_VIEW_STATE,
+            next: ptr::null_mut(),
+            view_state_flags: 0,
+        };
+        let empty = XrView {
+            ty: XR_TYPE_VIEW,
+            next: ptr::null_mut(),
+            pose: XrPosef::IDENTITY,
+            fov: XrFovf::default(),
+        };
+
+        let mut views = vec![empty; self.view_config.len()];
+        let mut count = 0;
+        check("xrLocateViews", unsafe {
+            (self.instance.fns.locate_views)(
+                self.session,
+                &info,
+                &mut state,
+                views.len() as u32,
+                &mut count,
+                views.as_mut_ptr(),
+            )
+        })?;
+        views.truncate(count as usize);
+
+        Ok(views)
+    }
+
+    /// Eye-to-tracking transforms for the current frame, e.g. for a
+    /// desktop mirror view.
+    pub fn eye_poses(&self) -> Vec<Mat4> {
+        let time = match self.timing {
+            Some(t) => t.predicted_display_time,
+            None => return Vec::new(),
+        };
+
+        self.locate_views(time)
+            .map(|views| views.iter().map(|v| pose_from_xr(&v.pose).matrix()).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl PoseProvider for OpenXrBackend {
+    fn sample(&mut self, _dt: f32) -> PoseFrame {
+        let time = match self.timing {
+            Some(t) => t.predicted_display_time,
+            None => return self.last.unwrap_or_default(),
+        };
+
+        let xr = &self.instance;
+        let tracked = self.focused() && self.input.sync(xr, self.session).is_ok();
+
+        let (left, right) = if tracked {
+            (
+                self.input.controller(xr, self.session, self.space, time, Hand::Left),
+                self.input.controller(xr, self.session, self.space, time, Hand::Right),
+            )
+        } else {
+            Default::default()
+        };
+
+        let (left_hand, right_hand) = match (&self.hands, tracked) {
+            (Some(hands), true) => (
+                hands.locate(xr, Hand::Left, self.space, time),
+                hands.locate(xr, Hand::Right, self.space, time),
+            ),
+            _ => Default::default(),
+        };
+
+        let start = self.first_time.unwrap_or(time);
+        let frame = PoseFrame {
+            time: (time - start) as f32 * 1e-9,
+            head: match locate(xr, self.view_space, self.space, time) {
+                pose if pose.valid => pose,
+                _ => TrackedPose::INVALID,
+            },
+            left,
+            right,
+            left_hand,
+            right_hand,
+        };
+
+        self.last = Some(frame);
+        frame
+    }
+
+    fn name(&self) -> &str {
+        "openxr"
+    }
+}
+
+impl Drop for OpenXrBackend {
+    fn drop(&mut self) {
+        let fns = &self.instance.fns;
+
+        if let Some(swapchain) = self.swapchain.take() {
+            drop(swapchain.images);
+            unsafe { (fns.destroy_swapchain)(swapchain.handle) };
+        }
+
+        if let Some(hands) = &mut self.hands {
+            hands.destroy(&self.instance);
+        }
+        self.input.destroy_spaces(&self.instance);
+        for space in [self.space, self.view_space] {
+            if space != XR_NULL_HANDLE {
+                unsafe { (fns.destroy_space)(space) };
+            }
+        }
+
+        unsafe { (fns.destroy_session)(self.session) };
+    }
+}
+
+fn create_session(
+    xr: &OpenXrInstance,
+    binding: Option<&VulkanBinding>,
+) -> Result<XrSession, XrError> {
+    let vulkan = binding.map(|b| XrGraphicsBindingVulkanKHR {
+        ty: XR_TYPE_GRAPHICS_BINDING_VULKAN2_KHR,
+        next: ptr::null(),
+        instance: b.instance,
+        physical_device: b.physical_device,
+        device: b.device,
+        queue_family_index: b.queue_family_index,
+        queue_index: 0,
+    });
+
+    // headless sessions (XR_MND_headless) take no graphics binding at all
+    let info = XrSessionCreateInfo {
+        ty: XR_TYPE_SESSION_CREATE_INFO,
+        next: vulkan
+            .as_ref()
+            .map_or(ptr::null(), |v| v as *const _ as *const _),
+        create_flags: 0,
+        system_id: xr.system,
+    };
+
+    let mut session = XR_NULL_HANDLE;
+    check("xrCreateSession", unsafe {
+        (xr.fns.create_session)(xr.handle, &info, &mut session)
+    })?;
+    Ok(session)
+}
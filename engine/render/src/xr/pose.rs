@@ -0,0 +1,232 @@
+use glam::{Mat4, Quat, Vec2, Vec3};
+
+use super::hand::HandSkeleton;
+
+/* =========================================================
+   TRACKED POSE
+   ========================================================= */
+
+#[derive(Clone, Copy, Debug)]
+pub struct TrackedPose {
+    pub position: Vec3,
+    pub orientation: Quat,
+    pub linear_velocity: Vec3,
+    pub angular_velocity: Vec3,
+    pub valid: bool,
+}
+
+impl TrackedPose {
+    pub const INVALID: Self = Self {
+        position: Vec3::ZERO,
+        orientation: Quat::IDENTITY,
+        linear_velocity: Vec3::ZERO,
+        angular_velocity: Vec3::ZERO,
+        valid: false,
+    };
+
+    pub fn new(position: Vec3, orientation: Quat) -> Self {
+        Self {
+            position,
+            orientation,
+            valid: true,
+            ..Self::INVALID
+        }
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_rotation_translation(self.orientation, self.position)
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        self.orientation * Vec3::NEG_Z
+    }
+
+    /// Fills in velocities by differencing against the previous sample.
+    pub fn with_velocity_from(mut self, prev: &TrackedPose, dt: f32) -> Self {
+        if !self.valid || !prev.valid || dt <= 0.0 {
+            return self;
+        }
+
+        self.linear_velocity = (self.position - prev.position) / dt;
+
+        let delta = self.orientation * prev.orientation.inverse();
+        let (axis, angle) = delta.to_axis_angle();
+        let angle = if angle > std::f32::consts::PI {
+            angle - std::f32::consts::TAU
+        } else {
+            angle
+        };
+        self.angular_velocity = axis * angle / dt;
+
+        self
+    }
+
+    pub fn lerp(&self, other: &TrackedPose, t: f32) -> Self {
+        Self {
+            position: self.position.lerp(other.position, t),
+            orientation: self.orientation.slerp(other.orientation, t),
+            linear_velocity: self.linear_velocity.lerp(other.linear_velocity, t),
+            angular_velocity: self.angular_velocity.lerp(other.angular_velocity, t),
+            valid: self.valid && other.valid,
+        }
+    }
+}
+
+impl Default for TrackedPose {
+    fn default() -> Self {
+        Self::INVALID
+    }
+}
+
+/* =========================================================
+   CONTROLLERS
+   ========================================================= */
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ControllerState {
+    pub pose: TrackedPose,
+
+    // analog inputs, 0..1 for triggers and -1..1 for the stick
+    pub trigger: f32,
+    pub grip: f32,
+    pub thumbstick: Vec2,
+
+    // A/X and B/Y
+    pub primary: bool,
+    pub secondary: bool,
+    pub thumbstick_click: bool,
+    pub menu: bool,
+}
+
+impl ControllerState {
+    pub const BUTTON_PRIMARY: u32 = 1 << 0;
+    pub const BUTTON_SECONDARY: u32 = 1 << 1;
+    pub const BUTTON_THUMBSTICK: u32 = 1 << 2;
+    pub const BUTTON_MENU: u32 = 1 << 3;
+
+    pub fn buttons(&self) -> u32 {
+        let mut bits = 0;
+        if self.primary {
+            bits |= Self::BUTTON_PRIMARY;
+        }
+        if self.secondary {
+            bits |= Self::BUTTON_SECONDARY;
+        }
+        if self.thumbstick_click {
+            bits |= Self::BUTTON_THUMBSTICK;
+        }
+        if self.menu {
+            bits |= Self::BUTTON_MENU;
+        }
+        bits
+    }
+
+    pub fn set_buttons(&mut self, bits: u32) {
+        self.primary = bits & Self::BUTTON_PRIMARY != 0;
+        self.secondary = bits & Self::BUTTON_SECONDARY != 0;
+        self.thumbstick_click = bits & Self::BUTTON_THUMBSTICK != 0;
+        self.menu = bits & Self::BUTTON_MENU != 0;
+    }
+
+    pub fn lerp(&self, other: &ControllerState, t: f32) -> Self {
+        // digital buttons snap to whichever sample is closer
+        let digital = if t < 0.5 { self } else { other };
+
+        Self {
+            pose: self.pose.lerp(&other.pose, t),
+            trigger: self.trigger + (other.trigger - self.trigger) * t,
+            grip: self.grip + (other.grip - self.grip) * t,
+            thumbstick: self.thumbstick.lerp(other.thumbstick, t),
+            primary: digital.primary,
+            secondary: digital.secondary,
+            thumbstick_click: digital.thumbstick_click,
+            menu: digital.menu,
+        }
+    }
+}
+
+/* =========================================================
+   FRAME
+   ========================================================= */
+
+/// Everything a provider reports for one frame, in tracking space
+/// (Y up, -Z forward, origin on the floor at the play-area centre).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PoseFrame {
+    pub time: f32,
+    pub head: TrackedPose,
+    pub left: ControllerState,
+    pub right: ControllerState,
+
+    /// Articulated hands, when the runtime tracks them (`valid` otherwise false).
+    pub left_hand: HandSkeleton,
+    pub right_hand: HandSkeleton,
+}
+
+impl PoseFrame {
+    pub fn controller(&self, hand: Hand) -> &ControllerState {
+        match hand {
+            Hand::Left => &self.left,
+            Hand::Right => &self.right,
+        }
+    }
+
+    pub fn controller_mut(&mut self, hand: Hand) -> &mut ControllerState {
+        match hand {
+            Hand::Left => &mut self.left,
+            Hand::Right => &mut self.right,
+        }
+    }
+
+    pub fn hand(&self, hand: Hand) -> &HandSkeleton {
+        match hand {
+            Hand::Left => &self.left_hand,
+            Hand::Right => &self.right_hand,
+        }
+    }
+
+    pub fn hand_mut(&mut self, hand: Hand) -> &mut HandSkeleton {
+        match hand {
+            Hand::Left => &mut self.left_hand,
+            Hand::Right => &mut self.right_hand,
+        }
+    }
+
+    pub fn lerp(&self, other: &PoseFrame, t: f32) -> Self {
+        Self {
+            time: self.time + (other.time - self.time) * t,
+            head: self.head.lerp(&other.head, t),
+            left: self.left.lerp(&other.left, t),
+            right: self.right.lerp(&other.right, t),
+            left_hand: self.left_hand.lerp(&other.left_hand, t),
+            right_hand: self.right_hand.lerp(&other.right_hand, t),
+        }
+    }
+
+    pub fn with_velocity_from(mut self, prev: &PoseFrame, dt: f32) -> Self {
+        self.head = self.head.with_velocity_from(&prev.head, dt);
+        self.left.pose = self.left.pose.with_velocity_from(&prev.left.pose, dt);
+        self.right.pose = self.right.pose.with_velocity_from(&prev.right.pose, dt);
+        self
+    }
+}
+
+/* =========================================================
+   PROVIDER
+   ========================================================= */
+
+/// Source of head, controller and hand poses. VR code reads poses only
+/// through this trait so it runs the same on a headset, a scripted
+/// mock, a recorded session or the keyboard/mouse emulator.
+pub trait PoseProvider {
+    /// Advances the provider by `dt` seconds and returns the new frame.
+    fn sample(&mut self, dt: f32) -> PoseFrame;
+
+    fn name(&self) -> &str;
+}
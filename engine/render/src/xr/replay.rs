@@ -0,0 +1,386 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use glam::{Quat, Vec2, Vec3};
+
+use super::mock::ScriptedPoseProvider;
+use super::hand::{HandJoint, HandJointPose, HandSkeleton};
+use super::pose::{ControllerState, Hand, PoseFrame, PoseProvider, TrackedPose};
+
+/*
+   Session files are plain text, one frame per line:
+
+     time  head  left  right
+
+   head       = valid px py pz qx qy qz qw
+   controller = valid px py pz qx qy qz qw trigger grip stick_x stick_y buttons
+
+   A frame line may be followed by hand-tracking lines for that frame:
+
+     hand left|right  26 x (px py pz qx qy qz qw radius)
+
+   Lines starting with '#' are comments. Velocities are not stored,
+   they are re-derived on playback. v1 files (no hand lines) still load.
+*/
+
+const HEADER: &str = "# u-vr pose session v2";
+const POSE_FIELDS: usize = 8;
+const CONTROLLER_FIELDS: usize = POSE_FIELDS + 5;
+const FRAME_FIELDS: usize = 1 + POSE_FIELDS + 2 * CONTROLLER_FIELDS;
+const HAND_JOINT_FIELDS: usize = 8;
+const HAND_FIELDS: usize = HandJoint::COUNT * HAND_JOINT_FIELDS;
+
+/* =========================================================
+   RECORDER
+   ========================================================= */
+
+/// Collects frames from any provider so a session can be replayed later.
+#[derive(Default)]
+pub struct PoseRecorder {
+    frames: Vec<PoseFrame>,
+}
+
+impl PoseRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, frame: &PoseFrame) {
+        self.frames.push(*frame);
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(HEADER);
+        out.push('\n');
+
+        for f in &self.frames {
+            let _ = write!(out, "{}", f.time);
+            write_pose(&mut out, &f.head);
+            write_controller(&mut out, &f.left);
+            write_controller(&mut out, &f.right);
+            out.push('\n');
+
+            for (name, hand) in [("left", &f.left_hand), ("right", &f.right_hand)] {
+                if hand.valid {
+                    write_hand(&mut out, name, hand);
+                }
+            }
+        }
+
+        out
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+}
+
+fn write_pose(out: &mut String, p: &TrackedPose) {
+    let q = p.orientation;
+    let _ = write!(
+        out,
+        " {} {} {} {} {} {} {} {}",
+        p.valid as u8, p.position.x, p.position.y, p.position.z, q.x, q.y, q.z, q.w,
+    );
+}
+
+fn write_controller(out: &mut String, c: &ControllerState) {
+    write_pose(out, &c.pose);
+    let _ = write!(
+        out,
+        " {} {} {} {} {}",
+        c.trigger,
+        c.grip,
+        c.thumbstick.x,
+        c.thumbstick.y,
+        c.buttons(),
+    );
+}
+
+fn write_hand(out: &mut String, name: &str, hand: &HandSkeleton) {
+    let _ = write!(out, "hand {}", name);
+    for j in &hand.joints {
+        let (p, q) = (j.position, j.orientation);
+        let _ = write!(
+            out,
+            " {} {} {} {} {} {} {} {}",
+            p.x, p.y, p.z, q.x, q.y, q.z, q.w, j.radius,
+        );
+    }
+    out.push('\n');
+}
+
+/* =========================================================
+   REPLAY
+   ========================================================= */
+
+/// Replays a session recorded with [`PoseRecorder`].
+pub struct ReplayPoseProvider {
+    inner: ScriptedPoseProvider,
+}
+
+impl ReplayPoseProvider {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::from_text(&text)
+    }
+
+    pub fn from_text(text: &str) -> io::Result<Self> {
+        let mut frames: Vec<PoseFrame> = Vec::new();
+
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |msg: String| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", n + 1, msg))
+            };
+
+            if let Some(rest) = line.strip_prefix("hand ") {
+                let (hand, skeleton) = parse_hand(rest).map_err(invalid)?;
+                let frame = frames
+                    .last_mut()
+                    .ok_or_else(|| invalid("hand data before the first frame".to_string()))?;
+                *frame.hand_mut(hand) = skeleton;
+                continue;
+            }
+
+            frames.push(parse_frame(line).map_err(invalid)?);
+        }
+
+        if frames.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "pose session contains no frames",
+            ));
+        }
+
+        Ok(Self {
+            inner: ScriptedPoseProvider::new(frames),
+        })
+    }
+
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.inner = self.inner.looping(looping);
+        self
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.inner.duration()
+    }
+
+    pub fn finished(&self) -> bool {
+        self.inner.finished()
+    }
+
+    pub fn rewind(&mut self) {
+        self.inner.reset();
+    }
+}
+
+impl PoseProvider for ReplayPoseProvider {
+    fn sample(&mut self, dt: f32) -> PoseFrame {
+        self.inner.sample(dt)
+    }
+
+    fn name(&self) -> &str {
+        "replay"
+    }
+}
+
+fn parse_frame(line: &str) -> Result<PoseFrame, String> {
+    let fields: Vec<f32> = line.split_whitespace().map(number).collect::<Result<_, _>>()?;
+
+    if fields.len() != FRAME_FIELDS {
+        return Err(format!(
+            "expected {} fields, found {}",
+            FRAME_FIELDS,
+            fields.len()
+        ));
+    }
+
+    let head_at = 1;
+    let left_at = head_at + POSE_FIELDS;
+    let right_at = left_at + CONTROLLER_FIELDS;
+
+    Ok(PoseFrame {
+        time: fields[0],
+        head: parse_pose(&fields[head_at..left_at])?,
+        left: parse_controller(&fields[left_at..right_at])?,
+        right: parse_controller(&fields[right_at..])?,
+        ..Default::default()
+    })
+}
+
+/// A finite number; "nan" and "inf" parse as floats but are never
+/// recorded.
+fn number(s: &str) -> Result<f32, String> {
+    s.parse::<f32>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or_else(|| format!("invalid number '{}'", s))
+}
+
+/// The unit quaternion `f` (x y z w) points along.
+fn orientation(f: &[f32]) -> Result<Quat, String> {
+    let q = Quat::from_xyzw(f[0], f[1], f[2], f[3]);
+    if q.length_squared() < 1e-12 {
+        return Err(format!("zero-length orientation ({} {} {} {})", f[0], f[1], f[2], f[3]));
+    }
+    Ok(q.normalize())
+}
+
+fn parse_pose(f: &[f32]) -> Result<TrackedPose, String> {
+    Ok(TrackedPose {
+        valid: f[0] != 0.0,
+        ..TrackedPose::new(Vec3::new(f[1], f[2], f[3]), orientation(&f[4..8])?)
+    })
+}
+
+fn parse_controller(f: &[f32]) -> Result<ControllerState, String> {
+    let mut state = ControllerState {
+        pose: parse_pose(&f[..POSE_FIELDS])?,
+        trigger: f[POSE_FIELDS],
+        grip: f[POSE_FIELDS + 1],
+        thumbstick: Vec2::new(f[POSE_FIELDS + 2], f[POSE_FIELDS + 3]),
+        ..Default::default()
+    };
+    state.set_buttons(f[POSE_FIELDS + 4] as u32);
+    Ok(state)
+}
+
+fn parse_hand(line: &str) -> Result<(Hand, HandSkeleton), String> {
+    let mut words = line.split_whitespace();
+
+    let hand = match words.next() {
+        Some("left") => Hand::Left,
+        Some("right") => Hand::Right,
+        other => return Err(format!("expected 'left' or 'right', found {:?}", other)),
+    };
+
+    let fields: Vec<f32> = words.map(number).collect::<Result<_, _>>()?;
+
+    if fields.len() != HAND_FIELDS {
+        return Err(format!(
+            "expected {} hand fields, found {}",
+            HAND_FIELDS,
+            fields.len()
+        ));
+    }
+
+    let mut skeleton = HandSkeleton {
+        valid: true,
+        ..Default::default()
+    };
+
+    for (joint, f) in skeleton.joints.iter_mut().zip(fields.chunks(HAND_JOINT_FIELDS)) {
+        *joint = HandJointPose {
+            position: Vec3::new(f[0], f[1], f[2]),
+            orientation: orientation(&f[3..7])?,
+            radius: f[7],
+        };
+    }
+
+    Ok((hand, skeleton))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(time: f32, x: f32) -> PoseFrame {
+        let mut frame = PoseFrame {
+            time,
+            head: TrackedPose::new(Vec3::new(x, 1.6, 0.0), Quat::from_rotation_y(x)),
+            ..Default::default()
+        };
+        frame.right.pose = TrackedPose::new(Vec3::new(x + 0.2, 1.0, -0.3), Quat::IDENTITY);
+        frame.right.trigger = 0.75;
+        frame.right.primary = true;
+        frame.left_hand.valid = true;
+        frame.left_hand.joints[3].position = Vec3::new(-0.2, 1.1, x);
+        frame
+    }
+
+    fn error_of(text: &str) -> String {
+        ReplayPoseProvider::from_text(text).err().expect("should not load").to_string()
+    }
+
+    #[test]
+    fn recorded_sessions_replay_the_same_poses() {
+        let mut recorder = PoseRecorder::new();
+        recorder.record(&frame(0.0, 0.0));
+        recorder.record(&frame(0.5, 0.4));
+
+        let mut replay = ReplayPoseProvider::from_text(&recorder.to_text()).unwrap();
+        assert_eq!(replay.duration(), 0.5);
+
+        for (dt, expected) in [(0.0, frame(0.0, 0.0)), (0.5, frame(0.5, 0.4))] {
+            let got = replay.sample(dt);
+            assert!(got.head.valid);
+            assert!(got.head.position.abs_diff_eq(expected.head.position, 1e-5));
+            assert!(got.head.orientation.abs_diff_eq(expected.head.orientation, 1e-5));
+            assert!(got.right.pose.position.abs_diff_eq(expected.right.pose.position, 1e-5));
+            assert!(!got.left.pose.valid);
+            assert_eq!(got.right.trigger, 0.75);
+            assert!(got.right.primary);
+            assert!(got.left_hand.valid && !got.right_hand.valid);
+            assert!(got.left_hand.joints[3].position.abs_diff_eq(expected.left_hand.joints[3].position, 1e-5));
+        }
+        assert!(replay.finished());
+    }
+
+    /// The frame line of a one-frame session, without hands.
+    fn frame_line() -> String {
+        let mut recorder = PoseRecorder::new();
+        recorder.record(&PoseFrame {
+            head: TrackedPose::new(Vec3::new(0.0, 1.6, 0.0), Quat::IDENTITY),
+            ..Default::default()
+        });
+        recorder.to_text().lines().nth(1).unwrap().to_string()
+    }
+
+    #[test]
+    fn malformed_lines_are_reported_with_their_number() {
+        let line = frame_line();
+        let fields = format!("line 3: expected {} fields, found 3", FRAME_FIELDS);
+
+        assert!(error_of("# nothing\n").contains("no frames"));
+        assert!(error_of(&format!("# session\n{}\n0 1 2\n", line)).starts_with(&fields));
+        assert!(error_of(&line.replacen("1.6", "x", 1)).starts_with("line 1: invalid number 'x'"));
+        let mut recorder = PoseRecorder::new();
+        recorder.record(&frame(0.0, 0.0));
+        let hand = recorder.to_text().lines().nth(2).unwrap().to_string();
+        assert!(error_of(&hand).starts_with("line 1: hand data before the first frame"));
+        assert!(error_of(&format!("{}\nhand middle\n", line)).starts_with("line 2: expected 'left' or 'right'"));
+    }
+
+    #[test]
+    fn degenerate_orientations_are_rejected() {
+        // replaces the head's quaternion, fields 5..9
+        let with_head = |q: &str| {
+            let line = frame_line();
+            let mut words: Vec<&str> = line.split_whitespace().collect();
+            words.splice(5..9, q.split_whitespace());
+            words.join(" ")
+        };
+
+        assert!(ReplayPoseProvider::from_text(&with_head("0 0 0 2")).is_ok());
+        assert!(error_of(&with_head("0 0 0 0")).starts_with("line 1: zero-length orientation (0 0 0 0)"));
+        assert!(error_of(&with_head("NaN 0 0 1")).starts_with("line 1: invalid number 'NaN'"));
+        assert!(error_of(&with_head("0 inf 0 1")).starts_with("line 1: invalid number 'inf'"));
+    }
+}